@@ -0,0 +1,42 @@
+use serde::Serialize;
+use std::{fs, io, path::Path};
+
+/// A folder whose contents can't be listed with the app's current rights
+#[derive(Serialize, Clone, Debug)]
+pub struct AccessDenied {
+    pub path: String,
+    pub message: String,
+    /// Running elevated might get in, so the UI can offer "Retry as administrator"
+    pub elevation_possible: bool,
+}
+
+#[cfg(target_os = "windows")]
+fn is_elevated() -> bool {
+    crate::filesys::os::windows::is_elevated()
+}
+
+#[cfg(target_os = "linux")]
+fn is_elevated() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Elsewhere there is no way to retry with more rights, so elevation never helps
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn is_elevated() -> bool {
+    true
+}
+
+/// Checks that `dir` can be enumerated. Protected folders such as
+/// `C:\System Volume Information` stat fine but fail to list, which would
+/// otherwise show up as an empty folder. Only permission errors are reported;
+/// anything else is left to the caller's usual handling.
+pub fn check_listable(dir: &Path) -> Result<(), AccessDenied> {
+    match fs::read_dir(dir) {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => Err(AccessDenied {
+            path: dir.to_string_lossy().to_string(),
+            message: format!("Access denied: {}", dir.display()),
+            elevation_possible: !is_elevated(),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -1,99 +1,268 @@
 // src/tauri/actions.rs
 use std::future::Future;
-use std::path::Path;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
 use tokio::fs;
 
-/// Create a new empty file asynchronously
-#[tauri::command]
-pub async fn create_new_file(path: String) -> Result<(), String> {
-    fs::File::create(&path)
-        .await
-        .map(|_| ())
-        .map_err(|e| format!("Failed to create file: {}", e))
-}
+use crate::filesys::links::LinkInfo;
+use crate::filesys::names::{
+    is_case_only_change, validate_file_name, validate_name_on_volume, validate_rename, NameError,
+    NameErrorCode,
+};
+use crate::filesys::nav::FileItem;
+use crate::filesys::stream::delstream::{delete_items, DeleteMode};
+use crate::filesys::stream::jobs::JobStreamState;
+use crate::filesys::stream::journal::JournalEntry;
+use crate::filesys::stream::mirror::tree_size;
+use crate::filesys::stream::opstream::{
+    start_transfer_job, CopyStreamState, TransferOp, TransferRoot,
+};
+use crate::filesys::stream::transfer::{is_cross_device, MoveMethod, MoveOutcome};
+use crate::filesys::stream::undo::{record_operation, FileOperation};
 
-/// Create a new directory asynchronously
+/// Create a new empty file asynchronously.
+/// If the target exists a unique name is chosen ("New file (2).txt").
+/// Returns the created entry and emits `entry-added`.
 #[tauri::command]
-pub async fn create_new_directory(path: String) -> Result<(), String> {
-    fs::create_dir_all(&path)
-        .await
-        .map_err(|e| format!("Failed to create directory: {}", e))
-}
+pub async fn create_new_file(handle: AppHandle, path: String) -> Result<FileItem, NameError> {
+    let requested = PathBuf::from(&path);
+    validate_requested_name(&requested)?;
+    let mut target;
+    if let Some(parent) = requested.parent() {
+        fs::create_dir_all(parent).await.map_err(|e| {
+            NameError::new(
+                NameErrorCode::Io,
+                format!("Failed to create parent directory: {}", e),
+            )
+        })?;
+    }
 
-/// Async recursive directory copy with boxed future
-fn copy_dir_recursive<'a>(
-    src: &'a Path,
-    dst: &'a Path,
-) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>> {
-    Box::pin(async move {
-        fs::create_dir_all(dst)
+    // create_new fails instead of truncating if another process wins the race
+    loop {
+        target = unique_path(&requested);
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&target)
             .await
-            .map_err(|e| format!("Failed to create destination dir: {}", e))?;
+        {
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(NameError::new(
+                    NameErrorCode::Io,
+                    format!("Failed to create file: {}", e),
+                ))
+            }
+        }
+    }
 
-        let mut entries = fs::read_dir(src)
-            .await
-            .map_err(|e| format!("Failed to read directory {}: {}", src.display(), e))?;
+    let item = created_item(&target, false);
+    emit_entry_added(&handle, &target, &item);
+    record_operation(
+        &handle,
+        FileOperation::Create {
+            path: target,
+            is_dir: false,
+        },
+    );
+    Ok(item)
+}
 
-        while let Some(entry) = entries
-            .next_entry()
-            .await
-            .map_err(|e| format!("Failed to read entry: {}", e))?
-        {
-            let file_type = entry
-                .file_type()
-                .await
-                .map_err(|e| format!("Failed to get file type: {}", e))?;
-            let dest_path = dst.join(entry.file_name());
+/// Create a new directory asynchronously.
+/// If the target exists a unique name is chosen ("New folder (2)").
+/// Returns the created entry and emits `entry-added`.
+#[tauri::command]
+pub async fn create_new_directory(handle: AppHandle, path: String) -> Result<FileItem, NameError> {
+    let requested = PathBuf::from(&path);
+    validate_requested_name(&requested)?;
+    let mut target;
+    if let Some(parent) = requested.parent() {
+        fs::create_dir_all(parent).await.map_err(|e| {
+            NameError::new(
+                NameErrorCode::Io,
+                format!("Failed to create parent directory: {}", e),
+            )
+        })?;
+    }
 
-            if file_type.is_dir() {
-                copy_dir_recursive(&entry.path(), &dest_path).await?;
-            } else if file_type.is_file() {
-                fs::copy(entry.path(), &dest_path).await.map_err(|e| {
-                    format!("Failed to copy file {}: {}", entry.path().display(), e)
-                })?;
+    loop {
+        target = unique_path(&requested);
+        match fs::create_dir(&target).await {
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(NameError::new(
+                    NameErrorCode::Io,
+                    format!("Failed to create directory: {}", e),
+                ))
             }
         }
+    }
 
-        Ok(())
-    })
+    let item = created_item(&target, true);
+    emit_entry_added(&handle, &target, &item);
+    record_operation(
+        &handle,
+        FileOperation::Create {
+            path: target,
+            is_dir: true,
+        },
+    );
+    Ok(item)
 }
 
-/// Copy a file or directory asynchronously
-#[tauri::command]
-pub async fn copy_item(src: String, dest: String) -> Result<(), String> {
-    let src_path = Path::new(&src);
-    let dest_path = Path::new(&dest);
+/// Validates the final component of a path requested for creation
+fn validate_requested_name(path: &Path) -> Result<(), NameError> {
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match path.parent().filter(|p| p.exists()) {
+        Some(parent) => validate_name_on_volume(parent, &name),
+        None => validate_file_name(&name),
+    }
+}
 
-    if !src_path.exists() {
-        return Err("Source path does not exist".into());
+/// Returns `path` if free, otherwise the first free "name (n).ext" starting at 2,
+/// matching Explorer's naming for duplicates.
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
     }
 
-    if src_path.is_file() {
-        fs::copy(src_path, dest_path)
-            .await
-            .map(|_| ())
-            .map_err(|e| format!("Failed to copy file: {}", e))
-    } else if src_path.is_dir() {
-        copy_dir_recursive(src_path, dest_path).await
-    } else {
-        Err("Source path is neither file nor directory".into())
+    let is_dir = path.is_dir();
+    let file_name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // directories keep dots in their name ("v1.2" -> "v1.2 (2)")
+    let (stem, ext) = match path.extension() {
+        Some(ext) if !is_dir => (
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            format!(".{}", ext.to_string_lossy()),
+        ),
+        _ => (file_name, String::new()),
+    };
+
+    let mut i = 2;
+    loop {
+        let candidate = path.with_file_name(format!("{} ({}){}", stem, i, ext));
+        if !candidate.exists() {
+            return candidate;
+        }
+        i += 1;
     }
 }
 
-/// Move a file or directory asynchronously
+fn created_item(path: &Path, is_dir: bool) -> FileItem {
+    FileItem {
+        name: path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+        size: if is_dir { None } else { Some(0) },
+        link: LinkInfo::default(),
+    }
+}
+
+fn emit_entry_added(handle: &AppHandle, path: &Path, item: &FileItem) {
+    let parent = path
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let _ = handle.emit(
+        "entry-added",
+        serde_json::json!({
+            "parent": parent,
+            "item": item,
+        }),
+    );
+}
+
+/// Move a file or directory asynchronously.
+/// Tries a fast rename first; across volumes it runs the same queued, journaled
+/// job as a cut and paste, with its conflict prompts, pausing, cancellation and
+/// `clipboard-paste-*` progress events.
 #[tauri::command]
-pub async fn move_item(src: String, dest: String) -> Result<(), String> {
+pub async fn move_item(
+    handle: AppHandle,
+    state: State<'_, Arc<JobStreamState>>,
+    copy_state: State<'_, Arc<CopyStreamState>>,
+    src: String,
+    dest: String,
+    request_id: u64,
+) -> Result<MoveOutcome, String> {
     let src_path = Path::new(&src);
     let dest_path = Path::new(&dest);
 
     if !src_path.exists() {
         return Err("Source path does not exist".into());
     }
+    if dest_path.exists() {
+        return Err("Destination already exists".into());
+    }
+
+    match fs::rename(src_path, dest_path).await {
+        Ok(_) => {
+            let items = vec![JournalEntry {
+                src: src_path.to_path_buf(),
+                dest: dest_path.to_path_buf(),
+            }];
+            record_operation(
+                &handle,
+                FileOperation::Move {
+                    items,
+                    created_dirs: Vec::new(),
+                    removed_dirs: Vec::new(),
+                },
+            );
+            return Ok(MoveOutcome {
+                method: MoveMethod::Rename,
+                dest,
+                bytes: 0,
+            });
+        }
+        Err(e) if !is_cross_device(&e) => return Err(format!("Failed to move item: {}", e)),
+        Err(_) => {}
+    }
+
+    let roots = vec![TransferRoot {
+        src: src_path.to_path_buf(),
+        dest: dest_path.to_path_buf(),
+    }];
+    start_transfer_job(
+        handle,
+        &copy_state,
+        &state,
+        roots,
+        TransferOp::Move,
+        request_id,
+    )
+    .await?;
 
-    fs::rename(src_path, dest_path)
-        .await
-        .map_err(|e| format!("Failed to move item: {}", e))
+    // the source only goes away once everything below it was moved
+    if fs::symlink_metadata(src_path).await.is_ok() {
+        return Err("The move was cancelled or not every item could be moved".into());
+    }
+    let bytes = match std::fs::symlink_metadata(dest_path) {
+        Ok(meta) if meta.is_dir() => tree_size(dest_path).0,
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    };
+    Ok(MoveOutcome {
+        method: MoveMethod::Transfer,
+        dest,
+        bytes,
+    })
 }
 
 /// Async recursive delete with boxed future
@@ -132,7 +301,8 @@ fn delete_dir_recursive(
     })
 }
 
-/// Delete a file or directory asynchronously
+/// Permanently delete a file or directory asynchronously. Nothing can be restored
+/// afterwards; the everyday Delete is `move_to_trash`.
 #[tauri::command]
 pub async fn delete_item(path: String) -> Result<(), String> {
     let path = Path::new(&path);
@@ -155,49 +325,65 @@ pub async fn delete_item(path: String) -> Result<(), String> {
     }
 }
 
-/// Paste items based on frontend-provided paths
+/// Move files or directories to the system trash (Recycle Bin through the Windows
+/// Shell, the freedesktop trash on Linux, the Finder trash on macOS) so they can
+/// be restored. All paths are checked first, so a typo trashes nothing. Runs as
+/// the same job as `delete_items` in `trash` mode, reporting through the
+/// `delete-*` events.
 #[tauri::command]
-pub async fn paste_item_from_paths(dest: String, paths: Vec<String>) -> Result<(), String> {
-    let dest_path = Path::new(&dest);
-
-    if !dest_path.exists() || !dest_path.is_dir() {
-        return Err("Destination path does not exist or is not a directory".into());
+pub async fn move_to_trash(
+    handle: AppHandle,
+    state: State<'_, Arc<JobStreamState>>,
+    paths: Vec<String>,
+    request_id: u64,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("No paths provided".into());
     }
-
-    for path_str in paths {
-        let src_path = Path::new(&path_str);
-        if !src_path.exists() {
-            continue;
-        }
-
-        let dest_file_path = dest_path.join(src_path.file_name().unwrap());
-
-        if src_path.is_file() {
-            fs::copy(src_path, &dest_file_path)
-                .await
-                .map(|_| ())
-                .map_err(|e| format!("Failed to copy file {}: {}", src_path.display(), e))?;
-        } else if src_path.is_dir() {
-            copy_dir_recursive(src_path, &dest_file_path).await?;
-        }
+    if let Some(missing) = paths.iter().find(|p| std::fs::symlink_metadata(p).is_err()) {
+        return Err(format!("Path does not exist: {}", missing));
     }
-
-    Ok(())
+    delete_items(handle, state, paths, DeleteMode::Trash, None, request_id).await
 }
 
-/// Rename a file or directory asynchronously
+/// Rename a file or directory asynchronously.
+/// The new name is validated first so the UI can show the error inline.
 #[tauri::command]
-pub async fn rename_item(path: String, new_name: String) -> Result<(), String> {
+pub async fn rename_item(
+    handle: AppHandle,
+    path: String,
+    new_name: String,
+) -> Result<(), NameError> {
     let path = Path::new(&path);
+    validate_rename(path, &new_name)?;
 
-    if !path.exists() {
-        return Err("Path does not exist".into());
+    let current_name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if current_name == new_name {
+        return Ok(());
     }
 
-    let parent = path.parent().ok_or("Failed to get parent directory")?;
-    let new_path = parent.join(new_name);
+    let new_path = path.with_file_name(&new_name);
+    let io_error = |e: std::io::Error| {
+        NameError::new(NameErrorCode::Io, format!("Failed to rename item: {}", e))
+    };
 
-    fs::rename(path, new_path)
-        .await
-        .map_err(|e| format!("Failed to rename item: {}", e))
+    // Case-only renames on case-insensitive filesystems go through a temporary name
+    if is_case_only_change(&current_name, &new_name) && new_path.exists() {
+        let tmp_path = path.with_file_name(format!(".{}.dagger-rename", current_name));
+        fs::rename(path, &tmp_path).await.map_err(io_error)?;
+        fs::rename(&tmp_path, &new_path).await.map_err(io_error)?;
+    } else {
+        fs::rename(path, &new_path).await.map_err(io_error)?;
+    }
+    record_operation(
+        &handle,
+        FileOperation::Rename {
+            from: path.to_path_buf(),
+            to: new_path,
+        },
+    );
+    Ok(())
 }
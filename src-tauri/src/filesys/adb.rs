@@ -0,0 +1,457 @@
+use serde::Serialize;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::{
+    actions::unique_path,
+    staging::StagingDir,
+    stream::{
+        jobs::{JobHandle, JobKind, JobStreamState},
+        FileStreamState,
+    },
+};
+use crate::util::{caches::SharedAppSettings, notify::notify_job_finished};
+
+/// How often a running `adb pull`/`adb push` is checked for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An Android device as reported by `adb devices -l`
+#[derive(Serialize, Clone, Debug)]
+pub struct AdbDevice {
+    pub serial: String,
+    /// `device`, `unauthorized`, `offline`, ...
+    pub state: String,
+    pub model: Option<String>,
+    pub product: Option<String>,
+}
+
+/// One `ls -la` row
+struct RemoteEntry {
+    name: String,
+    is_dir: bool,
+    size: Option<u64>,
+    modified: Option<u64>,
+    link_target: Option<String>,
+}
+
+/// Lists Android devices visible to adb, including ones still awaiting authorization
+#[tauri::command]
+pub async fn list_adb_devices(
+    settings: State<'_, SharedAppSettings>,
+) -> Result<Vec<AdbDevice>, String> {
+    let adb = adb_executable(&settings).await?;
+    let output = run_adb(&adb, None, &["devices", "-l"])?;
+    Ok(output
+        .lines()
+        .skip(1)
+        .filter_map(parse_device_line)
+        .collect())
+}
+
+/// Streams the entries of `path` on the device (default `/sdcard`) through the
+/// usual `file-metadata` events, so the listing renders like a local folder.
+#[tauri::command]
+pub async fn stream_adb_directory(
+    handle: AppHandle,
+    state: State<'_, Arc<FileStreamState>>,
+    settings: State<'_, SharedAppSettings>,
+    serial: String,
+    path: Option<String>,
+    request_id: u64,
+) -> Result<(), String> {
+    let adb = adb_executable(&settings).await?;
+    let path = path
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| "/sdcard".to_string());
+    let dir = path.trim_end_matches('/');
+
+    state.begin(request_id);
+    // the trailing slash makes ls follow symlinked folders such as /sdcard itself
+    let listing = run_adb(
+        &adb,
+        Some(&serial),
+        &["shell", &format!("ls -la {}/", shell_quote(dir))],
+    )?;
+    let mut entries: Vec<RemoteEntry> = listing.lines().filter_map(parse_ls_line).collect();
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    for entry in &entries {
+        if state.is_stale(request_id) {
+            return Ok(());
+        }
+        let filetype = Path::new(&entry.name)
+            .extension()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let _ = handle.emit(
+            "file-metadata",
+            serde_json::json!({
+                "request_id": request_id,
+                "name": entry.name,
+                "path": format!("{}/{}", dir, entry.name),
+                "is_dir": entry.is_dir,
+                "size": entry.size,
+                "filetype": filetype,
+                "date_modified": entry.modified,
+                "pinned": false,
+                "tags": [],
+                "rating": null,
+                "git_status": null,
+                "project_kind": null,
+                "device": serial,
+                "link_target": entry.link_target,
+            }),
+        );
+    }
+
+    if state.is_stale(request_id) {
+        return Ok(());
+    }
+    let _ = handle.emit(
+        "file-metadata-complete",
+        serde_json::json!({ "request_id": request_id, "path": path, "device": serial }),
+    );
+    let _ = handle.emit(
+        "file-stream-complete",
+        serde_json::json!({ "request_id": request_id, "path": path, "device": serial }),
+    );
+    Ok(())
+}
+
+/// Copies files or folders from the device into `dest_dir`. Runs as a job, so
+/// progress and cancellation go through `adb-progress` and `cancel_job`.
+#[tauri::command]
+pub async fn adb_pull(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    settings: State<'_, SharedAppSettings>,
+    serial: String,
+    remote_paths: Vec<String>,
+    dest_dir: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let adb = adb_executable(&settings).await?;
+    if !Path::new(&dest_dir).is_dir() {
+        return Err(format!("Not a directory: {}", dest_dir));
+    }
+    let job = jobs.start(request_id, JobKind::DeviceTransfer);
+    let result = run_transfer(&handle, &job, &adb, &serial, &remote_paths, &dest_dir, true);
+    jobs.finish(request_id);
+    result
+}
+
+/// Copies local files or folders into `remote_dir` on the device
+#[tauri::command]
+pub async fn adb_push(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    settings: State<'_, SharedAppSettings>,
+    serial: String,
+    local_paths: Vec<String>,
+    remote_dir: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let adb = adb_executable(&settings).await?;
+    let job = jobs.start(request_id, JobKind::DeviceTransfer);
+    let result = run_transfer(
+        &handle,
+        &job,
+        &adb,
+        &serial,
+        &local_paths,
+        &remote_dir,
+        false,
+    );
+    jobs.finish(request_id);
+    result
+}
+
+fn run_transfer(
+    handle: &AppHandle,
+    job: &JobHandle,
+    adb: &Path,
+    serial: &str,
+    sources: &[String],
+    dest: &str,
+    pull: bool,
+) -> Result<(), String> {
+    let request_id = job.request_id;
+    if sources.is_empty() {
+        return Err("No paths provided".into());
+    }
+
+    // pulls land in a staging folder first, so a cancelled or crashed pull never
+    // leaves a partial item that looks complete
+    let staging = if pull {
+        Some(
+            StagingDir::new(handle, Path::new(dest))
+                .map_err(|e| format!("Failed to prepare {}: {}", dest, e))?,
+        )
+    } else {
+        None
+    };
+
+    let total = sources.len() as u64;
+    let mut transferred: Vec<String> = Vec::new();
+    let mut failed: Vec<serde_json::Value> = Vec::new();
+
+    for (index, source) in sources.iter().enumerate() {
+        job.report_progress(index as u64, total);
+        let _ = handle.emit(
+            "adb-progress",
+            serde_json::json!({
+                "request_id": request_id,
+                "done": index,
+                "total": total,
+                "current": source,
+            }),
+        );
+
+        let name = source
+            .trim_end_matches(['/', '\\'])
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(source)
+            .to_string();
+        let target = if let Some(staging) = &staging {
+            staging.path().join(&name).display().to_string()
+        } else {
+            format!("{}/{}", dest.trim_end_matches('/'), name)
+        };
+        let verb = if pull { "pull" } else { "push" };
+
+        let child = adb_command(adb, Some(serial))
+            .args([verb, source.as_str(), target.as_str()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run adb: {}", e))?;
+
+        match wait_cancellable(job, child) {
+            Some(Ok(())) => match &staging {
+                Some(staging) => {
+                    let final_path = unique_path(&Path::new(dest).join(&name));
+                    match staging.commit(Path::new(&target), &final_path, false) {
+                        Ok(()) => transferred.push(final_path.display().to_string()),
+                        Err(e) => failed
+                            .push(serde_json::json!({ "path": source, "error": e.to_string() })),
+                    }
+                }
+                None => transferred.push(target),
+            },
+            Some(Err(e)) => failed.push(serde_json::json!({ "path": source, "error": e })),
+            None => {
+                // the staging folder takes the half-pulled item with it
+                let _ = handle.emit(
+                    "adb-cancelled",
+                    serde_json::json!({ "request_id": request_id }),
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    job.report_progress(total, total);
+    let _ = handle.emit(
+        "adb-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "direction": if pull { "pull" } else { "push" },
+            "transferred": transferred,
+            "failed": failed,
+        }),
+    );
+
+    let verb = if pull { "Copied from" } else { "Copied to" };
+    notify_job_finished(
+        handle,
+        job.kind,
+        failed.is_empty(),
+        &format!(
+            "{} device: {} of {} item(s)",
+            verb,
+            transferred.len(),
+            total
+        ),
+    );
+    Ok(())
+}
+
+/// Waits for an adb transfer, killing it when the job is cancelled (`None`)
+fn wait_cancellable(job: &JobHandle, mut child: Child) -> Option<Result<(), String>> {
+    loop {
+        if job.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Some(Err(e.to_string())),
+        }
+    }
+
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(e) => return Some(Err(e.to_string())),
+    };
+    if output.status.success() {
+        Some(Ok(()))
+    } else {
+        Some(Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_string()))
+    }
+}
+
+/// Resolves adb when the integration is switched on in settings
+async fn adb_executable(settings: &State<'_, SharedAppSettings>) -> Result<PathBuf, String> {
+    if !settings.0.read().await.integrations.adb_enabled {
+        return Err("ADB integration is disabled".into());
+    }
+    locate_adb().ok_or_else(|| "adb was not found on PATH or in the Android SDK".into())
+}
+
+/// Finds adb on PATH or in the Android SDK's platform-tools
+fn locate_adb() -> Option<PathBuf> {
+    let name = if cfg!(windows) { "adb.exe" } else { "adb" };
+    let mut dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|p| env::split_paths(&p).collect())
+        .unwrap_or_default();
+    for var in ["ANDROID_HOME", "ANDROID_SDK_ROOT"] {
+        if let Some(sdk) = env::var_os(var) {
+            dirs.push(PathBuf::from(sdk).join("platform-tools"));
+        }
+    }
+    // Android Studio's default SDK location
+    let default_sdk = if cfg!(windows) {
+        dirs_next::data_local_dir().map(|d| d.join("Android").join("Sdk"))
+    } else if cfg!(target_os = "macos") {
+        dirs_next::home_dir().map(|h| h.join("Library/Android/sdk"))
+    } else {
+        dirs_next::home_dir().map(|h| h.join("Android/Sdk"))
+    };
+    dirs.extend(default_sdk.map(|sdk| sdk.join("platform-tools")));
+    dirs.into_iter().map(|d| d.join(name)).find(|p| p.is_file())
+}
+
+fn adb_command(adb: &Path, serial: Option<&str>) -> Command {
+    let mut cmd = Command::new(adb);
+    if let Some(serial) = serial {
+        cmd.args(["-s", serial]);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd
+}
+
+/// Runs a short adb command and returns its stdout
+fn run_adb(adb: &Path, serial: Option<&str>, args: &[&str]) -> Result<String, String> {
+    let output = adb_command(adb, serial)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run adb: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "adb failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parses `<serial> <state> product:.. model:.. device:..`
+fn parse_device_line(line: &str) -> Option<AdbDevice> {
+    let mut fields = line.split_whitespace();
+    let serial = fields.next()?.to_string();
+    let state = fields.next()?.to_string();
+    let mut device = AdbDevice {
+        serial,
+        state,
+        model: None,
+        product: None,
+    };
+    for field in fields {
+        match field.split_once(':') {
+            Some(("model", value)) => device.model = Some(value.replace('_', " ")),
+            Some(("product", value)) => device.product = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Some(device)
+}
+
+/// Parses a toybox `ls -la` row: `drwxrwx--x 4 root sdcard_rw 3452 2024-01-31 12:00 Name`
+fn parse_ls_line(line: &str) -> Option<RemoteEntry> {
+    let mut rest = line.trim_start();
+    let mut fields: Vec<&str> = Vec::with_capacity(7);
+    while fields.len() < 7 {
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    let kind = fields[0].chars().next()?;
+    if !matches!(kind, 'd' | '-' | 'l') {
+        return None;
+    }
+
+    let (name, link_target) = match rest.split_once(" -> ") {
+        Some((name, target)) if kind == 'l' => (name, Some(target.to_string())),
+        _ => (rest, None),
+    };
+    if name.is_empty() || name == "." || name == ".." {
+        return None;
+    }
+
+    Some(RemoteEntry {
+        name: name.to_string(),
+        is_dir: kind == 'd',
+        size: (kind == '-').then(|| fields[4].parse().ok()).flatten(),
+        modified: parse_ls_time(fields[5], fields[6]),
+        link_target,
+    })
+}
+
+/// Converts `YYYY-MM-DD HH:MM` (device local time, treated as UTC) to Unix seconds
+fn parse_ls_time(date: &str, time: &str) -> Option<u64> {
+    let mut date = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (hour, minute) = time.split_once(':')?;
+    let (hour, minute): (i64, i64) = (hour.parse().ok()?, minute.parse().ok()?);
+
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60;
+    u64::try_from(secs).ok()
+}
+
+/// Days since the Unix epoch for a civil date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Quotes a path for the device shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
@@ -0,0 +1,203 @@
+use serde::Serialize;
+use std::{
+    fs::{self, File, FileTimes},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Whether this platform can change a file's creation time
+const CAN_SET_CREATED: bool = cfg!(any(target_os = "windows", target_os = "macos"));
+
+/// A path a batch edit could not be applied to
+#[derive(Serialize, Clone, Debug)]
+pub struct AttributeFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Seconds since the Unix epoch (negative before it) as a `SystemTime`
+fn from_unix(secs: i64) -> SystemTime {
+    if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    }
+}
+
+/// Opens `path` with just enough access to change its times; folders included
+#[cfg(target_os = "windows")]
+fn open_for_times(path: &Path) -> std::io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+    const FILE_WRITE_ATTRIBUTES: u32 = 0x0100;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+
+    fs::OpenOptions::new()
+        .access_mode(FILE_WRITE_ATTRIBUTES)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+}
+
+/// Opens `path` with just enough access to change its times; folders included.
+/// futimens only needs ownership, not a writable descriptor.
+#[cfg(not(target_os = "windows"))]
+fn open_for_times(path: &Path) -> std::io::Result<File> {
+    File::open(path)
+}
+
+#[cfg(target_os = "windows")]
+fn with_created(times: FileTimes, created: SystemTime) -> FileTimes {
+    use std::os::windows::fs::FileTimesExt;
+    times.set_created(created)
+}
+
+#[cfg(target_os = "macos")]
+fn with_created(times: FileTimes, created: SystemTime) -> FileTimes {
+    use std::os::macos::fs::FileTimesExt;
+    times.set_created(created)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn with_created(times: FileTimes, _created: SystemTime) -> FileTimes {
+    times
+}
+
+/// Applies whichever times are given to one path; the others stay as they are
+fn apply_times(
+    path: &Path,
+    created: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    accessed: Option<SystemTime>,
+) -> std::io::Result<()> {
+    let mut times = FileTimes::new();
+    if let Some(modified) = modified {
+        times = times.set_modified(modified);
+    }
+    if let Some(accessed) = accessed {
+        times = times.set_accessed(accessed);
+    }
+    if let Some(created) = created {
+        times = with_created(times, created);
+    }
+    open_for_times(path)?.set_times(times)
+}
+
+/// Runs `apply` on every path and collects the ones that failed
+fn for_each_path(
+    paths: &[String],
+    mut apply: impl FnMut(&Path) -> std::io::Result<()>,
+) -> Vec<AttributeFailure> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            apply(Path::new(path)).err().map(|e| AttributeFailure {
+                path: path.clone(),
+                error: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Sets the created, modified and/or accessed time of every path, in seconds since
+/// the Unix epoch. Times left out are not touched. Returns the paths that failed.
+#[tauri::command]
+pub async fn set_timestamps(
+    paths: Vec<String>,
+    created: Option<i64>,
+    modified: Option<i64>,
+    accessed: Option<i64>,
+) -> Result<Vec<AttributeFailure>, String> {
+    if created.is_some() && !CAN_SET_CREATED {
+        return Err("Creation times can't be changed on this platform".into());
+    }
+    let (created, modified, accessed) = (
+        created.map(from_unix),
+        modified.map(from_unix),
+        accessed.map(from_unix),
+    );
+    Ok(for_each_path(&paths, |path| {
+        apply_times(path, created, modified, accessed)
+    }))
+}
+
+/// Moves the modified (and, where supported, created) time of every path by
+/// `offset_secs`, e.g. to fix photos taken with a camera clock that was off
+#[tauri::command]
+pub async fn shift_timestamps(
+    paths: Vec<String>,
+    offset_secs: i64,
+) -> Result<Vec<AttributeFailure>, String> {
+    let offset = Duration::from_secs(offset_secs.unsigned_abs());
+    let shift = |time: SystemTime| {
+        if offset_secs >= 0 {
+            time.checked_add(offset)
+        } else {
+            time.checked_sub(offset)
+        }
+    };
+
+    Ok(for_each_path(&paths, |path| {
+        let meta = fs::metadata(path)?;
+        let modified = meta.modified().ok().and_then(shift);
+        let created = meta
+            .created()
+            .ok()
+            .filter(|_| CAN_SET_CREATED)
+            .and_then(shift);
+        apply_times(path, created, modified, None)
+    }))
+}
+
+#[cfg(unix)]
+fn apply_readonly(path: &Path, readonly: bool) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut perms = fs::metadata(path)?.permissions();
+    let mode = perms.mode();
+    // clear every write bit, but only give write access back to the owner
+    perms.set_mode(if readonly {
+        mode & !0o222
+    } else {
+        mode | 0o200
+    });
+    fs::set_permissions(path, perms)
+}
+
+#[cfg(not(unix))]
+fn apply_readonly(path: &Path, readonly: bool) -> std::io::Result<()> {
+    let mut perms = fs::metadata(path)?.permissions();
+    perms.set_readonly(readonly);
+    fs::set_permissions(path, perms)
+}
+
+#[cfg(target_os = "windows")]
+fn apply_hidden(path: &Path, hidden: bool) -> std::io::Result<()> {
+    crate::filesys::os::windows::set_hidden(path, hidden)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn apply_hidden(_path: &Path, _hidden: bool) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Sets or clears the read-only and/or hidden attribute on every path. Attributes
+/// left out are not touched. Hidden is a Windows attribute; elsewhere files are
+/// hidden by a leading dot, which is a rename. Returns the paths that failed.
+#[tauri::command]
+pub async fn set_attributes(
+    paths: Vec<String>,
+    readonly: Option<bool>,
+    hidden: Option<bool>,
+) -> Result<Vec<AttributeFailure>, String> {
+    if hidden.is_some() && !cfg!(target_os = "windows") {
+        return Err("The hidden attribute only exists on Windows".into());
+    }
+    Ok(for_each_path(&paths, |path| {
+        if let Some(hidden) = hidden {
+            apply_hidden(path, hidden)?;
+        }
+        if let Some(readonly) = readonly {
+            apply_readonly(path, readonly)?;
+        }
+        Ok(())
+    }))
+}
@@ -0,0 +1,321 @@
+use serde::Serialize;
+use std::{fs::Metadata, sync::Arc};
+
+use tauri::{AppHandle, State};
+
+use crate::filesys::stream::jobs::{JobHandle, JobKind, JobStreamState};
+use crate::util::notify::notify_job_finished;
+
+/// Bytes hydrated per Cloud Files call, so large downloads report progress and can be cancelled
+#[cfg(target_os = "windows")]
+const HYDRATE_CHUNK: u64 = 8 * 1024 * 1024;
+
+/// Sync state of a file managed by a cloud provider (OneDrive, Dropbox, iCloud, ...)
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudState {
+    /// A placeholder; reading the contents downloads them
+    OnlineOnly,
+    /// Always kept on this device
+    Pinned,
+    /// Downloaded, but the provider may free the space again
+    Available,
+}
+
+/// Cloud state from the placeholder attributes set by the Cloud Files API
+/// (Windows) or the dataless flag (macOS). `None` for ordinary local files.
+#[cfg(target_os = "windows")]
+pub fn cloud_state(meta: &Metadata) -> Option<CloudState> {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x0000_1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+    const FILE_ATTRIBUTE_PINNED: u32 = 0x0008_0000;
+    const FILE_ATTRIBUTE_UNPINNED: u32 = 0x0010_0000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+
+    let attributes = meta.file_attributes();
+    if attributes
+        & (FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+            | FILE_ATTRIBUTE_RECALL_ON_OPEN
+            | FILE_ATTRIBUTE_OFFLINE)
+        != 0
+    {
+        Some(CloudState::OnlineOnly)
+    } else if attributes & FILE_ATTRIBUTE_PINNED != 0 {
+        Some(CloudState::Pinned)
+    } else if attributes & FILE_ATTRIBUTE_UNPINNED != 0 {
+        Some(CloudState::Available)
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn cloud_state(meta: &Metadata) -> Option<CloudState> {
+    use std::os::macos::fs::MetadataExt;
+    const SF_DATALESS: u32 = 0x4000_0000;
+
+    (meta.st_flags() & SF_DATALESS != 0).then_some(CloudState::OnlineOnly)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn cloud_state(_meta: &Metadata) -> Option<CloudState> {
+    None
+}
+
+/// "Always keep on this device" (`keep_local`) or "Free up space" for the given
+/// files and folders. Keeping local downloads every online-only file below them,
+/// reporting bytes through `cloud-progress`; cancel with `cancel_job(request_id)`.
+#[tauri::command]
+pub async fn set_cloud_availability(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    paths: Vec<String>,
+    keep_local: bool,
+    request_id: u64,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("No paths provided".into());
+    }
+
+    let job = jobs.start(request_id, JobKind::CloudSync);
+    let result = run_cloud_job(&handle, &job, &paths, keep_local);
+    jobs.finish(request_id);
+    if let Err(e) = &result {
+        notify_job_finished(&handle, JobKind::CloudSync, false, e);
+    }
+    result
+}
+
+#[cfg(target_os = "windows")]
+fn run_cloud_job(
+    handle: &AppHandle,
+    job: &JobHandle,
+    paths: &[String],
+    keep_local: bool,
+) -> Result<(), String> {
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+    use tauri::Emitter;
+
+    let request_id = job.request_id;
+    let mut failed: Vec<serde_json::Value> = Vec::new();
+
+    // the pin state alone makes the provider sync in the background; the explicit
+    // hydrate/dehydrate below is what gives the user progress
+    let mut files: Vec<(PathBuf, u64)> = Vec::new();
+    for p in paths {
+        let path = PathBuf::from(p);
+        if let Err(e) = set_pin_state(&path, keep_local) {
+            failed.push(serde_json::json!({ "path": p, "error": e }));
+            continue;
+        }
+        files.extend(placeholder_files(&path, keep_local));
+    }
+
+    let total: u64 = files.iter().map(|(_, size)| size).sum();
+    let mut done: u64 = 0;
+    let mut last_emit = Instant::now();
+    let mut processed = 0usize;
+
+    for (path, size) in &files {
+        let outcome = if keep_local {
+            hydrate_file(path, *size, |hydrated| {
+                if job.is_cancelled() {
+                    return false;
+                }
+                job.report_progress(done + hydrated, total);
+                if last_emit.elapsed() >= Duration::from_millis(100) {
+                    last_emit = Instant::now();
+                    let _ = handle.emit(
+                        "cloud-progress",
+                        serde_json::json!({
+                            "request_id": request_id,
+                            "path": path.display().to_string(),
+                            "done": done + hydrated,
+                            "total": total,
+                        }),
+                    );
+                }
+                true
+            })
+        } else {
+            dehydrate_file(path).map(|_| true)
+        };
+
+        match outcome {
+            Ok(true) => processed += 1,
+            Ok(false) => {
+                let _ = handle.emit(
+                    "cloud-cancelled",
+                    serde_json::json!({ "request_id": request_id }),
+                );
+                return Ok(());
+            }
+            Err(e) => failed.push(serde_json::json!({
+                "path": path.display().to_string(),
+                "error": e,
+            })),
+        }
+        done += size;
+        job.report_progress(done, total);
+        if job.is_cancelled() {
+            let _ = handle.emit(
+                "cloud-cancelled",
+                serde_json::json!({ "request_id": request_id }),
+            );
+            return Ok(());
+        }
+    }
+
+    let _ = handle.emit(
+        "cloud-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "keep_local": keep_local,
+            "processed": processed,
+            "failed": failed,
+        }),
+    );
+
+    let verb = if keep_local { "Downloaded" } else { "Freed up" };
+    notify_job_finished(
+        handle,
+        job.kind,
+        failed.is_empty(),
+        &format!("{} {} of {} file(s)", verb, processed, files.len()),
+    );
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn run_cloud_job(
+    _handle: &AppHandle,
+    _job: &JobHandle,
+    _paths: &[String],
+    _keep_local: bool,
+) -> Result<(), String> {
+    Err("Managing cloud file availability is only supported on Windows".into())
+}
+
+/// Opens a file or folder without reading it, which would trigger a download
+#[cfg(target_os = "windows")]
+fn open_placeholder(path: &std::path::Path, write: bool) -> std::io::Result<std::fs::File> {
+    use std::os::windows::fs::OpenOptionsExt;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(write)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+}
+
+/// Pins or unpins through the Cloud Files API, falling back to `attrib +P/+U`
+/// for sync clients (older Dropbox, OneDrive on FAT drives) that only honour attributes
+#[cfg(target_os = "windows")]
+fn set_pin_state(path: &std::path::Path, pinned: bool) -> Result<(), String> {
+    use crate::filesys::os::windows::cloud_set_pin_state;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let is_dir = path.is_dir();
+    let cf_result =
+        open_placeholder(path, false).and_then(|file| cloud_set_pin_state(&file, pinned, is_dir));
+    if cf_result.is_ok() {
+        return Ok(());
+    }
+
+    let flags: &[&str] = if pinned { &["+P", "-U"] } else { &["-P", "+U"] };
+    let mut targets = vec![path.display().to_string()];
+    if is_dir {
+        targets.push(path.join("*").display().to_string());
+    }
+    for target in targets {
+        let status = Command::new("attrib")
+            .args(flags)
+            .arg(&target)
+            .args(if is_dir { &["/S", "/D"][..] } else { &[][..] })
+            .creation_flags(CREATE_NO_WINDOW)
+            .status()
+            .map_err(|e| format!("Failed to run attrib: {}", e))?;
+        if !status.success() {
+            return Err(format!(
+                "Could not change the sync state of {}",
+                path.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Files at or below `path` that still need work: online-only ones when keeping
+/// local, downloaded cloud files when freeing up space
+#[cfg(target_os = "windows")]
+fn placeholder_files(path: &std::path::Path, keep_local: bool) -> Vec<(std::path::PathBuf, u64)> {
+    let wanted = |meta: &Metadata| match cloud_state(meta) {
+        Some(CloudState::OnlineOnly) => keep_local,
+        Some(_) => !keep_local,
+        None => false,
+    };
+
+    if !path.is_dir() {
+        return std::fs::symlink_metadata(path)
+            .ok()
+            .filter(wanted)
+            .map(|meta| vec![(path.to_path_buf(), meta.len())])
+            .unwrap_or_default();
+    }
+
+    jwalk::WalkDir::new(path)
+        .follow_links(false)
+        .skip_hidden(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let meta = e.metadata().ok().filter(wanted)?;
+            Some((e.path(), meta.len()))
+        })
+        .collect()
+}
+
+/// Downloads a placeholder in chunks, calling `progress` with the bytes hydrated
+/// so far. Returns `Ok(false)` once `progress` asks to stop.
+#[cfg(target_os = "windows")]
+fn hydrate_file(
+    path: &std::path::Path,
+    size: u64,
+    mut progress: impl FnMut(u64) -> bool,
+) -> Result<bool, String> {
+    use crate::filesys::os::windows::cloud_hydrate_range;
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = open_placeholder(path, false).map_err(|e| e.to_string())?;
+    let mut offset = 0;
+    let mut buf: Vec<u8> = Vec::new();
+    while offset < size {
+        let length = HYDRATE_CHUNK.min(size - offset);
+        if cloud_hydrate_range(&file, offset, length).is_err() {
+            // not a Cloud Files placeholder: reading through the provider's filter hydrates it too
+            buf.resize(length as usize, 0);
+            file.seek(SeekFrom::Start(offset))
+                .map_err(|e| e.to_string())?;
+            file.read_exact(&mut buf).map_err(|e| e.to_string())?;
+        }
+        offset += length;
+        if !progress(offset) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(target_os = "windows")]
+fn dehydrate_file(path: &std::path::Path) -> Result<(), String> {
+    use crate::filesys::os::windows::cloud_dehydrate;
+
+    let file = open_placeholder(path, true).map_err(|e| e.to_string())?;
+    cloud_dehydrate(&file).map_err(|e| e.to_string())
+}
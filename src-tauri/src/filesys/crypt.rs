@@ -0,0 +1,276 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    iter,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use age::secrecy::SecretString;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::{
+    actions::unique_path,
+    stream::jobs::{JobHandle, JobKind, JobStreamState},
+};
+use crate::util::notify::notify_job_finished;
+
+/// Extension appended to encrypted files
+const AGE_EXT: &str = "age";
+
+/// Buffer size used when streaming through the encryptor
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Minimum delay between two `crypt-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Outcome of processing one file
+enum CryptOutcome {
+    Done(PathBuf),
+    Cancelled,
+}
+
+/// Bytes processed across the whole job, used for `crypt-progress`
+struct CryptProgress {
+    done: u64,
+    total: u64,
+    last_emit: Instant,
+}
+
+/// Encrypts each file into `<name>.age` next to it using an age passphrase
+/// (scrypt) recipient. Originals are left in place. Cancel with `cancel_job(request_id)`.
+#[tauri::command]
+pub async fn encrypt_files(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    paths: Vec<String>,
+    passphrase: String,
+    request_id: u64,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("No paths provided".into());
+    }
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".into());
+    }
+
+    let job = jobs.start(request_id, JobKind::Encrypt);
+    let result = run_crypt_job(&handle, &job, &paths, &SecretString::from(passphrase), true);
+    jobs.finish(request_id);
+    if let Err(e) = &result {
+        notify_job_finished(&handle, JobKind::Encrypt, false, e);
+    }
+    result
+}
+
+/// Decrypts an age file encrypted with a passphrase. The output drops the
+/// `.age` extension and never overwrites an existing file.
+#[tauri::command]
+pub async fn decrypt_file(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    path: String,
+    passphrase: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let job = jobs.start(request_id, JobKind::Decrypt);
+    let result = run_crypt_job(
+        &handle,
+        &job,
+        std::slice::from_ref(&path),
+        &SecretString::from(passphrase),
+        false,
+    );
+    jobs.finish(request_id);
+    if let Err(e) = &result {
+        notify_job_finished(&handle, JobKind::Decrypt, false, e);
+    }
+    result
+}
+
+fn run_crypt_job(
+    handle: &AppHandle,
+    job: &JobHandle,
+    paths: &[String],
+    passphrase: &SecretString,
+    encrypt: bool,
+) -> Result<(), String> {
+    let request_id = job.request_id;
+
+    let mut files: Vec<(PathBuf, u64)> = Vec::new();
+    for p in paths {
+        let path = PathBuf::from(p);
+        let meta = fs::metadata(&path).map_err(|e| format!("{}: {}", p, e))?;
+        if !meta.is_file() {
+            return Err(format!("Not a file: {}", p));
+        }
+        files.push((path, meta.len()));
+    }
+
+    let mut progress = CryptProgress {
+        done: 0,
+        total: files.iter().map(|(_, size)| size).sum(),
+        last_emit: Instant::now(),
+    };
+    let mut outputs: Vec<String> = Vec::new();
+    let mut failed: Vec<serde_json::Value> = Vec::new();
+
+    for (path, size) in &files {
+        let dest = if encrypt {
+            let mut name = path.file_name().unwrap_or_default().to_os_string();
+            name.push(format!(".{}", AGE_EXT));
+            unique_path(&path.with_file_name(name))
+        } else {
+            let is_age = path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case(AGE_EXT));
+            let plain = if is_age {
+                path.with_extension("")
+            } else {
+                path.with_file_name(format!(
+                    "{} (decrypted)",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ))
+            };
+            unique_path(&plain)
+        };
+
+        let start = progress.done;
+        let outcome = crypt_file(handle, job, path, &dest, passphrase, encrypt, &mut progress);
+        match outcome {
+            Ok(CryptOutcome::Done(out)) => outputs.push(out.display().to_string()),
+            Ok(CryptOutcome::Cancelled) => {
+                let _ = fs::remove_file(&dest);
+                let _ = handle.emit(
+                    "crypt-cancelled",
+                    serde_json::json!({ "request_id": request_id }),
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&dest);
+                failed.push(serde_json::json!({
+                    "path": path.display().to_string(),
+                    "error": e,
+                }));
+            }
+        }
+        progress.done = start + size;
+    }
+
+    let _ = handle.emit(
+        "crypt-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "encrypt": encrypt,
+            "outputs": outputs,
+            "failed": failed,
+        }),
+    );
+
+    let verb = if encrypt { "Encrypted" } else { "Decrypted" };
+    notify_job_finished(
+        handle,
+        job.kind,
+        failed.is_empty(),
+        &format!("{} {} of {} file(s)", verb, outputs.len(), files.len()),
+    );
+    Ok(())
+}
+
+/// Streams `src` through the age encryptor/decryptor into `dest`
+fn crypt_file(
+    handle: &AppHandle,
+    job: &JobHandle,
+    src: &Path,
+    dest: &Path,
+    passphrase: &SecretString,
+    encrypt: bool,
+    progress: &mut CryptProgress,
+) -> Result<CryptOutcome, String> {
+    let input = File::open(src).map_err(|e| e.to_string())?;
+    let output = File::create(dest).map_err(|e| e.to_string())?;
+
+    let finished = if encrypt {
+        let encryptor = age::Encryptor::with_user_passphrase(passphrase.clone());
+        let mut writer = encryptor
+            .wrap_output(BufWriter::new(output))
+            .map_err(|e| e.to_string())?;
+        let finished =
+            pump(handle, job, src, input, &mut writer, progress).map_err(|e| e.to_string())?;
+        if finished {
+            writer
+                .finish()
+                .and_then(|mut w| w.flush())
+                .map_err(|e| e.to_string())?;
+        }
+        finished
+    } else {
+        let decryptor =
+            age::Decryptor::new_buffered(BufReader::new(input)).map_err(|e| e.to_string())?;
+        if !decryptor.is_scrypt() {
+            return Err("File is not passphrase-encrypted".into());
+        }
+        let identity = age::scrypt::Identity::new(passphrase.clone());
+        let reader = decryptor
+            .decrypt(iter::once(&identity as &dyn age::Identity))
+            .map_err(|e| match e {
+                age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys => {
+                    "Incorrect passphrase".to_string()
+                }
+                e => e.to_string(),
+            })?;
+        let mut writer = BufWriter::new(output);
+        let finished =
+            pump(handle, job, src, reader, &mut writer, progress).map_err(|e| e.to_string())?;
+        if finished {
+            writer.flush().map_err(|e| e.to_string())?;
+        }
+        finished
+    };
+
+    Ok(if finished {
+        CryptOutcome::Done(dest.to_path_buf())
+    } else {
+        CryptOutcome::Cancelled
+    })
+}
+
+/// Copies `reader` into `writer` in chunks. Returns `Ok(false)` if the job was cancelled.
+fn pump(
+    handle: &AppHandle,
+    job: &JobHandle,
+    src: &Path,
+    mut reader: impl Read,
+    writer: &mut impl Write,
+    progress: &mut CryptProgress,
+) -> io::Result<bool> {
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        if job.is_cancelled() {
+            return Ok(false);
+        }
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(true);
+        }
+        writer.write_all(&buf[..n])?;
+
+        // decrypted output is slightly smaller than the input, so clamp to the total
+        progress.done = (progress.done + n as u64).min(progress.total);
+        job.report_progress(progress.done, progress.total);
+        if progress.last_emit.elapsed() >= PROGRESS_INTERVAL {
+            progress.last_emit = Instant::now();
+            let _ = handle.emit(
+                "crypt-progress",
+                serde_json::json!({
+                    "request_id": job.request_id,
+                    "processed_bytes": progress.done,
+                    "total_bytes": progress.total,
+                    "current": src.display().to_string(),
+                }),
+            );
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use jwalk::WalkDir;
+use tauri::{AppHandle, State};
+
+use crate::search::engine::SearchState;
+use crate::util::hashing::{hash_file_cached, ChecksumAlgo, ChecksumCache};
+
+/// Output format of `export_listing`
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingFormat {
+    Csv,
+    Json,
+}
+
+/// One exported row
+#[derive(Debug, Serialize)]
+struct ListingRow {
+    path: String,
+    name: String,
+    is_dir: bool,
+    size: Option<u64>,
+    /// Seconds since the Unix epoch
+    modified: Option<u64>,
+    /// xxh3 of the file contents, hex encoded
+    hash: Option<String>,
+}
+
+/// Writes an inventory of a directory, or of the results of search `request_id`,
+/// to `dest` as CSV or JSON. Returns the number of rows written.
+#[tauri::command]
+pub async fn export_listing(
+    handle: AppHandle,
+    search: State<'_, Arc<SearchState>>,
+    path_or_search_id: String,
+    format: ListingFormat,
+    dest: String,
+    recursive: Option<bool>,
+    include_hashes: Option<bool>,
+) -> Result<usize, String> {
+    let hashes = include_hashes.unwrap_or(false);
+    let cache = ChecksumCache::open(&handle);
+    let source = PathBuf::from(&path_or_search_id);
+
+    let rows = if source.is_dir() {
+        list_directory(&cache, &source, recursive.unwrap_or(false), hashes)
+    } else if let Ok(request_id) = path_or_search_id.parse::<u64>() {
+        let hits = search
+            .results(request_id)
+            .ok_or("Search results are no longer available")?;
+        hits.into_iter()
+            .map(|hit| {
+                // entries inside archives can't be hashed without extracting them
+                let hashable = hashes && !hit.is_dir && hit.archive.is_none();
+                ListingRow {
+                    hash: hashable
+                        .then(|| hex_hash(&cache, Path::new(&hit.path)))
+                        .flatten(),
+                    modified: hit.date_modified.and_then(unix_secs),
+                    path: hit.path,
+                    name: hit.name,
+                    is_dir: hit.is_dir,
+                    size: hit.size,
+                }
+            })
+            .collect()
+    } else {
+        return Err(format!("Not a directory or search: {}", path_or_search_id));
+    };
+
+    let file = File::create(&dest).map_err(|e| format!("Failed to create {}: {}", dest, e))?;
+    let mut out = BufWriter::new(file);
+    match format {
+        ListingFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &rows).map_err(|e| e.to_string())?
+        }
+        ListingFormat::Csv => write_csv(&mut out, &rows).map_err(|e| e.to_string())?,
+    }
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(rows.len())
+}
+
+fn list_directory(
+    cache: &ChecksumCache,
+    root: &Path,
+    recursive: bool,
+    hashes: bool,
+) -> Vec<ListingRow> {
+    WalkDir::new(root)
+        .follow_links(false)
+        .skip_hidden(false)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .sort(true)
+        .into_iter()
+        .skip(1)
+        .filter_map(|e| e.ok())
+        .map(|entry| {
+            let path = entry.path();
+            let is_dir = entry.file_type().is_dir();
+            let meta = entry.metadata().ok();
+            ListingRow {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_dir,
+                size: meta.as_ref().filter(|_| !is_dir).map(|m| m.len()),
+                modified: meta
+                    .as_ref()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(unix_secs),
+                hash: (hashes && !is_dir)
+                    .then(|| hex_hash(cache, &path))
+                    .flatten(),
+                path: path.display().to_string(),
+            }
+        })
+        .collect()
+}
+
+fn write_csv(out: &mut impl Write, rows: &[ListingRow]) -> std::io::Result<()> {
+    writeln!(out, "path,name,type,size,modified,hash")?;
+    for row in rows {
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            csv_field(&row.path),
+            csv_field(&row.name),
+            if row.is_dir { "directory" } else { "file" },
+            row.size.map(|s| s.to_string()).unwrap_or_default(),
+            row.modified.map(|m| m.to_string()).unwrap_or_default(),
+            row.hash.as_deref().unwrap_or_default(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// xxh3 through the shared hashing service, so files hashed before are not read again
+fn hex_hash(cache: &ChecksumCache, path: &Path) -> Option<String> {
+    hash_file_cached(cache, &path.to_string_lossy(), ChecksumAlgo::Xxh3, || true)
+        .ok()
+        .flatten()
+}
+
+fn unix_secs(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
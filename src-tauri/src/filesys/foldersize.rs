@@ -0,0 +1,256 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::util::{caches::SharedAppSettings, power::should_throttle};
+
+/// How long a cached size is trusted while the folder's own mtime is unchanged.
+/// Files growing deep inside a folder don't touch its mtime, so entries expire.
+const SIZE_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Recursive size of a folder
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct FolderSize {
+    pub bytes: u64,
+    pub files: u64,
+    pub folders: u64,
+}
+
+impl FolderSize {
+    fn add(&mut self, other: FolderSize) {
+        self.bytes = self.bytes.saturating_add(other.bytes);
+        self.files += other.files;
+        self.folders += other.folders;
+    }
+}
+
+/// Queue of folders whose size the sidebar tree wants, worked off by one
+/// background thread. Expanded nodes go to the front and preempt the walk in
+/// progress, which resumes later from the subfolders it already cached.
+#[derive(Default)]
+pub struct FolderSizeState {
+    queue: Mutex<VecDeque<PathBuf>>,
+    wake: Condvar,
+    preempt: AtomicBool,
+}
+
+impl FolderSizeState {
+    /// Queues `paths`; `priority` puts them ahead of everything else
+    pub fn request(&self, paths: impl IntoIterator<Item = PathBuf>, priority: bool) {
+        let mut queue = self.queue.lock().unwrap();
+        let mut added = false;
+        for path in paths {
+            if let Some(i) = queue.iter().position(|p| *p == path) {
+                if !priority {
+                    continue;
+                }
+                queue.remove(i);
+            }
+            if priority {
+                queue.push_front(path);
+            } else {
+                queue.push_back(path);
+            }
+            added = true;
+        }
+        if added && priority {
+            self.preempt.store(true, Ordering::Relaxed);
+        }
+        self.wake.notify_one();
+    }
+
+    fn clear(&self) {
+        self.queue.lock().unwrap().clear();
+    }
+
+    fn next(&self) -> PathBuf {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if let Some(path) = queue.pop_front() {
+                self.preempt.store(false, Ordering::Relaxed);
+                return path;
+            }
+            queue = self.wake.wait(queue).unwrap();
+        }
+    }
+}
+
+/// Whether the sidebar tree shows folder sizes
+pub fn tree_sizes_enabled(handle: &AppHandle) -> bool {
+    handle
+        .try_state::<SharedAppSettings>()
+        .and_then(|settings| {
+            settings
+                .0
+                .try_read()
+                .ok()
+                .map(|s| s.behavior.tree_folder_sizes)
+        })
+        .unwrap_or(false)
+}
+
+/// Queues the folders among `paths` for the tree if folder sizes are on. On
+/// battery only expanded folders (`priority`) are walked.
+pub fn request_tree_sizes(handle: &AppHandle, paths: &[String], priority: bool) {
+    if !tree_sizes_enabled(handle) || (!priority && should_throttle(handle)) {
+        return;
+    }
+    if let Some(state) = handle.try_state::<Arc<FolderSizeState>>() {
+        state.request(paths.iter().map(PathBuf::from), priority);
+    }
+}
+
+/// Location of the folder size cache DB, next to the thumbnail cache
+fn get_size_db_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("caches");
+    fs::create_dir_all(&path).ok();
+    path.push("folder_sizes.db");
+    path
+}
+
+fn open_size_db(handle: &AppHandle) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(get_size_db_path(handle))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS folder_sizes (
+            path TEXT PRIMARY KEY,
+            mtime INTEGER NOT NULL,
+            bytes INTEGER NOT NULL,
+            files INTEGER NOT NULL,
+            folders INTEGER NOT NULL,
+            scanned_at INTEGER NOT NULL
+        );
+        PRAGMA journal_mode=WAL;
+        PRAGMA synchronous=NORMAL;",
+    )?;
+    Ok(conn)
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn cached_size(conn: &Connection, path: &str, mtime: i64) -> Option<FolderSize> {
+    let fresh_after = unix_secs(SystemTime::now()) - SIZE_CACHE_TTL.as_secs() as i64;
+    conn.query_row(
+        "SELECT bytes, files, folders FROM folder_sizes
+         WHERE path = ?1 AND mtime = ?2 AND scanned_at >= ?3",
+        params![path, mtime, fresh_after],
+        |r| {
+            Ok(FolderSize {
+                bytes: r.get::<_, i64>(0)? as u64,
+                files: r.get::<_, i64>(1)? as u64,
+                folders: r.get::<_, i64>(2)? as u64,
+            })
+        },
+    )
+    .optional()
+    .ok()
+    .flatten()
+}
+
+/// Sums a folder bottom-up, caching every subfolder on the way so a later
+/// request for one of them (or a preempted walk) starts warm.
+/// Returns `None` when preempted.
+fn folder_size(conn: &Connection, state: &FolderSizeState, dir: &Path) -> Option<FolderSize> {
+    let path = dir.to_string_lossy().to_string();
+    let mtime = fs::symlink_metadata(dir)
+        .and_then(|m| m.modified())
+        .map(unix_secs)
+        .unwrap_or(0);
+    if let Some(size) = cached_size(conn, &path, mtime) {
+        return Some(size);
+    }
+
+    let mut total = FolderSize::default();
+    // unreadable folders count as empty
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        if state.preempt.load(Ordering::Relaxed) {
+            return None;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            total.add(folder_size(conn, state, &entry.path())?);
+            total.folders += 1;
+        } else if file_type.is_file() {
+            total.bytes = total
+                .bytes
+                .saturating_add(entry.metadata().map(|m| m.len()).unwrap_or(0));
+            total.files += 1;
+        }
+    }
+
+    let _ = conn.execute(
+        "INSERT OR REPLACE INTO folder_sizes (path, mtime, bytes, files, folders, scanned_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            path,
+            mtime,
+            total.bytes as i64,
+            total.files as i64,
+            total.folders as i64,
+            unix_secs(SystemTime::now())
+        ],
+    );
+    Some(total)
+}
+
+/// Starts the worker that computes queued folder sizes and reports each one
+/// through a `tree-folder-size` event. Turning the option off drops the queue.
+pub fn start_folder_size_worker(handle: &AppHandle, state: Arc<FolderSizeState>) {
+    let handle = handle.clone();
+    thread::spawn(move || {
+        let conn = match open_size_db(&handle) {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!(target: "foldersize", "Failed to open size cache: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            let dir = state.next();
+            if !tree_sizes_enabled(&handle) {
+                state.clear();
+                continue;
+            }
+            if !dir.is_dir() {
+                continue;
+            }
+
+            match folder_size(&conn, &state, &dir) {
+                Some(size) => {
+                    let _ = handle.emit(
+                        "tree-folder-size",
+                        serde_json::json!({
+                            "path": dir.to_string_lossy(),
+                            "bytes": size.bytes,
+                            "files": size.files,
+                            "folders": size.folders,
+                        }),
+                    );
+                }
+                // preempted by an expanded node: finish this one afterwards
+                None => state.request([dir], false),
+            }
+        }
+    });
+}
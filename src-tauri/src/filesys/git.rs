@@ -0,0 +1,187 @@
+use git2::{Repository, Status, StatusOptions};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Serialize;
+use std::{collections::HashMap, path::Path};
+
+/// Per-entry Git state shown as a badge in the file grid
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum GitStatus {
+    Ignored,
+    Untracked,
+    Staged,
+    Modified,
+    Conflicted,
+}
+
+impl GitStatus {
+    fn from_status(status: Status) -> Option<Self> {
+        if status.is_conflicted() {
+            Some(GitStatus::Conflicted)
+        } else if status.intersects(
+            Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+        ) {
+            Some(GitStatus::Modified)
+        } else if status.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        ) {
+            Some(GitStatus::Staged)
+        } else if status.is_wt_new() {
+            Some(GitStatus::Untracked)
+        } else if status.is_ignored() {
+            Some(GitStatus::Ignored)
+        } else {
+            None
+        }
+    }
+}
+
+/// Git state of a streamed directory: repo root, branch, and the status of each direct child
+pub struct GitDirStatus {
+    pub root: String,
+    pub branch: Option<String>,
+    /// Keyed by entry name. Folders carry the most significant status found beneath them.
+    pub entries: HashMap<String, GitStatus>,
+    /// Set when the directory itself is ignored, so every entry inherits it
+    pub ignored: bool,
+}
+
+impl GitDirStatus {
+    pub fn status_of(&self, name: &str) -> Option<GitStatus> {
+        if self.ignored {
+            return Some(GitStatus::Ignored);
+        }
+        self.entries.get(name).copied()
+    }
+}
+
+/// Returns the Git status of `dir`'s children, or None when `dir` is not in a work tree.
+pub fn git_status_for_dir(dir: &Path) -> Option<GitDirStatus> {
+    let repo = Repository::discover(dir).ok()?;
+    let workdir = dunce::canonicalize(repo.workdir()?).ok()?;
+    let dir = dunce::canonicalize(dir).ok()?;
+    let rel = dir.strip_prefix(&workdir).ok()?;
+
+    // Inside .git itself there is nothing to report
+    if rel
+        .components()
+        .next()
+        .is_some_and(|c| c.as_os_str() == ".git")
+    {
+        return None;
+    }
+
+    let rel = rel.to_string_lossy().replace('\\', "/");
+    let branch = repo
+        .head()
+        .ok()
+        .and_then(|head| head.shorthand().map(|s| s.to_string()));
+
+    let mut result = GitDirStatus {
+        root: workdir.to_string_lossy().to_string(),
+        branch,
+        entries: HashMap::new(),
+        ignored: false,
+    };
+
+    if !rel.is_empty() && repo.is_path_ignored(Path::new(&rel)).unwrap_or(false) {
+        result.ignored = true;
+        return Some(result);
+    }
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .include_ignored(true)
+        .recurse_untracked_dirs(false)
+        .recurse_ignored_dirs(false)
+        .exclude_submodules(true);
+    if !rel.is_empty() {
+        opts.pathspec(format!("{}/", rel))
+            .disable_pathspec_match(true);
+    }
+
+    let statuses = repo.statuses(Some(&mut opts)).ok()?;
+    let prefix = if rel.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", rel)
+    };
+
+    for entry in statuses.iter() {
+        let Some(path) = entry.path() else { continue };
+        let Some(status) = GitStatus::from_status(entry.status()) else {
+            continue;
+        };
+        let Some(rest) = path.strip_prefix(&prefix) else {
+            continue;
+        };
+        let Some(child) = rest.split('/').find(|s| !s.is_empty()) else {
+            continue;
+        };
+        // Ignored files deep inside a folder shouldn't grey out the whole folder
+        if status == GitStatus::Ignored && rest.trim_end_matches('/') != child {
+            continue;
+        }
+
+        result
+            .entries
+            .entry(child.to_string())
+            .and_modify(|s| *s = (*s).max(status))
+            .or_insert(status);
+    }
+
+    Some(result)
+}
+
+/// Matches entries against every .gitignore between the work tree root and a directory,
+/// plus `.git/info/exclude` and the user's global excludes file
+pub struct IgnoreMatcher {
+    /// Ordered deepest-first so the closest .gitignore decides
+    layers: Vec<Gitignore>,
+}
+
+impl IgnoreMatcher {
+    /// Builds a matcher for `dir`, or None when `dir` is not inside a Git work tree.
+    pub fn for_dir(dir: &Path) -> Option<Self> {
+        let root = dir.ancestors().find(|p| p.join(".git").exists())?;
+
+        let mut layers = Vec::new();
+        for ancestor in dir.ancestors() {
+            let mut builder = GitignoreBuilder::new(ancestor);
+            builder.add(ancestor.join(".gitignore"));
+            if ancestor == root {
+                builder.add(root.join(".git").join("info").join("exclude"));
+            }
+            if let Ok(gitignore) = builder.build() {
+                if !gitignore.is_empty() {
+                    layers.push(gitignore);
+                }
+            }
+            if ancestor == root {
+                break;
+            }
+        }
+
+        let (global, _) = Gitignore::global();
+        if !global.is_empty() {
+            layers.push(global);
+        }
+
+        Some(Self { layers })
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for layer in &self.layers {
+            match layer.matched(path, is_dir) {
+                m if m.is_ignore() => return true,
+                m if m.is_whitelist() => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+}
@@ -1 +1,44 @@
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
 
+use crate::filesys::stream::FileStreamState;
+use crate::util::hashing::{hash_files, ChecksumAlgo};
+
+/// Computes checksums for the checksum column through the shared hashing
+/// service, emitting a `file-checksum` event per file. Values are cached by
+/// path, size and mtime, so only new or changed files are read. Work for a
+/// superseded listing (`request_id`) is dropped, stopping mid-file for large ones.
+#[tauri::command]
+pub fn request_checksums(
+    handle: AppHandle,
+    state: State<'_, Arc<FileStreamState>>,
+    paths: Vec<String>,
+    algo: ChecksumAlgo,
+    request_id: u64,
+) -> Result<(), String> {
+    let state = state.inner().clone();
+    let emitter = handle.clone();
+    hash_files(
+        &handle,
+        paths,
+        algo,
+        move || !state.is_stale(request_id),
+        move |path, result| {
+            let (checksum, error) = match result {
+                Ok(checksum) => (Some(checksum), None),
+                Err(e) => (None, Some(e)),
+            };
+            let _ = emitter.emit(
+                "file-checksum",
+                serde_json::json!({
+                    "request_id": request_id,
+                    "path": path,
+                    "algo": algo,
+                    "checksum": checksum,
+                    "error": error,
+                }),
+            );
+        },
+    );
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Where the health data came from
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthSource {
+    /// smartmontools' `smartctl --json`
+    Smartctl,
+    /// Windows Storage module (Get-PhysicalDisk / Get-StorageReliabilityCounter)
+    StorageCmdlets,
+}
+
+/// One SMART attribute (ATA table) or NVMe health log field
+#[derive(Serialize, Clone, Debug)]
+pub struct SmartAttribute {
+    /// ATA attribute id; 0 for NVMe log fields
+    pub id: u32,
+    pub name: String,
+    pub value: Option<i64>,
+    pub worst: Option<i64>,
+    pub threshold: Option<i64>,
+    pub raw: String,
+    /// The drive flags this attribute as past its threshold
+    pub failing: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DriveHealth {
+    pub volume: String,
+    pub device: String,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub temperature_c: Option<i64>,
+    pub power_on_hours: Option<u64>,
+    /// Overall self-assessment, when the drive reports one
+    pub smart_passed: Option<bool>,
+    /// True when anything suggests the drive is failing
+    pub predicted_failure: bool,
+    pub attributes: Vec<SmartAttribute>,
+    pub source: HealthSource,
+}
+
+/// NVMe health log fields surfaced as attributes
+const NVME_FIELDS: &[&str] = &[
+    "critical_warning",
+    "available_spare",
+    "available_spare_threshold",
+    "percentage_used",
+    "data_units_read",
+    "data_units_written",
+    "power_cycles",
+    "unsafe_shutdowns",
+    "media_errors",
+    "num_err_log_entries",
+];
+
+/// Finds smartctl on PATH or in its default install locations
+fn locate_smartctl() -> Option<PathBuf> {
+    let name = if cfg!(windows) {
+        "smartctl.exe"
+    } else {
+        "smartctl"
+    };
+    let mut dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|p| env::split_paths(&p).collect())
+        .unwrap_or_default();
+    if cfg!(windows) {
+        if let Some(base) = env::var_os("ProgramFiles") {
+            dirs.push(PathBuf::from(base).join("smartmontools\\bin"));
+        }
+    } else {
+        // sbin is often missing from a desktop session's PATH
+        dirs.extend(
+            [
+                "/usr/sbin",
+                "/usr/local/sbin",
+                "/opt/homebrew/bin",
+                "/usr/local/bin",
+            ]
+            .iter()
+            .map(PathBuf::from),
+        );
+    }
+    dirs.into_iter().map(|d| d.join(name)).find(|p| p.is_file())
+}
+
+/// The device smartctl should query for a volume root
+#[cfg(target_os = "windows")]
+fn device_for_volume(volume: &str) -> Option<String> {
+    // smartctl accepts a drive letter and resolves the physical disk behind it
+    let letter = volume.chars().next().filter(|c| c.is_ascii_alphabetic())?;
+    Some(format!("{}:", letter.to_ascii_lowercase()))
+}
+
+#[cfg(target_os = "linux")]
+fn device_for_volume(volume: &str) -> Option<String> {
+    let device = crate::filesys::volumes::mount_device(volume)?;
+
+    // SMART lives on the whole disk: /sys/class/block/sda1 -> .../sda/sda1
+    let name = Path::new(&device)
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+    let sys = PathBuf::from("/sys/class/block").join(&name);
+    if sys.join("partition").exists() {
+        let parent = std::fs::canonicalize(&sys).ok()?;
+        let disk = parent.parent()?.file_name()?.to_string_lossy().to_string();
+        return Some(format!("/dev/{}", disk));
+    }
+    Some(device)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn device_for_volume(volume: &str) -> Option<String> {
+    // df's first column is the device node backing the mount
+    let output = Command::new("df").arg(volume).output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+}
+
+fn smartctl_health(smartctl: &Path, volume: &str, device: &str) -> Result<DriveHealth, String> {
+    // the exit code is a bitmask that is non-zero for healthy drives with logged errors,
+    // so only the JSON body decides success
+    let output = Command::new(smartctl)
+        .args(["--json", "--all", device])
+        .output()
+        .map_err(|e| format!("Failed to run smartctl: {}", e))?;
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Unexpected smartctl output: {}", e))?;
+
+    if json.get("smart_status").is_none() && json.get("model_name").is_none() {
+        let message = json["smartctl"]["messages"][0]["string"]
+            .as_str()
+            .unwrap_or("smartctl could not read the drive")
+            .to_string();
+        return Err(message);
+    }
+
+    let mut attributes: Vec<SmartAttribute> = json["ata_smart_attributes"]["table"]
+        .as_array()
+        .map(|table| {
+            table
+                .iter()
+                .map(|a| SmartAttribute {
+                    id: a["id"].as_u64().unwrap_or(0) as u32,
+                    name: a["name"].as_str().unwrap_or_default().to_string(),
+                    value: a["value"].as_i64(),
+                    worst: a["worst"].as_i64(),
+                    threshold: a["thresh"].as_i64(),
+                    raw: a["raw"]["string"].as_str().unwrap_or_default().to_string(),
+                    failing: a["when_failed"].as_str().is_some_and(|w| !w.is_empty()),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let nvme = &json["nvme_smart_health_information_log"];
+    let nvme_warning = nvme["critical_warning"].as_i64().unwrap_or(0) != 0;
+    for field in NVME_FIELDS {
+        if let Some(value) = nvme[field].as_i64() {
+            attributes.push(SmartAttribute {
+                id: 0,
+                name: field.to_string(),
+                value: Some(value),
+                worst: None,
+                threshold: None,
+                raw: value.to_string(),
+                failing: *field == "critical_warning" && value != 0,
+            });
+        }
+    }
+
+    let smart_passed = json["smart_status"]["passed"].as_bool();
+    let predicted_failure =
+        smart_passed == Some(false) || nvme_warning || attributes.iter().any(|a| a.failing);
+
+    Ok(DriveHealth {
+        volume: volume.to_string(),
+        device: device.to_string(),
+        model: json["model_name"].as_str().map(str::to_string),
+        serial: json["serial_number"].as_str().map(str::to_string),
+        temperature_c: json["temperature"]["current"].as_i64(),
+        power_on_hours: json["power_on_time"]["hours"].as_u64(),
+        smart_passed,
+        predicted_failure,
+        attributes,
+        source: HealthSource::Smartctl,
+    })
+}
+
+/// Fallback for Windows machines without smartmontools
+#[cfg(target_os = "windows")]
+fn storage_cmdlet_health(volume: &str) -> Result<DriveHealth, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let letter = volume
+        .chars()
+        .next()
+        .filter(|c| c.is_ascii_alphabetic())
+        .ok_or("Not a drive letter")?;
+    let script = format!(
+        "$d = Get-Partition -DriveLetter {} | Get-Disk; \
+         $p = Get-PhysicalDisk | Where-Object {{ $_.DeviceId -eq [string]$d.Number }}; \
+         $r = $p | Get-StorageReliabilityCounter; \
+         [pscustomobject]@{{ model = $p.FriendlyName; serial = $p.SerialNumber; \
+         health = [string]$p.HealthStatus; temperature = $r.Temperature; \
+         power_on_hours = $r.PowerOnHours; wear = $r.Wear; \
+         read_errors = $r.ReadErrorsUncorrected; write_errors = $r.WriteErrorsUncorrected }} \
+         | ConvertTo-Json -Compress",
+        letter
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script.as_str()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|_| "Drive health is not available for this volume".to_string())?;
+
+    let attributes = ["wear", "read_errors", "write_errors"]
+        .iter()
+        .filter_map(|field| {
+            let value = json[field].as_i64()?;
+            Some(SmartAttribute {
+                id: 0,
+                name: field.to_string(),
+                value: Some(value),
+                worst: None,
+                threshold: None,
+                raw: value.to_string(),
+                failing: false,
+            })
+        })
+        .collect();
+    let health = json["health"].as_str().unwrap_or_default();
+
+    Ok(DriveHealth {
+        volume: volume.to_string(),
+        device: format!("{}:", letter),
+        model: json["model"].as_str().map(|s| s.trim().to_string()),
+        serial: json["serial"].as_str().map(|s| s.trim().to_string()),
+        temperature_c: json["temperature"].as_i64().filter(|t| *t > 0),
+        power_on_hours: json["power_on_hours"].as_u64(),
+        smart_passed: (!health.is_empty()).then(|| health == "Healthy"),
+        predicted_failure: !health.is_empty() && health != "Healthy",
+        attributes,
+        source: HealthSource::StorageCmdlets,
+    })
+}
+
+/// SMART status, temperature and attributes for the disk behind `volume`.
+/// Uses smartctl when installed; Windows falls back to the Storage cmdlets.
+#[tauri::command]
+pub async fn get_drive_health(volume: String) -> Result<DriveHealth, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let device =
+            device_for_volume(&volume).ok_or_else(|| format!("No disk found for {}", volume))?;
+
+        match locate_smartctl() {
+            Some(smartctl) => smartctl_health(&smartctl, &volume, &device),
+            #[cfg(target_os = "windows")]
+            None => storage_cmdlet_health(&volume),
+            #[cfg(not(target_os = "windows"))]
+            None => Err("Drive health needs smartmontools (smartctl) to be installed".into()),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,444 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use jwalk::WalkDir;
+use tauri::{AppHandle, Emitter, Manager, State};
+use xxhash_rust::xxh3::Xxh3;
+
+/// How long a modified file must stay unchanged before its new content is recorded
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// Files larger than this are not versioned
+const MAX_VERSIONED_FILE: u64 = 256 * 1024 * 1024;
+
+/// Buffer size used when hashing files
+const HASH_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Folders with "keep history" enabled and how many versions each file keeps
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub folders: Vec<String>,
+    pub max_versions: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            folders: Vec::new(),
+            max_versions: 20,
+        }
+    }
+}
+
+/// One recorded version of a file
+#[derive(Serialize, Clone, Debug)]
+pub struct FileVersion {
+    pub id: i64,
+    pub size: u64,
+    /// Modification time of the file when recorded, in seconds since the Unix epoch
+    pub modified: u64,
+    /// When the version was recorded
+    pub recorded: u64,
+    /// True for the version matching the file as it is now
+    pub current: bool,
+}
+
+pub struct HistoryState {
+    pub config: Mutex<HistoryConfig>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl HistoryState {
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            watcher: Mutex::new(None),
+        }
+    }
+}
+
+/// Root of the version store at `%APPDATA%\dagger\history`
+fn get_history_dir(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("history");
+    fs::create_dir_all(path.join("objects")).ok();
+    path
+}
+
+/// Loads the history config from disk, or the defaults if missing
+pub fn load_history_config(handle: &AppHandle) -> HistoryConfig {
+    fs::read_to_string(get_history_dir(handle).join("config.json"))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_history_config(handle: &AppHandle, config: &HistoryConfig) -> Result<(), String> {
+    let path = get_history_dir(handle).join("config.json");
+    let tmp_path = path.with_extension("tmp");
+
+    let serialized = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    fs::write(&tmp_path, serialized)
+        .map_err(|e| format!("Failed to write history config: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to save history config: {}", e))
+}
+
+fn open_history_db(handle: &AppHandle) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(get_history_dir(handle).join("versions.db"))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS versions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            modified INTEGER NOT NULL,
+            recorded INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_versions_path ON versions(path);
+        CREATE INDEX IF NOT EXISTS idx_versions_hash ON versions(hash);
+        PRAGMA journal_mode=WAL;
+        PRAGMA synchronous=NORMAL;",
+    )?;
+    Ok(conn)
+}
+
+/// Objects are sharded by the first two hex digits of their hash
+fn object_path(history_dir: &Path, hash: &str) -> PathBuf {
+    history_dir.join("objects").join(&hash[..2]).join(hash)
+}
+
+fn hash_contents(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![0u8; HASH_CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(format!("{:032x}", hasher.digest128()))
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Stores the current content of `path` as a new version unless it matches the
+/// latest one. Returns true if a version was added.
+fn record_version(
+    conn: &Connection,
+    history_dir: &Path,
+    path: &Path,
+    max_versions: usize,
+) -> io::Result<bool> {
+    // a history folder may contain the app data dir; never version the store itself
+    if path.starts_with(history_dir) {
+        return Ok(false);
+    }
+    let meta = fs::metadata(path)?;
+    if !meta.is_file() || meta.len() > MAX_VERSIONED_FILE {
+        return Ok(false);
+    }
+
+    let key = path.to_string_lossy().to_string();
+    let hash = hash_contents(path)?;
+    let latest: Option<String> = conn
+        .query_row(
+            "SELECT hash FROM versions WHERE path = ?1 ORDER BY id DESC LIMIT 1",
+            [&key],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(io::Error::other)?;
+    if latest.as_deref() == Some(hash.as_str()) {
+        return Ok(false);
+    }
+
+    let object = object_path(history_dir, &hash);
+    if !object.exists() {
+        if let Some(parent) = object.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = object.with_extension("tmp");
+        fs::copy(path, &tmp)?;
+        fs::rename(&tmp, &object)?;
+    }
+
+    conn.execute(
+        "INSERT INTO versions (path, hash, size, modified, recorded) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            key,
+            hash,
+            meta.len() as i64,
+            meta.modified().map(unix_secs).unwrap_or(0) as i64,
+            unix_secs(SystemTime::now()) as i64
+        ],
+    )
+    .map_err(io::Error::other)?;
+
+    prune_versions(conn, history_dir, &key, max_versions).map_err(io::Error::other)?;
+    Ok(true)
+}
+
+/// Drops versions beyond `max_versions` and deletes objects no version references
+fn prune_versions(
+    conn: &Connection,
+    history_dir: &Path,
+    key: &str,
+    max_versions: usize,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT id, hash FROM versions WHERE path = ?1 ORDER BY id DESC LIMIT -1 OFFSET ?2",
+    )?;
+    let stale: Vec<(i64, String)> = stmt
+        .query_map(params![key, max_versions.max(1) as i64], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for (id, hash) in stale {
+        conn.execute("DELETE FROM versions WHERE id = ?1", [id])?;
+        let used: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM versions WHERE hash = ?1",
+            [&hash],
+            |r| r.get(0),
+        )?;
+        if used == 0 {
+            let _ = fs::remove_file(object_path(history_dir, &hash));
+        }
+    }
+    Ok(())
+}
+
+/// Records every file under `folders` so the first later change has a prior version
+fn baseline_folders(handle: &AppHandle, folders: &[String], max_versions: usize) {
+    let history_dir = get_history_dir(handle);
+    let conn = match open_history_db(handle) {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::warn!(target: "history", "Failed to open version store: {}", e);
+            return;
+        }
+    };
+
+    for folder in folders {
+        for entry in WalkDir::new(folder)
+            .follow_links(false)
+            .skip_hidden(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            if let Err(e) = record_version(&conn, &history_dir, &entry.path(), max_versions) {
+                tracing::warn!(target: "history", "Failed to record {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+}
+
+/// (Re)starts the watcher over the history folders. Changed files are recorded once
+/// they've stopped changing for `SETTLE_DELAY`.
+pub fn restart_history_watcher(handle: &AppHandle, state: &Arc<HistoryState>) {
+    let config = state.config.lock().unwrap().clone();
+    let mut slot = state.watcher.lock().unwrap();
+    *slot = None; // dropping the old watcher closes its channel and ends its worker
+
+    if config.folders.is_empty() {
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let watcher = RecommendedWatcher::new(
+        move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        },
+        notify::Config::default(),
+    );
+    let mut watcher = match watcher {
+        Ok(w) => w,
+        Err(e) => {
+            tracing::error!(target: "history", "Failed to start history watcher: {:?}", e);
+            return;
+        }
+    };
+
+    for folder in &config.folders {
+        if let Err(e) = watcher.watch(Path::new(folder), RecursiveMode::Recursive) {
+            tracing::warn!(target: "history", "Failed to watch {}: {:?}", folder, e);
+        }
+    }
+    *slot = Some(watcher);
+
+    let handle = handle.clone();
+    std::thread::spawn(move || {
+        let history_dir = get_history_dir(&handle);
+        let conn = match open_history_db(&handle) {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!(target: "history", "Failed to open version store: {}", e);
+                return;
+            }
+        };
+        let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+        loop {
+            match rx.recv_timeout(Duration::from_secs(1)) {
+                Ok(path) => {
+                    pending.insert(path, Instant::now());
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            let settled: Vec<PathBuf> = pending
+                .iter()
+                .filter(|(_, changed)| changed.elapsed() >= SETTLE_DELAY)
+                .map(|(path, _)| path.clone())
+                .collect();
+
+            for path in settled {
+                pending.remove(&path);
+                match record_version(&conn, &history_dir, &path, config.max_versions) {
+                    Ok(true) => {
+                        let _ = handle.emit(
+                            "file-version-recorded",
+                            serde_json::json!({ "path": path.to_string_lossy() }),
+                        );
+                    }
+                    Ok(false) => {}
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => {
+                        tracing::warn!(target: "history", "Failed to record {}: {}", path.display(), e)
+                    }
+                }
+            }
+        }
+    });
+}
+
+// ===============================
+// Commands
+// ===============================
+
+#[tauri::command]
+pub fn get_history_config(state: State<'_, Arc<HistoryState>>) -> Result<HistoryConfig, String> {
+    Ok(state.config.lock().unwrap().clone())
+}
+
+/// Replaces the history folders, records a baseline of newly added ones in the
+/// background, and restarts watching
+#[tauri::command]
+pub fn update_history_config(
+    handle: AppHandle,
+    state: State<'_, Arc<HistoryState>>,
+    config: HistoryConfig,
+) -> Result<(), String> {
+    if let Some(missing) = config.folders.iter().find(|f| !Path::new(f).is_dir()) {
+        return Err(format!("Not a directory: {}", missing));
+    }
+    save_history_config(&handle, &config)?;
+
+    let added: Vec<String> = {
+        let mut current = state.config.lock().unwrap();
+        let added = config
+            .folders
+            .iter()
+            .filter(|f| !current.folders.contains(f))
+            .cloned()
+            .collect();
+        *current = config.clone();
+        added
+    };
+    restart_history_watcher(&handle, state.inner());
+
+    if !added.is_empty() {
+        let handle = handle.clone();
+        std::thread::spawn(move || baseline_folders(&handle, &added, config.max_versions));
+    }
+    Ok(())
+}
+
+/// Recorded versions of `path`, newest first
+#[tauri::command]
+pub fn list_file_versions(handle: AppHandle, path: String) -> Result<Vec<FileVersion>, String> {
+    let conn = open_history_db(&handle).map_err(|e| e.to_string())?;
+    let current = hash_contents(Path::new(&path)).ok();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, hash, size, modified, recorded FROM versions
+             WHERE path = ?1 ORDER BY id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([&path], |r| {
+            let hash: String = r.get(1)?;
+            Ok(FileVersion {
+                id: r.get(0)?,
+                size: r.get::<_, i64>(2)? as u64,
+                modified: r.get::<_, i64>(3)? as u64,
+                recorded: r.get::<_, i64>(4)? as u64,
+                current: current.as_deref() == Some(hash.as_str()),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// Writes version `version_id` of `path` back over the file, or to `dest` when given.
+/// The file's current content is recorded first, so a restore can itself be undone.
+#[tauri::command]
+pub fn restore_file_version(
+    handle: AppHandle,
+    state: State<'_, Arc<HistoryState>>,
+    path: String,
+    version_id: i64,
+    dest: Option<String>,
+) -> Result<String, String> {
+    let history_dir = get_history_dir(&handle);
+    let conn = open_history_db(&handle).map_err(|e| e.to_string())?;
+    let hash: String = conn
+        .query_row(
+            "SELECT hash FROM versions WHERE id = ?1 AND path = ?2",
+            params![version_id, path],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Version not found")?;
+
+    let target = PathBuf::from(dest.as_deref().unwrap_or(&path));
+    if dest.is_none() && target.exists() {
+        let max_versions = state.config.lock().unwrap().max_versions;
+        record_version(&conn, &history_dir, &target, max_versions)
+            .map_err(|e| format!("Failed to save the current version: {}", e))?;
+    }
+
+    fs::copy(object_path(&history_dir, &hash), &target)
+        .map_err(|e| format!("Failed to restore: {}", e))?;
+    Ok(target.to_string_lossy().to_string())
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::Read, path::Path};
+
+const IMAGE_EXTS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "bmp", "webp", "tif", "tiff", "heic", "heif", "avif", "raw",
+    "cr2", "nef", "arw", "dng", "svg", "ico", "psd",
+];
+const VIDEO_EXTS: &[&str] = &[
+    "mp4", "mkv", "mov", "avi", "flv", "wmv", "webm", "m4v", "mpg", "mpeg", "3gp",
+];
+const AUDIO_EXTS: &[&str] = &[
+    "mp3", "wav", "flac", "ogg", "oga", "opus", "m4a", "aac", "wma", "aiff", "aif", "mid", "midi",
+];
+const DOCUMENT_EXTS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "rtf", "txt", "md",
+    "csv", "epub", "pages", "numbers", "key", "tex", "eml", "msg",
+];
+const ARCHIVE_EXTS: &[&str] = &[
+    "zip", "7z", "rar", "tar", "gz", "tgz", "bz2", "xz", "zst", "lz", "lzma", "cab", "iso", "jar",
+];
+const CODE_EXTS: &[&str] = &[
+    "rs", "c", "h", "cpp", "hpp", "cc", "cs", "java", "kt", "go", "py", "rb", "php", "js", "mjs",
+    "cjs", "ts", "tsx", "jsx", "vue", "svelte", "html", "css", "scss", "json", "toml", "yaml",
+    "yml", "xml", "sql", "sh", "bash", "zsh", "ps1", "lua", "swift", "dart", "zig",
+];
+const EXECUTABLE_EXTS: &[&str] = &[
+    "exe", "msi", "bat", "cmd", "com", "appx", "msix", "app", "appimage", "deb", "rpm", "apk",
+    "dmg", "pkg", "run", "bin",
+];
+
+/// Broad file kinds for the filter chips and `SearchOptions::kinds`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    Image,
+    Video,
+    Audio,
+    Document,
+    Archive,
+    Code,
+    Executable,
+}
+
+impl FileKind {
+    /// The serialized name, e.g. "image"
+    pub fn name(self) -> &'static str {
+        match self {
+            FileKind::Image => "image",
+            FileKind::Video => "video",
+            FileKind::Audio => "audio",
+            FileKind::Document => "document",
+            FileKind::Archive => "archive",
+            FileKind::Code => "code",
+            FileKind::Executable => "executable",
+        }
+    }
+}
+
+/// Kind implied by the extension alone
+pub fn kind_from_extension(path: &Path) -> Option<FileKind> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let ext = ext.as_str();
+    if IMAGE_EXTS.contains(&ext) {
+        Some(FileKind::Image)
+    } else if VIDEO_EXTS.contains(&ext) {
+        Some(FileKind::Video)
+    } else if AUDIO_EXTS.contains(&ext) {
+        Some(FileKind::Audio)
+    } else if DOCUMENT_EXTS.contains(&ext) {
+        Some(FileKind::Document)
+    } else if ARCHIVE_EXTS.contains(&ext) {
+        Some(FileKind::Archive)
+    } else if CODE_EXTS.contains(&ext) {
+        Some(FileKind::Code)
+    } else if EXECUTABLE_EXTS.contains(&ext) {
+        Some(FileKind::Executable)
+    } else {
+        None
+    }
+}
+
+/// Kind from the file's leading magic bytes
+fn kind_from_magic(header: &[u8]) -> Option<FileKind> {
+    let starts = |magic: &[u8]| header.starts_with(magic);
+    let at = |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+
+    if starts(b"\x89PNG")
+        || starts(b"\xFF\xD8\xFF")
+        || starts(b"GIF8")
+        || starts(b"BM")
+        || (starts(b"RIFF") && at(8, b"WEBP"))
+    {
+        Some(FileKind::Image)
+    } else if (at(4, b"ftyp") && !at(8, b"M4A "))
+        || starts(b"\x1A\x45\xDF\xA3")
+        || (starts(b"RIFF") && at(8, b"AVI "))
+    {
+        Some(FileKind::Video)
+    } else if starts(b"ID3")
+        || starts(b"fLaC")
+        || starts(b"OggS")
+        || (at(4, b"ftyp") && at(8, b"M4A "))
+        || (starts(b"RIFF") && at(8, b"WAVE"))
+    {
+        Some(FileKind::Audio)
+    } else if starts(b"%PDF") || starts(b"{\\rtf") || starts(b"\xD0\xCF\x11\xE0") {
+        Some(FileKind::Document)
+    } else if starts(b"PK\x03\x04")
+        || starts(b"7z\xBC\xAF\x27\x1C")
+        || starts(b"Rar!")
+        || starts(b"\x1F\x8B")
+        || starts(b"BZh")
+        || starts(b"\xFD7zXZ")
+        || starts(b"\x28\xB5\x2F\xFD")
+    {
+        Some(FileKind::Archive)
+    } else if starts(b"MZ")
+        || starts(b"\x7FELF")
+        || starts(b"\xCF\xFA\xED\xFE")
+        || starts(b"\xCA\xFE\xBA\xBE")
+    {
+        Some(FileKind::Executable)
+    } else if starts(b"#!") {
+        Some(FileKind::Code)
+    } else {
+        None
+    }
+}
+
+/// Kind of a file by extension, falling back to its magic bytes when the
+/// extension is missing or unknown and `sniff` allows opening the file
+pub fn file_kind(path: &Path, sniff: bool) -> Option<FileKind> {
+    if let Some(kind) = kind_from_extension(path) {
+        return Some(kind);
+    }
+    if !sniff {
+        return None;
+    }
+    let mut header = [0u8; 16];
+    let mut file = File::open(path).ok()?;
+    let len = file.read(&mut header).ok()?;
+    kind_from_magic(&header[..len])
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+/// Symlink/junction details of an entry, flattened into `FileItem` and
+/// `file-metadata` so the UI can draw link overlays
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct LinkInfo {
+    pub is_symlink: bool,
+    /// A Windows junction; deleting through one (rather than the link itself)
+    /// reaches into another tree
+    pub is_junction: bool,
+    /// Where the link points, as stored in the link (may be relative)
+    pub link_target: Option<String>,
+    /// The target no longer exists
+    pub link_broken: bool,
+}
+
+/// Link details for `path`; all defaults for anything that isn't a link.
+/// Costs one `lstat` for ordinary entries.
+pub fn link_info(path: &Path) -> LinkInfo {
+    let is_link = fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+    if !is_link {
+        return LinkInfo::default();
+    }
+
+    LinkInfo {
+        is_symlink: true,
+        is_junction: is_junction(path),
+        link_target: fs::read_link(path)
+            .ok()
+            .map(|target| target.to_string_lossy().to_string()),
+        link_broken: fs::metadata(path).is_err(),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn is_junction(path: &Path) -> bool {
+    crate::filesys::os::windows::is_junction(path)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_junction(_path: &Path) -> bool {
+    false
+}
+
+/// Recreates the symlink `src` at `dest`, pointing at the same (possibly relative
+/// or missing) target, rather than copying what it points to
+#[cfg(unix)]
+pub fn copy_symlink(src: &Path, dest: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dest)
+}
+
+#[cfg(windows)]
+pub fn copy_symlink(src: &Path, dest: &Path) -> io::Result<()> {
+    use std::os::windows::fs::FileTypeExt;
+    let target = fs::read_link(src)?;
+    if fs::symlink_metadata(src)?.file_type().is_symlink_dir() {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    }
+}
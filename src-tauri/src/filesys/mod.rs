@@ -1,6 +1,28 @@
+pub mod access;
 pub mod actions;
+pub mod adb;
+pub mod attributes;
+pub mod cloud;
+pub mod crypt;
+pub mod export;
+pub mod foldersize;
+pub mod git;
 pub mod hash;
+pub mod health;
+pub mod history;
+pub mod kind;
+pub mod links;
+pub mod names;
 pub mod nav;
 pub mod os;
+pub mod project;
+pub mod remote;
+pub mod rules;
+pub mod selection;
+pub mod shadow;
+pub mod snapshot;
+pub mod staging;
+pub mod storage;
 pub mod stream;
+pub mod volumes;
 pub mod watcher;
\ No newline at end of file
@@ -0,0 +1,222 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::filesys::volumes::{volume_capabilities, VolumeCapabilities};
+
+/// Longest file name accepted (UTF-16 units on Windows, bytes elsewhere)
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Characters Windows refuses in file names (in addition to control characters)
+const WINDOWS_ILLEGAL_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension ("CON", "nul.txt", ...)
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Stable error codes the UI maps to inline messages
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NameErrorCode {
+    Empty,
+    DotName,
+    IllegalCharacter,
+    ReservedName,
+    TrailingDotOrSpace,
+    TooLong,
+    AlreadyExists,
+    NotFound,
+    Io,
+}
+
+/// Structured error returned by create/rename commands
+#[derive(Serialize, Clone, Debug)]
+pub struct NameError {
+    pub code: NameErrorCode,
+    pub message: String,
+}
+
+impl NameError {
+    pub fn new(code: NameErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks a single path component against the current platform's naming rules.
+pub fn validate_file_name(name: &str) -> Result<(), NameError> {
+    if name.trim().is_empty() {
+        return Err(NameError::new(NameErrorCode::Empty, "Name cannot be empty"));
+    }
+
+    if name == "." || name == ".." {
+        return Err(NameError::new(
+            NameErrorCode::DotName,
+            format!("\"{}\" is not a valid name", name),
+        ));
+    }
+
+    if cfg!(windows) {
+        check_windows_name(name)?;
+    } else {
+        if let Some(c) = name.chars().find(|c| *c == '/' || *c == '\0') {
+            return Err(NameError::new(
+                NameErrorCode::IllegalCharacter,
+                format!("Names cannot contain {}", describe_char(c)),
+            ));
+        }
+
+        if name.len() > MAX_NAME_LENGTH {
+            return Err(NameError::new(
+                NameErrorCode::TooLong,
+                format!("Names cannot be longer than {} bytes", MAX_NAME_LENGTH),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Windows naming rules; also applied elsewhere to FAT, exFAT and NTFS volumes
+fn check_windows_name(name: &str) -> Result<(), NameError> {
+    if let Some(c) = name
+        .chars()
+        .find(|c| WINDOWS_ILLEGAL_CHARS.contains(c) || (*c as u32) < 32)
+    {
+        return Err(NameError::new(
+            NameErrorCode::IllegalCharacter,
+            format!("Names cannot contain {}", describe_char(c)),
+        ));
+    }
+
+    let base = name.split('.').next().unwrap_or("").trim_end();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|r| r.eq_ignore_ascii_case(base))
+    {
+        return Err(NameError::new(
+            NameErrorCode::ReservedName,
+            format!("\"{}\" is reserved by Windows", base),
+        ));
+    }
+
+    if name.ends_with('.') || name.ends_with(' ') {
+        return Err(NameError::new(
+            NameErrorCode::TrailingDotOrSpace,
+            "Names cannot end with a dot or a space",
+        ));
+    }
+
+    if name.encode_utf16().count() > MAX_NAME_LENGTH {
+        return Err(NameError::new(
+            NameErrorCode::TooLong,
+            format!("Names cannot be longer than {} characters", MAX_NAME_LENGTH),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a name against the platform rules and those of the filesystem `dir`
+/// lives on, e.g. a FAT stick mounted on Linux still rejects ':' and '?'.
+pub fn validate_name_on_volume(dir: &Path, name: &str) -> Result<(), NameError> {
+    validate_file_name(name)?;
+    check_name_for_volume(&volume_capabilities(dir), name)
+}
+
+/// The filesystem-specific part of [`validate_name_on_volume`], for callers
+/// checking many names against one volume
+pub fn check_name_for_volume(caps: &VolumeCapabilities, name: &str) -> Result<(), NameError> {
+    if caps.windows_names && !cfg!(windows) {
+        check_windows_name(name).map_err(|e| {
+            NameError::new(
+                e.code,
+                format!("{} on {} drives", e.message, caps.filesystem),
+            )
+        })?;
+    }
+    if name.chars().count() > caps.max_name_length {
+        return Err(NameError::new(
+            NameErrorCode::TooLong,
+            format!(
+                "Names on this drive cannot be longer than {} characters",
+                caps.max_name_length
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates renaming `path` to `new_name` inside the same directory.
+/// A case-only change ("readme" -> "README") is not treated as a collision,
+/// unless the volume is case-sensitive and both names exist side by side.
+pub fn validate_rename(path: &Path, new_name: &str) -> Result<(), NameError> {
+    let dir = path.parent().unwrap_or(path);
+    validate_name_on_volume(dir, new_name)?;
+
+    if !path.exists() {
+        return Err(NameError::new(
+            NameErrorCode::NotFound,
+            format!("{} no longer exists", path.display()),
+        ));
+    }
+
+    let current_name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if current_name == new_name
+        || (is_case_only_change(&current_name, new_name)
+            && !volume_capabilities(dir).case_sensitive)
+    {
+        return Ok(());
+    }
+
+    let target = path.with_file_name(new_name);
+    if target.exists() {
+        return Err(NameError::new(
+            NameErrorCode::AlreadyExists,
+            format!("An item named \"{}\" already exists", new_name),
+        ));
+    }
+
+    Ok(())
+}
+
+/// True when two names differ only by letter case.
+pub fn is_case_only_change(old: &str, new: &str) -> bool {
+    old != new && old.to_lowercase() == new.to_lowercase()
+}
+
+fn describe_char(c: char) -> String {
+    if (c as u32) < 32 {
+        "control characters".to_string()
+    } else {
+        format!("'{}'", c)
+    }
+}
+
+/// Validates a prospective name for live feedback while the user types.
+/// Pass `current_path` when renaming so the entry itself is not reported as a collision.
+#[tauri::command]
+pub fn validate_filename(
+    parent: String,
+    name: String,
+    current_path: Option<String>,
+) -> Result<(), NameError> {
+    match current_path {
+        Some(current) => validate_rename(Path::new(&current), &name),
+        None => {
+            validate_name_on_volume(Path::new(&parent), &name)?;
+            if Path::new(&parent).join(&name).exists() {
+                return Err(NameError::new(
+                    NameErrorCode::AlreadyExists,
+                    format!("An item named \"{}\" already exists", name),
+                ));
+            }
+            Ok(())
+        }
+    }
+}
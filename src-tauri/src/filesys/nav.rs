@@ -1,15 +1,24 @@
-use base64::engine::GeneralPurpose;
-use base64::Engine;
 use image::ImageReader;
 use jwalk::WalkDir;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use std::{fs, path::Component};
-use tauri::{AppHandle, Manager, State};
-
-use crate::util::caches::{get_thumb, hash_path, open_thumb_db, set_thumb, SharedHomeCache};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::filesys::access::{check_listable, AccessDenied};
+use crate::filesys::foldersize::request_tree_sizes;
+use crate::filesys::links::{link_info, LinkInfo};
+use crate::filesys::stream::thumbs::{get_thumbnail_for_path, video_thumbnail_frame};
+use crate::filesys::volumes::enumerate_volumes;
+use crate::util::caches::{
+    hash_path, open_thumb_db, set_thumb, thumb_is_fresh, SharedAppSettings, SharedHomeCache,
+};
 use crate::util::ffutils::ffmpeg_init;
+use crate::util::power::should_throttle;
 
 /// Represents a single file or directory entry.
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -18,6 +27,8 @@ pub struct FileItem {
     pub path: String,
     pub is_dir: bool,
     pub size: Option<u64>,
+    #[serde(flatten)]
+    pub link: LinkInfo,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -27,26 +38,179 @@ pub struct FileItemWithThumbnail {
     pub is_dir: bool,
     pub size: Option<u64>,
     pub thumbnail: Option<String>, // base64 PNG data for images, None for non-images
+    /// Thumb DB key of the cached thumbnail, served through the `thumb` protocol.
+    /// Recents keep this instead of inline base64 data.
+    #[serde(default)]
+    pub thumb_hash: Option<String>,
 }
 
 /// Represents a folder and its children recursively.
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct FileNode {
     pub name: String,
     pub path: String,
     pub is_dir: bool,
     pub children: Option<Vec<FileNode>>,
+    /// Set on the placeholder child of a folder that couldn't be listed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_denied: Option<AccessDenied>,
+}
+
+/// Maximum number of directory listings kept by the tree cache.
+const MAX_TREE_CACHE_ENTRIES: usize = 512;
+
+/// Caches one level of tree children per directory, keyed by the
+/// directory's mtime so stale listings are rebuilt automatically.
+#[derive(Default)]
+pub struct TreeCache {
+    entries: Mutex<HashMap<PathBuf, (SystemTime, Vec<FileNode>)>>,
+}
+
+impl TreeCache {
+    /// Returns the sorted children of `path`, listing the directory only
+    /// when it has changed since the cached listing was taken.
+    pub fn children_of(&self, path: &Path) -> Vec<FileNode> {
+        let mtime = fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        if let Some(mtime) = mtime {
+            let entries = self.entries.lock().unwrap();
+            if let Some((cached_mtime, children)) = entries.get(path) {
+                if *cached_mtime == mtime {
+                    return children.clone();
+                }
+            }
+        }
+
+        let children = list_tree_children(path);
+
+        if let Some(mtime) = mtime {
+            let mut entries = self.entries.lock().unwrap();
+            if entries.len() >= MAX_TREE_CACHE_ENTRIES {
+                entries.clear();
+            }
+            entries.insert(path.to_path_buf(), (mtime, children.clone()));
+        }
+
+        children
+    }
+}
+
+/// Files per folder whose thumbnails are warmed by the startup prefetch
+const PREFETCH_THUMBS_PER_DIR: usize = 64;
+
+/// Warms the tree and thumbnail caches for Home, Desktop, Downloads and Documents
+/// on the rayon pool, so the first navigation after launch doesn't hit a cold disk.
+pub fn prefetch_quick_access(handle: &AppHandle) {
+    // warming thumbnails nobody asked for yet isn't worth the battery
+    if should_throttle(handle) {
+        tracing::debug!(target: "nav", "skipping quick access prefetch on battery");
+        return;
+    }
+    let pool = handle.state::<Arc<rayon::ThreadPool>>().inner().clone();
+    let tree_cache = handle.state::<Arc<TreeCache>>().inner().clone();
+
+    let dirs = [
+        dirs_next::home_dir(),
+        dirs_next::desktop_dir(),
+        dirs_next::download_dir(),
+        dirs_next::document_dir(),
+    ];
+
+    for dir in dirs.into_iter().flatten() {
+        let handle = handle.clone();
+        let tree_cache = tree_cache.clone();
+        pool.spawn(move || {
+            let children = tree_cache.children_of(&dir);
+            let ffmpeg = ffmpeg_init(&handle);
+            for child in children
+                .iter()
+                .filter(|c| !c.is_dir)
+                .take(PREFETCH_THUMBS_PER_DIR)
+            {
+                get_thumbnail_for_path(&handle, &ffmpeg, &child.path);
+            }
+            tracing::debug!(target: "nav", "prefetched {}", dir.display());
+        });
+    }
+}
+
+/// Helper to clean \\?\ prefixes
+fn normalize_tree_path(p: &Path) -> String {
+    let s = p.to_string_lossy();
+    s.strip_prefix(r"\\?\").unwrap_or(&s).to_string()
+}
+
+/// Lists one level of a directory as tree nodes: directories first, then alphabetically
+fn list_tree_children(path: &Path) -> Vec<FileNode> {
+    // a protected folder gets one placeholder child the UI can offer elevation on
+    if let Err(denied) = check_listable(path) {
+        return vec![FileNode {
+            name: "Access denied".into(),
+            path: normalize_tree_path(path),
+            is_dir: false,
+            children: None,
+            access_denied: Some(denied),
+        }];
+    }
+
+    let mut children = Vec::new();
+
+    for entry in WalkDir::new(path)
+        .max_depth(1)
+        .skip_hidden(false)
+        .into_iter()
+        .flatten()
+    {
+        if entry.path() == path {
+            continue;
+        }
+
+        let is_dir = entry.file_type().is_dir();
+        let child_path = entry.path();
+        let child_name = entry.file_name().to_string_lossy().to_string();
+
+        children.push(FileNode {
+            name: child_name,
+            path: normalize_tree_path(&child_path),
+            is_dir,
+            children: if is_dir { Some(Vec::new()) } else { None },
+            access_denied: None,
+        });
+    }
+
+    children.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    });
+
+    children
 }
 
 /// Adds an entry to the "recent" list when a file/folder is accessed.
-/// Files are saved as FileItemWithThumbnail with cached or generated thumbnail.
+/// Files are saved as FileItemWithThumbnail referencing their cached or generated
+/// thumbnail in the thumb DB.
 /// Directories remain as FileItem.
-/// Automatically deduplicates and caps (50 files / 12 dirs).
+/// Automatically deduplicates, caps and expires them per the privacy settings.
 pub async fn register_recent_access(
     handle: &AppHandle,
     state: &State<'_, SharedHomeCache>,
     path: String,
 ) -> Result<(), String> {
+    let (track_recents, max_thumb_source) = match handle.try_state::<SharedAppSettings>() {
+        Some(settings) => {
+            let settings = settings.0.read().await;
+            (
+                settings.privacy.track_recents,
+                settings.performance.max_thumbnail_source_mb * 1024 * 1024,
+            )
+        }
+        None => (true, u64::MAX),
+    };
+    if !track_recents {
+        return Ok(());
+    }
+
     let shared_cache = state.inner();
     let path_obj = Path::new(&path);
 
@@ -72,8 +236,9 @@ pub async fn register_recent_access(
             path: path.clone(),
             is_dir: true,
             size: None,
+            link: link_info(path_obj),
         };
-        shared_cache.push_recent_dir(item).await;
+        shared_cache.push_recent_dir(handle, item).await;
     } else {
         // Handle files with thumbnail caching
         let ext = path_obj
@@ -81,10 +246,6 @@ pub async fn register_recent_access(
             .map(|s| s.to_string_lossy().to_lowercase())
             .unwrap_or_default();
 
-        let encoder = GeneralPurpose::new(
-            &base64::alphabet::STANDARD,
-            base64::engine::general_purpose::PAD,
-        );
         let conn = open_thumb_db(handle).map_err(|e| format!("Failed to open thumb DB: {}", e))?;
         let hash = hash_path(&path);
         let mtime = metadata
@@ -94,8 +255,10 @@ pub async fn register_recent_access(
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0);
 
-        let thumbnail = if let Ok(Some((thumb_bytes, _, _))) = get_thumb(&conn, hash, mtime) {
-            Some(encoder.encode(&thumb_bytes))
+        let has_thumb = if thumb_is_fresh(&conn, hash, mtime) {
+            true
+        } else if size.unwrap_or(0) > max_thumb_source {
+            false
         } else if ["png", "jpg", "jpeg", "gif", "bmp"].contains(&ext.as_str()) {
             match fs::read(&path) {
                 Ok(bytes) => {
@@ -108,31 +271,31 @@ pub async fn register_recent_access(
                                 .write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Jpeg)
                                 .is_ok()
                             {
-                                let _ = set_thumb(
+                                set_thumb(
                                     &conn,
                                     hash,
+                                    &path,
                                     mtime,
                                     Some(bytes.len() as i64),
                                     Some(&ext),
                                     &buf,
-                                );
-                                Some(encoder.encode(&buf))
+                                )
+                                .is_ok()
                             } else {
-                                None
+                                false
                             }
                         } else {
-                            None
+                            false
                         }
                     } else {
-                        None
+                        false
                     }
                 }
-                Err(_) => None,
+                Err(_) => false,
             }
         } else if ["mp4", "mkv", "mov", "avi", "flv"].contains(&ext.as_str()) {
             let ffmpeg_handler = ffmpeg_init(handle);
-            match std::panic::catch_unwind(|| {
-                let img = ffmpeg_handler.generate_thumbnail(&path, 1.0);
+            match video_thumbnail_frame(handle, &ffmpeg_handler, &path).and_then(|img| {
                 let thumb = img.resize(128, 128, image::imageops::FilterType::Nearest);
                 let mut buf = Vec::new();
                 thumb
@@ -140,14 +303,11 @@ pub async fn register_recent_access(
                     .ok()
                     .map(|_| buf)
             }) {
-                Ok(Some(buf)) => {
-                    let _ = set_thumb(&conn, hash, mtime, None, Some(&ext), &buf);
-                    Some(encoder.encode(&buf))
-                }
-                _ => None,
+                Some(buf) => set_thumb(&conn, hash, &path, mtime, None, Some(&ext), &buf).is_ok(),
+                None => false,
             }
         } else {
-            None
+            false
         };
 
         let item = FileItemWithThumbnail {
@@ -155,10 +315,11 @@ pub async fn register_recent_access(
             path: path.clone(),
             is_dir: false,
             size,
-            thumbnail,
+            thumbnail: None,
+            thumb_hash: has_thumb.then(|| hash.to_string()),
         };
 
-        shared_cache.push_recent_file(item).await;
+        shared_cache.push_recent_file(handle, item).await;
     }
 
     // Persist to disk
@@ -181,24 +342,108 @@ pub async fn open_from_path(
         return Err(format!("Path does not exist: {}", path.display()));
     }
     opener::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    #[cfg(target_os = "windows")]
+    if path.is_file() {
+        crate::filesys::os::windows::add_to_recent_docs(&path.to_string_lossy());
+    }
     register_recent_access(&handle, &state, path.to_string_lossy().to_string())
         .await
         .map_err(|e| format!("Failed to register recent access: {}", e))?;
     Ok(())
 }
 
+/// "Open file location": shows the folder containing `path` in the current tab
+/// (or a new one) and emits `select-entry` so the listing highlights the item
+/// once it has loaded. Returns the folder navigated to.
 #[tauri::command]
-pub fn get_tree_from_root(target_path: &str) -> Result<FileNode, String> {
-    // --- Normalize and canonicalize base path ---
+pub fn open_containing_folder(
+    handle: AppHandle,
+    path: String,
+    new_tab: Option<bool>,
+) -> Result<String, String> {
+    let target = PathBuf::from(&path);
+    if !target.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    let parent = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .ok_or_else(|| format!("{} has no containing folder", path))?
+        .display()
+        .to_string();
+
+    let event = if new_tab.unwrap_or(false) {
+        "open-new-tab"
+    } else {
+        "navigate-tab"
+    };
+    handle
+        .emit(event, serde_json::json!({ "path": parent }))
+        .map_err(|e| e.to_string())?;
+    let _ = handle.emit(
+        "select-entry",
+        serde_json::json!({ "dir": parent, "path": path }),
+    );
+    Ok(parent)
+}
+
+/// A top-level entry of the sidebar tree (home, a volume, or a network location).
+struct TreeRoot {
+    name: String,
+    path: PathBuf,
+}
+
+/// Collects the tree's root level: the user home, every mounted volume,
+/// then any configured remote/network locations.
+fn collect_tree_roots(handle: &AppHandle, network_locations: &[FileItem]) -> Vec<TreeRoot> {
+    let mut roots = Vec::new();
+
+    if let Ok(home) = handle.path().home_dir() {
+        let name = home
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Home".to_string());
+        roots.push(TreeRoot { name, path: home });
+    }
+
+    for volume in enumerate_volumes() {
+        roots.push(TreeRoot {
+            name: volume.name,
+            path: PathBuf::from(volume.path),
+        });
+    }
+
+    for location in network_locations {
+        roots.push(TreeRoot {
+            name: location.name.clone(),
+            path: PathBuf::from(&location.path),
+        });
+    }
+
+    // Canonicalize so roots can be prefix-matched against the target path
+    for root in &mut roots {
+        if let Ok(canonical) = dunce::canonicalize(&root.path) {
+            root.path = canonical;
+        }
+    }
+
+    roots
+}
+
+#[tauri::command]
+pub async fn get_tree_from_root(
+    handle: AppHandle,
+    tree_cache: State<'_, Arc<TreeCache>>,
+    home_cache: State<'_, SharedHomeCache>,
+    target_path: &str,
+) -> Result<FileNode, String> {
+    let network_locations = home_cache.0.read().await.network_locations.clone();
+    let roots = collect_tree_roots(&handle, &network_locations);
+
+    // --- Normalize and canonicalize target path ---
     let mut normalized = target_path.to_string();
 
-    if normalized.is_empty() {
-        normalized = if cfg!(windows) {
-            "C:\\".into()
-        } else {
-            "/".into()
-        };
-    } else if cfg!(windows) {
+    if cfg!(windows) {
         if normalized.ends_with(':') {
             normalized.push('\\');
         }
@@ -208,70 +453,19 @@ pub fn get_tree_from_root(target_path: &str) -> Result<FileNode, String> {
     // Canonicalize with dunce (removes \\?\ and resolves symlinks)
     let target = dunce::canonicalize(&normalized).unwrap_or_else(|_| PathBuf::from(&normalized));
 
-    // --- Determine drive root (C:\, D:\, etc.) ---
-    #[cfg(windows)]
-    let root_path: PathBuf = {
-        if let Some(Component::Prefix(prefix)) = target.components().next() {
-            let drive = prefix.as_os_str().to_string_lossy();
-            PathBuf::from(format!("{}\\", drive.trim_end_matches('\\')))
-        } else {
-            PathBuf::from("C:\\")
-        }
-    };
-
-    #[cfg(not(windows))]
-    let root_path = PathBuf::from("/");
-
-    let root_path = dunce::canonicalize(&root_path).unwrap_or(root_path.clone());
-
-    // --- Compute relative path from root to target ---
-    let relative = target.strip_prefix(&root_path).unwrap_or(target.as_path());
-    let components: Vec<_> = relative.components().collect();
-
-    // --- Helper to clean \\?\ prefixes ---
-    fn normalize_path(p: &Path) -> String {
-        let s = p.to_string_lossy();
-        s.strip_prefix(r"\\?\").unwrap_or(&s).to_string()
-    }
-
     // --- Recursive tree builder ---
-    fn build_tree_along_path(path: PathBuf, remaining: &[Component]) -> FileNode {
-        let path_str = normalize_path(&path);
+    fn build_tree_along_path(
+        tree_cache: &TreeCache,
+        path: PathBuf,
+        remaining: &[Component],
+    ) -> FileNode {
+        let path_str = normalize_tree_path(&path);
         let name = path
             .file_name()
             .map(|s| s.to_string_lossy().to_string())
             .unwrap_or_else(|| path_str.clone());
 
-        let mut children = Vec::new();
-
-        for entry in WalkDir::new(&path)
-            .max_depth(1)
-            .skip_hidden(false)
-            .into_iter()
-            .flatten()
-        {
-            if entry.path() == path {
-                continue;
-            }
-
-            let is_dir = entry.file_type().is_dir();
-            let child_path = entry.path();
-            let child_name = entry.file_name().to_string_lossy().to_string();
-
-            children.push(FileNode {
-                name: child_name,
-                path: normalize_path(&child_path),
-                is_dir,
-                children: if is_dir { Some(Vec::new()) } else { None },
-            });
-        }
-
-        // Sort: directories first, then alphabetically
-        children.sort_by(|a, b| match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-        });
+        let mut children = tree_cache.children_of(&path);
 
         // Recurse along target subpath
         if let Some((first, rest)) = remaining.split_first() {
@@ -280,7 +474,7 @@ pub fn get_tree_from_root(target_path: &str) -> Result<FileNode, String> {
                 if child.name.eq_ignore_ascii_case(&next_name) {
                     let mut next_path = path.clone();
                     next_path.push(&child.name);
-                    *child = build_tree_along_path(next_path, rest);
+                    *child = build_tree_along_path(tree_cache, next_path, rest);
                     break;
                 }
             }
@@ -291,10 +485,83 @@ pub fn get_tree_from_root(target_path: &str) -> Result<FileNode, String> {
             path: path_str,
             is_dir: true,
             children: Some(children),
+            access_denied: None,
         }
     }
 
-    Ok(build_tree_along_path(root_path, &components))
+    let mut children: Vec<FileNode> = roots
+        .iter()
+        .map(|root| FileNode {
+            name: root.name.clone(),
+            path: normalize_tree_path(&root.path),
+            is_dir: true,
+            children: Some(Vec::new()),
+            access_denied: None,
+        })
+        .collect();
+
+    // --- Expand the deepest root containing the target (e.g. home over C:\) ---
+    if !target_path.is_empty() {
+        let containing = roots
+            .iter()
+            .enumerate()
+            .filter(|(_, root)| target.starts_with(&root.path))
+            .max_by_key(|(_, root)| root.path.components().count());
+
+        if let Some((index, root)) = containing {
+            let relative = target.strip_prefix(&root.path).unwrap_or(Path::new(""));
+            let components: Vec<_> = relative.components().collect();
+            let mut node = build_tree_along_path(&tree_cache, root.path.clone(), &components);
+            node.name = root.name.clone();
+            children[index] = node;
+        }
+    }
+
+    let mut visible = Vec::new();
+    collect_dir_paths(&children, &mut visible);
+    request_tree_sizes(&handle, &visible, false);
+
+    Ok(FileNode {
+        name: "This PC".into(),
+        path: String::new(),
+        is_dir: true,
+        children: Some(children),
+        access_denied: None,
+    })
+}
+
+/// Paths of every folder node in a (partially expanded) tree
+fn collect_dir_paths(nodes: &[FileNode], out: &mut Vec<String>) {
+    for node in nodes.iter().filter(|n| n.is_dir) {
+        out.push(node.path.clone());
+        if let Some(children) = &node.children {
+            collect_dir_paths(children, out);
+        }
+    }
+}
+
+/// Returns the immediate children of a single tree node, for expanding
+/// the sidebar tree one level at a time. With folder sizes on, the child
+/// folders are queued for sizing (see `tree-folder-size`).
+#[tauri::command]
+pub fn get_tree_children(
+    handle: AppHandle,
+    tree_cache: State<'_, Arc<TreeCache>>,
+    path: &str,
+) -> Result<Vec<FileNode>, String> {
+    let dir = Path::new(path);
+    if !dir.is_dir() {
+        return Err(format!("Path is not a valid directory: {}", path));
+    }
+    let children = tree_cache.children_of(dir);
+    // the node was just expanded, so its folders are sized first
+    let folders: Vec<String> = children
+        .iter()
+        .filter(|c| c.is_dir)
+        .map(|c| c.path.clone())
+        .collect();
+    request_tree_sizes(&handle, &folders, true);
+    Ok(children)
 }
 
 /// Returns immediate directory contents (non-recursive)
@@ -316,6 +583,8 @@ pub fn list_directory_contents(path: &str) -> Result<Vec<FileItem>, String> {
 
             let name = entry.file_name().to_string_lossy().to_string();
 
+            let link = link_info(&entry.path());
+
             let path = entry.path().to_string_lossy().to_string();
 
             Some(FileItem {
@@ -323,6 +592,7 @@ pub fn list_directory_contents(path: &str) -> Result<Vec<FileItem>, String> {
                 path,
                 is_dir,
                 size,
+                link,
             })
         })
         .collect();
@@ -178,4 +178,665 @@ pub fn get_system_clipboard() -> Result<(Vec<PathBuf>, ClipboardOp), String> {
 
         Ok((file_list, op))
     }
-}
\ No newline at end of file
+}
+
+/// Counter Windows bumps on every clipboard change, by any app.
+/// Cheap enough to poll, unlike opening the clipboard.
+pub fn clipboard_sequence() -> u32 {
+    use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
+    unsafe { GetClipboardSequenceNumber() }
+}
+
+/// Raw details of a single logical drive as reported by the Win32 volume APIs.
+#[derive(Debug)]
+pub struct DriveInfo {
+    pub root: String,
+    pub label: String,
+    pub filesystem: String,
+    pub drive_type: u32,
+    pub total_bytes: Option<u64>,
+    pub free_bytes: Option<u64>,
+}
+
+/// Enumerate every mounted drive letter (fixed, removable, mapped network shares, ...).
+pub fn list_logical_drives() -> Vec<DriveInfo> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{
+        GetDiskFreeSpaceExW, GetDriveTypeW, GetLogicalDrives, GetVolumeInformationW,
+    };
+
+    let mut drives = Vec::new();
+    let mask = unsafe { GetLogicalDrives() };
+
+    for i in 0..26u32 {
+        if mask & (1 << i) == 0 {
+            continue;
+        }
+
+        let root = format!("{}:\\", (b'A' + i as u8) as char);
+        let wide: Vec<u16> = root.encode_utf16().chain(Some(0)).collect();
+
+        unsafe {
+            let drive_type = GetDriveTypeW(PCWSTR(wide.as_ptr()));
+
+            // Volume label and filesystem name (fails for empty card readers / optical drives)
+            let mut label_buf = [0u16; 261];
+            let mut fs_buf = [0u16; 261];
+            let (label, filesystem) = match GetVolumeInformationW(
+                PCWSTR(wide.as_ptr()),
+                Some(&mut label_buf),
+                None,
+                None,
+                None,
+                Some(&mut fs_buf),
+            ) {
+                Ok(_) => (wide_to_string(&label_buf), wide_to_string(&fs_buf)),
+                Err(_) => (String::new(), String::new()),
+            };
+
+            // Capacity
+            let mut total: u64 = 0;
+            let mut free: u64 = 0;
+            let space = GetDiskFreeSpaceExW(
+                PCWSTR(wide.as_ptr()),
+                Some(&mut free),
+                Some(&mut total),
+                None,
+            );
+
+            drives.push(DriveInfo {
+                root,
+                label,
+                filesystem,
+                drive_type,
+                total_bytes: space.is_ok().then_some(total),
+                free_bytes: space.is_ok().then_some(free),
+            });
+        }
+    }
+
+    drives
+}
+
+/// Feature flags a volume's filesystem reports through GetVolumeInformationW
+pub struct VolumeFeatures {
+    pub symlinks: bool,
+    pub named_streams: bool,
+    pub compression: bool,
+    pub max_component_length: u32,
+}
+
+pub fn volume_features(root: &str) -> Option<VolumeFeatures> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetVolumeInformationW;
+    const FILE_FILE_COMPRESSION: u32 = 0x0000_0010;
+    const FILE_SUPPORTS_REPARSE_POINTS: u32 = 0x0000_0080;
+    const FILE_NAMED_STREAMS: u32 = 0x0004_0000;
+
+    let wide: Vec<u16> = root.encode_utf16().chain(Some(0)).collect();
+    let mut max_component: u32 = 0;
+    let mut flags: u32 = 0;
+    unsafe {
+        GetVolumeInformationW(
+            PCWSTR(wide.as_ptr()),
+            None,
+            None,
+            Some(&mut max_component),
+            Some(&mut flags),
+            None,
+        )
+    }
+    .ok()?;
+
+    Some(VolumeFeatures {
+        symlinks: flags & FILE_SUPPORTS_REPARSE_POINTS != 0,
+        named_streams: flags & FILE_NAMED_STREAMS != 0,
+        compression: flags & FILE_FILE_COMPRESSION != 0,
+        max_component_length: max_component,
+    })
+}
+
+/// Convert a null-terminated UTF-16 buffer to a Rust string.
+fn wide_to_string(buf: &[u16]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..len])
+}
+
+/// Reads the user's accent color as (r, g, b) from the DWM registry key
+/// (stored as a 0xAABBGGRR DWORD).
+pub fn get_accent_color() -> Option<(u8, u8, u8)> {
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut value: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\DWM"),
+            w!("AccentColor"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    };
+    if status.is_err() {
+        return None;
+    }
+
+    Some((
+        (value & 0xFF) as u8,
+        ((value >> 8) & 0xFF) as u8,
+        ((value >> 16) & 0xFF) as u8,
+    ))
+}
+
+/// Writes a REG_SZ value under HKEY_CURRENT_USER, creating the key if needed.
+/// `name` of `None` sets the key's default value.
+pub fn set_user_registry_string(
+    subkey: &str,
+    name: Option<&str>,
+    value: &str,
+) -> std::io::Result<()> {
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let subkey: Vec<u16> = subkey.encode_utf16().chain(Some(0)).collect();
+    let name: Option<Vec<u16>> = name.map(|n| n.encode_utf16().chain(Some(0)).collect());
+    let data: Vec<u16> = value.encode_utf16().chain(Some(0)).collect();
+    let status = unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(subkey.as_ptr()),
+            name.as_ref().map_or(PCWSTR::null(), |n| PCWSTR(n.as_ptr())),
+            REG_SZ.0,
+            Some(data.as_ptr() as *const _),
+            (data.len() * 2) as u32,
+        )
+    };
+    status.ok().map_err(std::io::Error::other)
+}
+
+/// Deletes a key below HKEY_CURRENT_USER with all of its subkeys.
+/// A key that does not exist counts as deleted.
+pub fn delete_user_registry_tree(subkey: &str) -> std::io::Result<()> {
+    use windows::core::PCWSTR;
+    use windows::Win32::{
+        Foundation::ERROR_FILE_NOT_FOUND,
+        System::Registry::{RegDeleteTreeW, HKEY_CURRENT_USER},
+    };
+
+    let subkey: Vec<u16> = subkey.encode_utf16().chain(Some(0)).collect();
+    let status = unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, PCWSTR(subkey.as_ptr())) };
+    if status == ERROR_FILE_NOT_FOUND {
+        return Ok(());
+    }
+    status.ok().map_err(std::io::Error::other)
+}
+
+/// Deletes a value below HKEY_CURRENT_USER; `name` of `None` clears the default value.
+/// A value that does not exist counts as deleted.
+pub fn delete_user_registry_value(subkey: &str, name: Option<&str>) -> std::io::Result<()> {
+    use windows::core::PCWSTR;
+    use windows::Win32::{
+        Foundation::ERROR_FILE_NOT_FOUND,
+        System::Registry::{RegDeleteKeyValueW, HKEY_CURRENT_USER},
+    };
+
+    let subkey: Vec<u16> = subkey.encode_utf16().chain(Some(0)).collect();
+    let name: Option<Vec<u16>> = name.map(|n| n.encode_utf16().chain(Some(0)).collect());
+    let status = unsafe {
+        RegDeleteKeyValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(subkey.as_ptr()),
+            name.as_ref().map_or(PCWSTR::null(), |n| PCWSTR(n.as_ptr())),
+        )
+    };
+    if status == ERROR_FILE_NOT_FOUND {
+        return Ok(());
+    }
+    status.ok().map_err(std::io::Error::other)
+}
+
+/// Reads a REG_SZ value below HKEY_CURRENT_USER; `name` of `None` reads the default value.
+pub fn get_user_registry_string(subkey: &str, name: Option<&str>) -> Option<String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_SZ};
+
+    let subkey: Vec<u16> = subkey.encode_utf16().chain(Some(0)).collect();
+    let name: Option<Vec<u16>> = name.map(|n| n.encode_utf16().chain(Some(0)).collect());
+    let name_ptr = name.as_ref().map_or(PCWSTR::null(), |n| PCWSTR(n.as_ptr()));
+
+    let mut buf = [0u16; 1024];
+    let mut size = std::mem::size_of_val(&buf) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(subkey.as_ptr()),
+            name_ptr,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buf.as_mut_ptr() as *mut _),
+            Some(&mut size),
+        )
+    };
+    if status.is_err() {
+        return None;
+    }
+    Some(wide_to_string(&buf))
+}
+
+/// The handler Windows resolves for a file extension (".txt"), honouring the
+/// user's Open With choice
+pub struct AssociationInfo {
+    pub prog_id: Option<String>,
+    pub app_name: Option<String>,
+    pub executable: Option<String>,
+}
+
+pub fn query_association(extension: &str) -> AssociationInfo {
+    use windows::core::{PCWSTR, PWSTR};
+    use windows::Win32::UI::Shell::{
+        AssocQueryStringW, ASSOCF_NOTRUNCATE, ASSOCSTR, ASSOCSTR_EXECUTABLE,
+        ASSOCSTR_FRIENDLYAPPNAME, ASSOCSTR_PROGID,
+    };
+
+    let assoc: Vec<u16> = extension.encode_utf16().chain(Some(0)).collect();
+    let query = |what: ASSOCSTR| -> Option<String> {
+        let mut buf = [0u16; 1024];
+        let mut len = buf.len() as u32;
+        unsafe {
+            AssocQueryStringW(
+                ASSOCF_NOTRUNCATE,
+                what,
+                PCWSTR(assoc.as_ptr()),
+                PCWSTR::null(),
+                Some(PWSTR(buf.as_mut_ptr())),
+                &mut len,
+            )
+        }
+        .ok()?;
+        Some(wide_to_string(&buf)).filter(|s| !s.is_empty())
+    };
+
+    AssociationInfo {
+        prog_id: query(ASSOCSTR_PROGID),
+        app_name: query(ASSOCSTR_FRIENDLYAPPNAME),
+        executable: query(ASSOCSTR_EXECUTABLE),
+    }
+}
+
+/// Tells Explorer that file associations changed, so icons and verbs refresh
+pub fn notify_association_changed() {
+    use windows::Win32::UI::Shell::{SHChangeNotify, SHCNE_ASSOCCHANGED, SHCNF_IDLIST};
+
+    unsafe { SHChangeNotify(SHCNE_ASSOCCHANGED, SHCNF_IDLIST, None, None) };
+}
+
+/// Whether `path` is a junction (mount point reparse point) rather than a symlink.
+/// Both look like symlinks to std; the reparse tag tells them apart.
+pub fn is_junction(path: &std::path::Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{
+        FindClose, FindFirstFileW, FILE_ATTRIBUTE_REPARSE_POINT, WIN32_FIND_DATAW,
+    };
+    const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut data = WIN32_FIND_DATAW::default();
+    unsafe {
+        let Ok(handle) = FindFirstFileW(PCWSTR(wide.as_ptr()), &mut data) else {
+            return false;
+        };
+        let _ = FindClose(handle);
+    }
+    data.dwFileAttributes & FILE_ATTRIBUTE_REPARSE_POINT.0 != 0
+        && data.dwReserved0 == IO_REPARSE_TAG_MOUNT_POINT
+}
+
+/// Allocated (non-hole) byte ranges of a file as (offset, length),
+/// via FSCTL_QUERY_ALLOCATED_RANGES.
+pub fn allocated_ranges(file: &std::fs::File, len: u64) -> std::io::Result<Vec<(u64, u64)>> {
+    use std::{ffi::c_void, mem::size_of, os::windows::io::AsRawHandle};
+    use windows::Win32::{
+        Foundation::ERROR_MORE_DATA,
+        System::{
+            Ioctl::{FILE_ALLOCATED_RANGE_BUFFER, FSCTL_QUERY_ALLOCATED_RANGES},
+            IO::DeviceIoControl,
+        },
+    };
+
+    let handle = HANDLE(file.as_raw_handle());
+    let mut query = FILE_ALLOCATED_RANGE_BUFFER {
+        FileOffset: 0,
+        Length: len as i64,
+    };
+    let mut out = [FILE_ALLOCATED_RANGE_BUFFER::default(); 64];
+    let mut ranges = Vec::new();
+
+    loop {
+        let mut returned = 0u32;
+        let result = unsafe {
+            DeviceIoControl(
+                handle,
+                FSCTL_QUERY_ALLOCATED_RANGES,
+                Some(&query as *const _ as *const c_void),
+                size_of::<FILE_ALLOCATED_RANGE_BUFFER>() as u32,
+                Some(out.as_mut_ptr() as *mut c_void),
+                std::mem::size_of_val(&out) as u32,
+                Some(&mut returned),
+                None,
+            )
+        };
+        // ERROR_MORE_DATA: the output buffer filled up, ask again from the last range
+        let more = match result {
+            Ok(()) => false,
+            Err(e) if e.code() == ERROR_MORE_DATA.to_hresult() => true,
+            Err(e) => return Err(e.into()),
+        };
+
+        let count = returned as usize / size_of::<FILE_ALLOCATED_RANGE_BUFFER>();
+        ranges.extend(
+            out[..count]
+                .iter()
+                .map(|r| (r.FileOffset as u64, r.Length as u64)),
+        );
+        if !more || count == 0 {
+            break;
+        }
+        let last = out[count - 1];
+        let next = last.FileOffset + last.Length;
+        query = FILE_ALLOCATED_RANGE_BUFFER {
+            FileOffset: next,
+            Length: len as i64 - next,
+        };
+    }
+
+    Ok(ranges)
+}
+
+/// Marks a file as sparse so ranges that are never written stay unallocated.
+pub fn set_sparse(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::System::{Ioctl::FSCTL_SET_SPARSE, IO::DeviceIoControl};
+
+    let mut returned = 0u32;
+    unsafe {
+        DeviceIoControl(
+            HANDLE(file.as_raw_handle()),
+            FSCTL_SET_SPARSE,
+            None,
+            0,
+            None,
+            0,
+            Some(&mut returned),
+            None,
+        )
+    }?;
+    Ok(())
+}
+
+/// Raw `System.Volume.BitLockerProtection` shell property of a drive root.
+/// Read through the shell so it works without elevation, unlike manage-bde/WMI.
+pub fn bitlocker_protection(root: &str) -> Option<i32> {
+    use windows::core::PCWSTR;
+    use windows::Win32::{
+        Foundation::PROPERTYKEY,
+        System::Com::{
+            CoInitializeEx, IBindCtx, StructuredStorage::PropVariantToInt32,
+            COINIT_APARTMENTTHREADED,
+        },
+        UI::Shell::PropertiesSystem::{
+            IPropertyStore, PSGetPropertyKeyFromName, SHGetPropertyStoreFromParsingName,
+            GPS_DEFAULT,
+        },
+    };
+
+    let wide: Vec<u16> = root.encode_utf16().chain(Some(0)).collect();
+    unsafe {
+        // harmless if COM is already initialized on this thread
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let mut key = PROPERTYKEY::default();
+        PSGetPropertyKeyFromName(w!("System.Volume.BitLockerProtection"), &mut key).ok()?;
+        let store: IPropertyStore = SHGetPropertyStoreFromParsingName(
+            PCWSTR(wide.as_ptr()),
+            None::<&IBindCtx>,
+            GPS_DEFAULT,
+        )
+        .ok()?;
+        let value = store.GetValue(&key).ok()?;
+        PropVariantToInt32(&value).ok()
+    }
+}
+
+/// Adds a file to the shell's recent documents, so it shows up in Start,
+/// Quick Access and the owning app's Jump List.
+pub fn add_to_recent_docs(path: &str) {
+    use windows::Win32::UI::Shell::{SHAddToRecentDocs, SHARD_PATHW};
+
+    let wide: Vec<u16> = path.encode_utf16().chain(Some(0)).collect();
+    unsafe { SHAddToRecentDocs(SHARD_PATHW.0 as u32, Some(wide.as_ptr() as *const _)) };
+}
+
+/// Replaces the taskbar Jump List with custom categories of (name, folder path)
+/// entries. Each entry relaunches Dagger with the folder as its only argument.
+/// Entries the user removed from the Jump List are not added back.
+pub fn update_jump_list(categories: &[(&str, Vec<(String, String)>)]) -> std::io::Result<()> {
+    use std::collections::HashSet;
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::{
+        Foundation::PROPERTYKEY,
+        System::Com::{
+            CoCreateInstance, CoInitializeEx, StructuredStorage::PROPVARIANT, CLSCTX_INPROC_SERVER,
+            COINIT_APARTMENTTHREADED,
+        },
+        UI::Shell::{
+            Common::{IObjectArray, IObjectCollection},
+            DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW,
+            PropertiesSystem::{IPropertyStore, PSGetPropertyKeyFromName},
+            ShellLink,
+        },
+    };
+
+    let exe = std::env::current_exe()?;
+    unsafe {
+        // harmless if COM is already initialized on this thread
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut slots = 0u32;
+        let removed: IObjectArray = list.BeginList(&mut slots)?;
+
+        let mut removed_args: HashSet<String> = HashSet::new();
+        for i in 0..removed.GetCount()? {
+            if let Ok(link) = removed.GetAt::<IShellLinkW>(i) {
+                let mut buf = [0u16; 1024];
+                if link.GetArguments(&mut buf).is_ok() {
+                    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+                    removed_args.insert(String::from_utf16_lossy(&buf[..len]));
+                }
+            }
+        }
+
+        let mut title_key = PROPERTYKEY::default();
+        PSGetPropertyKeyFromName(w!("System.Title"), &mut title_key)?;
+
+        for (category, items) in categories {
+            let collection: IObjectCollection =
+                CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+            let mut added = 0;
+            for (name, path) in items {
+                let args = format!("\"{}\"", path);
+                if added >= slots || removed_args.contains(&args) {
+                    continue;
+                }
+                let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+                link.SetPath(&HSTRING::from(exe.as_os_str()))?;
+                link.SetArguments(&HSTRING::from(args.as_str()))?;
+                link.SetDescription(&HSTRING::from(path.as_str()))?;
+                let store: IPropertyStore = link.cast()?;
+                store.SetValue(&title_key, &PROPVARIANT::from(name.as_str()))?;
+                store.Commit()?;
+                collection.AddObject(&link)?;
+                added += 1;
+            }
+            if added > 0 {
+                let array: IObjectArray = collection.cast()?;
+                list.AppendCategory(&HSTRING::from(*category), &array)?;
+            }
+        }
+        list.CommitList()?;
+    }
+    Ok(())
+}
+
+/// Sets the Cloud Files pin state of a placeholder ("Always keep on this device"
+/// when `pinned`, otherwise let the provider free it up). Folders apply it recursively.
+pub fn cloud_set_pin_state(
+    file: &std::fs::File,
+    pinned: bool,
+    is_dir: bool,
+) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Storage::CloudFilters::{
+        CfSetPinState, CF_PIN_STATE_PINNED, CF_PIN_STATE_UNPINNED, CF_SET_PIN_FLAG_NONE,
+        CF_SET_PIN_FLAG_RECURSE,
+    };
+
+    let state = if pinned {
+        CF_PIN_STATE_PINNED
+    } else {
+        CF_PIN_STATE_UNPINNED
+    };
+    let flags = if is_dir {
+        CF_SET_PIN_FLAG_RECURSE
+    } else {
+        CF_SET_PIN_FLAG_NONE
+    };
+    unsafe { CfSetPinState(HANDLE(file.as_raw_handle()), state, flags, None) }?;
+    Ok(())
+}
+
+/// Downloads `length` bytes of a placeholder starting at `offset`
+pub fn cloud_hydrate_range(file: &std::fs::File, offset: u64, length: u64) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Storage::CloudFilters::{CfHydratePlaceholder, CF_HYDRATE_FLAG_NONE};
+
+    unsafe {
+        CfHydratePlaceholder(
+            HANDLE(file.as_raw_handle()),
+            offset as i64,
+            length as i64,
+            CF_HYDRATE_FLAG_NONE,
+            None,
+        )
+    }?;
+    Ok(())
+}
+
+/// Discards the local contents of a hydrated placeholder, leaving it online-only
+pub fn cloud_dehydrate(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Storage::CloudFilters::{CfDehydratePlaceholder, CF_DEHYDRATE_FLAG_NONE};
+
+    // a length of -1 means up to the end of the file
+    unsafe {
+        CfDehydratePlaceholder(
+            HANDLE(file.as_raw_handle()),
+            0,
+            -1,
+            CF_DEHYDRATE_FLAG_NONE,
+            None,
+        )
+    }?;
+    Ok(())
+}
+
+/// Whether the disk behind drive `letter` has a seek penalty (a spinning disk),
+/// via IOCTL_STORAGE_QUERY_PROPERTY. `None` when the volume can't be queried.
+pub fn incurs_seek_penalty(letter: char) -> Option<bool> {
+    use std::{
+        ffi::c_void,
+        mem::size_of,
+        os::windows::{fs::OpenOptionsExt, io::AsRawHandle},
+    };
+    use windows::Win32::System::{
+        Ioctl::{
+            PropertyStandardQuery, StorageDeviceSeekPenaltyProperty,
+            DEVICE_SEEK_PENALTY_DESCRIPTOR, IOCTL_STORAGE_QUERY_PROPERTY, STORAGE_PROPERTY_QUERY,
+        },
+        IO::DeviceIoControl,
+    };
+
+    // no access rights are needed to query device properties
+    let volume = std::fs::OpenOptions::new()
+        .access_mode(0)
+        .open(format!(r"\\.\{}:", letter))
+        .ok()?;
+    let query = STORAGE_PROPERTY_QUERY {
+        PropertyId: StorageDeviceSeekPenaltyProperty,
+        QueryType: PropertyStandardQuery,
+        ..Default::default()
+    };
+    let mut descriptor = DEVICE_SEEK_PENALTY_DESCRIPTOR::default();
+    let mut returned = 0u32;
+    unsafe {
+        DeviceIoControl(
+            HANDLE(volume.as_raw_handle()),
+            IOCTL_STORAGE_QUERY_PROPERTY,
+            Some(&query as *const _ as *const c_void),
+            size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+            Some(&mut descriptor as *mut _ as *mut c_void),
+            size_of::<DEVICE_SEEK_PENALTY_DESCRIPTOR>() as u32,
+            Some(&mut returned),
+            None,
+        )
+    }
+    .ok()?;
+    Some(descriptor.IncursSeekPenalty)
+}
+
+/// Sets or clears the hidden attribute of a file or folder
+pub fn set_hidden(path: &std::path::Path, hidden: bool) -> std::io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileAttributesW, SetFileAttributesW, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_NORMAL,
+        FILE_FLAGS_AND_ATTRIBUTES, INVALID_FILE_ATTRIBUTES,
+    };
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let current = unsafe { GetFileAttributesW(PCWSTR(wide.as_ptr())) };
+    if current == INVALID_FILE_ATTRIBUTES {
+        return Err(std::io::Error::last_os_error());
+    }
+    let mut attributes = if hidden {
+        current | FILE_ATTRIBUTE_HIDDEN.0
+    } else {
+        current & !FILE_ATTRIBUTE_HIDDEN.0
+    };
+    // an empty set must be spelled as NORMAL
+    if attributes == 0 {
+        attributes = FILE_ATTRIBUTE_NORMAL.0;
+    }
+    unsafe { SetFileAttributesW(PCWSTR(wide.as_ptr()), FILE_FLAGS_AND_ATTRIBUTES(attributes)) }?;
+    Ok(())
+}
+
+/// Whether the process runs elevated (as administrator)
+pub fn is_elevated() -> bool {
+    unsafe { windows::Win32::UI::Shell::IsUserAnAdmin() }.as_bool()
+}
+
+/// Whether the machine runs on battery, and whether battery saver is on
+pub fn power_status() -> Option<(bool, bool)> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    // ACLineStatus: 0 offline, 1 online, 255 unknown
+    Some((status.ACLineStatus == 0, status.SystemStatusFlag == 1))
+}
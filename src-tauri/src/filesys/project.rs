@@ -0,0 +1,46 @@
+use serde::Serialize;
+use std::path::Path;
+
+/// Language/tooling hint for a folder, used to render project badges
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    Rust,
+    Node,
+    Python,
+    Go,
+    Java,
+    Dart,
+    Php,
+    Ruby,
+    Cmake,
+    Git,
+}
+
+/// Marker files checked in order; the first hit wins, so specific
+/// build manifests take precedence over a bare `.git` folder.
+const PROJECT_MARKERS: &[(&str, ProjectKind)] = &[
+    ("Cargo.toml", ProjectKind::Rust),
+    ("package.json", ProjectKind::Node),
+    ("pyproject.toml", ProjectKind::Python),
+    ("setup.py", ProjectKind::Python),
+    ("requirements.txt", ProjectKind::Python),
+    ("go.mod", ProjectKind::Go),
+    ("pom.xml", ProjectKind::Java),
+    ("build.gradle", ProjectKind::Java),
+    ("build.gradle.kts", ProjectKind::Java),
+    ("pubspec.yaml", ProjectKind::Dart),
+    ("composer.json", ProjectKind::Php),
+    ("Gemfile", ProjectKind::Ruby),
+    ("CMakeLists.txt", ProjectKind::Cmake),
+    (".git", ProjectKind::Git),
+];
+
+/// Probes a folder for well-known project markers.
+/// Only stats a fixed set of names, so it stays cheap for huge folders.
+pub fn detect_project_kind(dir: &Path) -> Option<ProjectKind> {
+    PROJECT_MARKERS
+        .iter()
+        .find(|(marker, _)| dir.join(marker).exists())
+        .map(|(_, kind)| *kind)
+}
@@ -0,0 +1,676 @@
+use base64::Engine;
+use jwalk::WalkDir;
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Emitter, State};
+use url::Url;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::filesys::stream::jobs::{JobHandle, JobKind, JobStreamState};
+use crate::util::notify::notify_job_finished;
+
+/// Minimum gap between `upload-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Chunks of an archive buffered between the zip writer and the WebDAV upload
+const BODY_PIPE_CHUNKS: usize = 16;
+
+/// Where `upload_folder` sends a folder
+enum RemoteTarget {
+    /// `sftp://[user@]host[:port]/path`, reached through the system OpenSSH client
+    /// with key or agent authentication. Needs a shell (and `tar` unless archiving)
+    /// on the server.
+    Ssh {
+        destination: String,
+        port: Option<u16>,
+        dir: String,
+    },
+    /// `http(s)://[user:password@]host/path/` on a WebDAV server
+    WebDav {
+        dir: Url,
+        authorization: Option<String>,
+    },
+}
+
+impl RemoteTarget {
+    fn parse(target: &str) -> Result<Self, String> {
+        let url = Url::parse(target).map_err(|e| format!("Invalid remote target: {}", e))?;
+        let host = url
+            .host_str()
+            .filter(|h| !h.is_empty())
+            .ok_or("The remote target has no host")?;
+        match url.scheme() {
+            "sftp" | "ssh" => {
+                let destination = match url.username() {
+                    "" => host.to_string(),
+                    user => format!("{}@{}", decode_component(user), host),
+                };
+                // sftp://host/~/backups is relative to the login folder
+                let path = decode_component(url.path());
+                let dir = match path.strip_prefix("/~") {
+                    Some(home) => format!("~{}", home),
+                    None => path,
+                };
+                Ok(Self::Ssh {
+                    destination,
+                    port: url.port(),
+                    dir: if dir.is_empty() { "~".into() } else { dir },
+                })
+            }
+            "http" | "https" | "dav" | "davs" => {
+                let authorization = (!url.username().is_empty()).then(|| {
+                    let credentials = format!(
+                        "{}:{}",
+                        decode_component(url.username()),
+                        decode_component(url.password().unwrap_or(""))
+                    );
+                    format!(
+                        "Basic {}",
+                        base64::engine::general_purpose::STANDARD.encode(credentials)
+                    )
+                });
+                let mut dir = url.clone();
+                let _ = dir.set_username("");
+                let _ = dir.set_password(None);
+                if matches!(url.scheme(), "dav" | "davs") {
+                    let scheme = if url.scheme() == "dav" {
+                        "http"
+                    } else {
+                        "https"
+                    };
+                    dir = Url::parse(&dir.as_str().replacen(url.scheme(), scheme, 1))
+                        .map_err(|e| e.to_string())?;
+                }
+                Ok(Self::WebDav { dir, authorization })
+            }
+            scheme => Err(format!("Unsupported remote target: {}://", scheme)),
+        }
+    }
+}
+
+fn decode_component(text: &str) -> String {
+    crate::util::icon_packs::percent_decode(text)
+}
+
+/// `text` as a single-quoted POSIX shell word; a leading "~/" stays unquoted so it
+/// still expands on the server
+fn shell_quote(text: &str) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', r"'\''"));
+    match text.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", quote(rest)),
+        None if text == "~" => "~".into(),
+        None => quote(text),
+    }
+}
+
+// ===============================
+// Progress
+// ===============================
+
+/// Byte counters shared between the reading side and the (compressed) sending side
+#[derive(Default)]
+struct Counters {
+    read: AtomicU64,
+    sent: AtomicU64,
+}
+
+struct Progress<'a> {
+    handle: &'a AppHandle,
+    job: &'a JobHandle,
+    counters: Arc<Counters>,
+    files_done: u64,
+    files_total: u64,
+    bytes_total: u64,
+    last_emit: Instant,
+}
+
+impl Progress<'_> {
+    /// Emits `upload-progress`; at most every `PROGRESS_INTERVAL` unless `force`d
+    fn emit(&mut self, current: &str, force: bool) {
+        if !force && self.last_emit.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        self.last_emit = Instant::now();
+        let read = self.counters.read.load(Ordering::Relaxed);
+        self.job.report_progress(read, self.bytes_total);
+        let _ = self.handle.emit(
+            "upload-progress",
+            serde_json::json!({
+                "request_id": self.job.request_id,
+                "files_done": self.files_done,
+                "files_total": self.files_total,
+                "bytes_read": read,
+                "bytes_total": self.bytes_total,
+                // compressed bytes when archiving, so the ratio shows as it goes
+                "bytes_sent": self.counters.sent.load(Ordering::Relaxed),
+                "current": current,
+            }),
+        );
+    }
+}
+
+/// Reads a source file while counting bytes, reporting progress and stopping
+/// with an error once the job is cancelled
+struct SourceReader<'a, 'p> {
+    file: File,
+    name: &'a str,
+    progress: &'a mut Progress<'p>,
+}
+
+impl Read for SourceReader<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.progress.job.is_cancelled() {
+            // not `Interrupted`, which `io::copy` would retry
+            return Err(io::Error::other("Upload cancelled"));
+        }
+        let n = self.file.read(buf)?;
+        self.progress
+            .counters
+            .read
+            .fetch_add(n as u64, Ordering::Relaxed);
+        self.progress.emit(self.name, false);
+        Ok(n)
+    }
+}
+
+/// Counts what actually goes over the wire
+struct SentCounter<W: Write> {
+    inner: W,
+    counters: Arc<Counters>,
+}
+
+impl<W: Write> Write for SentCounter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.counters.sent.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// ===============================
+// Sources
+// ===============================
+
+/// One item of the folder being uploaded, relative to the folder's parent so the
+/// folder itself arrives at the target
+struct SourceEntry {
+    path: PathBuf,
+    /// '/'-separated, starting with the folder's own name
+    relative: String,
+    is_dir: bool,
+    size: u64,
+}
+
+/// The folder and everything below it; symlinks are not followed or uploaded
+fn collect_sources(src: &Path) -> Result<Vec<SourceEntry>, String> {
+    let base = src.parent().unwrap_or(src);
+    let mut entries = Vec::new();
+    for entry in WalkDir::new(src)
+        .follow_links(false)
+        .skip_hidden(false)
+        .sort(true)
+    {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        let file_type = entry.file_type();
+        if file_type.is_symlink() {
+            continue;
+        }
+        let path = entry.path();
+        let relative = path
+            .strip_prefix(base)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let size = if file_type.is_dir() {
+            0
+        } else {
+            entry.metadata().map(|m| m.len()).unwrap_or(0)
+        };
+        entries.push(SourceEntry {
+            path,
+            relative,
+            is_dir: file_type.is_dir(),
+            size,
+        });
+    }
+    Ok(entries)
+}
+
+/// Streams the entries as a zip into `out`
+fn write_zip<W: Write>(out: W, entries: &[SourceEntry], progress: &mut Progress) -> io::Result<W> {
+    let mut zip = ZipWriter::new_stream(out);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    for entry in entries {
+        if entry.is_dir {
+            zip.add_directory(format!("{}/", entry.relative), options)?;
+            continue;
+        }
+        zip.start_file(entry.relative.as_str(), options)?;
+        let mut reader = SourceReader {
+            file: File::open(&entry.path)?,
+            name: &entry.relative,
+            progress,
+        };
+        io::copy(&mut reader, &mut zip)?;
+        progress.files_done += 1;
+        progress.emit(&entry.relative, true);
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Streams the entries as a tar into `out`, to be unpacked on the server
+fn write_tar<W: Write>(out: W, entries: &[SourceEntry], progress: &mut Progress) -> io::Result<W> {
+    let mut tar = tar::Builder::new(out);
+    for entry in entries {
+        if entry.is_dir {
+            tar.append_dir(&entry.relative, &entry.path)?;
+            continue;
+        }
+        let file = File::open(&entry.path)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(&file.metadata()?);
+        let reader = SourceReader {
+            file,
+            name: &entry.relative,
+            progress,
+        };
+        tar.append_data(&mut header, &entry.relative, reader)?;
+        progress.files_done += 1;
+        progress.emit(&entry.relative, true);
+    }
+    tar.into_inner()
+}
+
+// ===============================
+// SSH
+// ===============================
+
+fn ssh_command(destination: &str, port: Option<u16>, remote_command: &str) -> Command {
+    let mut cmd = Command::new("ssh");
+    // never hang on a password or host key prompt nobody can answer
+    cmd.args(["-o", "BatchMode=yes"]);
+    if let Some(port) = port {
+        cmd.args(["-p", &port.to_string()]);
+    }
+    cmd.args([destination, "--", remote_command]);
+    cmd
+}
+
+/// Waits for ssh after its input was closed; errors carry what ssh printed
+fn finish_ssh(child: Child) -> Result<(), String> {
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run ssh: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+fn upload_ssh(
+    destination: &str,
+    port: Option<u16>,
+    dir: &str,
+    name: &str,
+    entries: &[SourceEntry],
+    archive: bool,
+    progress: &mut Progress,
+) -> Result<String, String> {
+    let (remote_command, location) = if archive {
+        let file = format!("{}/{}.zip", dir.trim_end_matches('/'), name);
+        (
+            format!(
+                "mkdir -p {} && cat > {}",
+                shell_quote(dir),
+                shell_quote(&file)
+            ),
+            file,
+        )
+    } else {
+        (
+            format!("mkdir -p {0} && tar -xf - -C {0}", shell_quote(dir)),
+            format!("{}/{}", dir.trim_end_matches('/'), name),
+        )
+    };
+
+    let mut child = ssh_command(destination, port, &remote_command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ssh: {}", e))?;
+    let stdin = child.stdin.take().ok_or("Failed to open the ssh input")?;
+    let out = SentCounter {
+        inner: stdin,
+        counters: progress.counters.clone(),
+    };
+    let written = if archive {
+        write_zip(out, entries, progress)
+    } else {
+        write_tar(out, entries, progress)
+    };
+
+    match written {
+        // dropping the input ends the remote `cat`/`tar`
+        Ok(out) => {
+            drop(out);
+            finish_ssh(child)?;
+            Ok(format!("{}:{}", destination, location))
+        }
+        Err(e) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            if archive {
+                // a half-written zip would look like a real one
+                let _ = ssh_command(
+                    destination,
+                    port,
+                    &format!("rm -f {}", shell_quote(&location)),
+                )
+                .output();
+            }
+            Err(e.to_string())
+        }
+    }
+}
+
+// ===============================
+// WebDAV
+// ===============================
+
+/// The write end of a WebDAV request body. The upload only completes once
+/// `finish` is called; dropping it unfinished fails the request, so the server
+/// never keeps a truncated file.
+struct BodyWriter {
+    tx: Option<mpsc::SyncSender<io::Result<Vec<u8>>>>,
+}
+
+impl BodyWriter {
+    fn finish(mut self) {
+        self.tx = None;
+    }
+}
+
+impl Write for BodyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let tx = self.tx.as_ref().ok_or(io::ErrorKind::BrokenPipe)?;
+        tx.send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Upload stopped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for BodyWriter {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(Err(io::Error::other("Upload cancelled")));
+        }
+    }
+}
+
+/// The read end handed to the HTTP client
+struct BodyReader {
+    rx: mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for BodyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.chunk.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+                // the writer finished
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// `dir` with more path segments appended, each percent-encoded
+fn dav_url(dir: &Url, relative: &str, is_dir: bool) -> Result<Url, String> {
+    let mut url = dir.clone();
+    {
+        let mut segments = url
+            .path_segments_mut()
+            .map_err(|_| "Invalid WebDAV URL".to_string())?;
+        segments.pop_if_empty();
+        segments.extend(relative.split('/'));
+        if is_dir {
+            segments.push("");
+        }
+    }
+    Ok(url)
+}
+
+fn dav_request(
+    agent: &ureq::Agent,
+    method: &str,
+    url: &Url,
+    authorization: Option<&str>,
+) -> ureq::Request {
+    let request = agent.request(method, url.as_str());
+    match authorization {
+        Some(auth) => request.set("Authorization", auth),
+        None => request,
+    }
+}
+
+fn dav_error(url: &Url, e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, response) => {
+            format!("{} answered {} {}", url, code, response.status_text())
+        }
+        e => format!("Failed to reach {}: {}", url, e),
+    }
+}
+
+/// Creates a collection; one that already exists (405) is fine
+fn dav_mkcol(agent: &ureq::Agent, url: &Url, authorization: Option<&str>) -> Result<(), String> {
+    match dav_request(agent, "MKCOL", url, authorization).call() {
+        Ok(_) | Err(ureq::Error::Status(405, _)) => Ok(()),
+        Err(e) => Err(dav_error(url, e)),
+    }
+}
+
+fn upload_webdav(
+    dir: &Url,
+    authorization: Option<&str>,
+    name: &str,
+    entries: &[SourceEntry],
+    archive: bool,
+    progress: &mut Progress,
+) -> Result<String, String> {
+    let agent = ureq::AgentBuilder::new().build();
+
+    if !archive {
+        for entry in entries {
+            let url = dav_url(dir, &entry.relative, entry.is_dir)?;
+            if entry.is_dir {
+                dav_mkcol(&agent, &url, authorization)?;
+                continue;
+            }
+            let file = File::open(&entry.path)
+                .map_err(|e| format!("Failed to read {}: {}", entry.path.display(), e))?;
+            let reader = SourceReader {
+                file,
+                name: &entry.relative,
+                progress: &mut *progress,
+            };
+            dav_request(&agent, "PUT", &url, authorization)
+                .send(reader)
+                .map_err(|e| dav_error(&url, e))?;
+            // files go up as they are
+            let counters = &progress.counters;
+            counters
+                .sent
+                .store(counters.read.load(Ordering::Relaxed), Ordering::Relaxed);
+            progress.files_done += 1;
+            progress.emit(&entry.relative, true);
+        }
+        return dav_url(dir, name, true).map(|u| u.to_string());
+    }
+
+    // the archive is zipped on this thread while the request sends it from another
+    let url = dav_url(dir, &format!("{}.zip", name), false)?;
+    let (tx, rx) = mpsc::sync_channel(BODY_PIPE_CHUNKS);
+    let request = dav_request(&agent, "PUT", &url, authorization);
+    let sender = thread::spawn({
+        let url = url.clone();
+        move || {
+            request
+                .send(BodyReader {
+                    rx,
+                    chunk: Vec::new(),
+                    pos: 0,
+                })
+                .map(|_| ())
+                .map_err(|e| dav_error(&url, e))
+        }
+    });
+
+    let out = SentCounter {
+        inner: BodyWriter { tx: Some(tx) },
+        counters: progress.counters.clone(),
+    };
+    let zipped = write_zip(out, entries, progress).map(|out| out.inner.finish());
+    // a refused request stops the zipping too; its error says more
+    sender
+        .join()
+        .map_err(|_| "The upload thread panicked".to_string())??;
+    zipped.map_err(|e| e.to_string())?;
+    Ok(url.to_string())
+}
+
+// ===============================
+// Command
+// ===============================
+
+/// Uploads a local folder to `remote_target`: an `sftp://` or WebDAV
+/// (`http(s)://`) folder URL. With `archive` the folder is zipped on the fly into
+/// `<name>.zip` without a temporary file; otherwise it is recreated file by file.
+/// Runs as a job (`cancel_job` stops it) and emits `upload-progress` with source
+/// bytes read and bytes sent, then `upload-complete`. Returns the uploaded location.
+#[tauri::command]
+pub async fn upload_folder(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    src: String,
+    remote_target: String,
+    archive: bool,
+    request_id: u64,
+) -> Result<String, String> {
+    let src_path = PathBuf::from(&src);
+    if !src_path.is_dir() {
+        return Err(format!("Not a directory: {}", src));
+    }
+    let target = RemoteTarget::parse(&remote_target)?;
+    let name = src_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Can't upload a drive root")?;
+
+    let jobs = jobs.inner().clone();
+    let job = jobs.start(request_id, JobKind::Upload);
+    let result = tauri::async_runtime::spawn_blocking({
+        let handle = handle.clone();
+        let job = job.clone();
+        move || {
+            let entries = collect_sources(&src_path)?;
+            let mut progress = Progress {
+                handle: &handle,
+                job: &job,
+                counters: Arc::default(),
+                files_done: 0,
+                files_total: entries.iter().filter(|e| !e.is_dir).count() as u64,
+                bytes_total: entries.iter().map(|e| e.size).sum(),
+                last_emit: Instant::now(),
+            };
+            progress.emit(&name, true);
+
+            let location = match &target {
+                RemoteTarget::Ssh {
+                    destination,
+                    port,
+                    dir,
+                } => upload_ssh(
+                    destination,
+                    *port,
+                    dir,
+                    &name,
+                    &entries,
+                    archive,
+                    &mut progress,
+                ),
+                RemoteTarget::WebDav { dir, authorization } => upload_webdav(
+                    dir,
+                    authorization.as_deref(),
+                    &name,
+                    &entries,
+                    archive,
+                    &mut progress,
+                ),
+            };
+            if job.is_cancelled() {
+                return Err("Upload cancelled".to_string());
+            }
+            let location = location?;
+            let counters = &progress.counters;
+            let _ = handle.emit(
+                "upload-complete",
+                serde_json::json!({
+                    "request_id": request_id,
+                    "location": location,
+                    "archive": archive,
+                    "files": progress.files_done,
+                    "bytes_read": counters.read.load(Ordering::Relaxed),
+                    "bytes_sent": counters.sent.load(Ordering::Relaxed),
+                }),
+            );
+            Ok(location)
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+    jobs.finish(request_id);
+
+    if !job.is_cancelled() {
+        let message = match &result {
+            Ok(location) => format!("Uploaded {} to {}", src, location),
+            Err(e) => format!("Upload of {} failed: {}", src, e),
+        };
+        notify_job_finished(&handle, JobKind::Upload, result.is_ok(), &message);
+    }
+    result
+}
@@ -0,0 +1,585 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    filesys::{
+        actions::unique_path,
+        stream::{
+            jobs::{JobKind, JobStreamState},
+            transfer::{is_cross_device, run_transfer_move},
+            undo::{record_operation, trash_origin, FileOperation},
+        },
+    },
+    util::tags::{open_tags_db, store},
+};
+
+/// How long a new file in an auto-organized folder must stay unchanged before rules run,
+/// so browsers and copy tools get to finish writing it
+const SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+/// In-progress download extensions that are never touched
+const PARTIAL_EXTENSIONS: &[&str] = &["crdownload", "part", "partial", "download", "tmp"];
+
+// ===============================
+// Rule Structures
+// ===============================
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// Extension is one of the list (case-insensitive, without dot)
+    Extension {
+        extensions: Vec<String>,
+    },
+    /// File name contains the text (case-insensitive)
+    NameContains {
+        text: String,
+    },
+    /// File name matches the regex (case-insensitive)
+    NameMatches {
+        pattern: String,
+    },
+    /// Last modified more than N days ago
+    OlderThanDays {
+        days: u64,
+    },
+    /// Last modified within the last N days
+    NewerThanDays {
+        days: u64,
+    },
+    LargerThan {
+        bytes: u64,
+    },
+    SmallerThan {
+        bytes: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Move into a folder. Supports the tokens `{ext}`, `{year}`, `{month}` and `{date}`.
+    Move {
+        destination: String,
+    },
+    /// Rename in place. Supports `{name}`, `{ext}`, `{year}`, `{month}` and `{date}`.
+    Rename {
+        pattern: String,
+    },
+    Tag {
+        tag: String,
+        color: Option<String>,
+    },
+    /// Moves the file to the trash
+    Delete,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Rule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// All conditions must match when true, any of them when false
+    #[serde(default = "default_true")]
+    pub match_all: bool,
+    pub conditions: Vec<RuleCondition>,
+    pub action: RuleAction,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct RulesConfig {
+    pub rules: Vec<Rule>,
+    /// Folders whose new files are organized automatically as they arrive
+    pub auto_folders: Vec<String>,
+}
+
+/// Result of applying (or previewing) a rule on one file
+#[derive(Serialize, Clone, Debug)]
+pub struct RuleOutcome {
+    pub path: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub action: String,
+    pub new_path: Option<String>,
+    pub error: Option<String>,
+}
+
+// ===============================
+// Rules State
+// ===============================
+
+pub struct RulesState {
+    pub config: Mutex<RulesConfig>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl RulesState {
+    pub fn new(config: RulesConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    /// True while auto-organize is watching at least one folder
+    pub fn is_watching(&self) -> bool {
+        self.watcher.lock().unwrap().is_some()
+    }
+}
+
+fn get_rules_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    fs::create_dir_all(&path).ok();
+    path.push("rules.json");
+    path
+}
+
+/// Loads rules from disk, or an empty set if missing
+pub fn load_rules(handle: &AppHandle) -> RulesConfig {
+    let path = get_rules_path(handle);
+
+    if let Ok(mut file) = fs::File::open(&path) {
+        let mut data = String::new();
+        if file.read_to_string(&mut data).is_ok() {
+            if let Ok(config) = serde_json::from_str::<RulesConfig>(&data) {
+                return config;
+            }
+        }
+    }
+
+    RulesConfig::default()
+}
+
+/// Saves rules to disk atomically
+fn save_rules(handle: &AppHandle, config: &RulesConfig) -> Result<(), String> {
+    let path = get_rules_path(handle);
+    let tmp_path = path.with_extension("tmp");
+
+    let serialized = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    fs::write(&tmp_path, serialized).map_err(|e| format!("Failed to write rules: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to save rules: {}", e))
+}
+
+// ===============================
+// Matching
+// ===============================
+
+fn days_ago(meta: &fs::Metadata) -> Option<u64> {
+    let modified = meta.modified().ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    Some(age.as_secs() / 86_400)
+}
+
+fn condition_matches(condition: &RuleCondition, path: &Path, meta: &fs::Metadata) -> bool {
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match condition {
+        RuleCondition::Extension { extensions } => extensions
+            .iter()
+            .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext)),
+        RuleCondition::NameContains { text } => name.to_lowercase().contains(&text.to_lowercase()),
+        RuleCondition::NameMatches { pattern } => RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .is_ok_and(|re| re.is_match(&name)),
+        RuleCondition::OlderThanDays { days } => days_ago(meta).is_some_and(|d| d >= *days),
+        RuleCondition::NewerThanDays { days } => days_ago(meta).is_some_and(|d| d < *days),
+        RuleCondition::LargerThan { bytes } => meta.len() > *bytes,
+        RuleCondition::SmallerThan { bytes } => meta.len() < *bytes,
+    }
+}
+
+fn rule_matches(rule: &Rule, path: &Path, meta: &fs::Metadata) -> bool {
+    if !rule.enabled || rule.conditions.is_empty() {
+        return false;
+    }
+    let mut results = rule
+        .conditions
+        .iter()
+        .map(|c| condition_matches(c, path, meta));
+    if rule.match_all {
+        results.all(|m| m)
+    } else {
+        results.any(|m| m)
+    }
+}
+
+/// Converts days since the Unix epoch into a (year, month, day) civil date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Expands `{name}`, `{ext}`, `{year}`, `{month}` and `{date}` using the file's modified date
+fn expand_tokens(template: &str, path: &Path, meta: &fs::Metadata) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let secs = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+
+    template
+        .replace("{name}", &stem)
+        .replace("{ext}", &ext)
+        .replace("{year}", &format!("{:04}", year))
+        .replace("{month}", &format!("{:02}", month))
+        .replace("{date}", &format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+// ===============================
+// Actions
+// ===============================
+
+/// Moves a file. Across volumes it's copied, verified and only then deleted, as a
+/// background job so it shows up in the taskbar and can be cancelled.
+fn relocate(handle: &AppHandle, src: &Path, dest: &Path) -> Result<(), String> {
+    match fs::rename(src, dest) {
+        Ok(()) => Ok(()),
+        Err(e) if !is_cross_device(&e) => Err(e.to_string()),
+        Err(_) => {
+            let jobs = handle.state::<Arc<JobStreamState>>();
+            let job = jobs.start_background(JobKind::Move);
+            let result = run_transfer_move(handle, &job, src, dest);
+            jobs.finish(job.request_id);
+            match result? {
+                Some(_) => Ok(()),
+                None => Err("Move was cancelled".into()),
+            }
+        }
+    }
+}
+
+/// Computes where the action would put the file, without touching disk
+fn plan_action(rule: &Rule, path: &Path, meta: &fs::Metadata) -> Option<PathBuf> {
+    match &rule.action {
+        RuleAction::Move { destination } => {
+            let dir = PathBuf::from(expand_tokens(destination, path, meta));
+            Some(dir.join(path.file_name()?))
+        }
+        RuleAction::Rename { pattern } => {
+            Some(path.with_file_name(expand_tokens(pattern, path, meta)))
+        }
+        RuleAction::Tag { .. } | RuleAction::Delete => None,
+    }
+}
+
+fn apply_action(
+    handle: &AppHandle,
+    rule: &Rule,
+    path: &Path,
+    target: Option<PathBuf>,
+) -> Result<Option<PathBuf>, String> {
+    match &rule.action {
+        RuleAction::Move { .. } | RuleAction::Rename { .. } => {
+            let target = target.ok_or("Invalid target path")?;
+            if target == path {
+                return Ok(None);
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let target = unique_path(&target);
+            relocate(handle, path, &target)?;
+            Ok(Some(target))
+        }
+        RuleAction::Tag { tag, color } => {
+            let conn = open_tags_db(handle).map_err(|e| e.to_string())?;
+            let tag_id =
+                store::upsert_tag(&conn, tag, color.as_deref()).map_err(|e| e.to_string())?;
+            store::add_tag(&conn, &path.to_string_lossy(), tag_id).map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+        // runs unattended, so always to the trash and undoable
+        RuleAction::Delete => {
+            let origin = trash_origin(path);
+            trash::delete(path).map_err(|e| format!("Failed to move to trash: {}", e))?;
+            record_operation(
+                handle,
+                FileOperation::Trash {
+                    paths: vec![origin],
+                },
+            );
+            Ok(None)
+        }
+    }
+}
+
+fn action_name(action: &RuleAction) -> &'static str {
+    match action {
+        RuleAction::Move { .. } => "move",
+        RuleAction::Rename { .. } => "rename",
+        RuleAction::Tag { .. } => "tag",
+        RuleAction::Delete => "delete",
+    }
+}
+
+/// Runs the first matching rule on a single file. Returns None when no rule applies.
+fn apply_rules_to_file(
+    handle: &AppHandle,
+    rules: &[Rule],
+    path: &Path,
+    dry_run: bool,
+) -> Option<RuleOutcome> {
+    let meta = fs::metadata(path).ok()?;
+    if !meta.is_file() {
+        return None;
+    }
+    let rule = rules.iter().find(|r| rule_matches(r, path, &meta))?;
+    let target = plan_action(rule, path, &meta);
+
+    let (new_path, error) = if dry_run {
+        (target, None)
+    } else {
+        match apply_action(handle, rule, path, target) {
+            Ok(new_path) => (new_path, None),
+            Err(e) => (None, Some(e)),
+        }
+    };
+
+    Some(RuleOutcome {
+        path: path.to_string_lossy().to_string(),
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        action: action_name(&rule.action).to_string(),
+        new_path: new_path.map(|p| p.to_string_lossy().to_string()),
+        error,
+    })
+}
+
+fn validate_rules(config: &RulesConfig) -> Result<(), String> {
+    for rule in &config.rules {
+        for condition in &rule.conditions {
+            if let RuleCondition::NameMatches { pattern } = condition {
+                RegexBuilder::new(pattern)
+                    .build()
+                    .map_err(|e| format!("Rule \"{}\" has an invalid pattern: {}", rule.name, e))?;
+            }
+        }
+        match &rule.action {
+            RuleAction::Move { destination } if destination.trim().is_empty() => {
+                return Err(format!("Rule \"{}\" has no destination", rule.name));
+            }
+            RuleAction::Rename { pattern } if pattern.trim().is_empty() => {
+                return Err(format!(
+                    "Rule \"{}\" has an empty rename pattern",
+                    rule.name
+                ));
+            }
+            RuleAction::Tag { tag, .. } if tag.trim().is_empty() => {
+                return Err(format!("Rule \"{}\" has an empty tag", rule.name));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// ===============================
+// Auto-organize Watcher
+// ===============================
+
+fn is_partial_download(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| PARTIAL_EXTENSIONS.contains(&e.as_str()))
+}
+
+/// (Re)starts the watcher over the configured auto folders.
+/// New files are queued and organized once they've stopped changing for `SETTLE_DELAY`.
+pub fn restart_rules_watcher(handle: &AppHandle, state: &Arc<RulesState>) {
+    let folders = state.config.lock().unwrap().auto_folders.clone();
+    let mut slot = state.watcher.lock().unwrap();
+    *slot = None; // dropping the old watcher closes its channel and ends its worker
+
+    if folders.is_empty() {
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let watcher = RecommendedWatcher::new(
+        move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        },
+        notify::Config::default(),
+    );
+    let mut watcher = match watcher {
+        Ok(w) => w,
+        Err(e) => {
+            tracing::error!(target: "rules", "Failed to start rules watcher: {:?}", e);
+            return;
+        }
+    };
+
+    for folder in &folders {
+        if let Err(e) = watcher.watch(Path::new(folder), RecursiveMode::NonRecursive) {
+            tracing::warn!(target: "rules", "Failed to watch {}: {:?}", folder, e);
+        }
+    }
+    *slot = Some(watcher);
+
+    let handle = handle.clone();
+    let state = state.clone();
+    std::thread::spawn(move || {
+        // path -> (last change, last seen size)
+        let mut pending: HashMap<PathBuf, (Instant, u64)> = HashMap::new();
+        // files produced by our own renames, so a rename rule doesn't keep re-firing
+        let mut produced: HashSet<PathBuf> = HashSet::new();
+
+        loop {
+            match rx.recv_timeout(Duration::from_secs(1)) {
+                Ok(path) => {
+                    if !produced.remove(&path) && !is_partial_download(&path) {
+                        pending.insert(path, (Instant::now(), u64::MAX));
+                    }
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            let mut settled = Vec::new();
+            pending.retain(|path, (changed, size)| {
+                let Ok(meta) = fs::metadata(path) else {
+                    return false;
+                };
+                if meta.len() != *size {
+                    *size = meta.len();
+                    *changed = Instant::now();
+                    return true;
+                }
+                if changed.elapsed() >= SETTLE_DELAY {
+                    settled.push(path.clone());
+                    return false;
+                }
+                true
+            });
+
+            if settled.is_empty() {
+                continue;
+            }
+
+            let rules = state.config.lock().unwrap().rules.clone();
+            let outcomes: Vec<RuleOutcome> = settled
+                .iter()
+                .filter_map(|path| apply_rules_to_file(&handle, &rules, path, false))
+                .collect();
+
+            produced.extend(
+                outcomes
+                    .iter()
+                    .filter_map(|o| o.new_path.as_ref().map(PathBuf::from)),
+            );
+
+            if !outcomes.is_empty() {
+                let _ = handle.emit(
+                    "rules-applied",
+                    serde_json::json!({ "auto": true, "outcomes": outcomes }),
+                );
+            }
+        }
+    });
+}
+
+// ===============================
+// Commands
+// ===============================
+
+#[tauri::command]
+pub fn get_rules(state: State<'_, Arc<RulesState>>) -> Result<RulesConfig, String> {
+    Ok(state.config.lock().unwrap().clone())
+}
+
+/// Replaces the rule set, persists it, and restarts auto-organize watching
+#[tauri::command]
+pub fn update_rules(
+    handle: AppHandle,
+    state: State<'_, Arc<RulesState>>,
+    config: RulesConfig,
+) -> Result<(), String> {
+    validate_rules(&config)?;
+    save_rules(&handle, &config)?;
+    *state.config.lock().unwrap() = config;
+    restart_rules_watcher(&handle, state.inner());
+    Ok(())
+}
+
+/// Applies the rules to every file directly inside `path`.
+/// With `dry_run` set, nothing is changed and the planned outcomes are returned.
+#[tauri::command]
+pub async fn run_rules_on_folder(
+    handle: AppHandle,
+    state: State<'_, Arc<RulesState>>,
+    path: String,
+    dry_run: bool,
+) -> Result<Vec<RuleOutcome>, String> {
+    let rules = state.config.lock().unwrap().rules.clone();
+    let entries = fs::read_dir(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    let outcomes: Vec<RuleOutcome> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| !is_partial_download(p))
+        .filter_map(|p| apply_rules_to_file(&handle, &rules, &p, dry_run))
+        .collect();
+
+    if !dry_run && !outcomes.is_empty() {
+        let _ = handle.emit(
+            "rules-applied",
+            serde_json::json!({ "auto": false, "outcomes": outcomes }),
+        );
+    }
+
+    Ok(outcomes)
+}
@@ -0,0 +1,209 @@
+use serde::Serialize;
+use std::{
+    fs::Metadata,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use jwalk::WalkDir;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::{
+    cloud::{cloud_state, CloudState},
+    stream::jobs::{JobHandle, JobKind, JobStreamState},
+};
+
+/// Minimum delay between two `selection-summary-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Combined properties of a multi-selection, like Explorer's multi-file
+/// Properties dialog. Attribute fields are `None` when the items differ.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SelectionSummary {
+    /// Number of selected items
+    pub selected: usize,
+    /// Files and folders counted so far, including everything inside selected folders
+    pub files: u64,
+    pub folders: u64,
+    pub total_bytes: u64,
+    /// Entries that could not be read (permissions, races with deletion)
+    pub inaccessible: u64,
+    pub readonly: Option<bool>,
+    pub hidden: Option<bool>,
+    /// Extension shared by every selected file ("All of type .jpg")
+    pub extension: Option<String>,
+    /// Folder holding every selected item ("All in C:\Photos")
+    pub parent: Option<String>,
+    pub cloud: Option<CloudState>,
+    /// Oldest and newest modification time among the selected items
+    pub modified_from: Option<u64>,
+    pub modified_to: Option<u64>,
+}
+
+/// `Some(value)` while every item agrees, `None` once two differ
+fn merge<T: PartialEq>(common: &mut Option<Option<T>>, value: Option<T>) {
+    match common {
+        None => *common = Some(value),
+        Some(current) if *current != value => *current = None,
+        _ => {}
+    }
+}
+
+fn is_hidden(path: &Path, meta: &Metadata) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if meta.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0 {
+            return true;
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = meta;
+    path.file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+}
+
+/// Computes the combined size, counts and shared attributes of `paths`. Folders
+/// are walked in full, so huge selections stream `selection-summary-progress`
+/// before `selection-summary-complete`. Cancel with `cancel_job(request_id)`.
+#[tauri::command]
+pub async fn get_selection_summary(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    pool: State<'_, Arc<rayon::ThreadPool>>,
+    paths: Vec<String>,
+    request_id: u64,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("No paths provided".into());
+    }
+
+    let job = jobs.start(request_id, JobKind::SelectionSummary);
+    run_selection_summary(&handle, &job, pool.inner().clone(), &paths);
+    jobs.finish(request_id);
+    Ok(())
+}
+
+fn run_selection_summary(
+    handle: &AppHandle,
+    job: &JobHandle,
+    pool: Arc<rayon::ThreadPool>,
+    paths: &[String],
+) {
+    let request_id = job.request_id;
+    let secs = |meta: &Metadata| {
+        meta.modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+    };
+
+    let mut summary = SelectionSummary {
+        selected: paths.len(),
+        ..Default::default()
+    };
+    let mut readonly = None;
+    let mut hidden = None;
+    let mut extension = None;
+    let mut parent = None;
+    let mut cloud = None;
+    let mut folders: Vec<PathBuf> = Vec::new();
+
+    // Pass 1: attributes of the selected items themselves
+    for p in paths {
+        let path = Path::new(p);
+        let Ok(meta) = std::fs::symlink_metadata(path) else {
+            summary.inaccessible += 1;
+            continue;
+        };
+
+        merge(&mut readonly, Some(meta.permissions().readonly()));
+        merge(&mut hidden, Some(is_hidden(path, &meta)));
+        merge(&mut parent, path.parent().map(|p| p.display().to_string()));
+        merge(&mut cloud, cloud_state(&meta));
+        if let Some(modified) = secs(&meta) {
+            summary.modified_from =
+                Some(summary.modified_from.map_or(modified, |m| m.min(modified)));
+            summary.modified_to = Some(summary.modified_to.map_or(modified, |m| m.max(modified)));
+        }
+
+        if meta.is_dir() {
+            // folders have no type, so they never share one with files
+            merge(&mut extension, None);
+            summary.folders += 1;
+            folders.push(path.to_path_buf());
+        } else {
+            let ext = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            merge(&mut extension, Some(ext));
+            summary.files += 1;
+            summary.total_bytes = summary.total_bytes.saturating_add(meta.len());
+        }
+    }
+    summary.readonly = readonly.flatten();
+    summary.hidden = hidden.flatten();
+    summary.extension = extension.flatten().filter(|e| !e.is_empty());
+    summary.parent = parent.flatten();
+    summary.cloud = cloud.flatten();
+
+    let _ = handle.emit(
+        "selection-summary-progress",
+        serde_json::json!({ "request_id": request_id, "summary": summary }),
+    );
+
+    // Pass 2: everything inside the selected folders
+    let mut last_progress = Instant::now();
+    for folder in &folders {
+        let walker = WalkDir::new(folder)
+            .follow_links(false)
+            .skip_hidden(false)
+            .min_depth(1)
+            .parallelism(jwalk::Parallelism::RayonExistingPool {
+                pool: pool.clone(),
+                busy_timeout: Some(Duration::from_millis(20)),
+            });
+
+        for entry in walker {
+            if job.is_cancelled() {
+                let _ = handle.emit(
+                    "selection-summary-cancelled",
+                    serde_json::json!({ "request_id": request_id }),
+                );
+                return;
+            }
+
+            let Ok(entry) = entry else {
+                summary.inaccessible += 1;
+                continue;
+            };
+            if entry.file_type().is_dir() {
+                summary.folders += 1;
+            } else {
+                summary.files += 1;
+                match entry.metadata() {
+                    Ok(meta) => {
+                        summary.total_bytes = summary.total_bytes.saturating_add(meta.len())
+                    }
+                    Err(_) => summary.inaccessible += 1,
+                }
+            }
+
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                let _ = handle.emit(
+                    "selection-summary-progress",
+                    serde_json::json!({ "request_id": request_id, "summary": summary }),
+                );
+            }
+        }
+    }
+
+    let _ = handle.emit(
+        "selection-summary-complete",
+        serde_json::json!({ "request_id": request_id, "summary": summary }),
+    );
+}
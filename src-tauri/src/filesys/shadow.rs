@@ -0,0 +1,175 @@
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Device prefix of every Volume Shadow Copy path
+const SHADOW_DEVICE_PREFIX: &str = r"\\?\GLOBALROOT\Device\HarddiskVolumeShadowCopy";
+
+/// A copy of a file or folder kept in a Volume Shadow Copy snapshot
+#[derive(Serialize, Clone, Debug)]
+pub struct PreviousVersion {
+    /// Shadow copy id (`{GUID}`)
+    pub snapshot_id: String,
+    /// When the snapshot was taken, in seconds since the Unix epoch
+    pub created: Option<u64>,
+    /// Path of the item inside the snapshot; pass it to `restore_previous_version`
+    pub path: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    pub modified: Option<u64>,
+}
+
+/// A shadow copy of the volume holding the requested path
+#[cfg(target_os = "windows")]
+struct ShadowCopy {
+    id: String,
+    device: String,
+    created: Option<u64>,
+}
+
+/// Lists the shadow copy snapshots holding a distinct version of `path`, newest
+/// first, like Explorer's "Previous Versions" tab. Enumerating shadow copies
+/// usually requires administrator rights.
+#[tauri::command]
+pub async fn list_previous_versions(path: String) -> Result<Vec<PreviousVersion>, String> {
+    tauri::async_runtime::spawn_blocking(move || previous_versions(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Copies a previous version (file or folder) to `dest`. Restoring over the
+/// original means passing the original path with `overwrite` set.
+#[tauri::command]
+pub async fn restore_previous_version(
+    version_path: String,
+    dest: String,
+    overwrite: bool,
+) -> Result<(), String> {
+    if !version_path.starts_with(SHADOW_DEVICE_PREFIX) {
+        return Err("Not a previous version path".into());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let src = PathBuf::from(&version_path);
+        let dest = PathBuf::from(&dest);
+        if !overwrite && dest.exists() {
+            return Err(format!("{} already exists", dest.display()));
+        }
+        copy_recursive(&src, &dest).map_err(|e| format!("Failed to restore: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(target_os = "windows")]
+fn previous_versions(path: &str) -> Result<Vec<PreviousVersion>, String> {
+    let path = dunce::canonicalize(path).map_err(|e| format!("{}: {}", path, e))?;
+    let display = path.display().to_string();
+    let mut chars = display.chars();
+    let (Some(letter), Some(':')) = (chars.next(), chars.next()) else {
+        return Err("Previous versions are only available for local drives".into());
+    };
+    // the path relative to the volume root, e.g. "Users\me\file.txt"
+    let relative = display[2..].trim_start_matches('\\').to_string();
+
+    let mut versions: Vec<PreviousVersion> = Vec::new();
+    for shadow in shadow_copies(letter)? {
+        let candidate = if relative.is_empty() {
+            format!("{}\\", shadow.device)
+        } else {
+            format!("{}\\{}", shadow.device, relative)
+        };
+        let Ok(meta) = fs::metadata(&candidate) else {
+            continue;
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        versions.push(PreviousVersion {
+            snapshot_id: shadow.id,
+            created: shadow.created,
+            path: candidate,
+            is_dir: meta.is_dir(),
+            size: (!meta.is_dir()).then_some(meta.len()),
+            modified,
+        });
+    }
+
+    // like Explorer, list a version once even if several snapshots hold it unchanged
+    versions.sort_by(|a, b| b.created.cmp(&a.created));
+    versions.dedup_by(|older, newer| {
+        !older.is_dir && older.modified == newer.modified && older.size == newer.size
+    });
+    Ok(versions)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn previous_versions(_path: &str) -> Result<Vec<PreviousVersion>, String> {
+    Err("Previous versions are only available on Windows".into())
+}
+
+/// Shadow copies of the volume mounted at `letter:` via `Win32_ShadowCopy`
+#[cfg(target_os = "windows")]
+fn shadow_copies(letter: char) -> Result<Vec<ShadowCopy>, String> {
+    use serde_json::Value;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let script = format!(
+        "$v = (Get-Volume -DriveLetter {}).Path; \
+         $s = Get-CimInstance Win32_ShadowCopy | Where-Object {{ $_.VolumeName -eq $v }} | \
+         ForEach-Object {{ [pscustomobject]@{{ id = $_.ID; device = $_.DeviceObject; \
+         created = ([DateTimeOffset]$_.InstallDate).ToUnixTimeSeconds() }} }}; \
+         ConvertTo-Json -Compress -InputObject @($s)",
+        letter
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script.as_str()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list shadow copies (administrator rights may be required): {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Unexpected shadow copy listing: {}", e))?;
+    Ok(json
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    Some(ShadowCopy {
+                        id: item["id"].as_str()?.to_string(),
+                        device: item["device"].as_str()?.to_string(),
+                        created: item["created"].as_u64(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Copies a file, or a folder with everything below it, merging into existing folders
+fn copy_recursive(src: &Path, dest: &Path) -> std::io::Result<()> {
+    if !fs::metadata(src)?.is_dir() {
+        fs::copy(src, dest)?;
+        return Ok(());
+    }
+
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &dest.join(entry.file_name()))?;
+    }
+    Ok(())
+}
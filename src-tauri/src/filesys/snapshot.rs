@@ -0,0 +1,495 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use jwalk::WalkDir;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::filesys::stream::jobs::{JobHandle, JobKind, JobStreamState};
+use crate::util::hashing::{hash_file, ChecksumAlgo};
+use crate::util::notify::notify_job_finished;
+
+/// Minimum delay between two `snapshot-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Changes sent per `snapshot-diff` event
+const DIFF_BATCH_SIZE: usize = 200;
+
+/// A stored snapshot of one directory tree
+#[derive(Serialize, Clone, Debug)]
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub root: String,
+    pub label: Option<String>,
+    /// Seconds since the Unix epoch
+    pub created: u64,
+    pub entries: u64,
+    pub total_bytes: u64,
+    /// Whether file contents were hashed (enables content comparison)
+    pub hashed: bool,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// One difference between a snapshot and the live tree
+#[derive(Serialize, Clone, Debug)]
+pub struct SnapshotChange {
+    /// Path relative to the snapshot root, `/`-separated
+    pub path: String,
+    pub change: ChangeKind,
+    pub is_dir: bool,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+}
+
+/// An entry as stored in (or walked for) a snapshot
+struct SnapshotEntry {
+    is_dir: bool,
+    size: u64,
+    modified: u64,
+    hash: Option<String>,
+}
+
+/// Location of the snapshot store at `%APPDATA%\dagger\snapshots.db`
+fn get_snapshot_db_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    fs::create_dir_all(&path).ok();
+    path.push("snapshots.db");
+    path
+}
+
+fn open_snapshot_db(handle: &AppHandle) -> Result<Connection, String> {
+    let conn = Connection::open(get_snapshot_db_path(handle))
+        .map_err(|e| format!("Failed to open snapshot DB: {}", e))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            root TEXT NOT NULL,
+            label TEXT,
+            created INTEGER NOT NULL,
+            entries INTEGER NOT NULL DEFAULT 0,
+            total_bytes INTEGER NOT NULL DEFAULT 0,
+            hashed INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS snapshot_entries (
+            snapshot_id INTEGER NOT NULL REFERENCES snapshots(id) ON DELETE CASCADE,
+            path TEXT NOT NULL,
+            is_dir INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            modified INTEGER NOT NULL,
+            hash TEXT,
+            PRIMARY KEY (snapshot_id, path)
+        ) WITHOUT ROWID;
+        PRAGMA foreign_keys=ON;
+        PRAGMA journal_mode=WAL;
+        PRAGMA synchronous=NORMAL;",
+    )
+    .map_err(|e| format!("Failed to open snapshot DB: {}", e))?;
+    Ok(conn)
+}
+
+/// Records every entry under `root` (size, mtime and optionally an xxh3 hash) as a new
+/// snapshot. Returns its id, or `None` if cancelled via `cancel_job(request_id)`.
+#[tauri::command]
+pub async fn create_snapshot(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    root: String,
+    label: Option<String>,
+    hash: Option<bool>,
+    request_id: u64,
+) -> Result<Option<i64>, String> {
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    let job = jobs.start(request_id, JobKind::Snapshot);
+    let result = run_create_snapshot(&handle, &job, &root_path, label, hash.unwrap_or(false));
+    jobs.finish(request_id);
+
+    match &result {
+        Ok(Some(_)) => notify_job_finished(
+            &handle,
+            JobKind::Snapshot,
+            true,
+            &format!("Snapshot of {} saved", root),
+        ),
+        Ok(None) => {}
+        Err(e) => notify_job_finished(&handle, JobKind::Snapshot, false, e),
+    }
+    result
+}
+
+fn run_create_snapshot(
+    handle: &AppHandle,
+    job: &JobHandle,
+    root: &Path,
+    label: Option<String>,
+    hash: bool,
+) -> Result<Option<i64>, String> {
+    let request_id = job.request_id;
+    let mut conn = open_snapshot_db(handle)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "INSERT INTO snapshots (root, label, created, hashed) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            root.display().to_string(),
+            label,
+            unix_secs(SystemTime::now()),
+            hash
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+
+    let mut entries: u64 = 0;
+    let mut total_bytes: u64 = 0;
+    {
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO snapshot_entries (snapshot_id, path, is_dir, size, modified, hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let mut last_progress = Instant::now();
+        for (rel, entry) in walk_tree(root, hash) {
+            if job.is_cancelled() {
+                // dropping the transaction rolls the partial snapshot back
+                let _ = handle.emit(
+                    "snapshot-cancelled",
+                    serde_json::json!({ "request_id": request_id }),
+                );
+                return Ok(None);
+            }
+
+            insert
+                .execute(params![
+                    id,
+                    rel,
+                    entry.is_dir,
+                    entry.size as i64,
+                    entry.modified as i64,
+                    entry.hash
+                ])
+                .map_err(|e| e.to_string())?;
+            entries += 1;
+            total_bytes = total_bytes.saturating_add(entry.size);
+
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                let _ = handle.emit(
+                    "snapshot-progress",
+                    serde_json::json!({
+                        "request_id": request_id,
+                        "entries": entries,
+                        "total_bytes": total_bytes,
+                        "current": rel,
+                    }),
+                );
+            }
+        }
+    }
+
+    tx.execute(
+        "UPDATE snapshots SET entries = ?1, total_bytes = ?2 WHERE id = ?3",
+        params![entries as i64, total_bytes as i64, id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let _ = handle.emit(
+        "snapshot-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "id": id,
+            "entries": entries,
+            "total_bytes": total_bytes,
+        }),
+    );
+    Ok(Some(id))
+}
+
+/// Lists stored snapshots, newest first, optionally only those of `root`
+#[tauri::command]
+pub fn list_snapshots(
+    handle: AppHandle,
+    root: Option<String>,
+) -> Result<Vec<SnapshotInfo>, String> {
+    let conn = open_snapshot_db(&handle)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, root, label, created, entries, total_bytes, hashed FROM snapshots
+             WHERE ?1 IS NULL OR root = ?1 ORDER BY created DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([root], |r| {
+            Ok(SnapshotInfo {
+                id: r.get(0)?,
+                root: r.get(1)?,
+                label: r.get(2)?,
+                created: r.get::<_, i64>(3)? as u64,
+                entries: r.get::<_, i64>(4)? as u64,
+                total_bytes: r.get::<_, i64>(5)? as u64,
+                hashed: r.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_snapshot(handle: AppHandle, id: i64) -> Result<(), String> {
+    let conn = open_snapshot_db(&handle)?;
+    conn.execute("DELETE FROM snapshots WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Compares the live tree against snapshot `id`, streaming `snapshot-diff` batches of
+/// added/removed/modified entries. Content is compared by hash when the snapshot was
+/// hashed, otherwise by size and modification time.
+#[tauri::command]
+pub async fn diff_snapshot(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    id: i64,
+    request_id: u64,
+) -> Result<(), String> {
+    let job = jobs.start(request_id, JobKind::SnapshotDiff);
+    let result = run_diff_snapshot(&handle, &job, id);
+    jobs.finish(request_id);
+
+    match &result {
+        Ok(Some(changes)) => notify_job_finished(
+            &handle,
+            JobKind::SnapshotDiff,
+            true,
+            &format!("{} change(s) since the snapshot", changes),
+        ),
+        Ok(None) => {}
+        Err(e) => notify_job_finished(&handle, JobKind::SnapshotDiff, false, e),
+    }
+    result.map(|_| ())
+}
+
+/// Returns the number of changes, or `Ok(None)` if the job was cancelled
+fn run_diff_snapshot(
+    handle: &AppHandle,
+    job: &JobHandle,
+    id: i64,
+) -> Result<Option<usize>, String> {
+    let request_id = job.request_id;
+    let conn = open_snapshot_db(handle)?;
+    let (root, hashed): (String, bool) = conn
+        .query_row(
+            "SELECT root, hashed FROM snapshots WHERE id = ?1",
+            [id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Snapshot {} not found", id))?;
+
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(format!("Snapshot root no longer exists: {}", root));
+    }
+
+    let mut old: HashMap<String, SnapshotEntry> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, is_dir, size, modified, hash FROM snapshot_entries
+                 WHERE snapshot_id = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([id], |r| {
+                Ok((
+                    r.get::<_, String>(0)?,
+                    SnapshotEntry {
+                        is_dir: r.get(1)?,
+                        size: r.get::<_, i64>(2)? as u64,
+                        modified: r.get::<_, i64>(3)? as u64,
+                        hash: r.get(4)?,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+
+    let mut batch: Vec<SnapshotChange> = Vec::new();
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    let mut push = |change: SnapshotChange, batch: &mut Vec<SnapshotChange>| {
+        let key = match change.change {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Modified => "modified",
+        };
+        *counts.entry(key).or_default() += 1;
+        batch.push(change);
+        if batch.len() >= DIFF_BATCH_SIZE {
+            emit_diff_batch(handle, request_id, batch);
+        }
+    };
+
+    // hashing is deferred so unchanged-looking files are only read when needed
+    for (rel, live) in walk_tree(&root_path, false) {
+        if job.is_cancelled() {
+            let _ = handle.emit(
+                "snapshot-diff-cancelled",
+                serde_json::json!({ "request_id": request_id }),
+            );
+            return Ok(None);
+        }
+
+        let Some(before) = old.remove(&rel) else {
+            push(
+                SnapshotChange {
+                    path: rel,
+                    change: ChangeKind::Added,
+                    is_dir: live.is_dir,
+                    old_size: None,
+                    new_size: (!live.is_dir).then_some(live.size),
+                },
+                &mut batch,
+            );
+            continue;
+        };
+
+        let modified = if before.is_dir != live.is_dir {
+            true
+        } else if live.is_dir {
+            false
+        } else if before.size != live.size {
+            true
+        } else if hashed && before.hash.is_some() {
+            let now = hash_file(&root_path.join(&rel), ChecksumAlgo::Xxh3, || true)
+                .ok()
+                .flatten();
+            now != before.hash
+        } else {
+            before.modified != live.modified
+        };
+
+        if modified {
+            push(
+                SnapshotChange {
+                    path: rel,
+                    change: ChangeKind::Modified,
+                    is_dir: live.is_dir,
+                    old_size: (!before.is_dir).then_some(before.size),
+                    new_size: (!live.is_dir).then_some(live.size),
+                },
+                &mut batch,
+            );
+        }
+    }
+
+    let mut removed: Vec<(String, SnapshotEntry)> = old.into_iter().collect();
+    removed.sort_by(|a, b| a.0.cmp(&b.0));
+    for (rel, before) in removed {
+        push(
+            SnapshotChange {
+                path: rel,
+                change: ChangeKind::Removed,
+                is_dir: before.is_dir,
+                old_size: (!before.is_dir).then_some(before.size),
+                new_size: None,
+            },
+            &mut batch,
+        );
+    }
+    if !batch.is_empty() {
+        emit_diff_batch(handle, request_id, &mut batch);
+    }
+
+    let total = counts.values().sum();
+    let _ = handle.emit(
+        "snapshot-diff-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "id": id,
+            "added": counts.get("added").copied().unwrap_or(0),
+            "removed": counts.get("removed").copied().unwrap_or(0),
+            "modified": counts.get("modified").copied().unwrap_or(0),
+        }),
+    );
+    Ok(Some(total))
+}
+
+fn emit_diff_batch(handle: &AppHandle, request_id: u64, batch: &mut Vec<SnapshotChange>) {
+    let _ = handle.emit(
+        "snapshot-diff",
+        serde_json::json!({
+            "request_id": request_id,
+            "changes": std::mem::take(batch),
+        }),
+    );
+}
+
+/// Walks `root` in sorted order, yielding `/`-separated relative paths
+fn walk_tree(root: &Path, hash: bool) -> impl Iterator<Item = (String, SnapshotEntry)> + '_ {
+    WalkDir::new(root)
+        .follow_links(false)
+        .skip_hidden(false)
+        .sort(true)
+        .into_iter()
+        .skip(1)
+        .filter_map(|e| e.ok())
+        .filter_map(move |entry| {
+            let path = entry.path();
+            let rel = path.strip_prefix(root).ok()?;
+            let rel = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let is_dir = entry.file_type().is_dir();
+            let meta = entry.metadata().ok();
+            Some((
+                rel,
+                SnapshotEntry {
+                    is_dir,
+                    size: meta
+                        .as_ref()
+                        .filter(|_| !is_dir)
+                        .map(|m| m.len())
+                        .unwrap_or(0),
+                    modified: meta
+                        .as_ref()
+                        .and_then(|m| m.modified().ok())
+                        .map(unix_secs)
+                        .unwrap_or(0),
+                    hash: (hash && !is_dir)
+                        .then(|| hash_file(&path, ChecksumAlgo::Xxh3, || true).ok().flatten())
+                        .flatten(),
+                },
+            ))
+        })
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
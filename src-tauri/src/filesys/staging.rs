@@ -0,0 +1,173 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tauri::{AppHandle, Manager};
+
+/// Prefix of staging folders, so leftovers are recognisable in a listing
+const STAGING_PREFIX: &str = ".dagger-staging-";
+
+/// Serialises read-modify-write of the staging manifest
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Tells staging folders created in the same instant apart
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A private folder to build results in before they appear at their destination.
+/// It sits inside the destination folder so committing is a rename on the same
+/// volume: the user sees either nothing or the finished item, never a partial one.
+/// Dropping it removes whatever was not committed. Every staging folder is listed
+/// in `staging.json` until dropped, so folders orphaned by a crash are removed at
+/// the next startup by `clean_orphaned_staging`.
+pub struct StagingDir {
+    handle: AppHandle,
+    dir: PathBuf,
+}
+
+fn get_manifest_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    fs::create_dir_all(&path).ok();
+    path.push("staging.json");
+    path
+}
+
+fn load_manifest(handle: &AppHandle) -> Vec<PathBuf> {
+    fs::read_to_string(get_manifest_path(handle))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Saves the manifest atomically
+fn save_manifest(handle: &AppHandle, dirs: &[PathBuf]) -> io::Result<()> {
+    let path = get_manifest_path(handle);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(dirs)?)?;
+    fs::rename(&tmp_path, &path)
+}
+
+/// Adds or removes one folder from the manifest
+fn update_manifest(handle: &AppHandle, dir: &Path, present: bool) -> io::Result<()> {
+    let _guard = MANIFEST_LOCK.lock().unwrap();
+    let mut dirs = load_manifest(handle);
+    dirs.retain(|d| d != dir);
+    if present {
+        dirs.push(dir.to_path_buf());
+    }
+    save_manifest(handle, &dirs)
+}
+
+#[cfg(target_os = "windows")]
+fn hide(dir: &Path) {
+    let _ = crate::filesys::os::windows::set_hidden(dir, true);
+}
+
+#[cfg(not(target_os = "windows"))]
+fn hide(_dir: &Path) {
+    // the leading dot already hides it
+}
+
+impl StagingDir {
+    /// Creates a staging folder for results that will end up in `dest_dir`
+    pub fn new(handle: &AppHandle, dest_dir: &Path) -> io::Result<Self> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let dir = dest_dir.join(format!("{}{:x}-{}", STAGING_PREFIX, stamp, id));
+
+        // recorded first, so a crash between the two steps still gets cleaned up
+        update_manifest(handle, &dir, true)?;
+        if let Err(e) = fs::create_dir(&dir) {
+            let _ = update_manifest(handle, &dir, false);
+            return Err(e);
+        }
+        hide(&dir);
+        Ok(Self {
+            handle: handle.clone(),
+            dir,
+        })
+    }
+
+    /// Where staged items are written
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Moves a finished item from the staging folder to `target`. Unless `replace`
+    /// is set, an existing `target` is left alone and `AlreadyExists` returned.
+    pub fn commit(&self, staged: &Path, target: &Path, replace: bool) -> io::Result<()> {
+        if !staged.starts_with(&self.dir) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Item is not in the staging folder",
+            ));
+        }
+        if !replace && fs::symlink_metadata(target).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", target.display()),
+            ));
+        }
+        fs::rename(staged, target)
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if self.dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.dir) {
+                tracing::warn!(
+                    "Failed to remove staging folder {}: {}",
+                    self.dir.display(),
+                    e
+                );
+                // left in the manifest for the next startup
+                return;
+            }
+        }
+        let _ = update_manifest(&self.handle, &self.dir, false);
+    }
+}
+
+/// Removes staging folders left behind by a crash or a forced quit. Runs in the
+/// background at startup; folders that can't be removed yet stay listed.
+pub fn clean_orphaned_staging(handle: &AppHandle) {
+    let handle = handle.clone();
+    std::thread::spawn(move || {
+        let _guard = MANIFEST_LOCK.lock().unwrap();
+        let remaining: Vec<PathBuf> = load_manifest(&handle)
+            .into_iter()
+            .filter(|dir| {
+                // never touch anything that isn't one of ours
+                let ours = dir
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with(STAGING_PREFIX));
+                if !ours || !dir.exists() {
+                    return false;
+                }
+                match fs::remove_dir_all(dir) {
+                    Ok(()) => {
+                        tracing::info!("Removed orphaned staging folder {}", dir.display());
+                        false
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to remove staging folder {}: {}", dir.display(), e);
+                        true
+                    }
+                }
+            })
+            .collect();
+        let _ = save_manifest(&handle, &remaining);
+    });
+}
@@ -0,0 +1,245 @@
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use jwalk::WalkDir;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::{
+    stream::jobs::{JobHandle, JobKind, JobStreamState},
+    volumes::enumerate_volumes,
+};
+use crate::util::notify::notify_job_finished;
+
+/// Minimum delay between two `storage-analysis-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+const VIDEO_EXTS: &[&str] = &[
+    "mp4", "mkv", "mov", "avi", "flv", "wmv", "webm", "m4v", "mpg", "mpeg", "3gp",
+];
+const IMAGE_EXTS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "bmp", "webp", "tif", "tiff", "heic", "heif", "raw", "cr2", "nef",
+    "arw", "dng", "svg", "ico", "psd",
+];
+const DOCUMENT_EXTS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "rtf", "txt", "md",
+    "csv", "epub", "pages", "numbers", "key",
+];
+const APP_EXTS: &[&str] = &[
+    "exe", "msi", "dll", "sys", "appx", "msix", "app", "appimage", "deb", "rpm", "apk", "dmg",
+    "pkg", "so", "dylib",
+];
+/// Install locations whose contents count as apps regardless of extension
+const APP_DIRS: &[&str] = &[
+    "Program Files",
+    "Program Files (x86)",
+    "WindowsApps",
+    "Applications",
+];
+
+/// Broad buckets used by the storage overview
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCategory {
+    Videos,
+    Images,
+    Documents,
+    Apps,
+    Other,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct CategoryUsage {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// Disk usage of a volume broken down by category
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct StorageReport {
+    pub volume: String,
+    pub total_bytes: Option<u64>,
+    pub free_bytes: Option<u64>,
+    /// Bytes accounted for by the scan so far
+    pub scanned_bytes: u64,
+    pub scanned_files: u64,
+    /// Entries that could not be read (permissions, races with deletion)
+    pub inaccessible: u64,
+    pub categories: BTreeMap<StorageCategory, CategoryUsage>,
+}
+
+fn classify(path: &Path) -> StorageCategory {
+    let in_app_dir = path.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        APP_DIRS.iter().any(|d| name.eq_ignore_ascii_case(d))
+    });
+    if in_app_dir {
+        return StorageCategory::Apps;
+    }
+
+    let ext = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let ext = ext.as_str();
+    if VIDEO_EXTS.contains(&ext) {
+        StorageCategory::Videos
+    } else if IMAGE_EXTS.contains(&ext) {
+        StorageCategory::Images
+    } else if DOCUMENT_EXTS.contains(&ext) {
+        StorageCategory::Documents
+    } else if APP_EXTS.contains(&ext) {
+        StorageCategory::Apps
+    } else {
+        StorageCategory::Other
+    }
+}
+
+/// Walks a whole volume and classifies its usage into categories, streaming
+/// `storage-analysis-progress` events. Cancel with `cancel_job(request_id)`.
+#[tauri::command]
+pub async fn analyze_storage(
+    handle: AppHandle,
+    jobs: State<'_, Arc<JobStreamState>>,
+    pool: State<'_, Arc<rayon::ThreadPool>>,
+    volume: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let root = PathBuf::from(&volume);
+    if !root.is_dir() {
+        return Err(format!("Not a volume: {}", volume));
+    }
+
+    let job = jobs.start(request_id, JobKind::StorageAnalysis);
+    let result = run_storage_analysis(&handle, &job, pool.inner().clone(), &root);
+    jobs.finish(request_id);
+
+    match &result {
+        Ok(Some(report)) => notify_job_finished(
+            &handle,
+            JobKind::StorageAnalysis,
+            true,
+            &format!(
+                "Analyzed {} files on {}",
+                report.scanned_files, report.volume
+            ),
+        ),
+        Ok(None) => {}
+        Err(e) => notify_job_finished(&handle, JobKind::StorageAnalysis, false, e),
+    }
+    result.map(|_| ())
+}
+
+/// Returns `Ok(None)` if the job was cancelled
+fn run_storage_analysis(
+    handle: &AppHandle,
+    job: &JobHandle,
+    pool: Arc<rayon::ThreadPool>,
+    root: &Path,
+) -> Result<Option<StorageReport>, String> {
+    let request_id = job.request_id;
+    let volume_info = enumerate_volumes()
+        .into_iter()
+        .find(|v| Path::new(&v.path) == root);
+
+    let mut report = StorageReport {
+        volume: root.display().to_string(),
+        total_bytes: volume_info.as_ref().and_then(|v| v.total_bytes),
+        free_bytes: volume_info.as_ref().and_then(|v| v.free_bytes),
+        ..Default::default()
+    };
+    // progress is measured against the used space when the volume reports it
+    let used = report
+        .total_bytes
+        .zip(report.free_bytes)
+        .map(|(total, free)| total.saturating_sub(free))
+        .unwrap_or(0);
+
+    #[cfg(unix)]
+    let root_dev = {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(root).map(|m| m.dev()).ok()
+    };
+
+    let walker = WalkDir::new(root)
+        .follow_links(false)
+        .skip_hidden(false)
+        .parallelism(jwalk::Parallelism::RayonExistingPool {
+            pool,
+            busy_timeout: Some(Duration::from_millis(20)),
+        })
+        .process_read_dir(move |_, _, _, children| {
+            // stay on this volume: don't descend into other mounts (/proc, network shares)
+            #[cfg(unix)]
+            children.retain(|child| {
+                use std::os::unix::fs::MetadataExt;
+                match child {
+                    Ok(entry) if entry.file_type().is_dir() => entry
+                        .metadata()
+                        .map(|m| Some(m.dev()) == root_dev)
+                        .unwrap_or(true),
+                    _ => true,
+                }
+            });
+            #[cfg(not(unix))]
+            let _ = children;
+        });
+
+    let mut last_progress = Instant::now();
+    for entry in walker {
+        if job.is_cancelled() {
+            let _ = handle.emit(
+                "storage-analysis-cancelled",
+                serde_json::json!({ "request_id": request_id }),
+            );
+            return Ok(None);
+        }
+
+        let Ok(entry) = entry else {
+            report.inaccessible += 1;
+            continue;
+        };
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            report.inaccessible += 1;
+            continue;
+        };
+
+        let usage = report
+            .categories
+            .entry(classify(&entry.path()))
+            .or_default();
+        usage.bytes = usage.bytes.saturating_add(meta.len());
+        usage.files += 1;
+        report.scanned_bytes = report.scanned_bytes.saturating_add(meta.len());
+        report.scanned_files += 1;
+        job.report_progress(report.scanned_bytes, used);
+
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            let _ = handle.emit(
+                "storage-analysis-progress",
+                serde_json::json!({
+                    "request_id": request_id,
+                    "report": report,
+                    "current": entry.path().display().to_string(),
+                }),
+            );
+        }
+    }
+
+    let _ = handle.emit(
+        "storage-analysis-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "report": report,
+        }),
+    );
+    Ok(Some(report))
+}
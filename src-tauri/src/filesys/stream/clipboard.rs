@@ -0,0 +1,143 @@
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::stream::opstream::TransferOp;
+
+/// Copy/cut operations remembered for re-pasting
+const MAX_CLIPBOARD_HISTORY: usize = 20;
+
+/// How often the system clipboard is checked for files copied in other apps
+#[cfg(target_os = "windows")]
+const CLIPBOARD_POLL_INTERVAL: std::time::Duration =
+    std::time::Duration = Duration::from_millis(500);
+
+/// A selection that was copied or cut through Dagger
+#[derive(Serialize, Clone, Debug)]
+pub struct ClipboardEntry {
+    pub paths: Vec<String>,
+    pub operation: TransferOp,
+    pub timestamp: u64,
+}
+
+/// Recent copy/cut operations, newest first
+#[derive(Default)]
+pub struct ClipboardHistory(pub Mutex<VecDeque<ClipboardEntry>>);
+
+impl ClipboardHistory {
+    /// Records a copy/cut; repeating the latest selection only refreshes it
+    pub fn record(&self, handle: &AppHandle, paths: &[String], operation: TransferOp) {
+        let entry = ClipboardEntry {
+            paths: paths.to_vec(),
+            operation,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+
+        {
+            let mut entries = self.0.lock().unwrap();
+            entries.retain(|e| e.paths != entry.paths || e.operation != operation);
+            entries.push_front(entry);
+            entries.truncate(MAX_CLIPBOARD_HISTORY);
+        }
+        let _ = handle.emit("clipboard-history-changed", ());
+    }
+
+    pub fn get(&self, index: usize) -> Option<ClipboardEntry> {
+        self.0.lock().unwrap().get(index).cloned()
+    }
+}
+
+#[tauri::command]
+pub fn get_clipboard_history(history: State<'_, ClipboardHistory>) -> Vec<ClipboardEntry> {
+    history.0.lock().unwrap().iter().cloned().collect()
+}
+
+/// Files currently on the system clipboard, whoever put them there
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClipboardState {
+    pub file_count: usize,
+    /// `None` when the clipboard holds no files
+    pub operation: Option<TransferOp>,
+    pub paths: Vec<String>,
+}
+
+/// Reads the file list and drop effect from the system clipboard.
+/// `None` while another app holds the clipboard open.
+#[cfg(target_os = "windows")]
+fn read_clipboard_state() -> Option<ClipboardState> {
+    use crate::filesys::os::windows::{get_system_clipboard, ClipboardOp};
+
+    let (paths, op) = get_system_clipboard().ok()?;
+    if paths.is_empty() {
+        return Some(ClipboardState::default());
+    }
+    // Explorer pastes anything that isn't a cut as a copy
+    let operation = match op {
+        ClipboardOp::Move => TransferOp::Move,
+        _ => TransferOp::Copy,
+    };
+    Some(ClipboardState {
+        file_count: paths.len(),
+        operation: Some(operation),
+        paths: paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_clipboard_state() -> Option<ClipboardState> {
+    Some(ClipboardState::default())
+}
+
+/// Watches the system clipboard so files copied or cut in another app (e.g. Explorer)
+/// show up as pasteable. Emits `clipboard-changed` whenever the file list changes.
+#[cfg(target_os = "windows")]
+pub fn start_clipboard_watcher(handle: &AppHandle) {
+    use crate::filesys::os::windows::clipboard_sequence;
+    use std::thread;
+
+    let handle = handle.clone();
+    thread::spawn(move || {
+        let mut last_sequence = None;
+        let mut last_state = read_clipboard_state().unwrap_or_default();
+
+        loop {
+            thread::sleep(CLIPBOARD_POLL_INTERVAL);
+
+            let sequence = clipboard_sequence();
+            if last_sequence == Some(sequence) {
+                continue;
+            }
+            // clipboard busy: try again on the next tick
+            let Some(state) = read_clipboard_state() else {
+                continue;
+            };
+            last_sequence = Some(sequence);
+            if state == last_state {
+                continue;
+            }
+
+            let _ = handle.emit("clipboard-changed", &state);
+            last_state = state;
+        }
+    });
+}
+
+/// Only Windows exposes a file list on the clipboard to watch
+#[cfg(not(target_os = "windows"))]
+pub fn start_clipboard_watcher(_handle: &AppHandle) {}
+
+/// What a paste would bring in right now, for enabling Paste on startup
+#[tauri::command]
+pub fn get_clipboard_state() -> Result<ClipboardState, String> {
+    read_clipboard_state().ok_or_else(|| "The clipboard is in use by another app".into())
+}
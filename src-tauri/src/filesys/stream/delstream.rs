@@ -0,0 +1,522 @@
+use std::{
+    fs,
+    io::{self, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use jwalk::WalkDir;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::stream::jobs::{
+    classify_io_error, clear_readonly, ErrorAction, JobHandle, JobKind, JobStreamState,
+};
+use crate::filesys::stream::undo::{record_operation, trash_origin, FileOperation};
+use crate::util::notify::notify_job_finished;
+
+/// Minimum delay between two `delete-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Buffer size used when overwriting file contents
+const WIPE_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Upper bound on overwrite passes accepted by `secure_delete`
+const MAX_WIPE_PASSES: u32 = 35;
+
+/// Overwrite passes used when a secure delete does not specify any
+const DEFAULT_WIPE_PASSES: u32 = 3;
+
+/// Shown to the user before deleting without the trash
+const PERMANENT_WARNING: &str = "These items will be deleted permanently and cannot be restored.";
+
+/// Shown to the user before a secure wipe starts
+const SSD_WARNING: &str =
+    "Overwriting is not reliable on SSDs, flash drives, and copy-on-write or \
+journaling filesystems: wear levelling and snapshots may keep old copies of the data. \
+Use full-disk encryption for sensitive data on these devices.";
+
+/// How `delete_items` disposes of the selection
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteMode {
+    /// Move to the OS trash / recycle bin (Delete)
+    Trash,
+    /// Unlink right away (Shift+Delete)
+    Permanent,
+    /// Overwrite file contents before unlinking
+    Secure,
+}
+
+impl DeleteMode {
+    pub(crate) fn job_kind(self) -> JobKind {
+        match self {
+            DeleteMode::Trash => JobKind::Trash,
+            DeleteMode::Permanent => JobKind::Delete,
+            DeleteMode::Secure => JobKind::SecureDelete,
+        }
+    }
+
+    fn warning(self) -> Option<&'static str> {
+        match self {
+            DeleteMode::Trash => None,
+            DeleteMode::Permanent => Some(PERMANENT_WARNING),
+            DeleteMode::Secure => Some(SSD_WARNING),
+        }
+    }
+}
+
+/// What a delete is about to affect, for the confirmation dialog
+#[derive(Serialize, Clone, Debug)]
+pub struct DeleteSummary {
+    pub mode: DeleteMode,
+    pub files: usize,
+    pub folders: usize,
+    pub total_size: u64,
+    /// Whether the items can be restored afterwards (only from the trash)
+    pub reversible: bool,
+    /// Selected paths that no longer exist
+    pub missing: usize,
+    pub warning: Option<&'static str>,
+}
+
+/// A single filesystem entry scheduled for removal
+struct DeleteEntry {
+    path: PathBuf,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Outcome of removing one entry after error resolution
+enum EntryOutcome {
+    Removed,
+    Skipped,
+    Aborted,
+}
+
+/// Everything below the selected paths: files, then directories deepest first
+#[derive(Default)]
+struct DeleteScan {
+    files: Vec<DeleteEntry>,
+    dirs: Vec<DeleteEntry>,
+    /// The selected paths themselves, with the size of everything below them
+    roots: Vec<DeleteEntry>,
+    total_size: u64,
+    missing: usize,
+}
+
+/// Walks `paths`. Returns `None` once `keep_going` returns false.
+fn scan_paths(paths: &[String], mut keep_going: impl FnMut() -> bool) -> Option<DeleteScan> {
+    let mut scan = DeleteScan::default();
+
+    for root in paths {
+        let root_path = PathBuf::from(root);
+        let meta = match fs::symlink_metadata(&root_path) {
+            Ok(m) => m,
+            Err(_) => {
+                scan.missing += 1; // already gone
+                continue;
+            }
+        };
+
+        if !meta.is_dir() {
+            scan.total_size = scan.total_size.saturating_add(meta.len());
+            scan.roots.push(DeleteEntry {
+                path: root_path.clone(),
+                is_dir: false,
+                size: meta.len(),
+            });
+            scan.files.push(DeleteEntry {
+                path: root_path,
+                is_dir: false,
+                size: meta.len(),
+            });
+            continue;
+        }
+
+        let mut root_size: u64 = 0;
+        for entry in WalkDir::new(&root_path)
+            .follow_links(false)
+            .skip_hidden(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if !keep_going() {
+                return None;
+            }
+
+            let path = entry.path();
+            if entry.file_type().is_dir() {
+                scan.dirs.push(DeleteEntry {
+                    path,
+                    is_dir: true,
+                    size: 0,
+                });
+            } else {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                root_size = root_size.saturating_add(size);
+                scan.files.push(DeleteEntry {
+                    path,
+                    is_dir: false,
+                    size,
+                });
+            }
+        }
+        scan.total_size = scan.total_size.saturating_add(root_size);
+        scan.roots.push(DeleteEntry {
+            path: root_path,
+            is_dir: true,
+            size: root_size,
+        });
+    }
+
+    scan.dirs
+        .sort_by_key(|d| std::cmp::Reverse(d.path.components().count()));
+    Some(scan)
+}
+
+/// Counts and sizes what deleting `paths` in `mode` would remove, so the UI can
+/// word its confirmation (e.g. "Permanently delete 3 files, 1.2 GB?").
+#[tauri::command]
+pub async fn get_delete_summary(
+    paths: Vec<String>,
+    mode: DeleteMode,
+) -> Result<DeleteSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = scan_paths(&paths, || true).unwrap_or_default();
+        DeleteSummary {
+            mode,
+            files: scan.files.len(),
+            folders: scan.dirs.len(),
+            total_size: scan.total_size,
+            reversible: mode == DeleteMode::Trash,
+            missing: scan.missing,
+            warning: mode.warning(),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Deletes `paths` by moving them to the trash, unlinking them, or wiping them
+/// first (`passes` overwrites, default 3). Progress is reported through the
+/// `delete-*` events whatever the mode.
+#[tauri::command]
+pub async fn delete_items(
+    handle: AppHandle,
+    state: State<'_, Arc<JobStreamState>>,
+    paths: Vec<String>,
+    mode: DeleteMode,
+    passes: Option<u32>,
+    request_id: u64,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("No paths provided".into());
+    }
+    let passes = match mode {
+        DeleteMode::Secure => passes.unwrap_or(DEFAULT_WIPE_PASSES),
+        _ => 0,
+    };
+    if mode == DeleteMode::Secure && (passes == 0 || passes > MAX_WIPE_PASSES) {
+        return Err(format!("passes must be between 1 and {}", MAX_WIPE_PASSES));
+    }
+
+    let kind = mode.job_kind();
+    let job = state.start(request_id, kind);
+    // blocks on the disk and on the user's answer to delete errors
+    let result = tauri::async_runtime::spawn_blocking({
+        let handle = handle.clone();
+        let job = job.clone();
+        move || run_delete_job(&handle, &job, &paths, mode, passes)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+    state.finish(request_id);
+    if let Err(e) = &result {
+        notify_job_finished(&handle, kind, false, e);
+    }
+    result
+}
+
+/// Overwrites every file `passes` times (zeros, ones, then random data) before
+/// unlinking it. Same as `delete_items` in `secure` mode.
+///
+/// Note: overwriting in place does not guarantee erasure on SSDs or
+/// copy-on-write filesystems; the scan event carries a warning for the UI.
+#[tauri::command]
+pub async fn secure_delete(
+    handle: AppHandle,
+    state: State<'_, Arc<JobStreamState>>,
+    paths: Vec<String>,
+    passes: u32,
+    request_id: u64,
+) -> Result<(), String> {
+    delete_items(
+        handle,
+        state,
+        paths,
+        DeleteMode::Secure,
+        Some(passes),
+        request_id,
+    )
+    .await
+}
+
+/// Scans and removes `paths`. With `passes > 0` file contents are wiped first;
+/// in trash mode only the selected paths themselves are moved.
+pub(crate) fn run_delete_job(
+    handle: &AppHandle,
+    job: &JobHandle,
+    paths: &[String],
+    mode: DeleteMode,
+    passes: u32,
+) -> Result<(), String> {
+    let request_id = job.request_id;
+
+    // Phase 1: scan -> files first, directories afterwards (deepest first)
+    let Some(scan) = scan_paths(paths, || !job.is_cancelled()) else {
+        emit_cancelled(handle, request_id);
+        return Ok(());
+    };
+    let entries: Vec<&DeleteEntry> = match mode {
+        DeleteMode::Trash => scan.roots.iter().collect(),
+        _ => scan.files.iter().chain(scan.dirs.iter()).collect(),
+    };
+    let total_size = scan.total_size;
+    let total_items = entries.len();
+
+    let _ = handle.emit(
+        "delete-scan",
+        serde_json::json!({
+            "request_id": request_id,
+            "mode": mode,
+            "total_items": total_items,
+            "files": scan.files.len(),
+            "folders": scan.dirs.len(),
+            "total_size": total_size,
+            "passes": passes,
+            "reversible": mode == DeleteMode::Trash,
+            "warning": mode.warning(),
+        }),
+    );
+
+    // Phase 2: remove files, then the emptied directories
+    let mut deleted_items: usize = 0;
+    let mut deleted_bytes: u64 = 0;
+    let mut skipped: Vec<String> = Vec::new();
+    let mut repeat_action: Option<ErrorAction> = None;
+    let mut last_progress = Instant::now();
+    // roots that went to the trash, for undoing
+    let mut trashed: Vec<PathBuf> = Vec::new();
+
+    for entry in entries {
+        if job.is_cancelled() {
+            record_trashed(handle, trashed);
+            emit_cancelled(handle, request_id);
+            return Ok(());
+        }
+
+        // resolved while the item is still there to resolve
+        let origin = (mode == DeleteMode::Trash).then(|| trash_origin(&entry.path));
+        match delete_entry(handle, job, entry, mode, passes, &mut repeat_action) {
+            EntryOutcome::Removed => {
+                deleted_items += 1;
+                deleted_bytes = deleted_bytes.saturating_add(entry.size);
+                trashed.extend(origin);
+            }
+            EntryOutcome::Skipped => skipped.push(entry.path.display().to_string()),
+            EntryOutcome::Aborted => {
+                record_trashed(handle, trashed);
+                emit_cancelled(handle, request_id);
+                return Ok(());
+            }
+        }
+        job.report_progress((deleted_items + skipped.len()) as u64, total_items as u64);
+
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            let _ = handle.emit(
+                "delete-progress",
+                serde_json::json!({
+                    "request_id": request_id,
+                    "deleted_items": deleted_items,
+                    "total_items": total_items,
+                    "deleted_bytes": deleted_bytes,
+                    "total_size": total_size,
+                    "current": entry.path.display().to_string(),
+                }),
+            );
+        }
+    }
+
+    record_trashed(handle, trashed);
+
+    let _ = handle.emit(
+        "delete-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "mode": mode,
+            "deleted_items": deleted_items,
+            "total_items": total_items,
+            "deleted_bytes": deleted_bytes,
+            "skipped": skipped,
+        }),
+    );
+
+    let verb = match mode {
+        DeleteMode::Trash => "Moved to trash",
+        _ => "Deleted",
+    };
+    notify_job_finished(
+        handle,
+        job.kind,
+        skipped.is_empty(),
+        &format!(
+            "{} {} of {} item(s), {} skipped",
+            verb,
+            deleted_items,
+            total_items,
+            skipped.len()
+        ),
+    );
+
+    Ok(())
+}
+
+/// Records the roots a trash job got through, including one stopped part way
+fn record_trashed(handle: &AppHandle, trashed: Vec<PathBuf>) {
+    if !trashed.is_empty() {
+        record_operation(handle, FileOperation::Trash { paths: trashed });
+    }
+}
+
+/// Removes one entry, asking the UI how to proceed whenever removal fails.
+fn delete_entry(
+    handle: &AppHandle,
+    job: &JobHandle,
+    entry: &DeleteEntry,
+    mode: DeleteMode,
+    passes: u32,
+    repeat_action: &mut Option<ErrorAction>,
+) -> EntryOutcome {
+    let mut forced = false;
+
+    loop {
+        let result = if mode == DeleteMode::Trash {
+            trash::delete(&entry.path).map_err(io::Error::other)
+        } else if entry.is_dir {
+            fs::remove_dir(&entry.path)
+        } else if passes > 0 && !entry.path.is_symlink() {
+            wipe_file(job, &entry.path, entry.size, passes)
+                .and_then(|_| fs::remove_file(&entry.path))
+        } else {
+            // directory symlinks/junctions on Windows need remove_dir
+            fs::remove_file(&entry.path).or_else(|e| fs::remove_dir(&entry.path).map_err(|_| e))
+        };
+
+        let err = match result {
+            Ok(_) => return EntryOutcome::Removed,
+            Err(e) => e,
+        };
+
+        let code = classify_io_error(&err, &entry.path);
+        if code == "not_found" {
+            return EntryOutcome::Removed;
+        }
+
+        let action = match repeat_action {
+            // a repeated Force must not loop forever on a file that stays locked
+            Some(ErrorAction::Force) if forced => ErrorAction::Skip,
+            Some(action) => *action,
+            None => {
+                let _ = handle.emit(
+                    "delete-error",
+                    serde_json::json!({
+                        "request_id": job.request_id,
+                        "path": entry.path.display().to_string(),
+                        "is_dir": entry.is_dir,
+                        "code": code,
+                        "error": err.to_string(),
+                    }),
+                );
+
+                let resp = job.request_error_decision();
+                if resp.repeat_for_all {
+                    *repeat_action = Some(resp.action);
+                }
+                resp.action
+            }
+        };
+
+        match action {
+            ErrorAction::Retry => continue,
+            ErrorAction::Skip => return EntryOutcome::Skipped,
+            ErrorAction::Abort => return EntryOutcome::Aborted,
+            ErrorAction::Force => {
+                forced = true;
+                let _ = clear_readonly(&entry.path);
+            }
+        }
+    }
+}
+
+/// Overwrites a file in place `passes` times and flushes each pass to disk.
+/// Cancelling stops at the next chunk but still truncates the file, which then
+/// goes on to be removed, so it's never left behind half overwritten.
+fn wipe_file(job: &JobHandle, path: &Path, size: u64, passes: u32) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    let mut buf = vec![0u8; WIPE_CHUNK_SIZE];
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0x9E37_79B9_7F4A_7C15)
+        | 1;
+
+    'passes: for pass in 0..passes {
+        match pass {
+            0 => buf.fill(0x00),
+            1 => buf.fill(0xFF),
+            _ => {}
+        }
+
+        file.seek(SeekFrom::Start(0))?;
+        let mut written: u64 = 0;
+        while written < size {
+            if job.is_cancelled() {
+                file.sync_all()?;
+                break 'passes;
+            }
+            if pass >= 2 {
+                fill_random(&mut buf, &mut seed);
+            }
+            let chunk = (size - written).min(WIPE_CHUNK_SIZE as u64) as usize;
+            file.write_all(&buf[..chunk])?;
+            written += chunk as u64;
+        }
+        file.sync_all()?;
+    }
+
+    // Truncate so the original length is not left behind in the directory entry
+    file.set_len(0)?;
+    file.sync_all()
+}
+
+/// Fills a buffer with xorshift64 output; enough to defeat pattern recovery.
+fn fill_random(buf: &mut [u8], state: &mut u64) {
+    for chunk in buf.chunks_mut(8) {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        let bytes = state.to_le_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+}
+
+fn emit_cancelled(handle: &AppHandle, request_id: u64) {
+    let _ = handle.emit(
+        "delete-cancelled",
+        serde_json::json!({ "request_id": request_id }),
+    );
+}
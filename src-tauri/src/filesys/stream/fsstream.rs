@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs,
     path::Path,
     sync::{
@@ -10,18 +11,90 @@ use std::{
 
 use jwalk::WalkDir;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::Serialize;
 use tauri::{AppHandle, Emitter, State};
 
 use crate::{
-    filesys::{nav::register_recent_access, stream::thumbs::get_thumbnail_for_path},
-    util::{caches::SharedHomeCache, ffutils::ffmpeg_init},
+    filesys::{
+        access::check_listable,
+        cloud::cloud_state,
+        git::{git_status_for_dir, IgnoreMatcher},
+        kind::file_kind,
+        links::{link_info, LinkInfo},
+        nav::register_recent_access,
+        project::detect_project_kind,
+        stream::thumbs::{get_thumbnail_for_path, image_dimensions, thumb_url, thumbnail_slots},
+        volumes::{enumerate_volumes, is_network_path, locked_volume_for},
+    },
+    util::{
+        caches::{hash_path, open_thumb_db, thumb_is_fresh, HomeSectionKind, SharedHomeCache},
+        ffutils::ffmpeg_init,
+        icon_packs::icon_url,
+        tags::{
+            notes_for_path, notes_in_directory, open_tags_db, tags_for_path, tags_in_directory,
+        },
+    },
 };
 
+/// Folders list at most this many children in their item count ("500+")
+const CHILD_COUNT_CAP: usize = 500;
+
+/// Item count of a folder, stopping at `CHILD_COUNT_CAP`
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct ChildCount {
+    pub count: usize,
+    /// The folder holds more than `count` items
+    pub capped: bool,
+}
+
+/// Counts the entries of `dir` the listing would show, reading no further than the cap
+pub fn count_children(dir: &Path, show_hidden: bool) -> Option<ChildCount> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        if !show_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if count == CHILD_COUNT_CAP {
+            return Some(ChildCount {
+                count,
+                capped: true,
+            });
+        }
+        count += 1;
+    }
+    Some(ChildCount {
+        count,
+        capped: false,
+    })
+}
+
 pub struct FileStreamState {
     pub current_id: AtomicU64,
     pub cancelled: AtomicBool,
 }
 
+impl FileStreamState {
+    /// Makes `request_id` the active stream, implicitly superseding any older one
+    pub fn begin(&self, request_id: u64) {
+        self.current_id.store(request_id, Ordering::Relaxed);
+        self.cancelled.store(false, Ordering::Relaxed);
+    }
+
+    /// True once `request_id` was cancelled or replaced; its events must be dropped
+    pub fn is_stale(&self, request_id: u64) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.current_id.load(Ordering::Relaxed) != request_id
+    }
+
+    /// Cancels `request_id` if it is still the active stream
+    pub fn cancel(&self, request_id: u64) -> bool {
+        if self.current_id.load(Ordering::Relaxed) != request_id {
+            return false;
+        }
+        !self.cancelled.swap(true, Ordering::Relaxed)
+    }
+}
+
 impl Default for FileStreamState {
     fn default() -> Self {
         Self {
@@ -41,10 +114,12 @@ pub async fn stream_directory_contents(
     sort_key: String,
     ascending: bool,
     show_hidden: bool,
+    hide_ignored: Option<bool>,
+    fast_listing: Option<bool>,
     request_id: u64,
 ) -> Result<(), String> {
     if path == "Home" {
-        return stream_home_directory(handle, cache_state, request_id).await;
+        return stream_home_directory(handle, state, cache_state, request_id).await;
     }
 
     if path.is_empty() {
@@ -66,16 +141,41 @@ pub async fn stream_directory_contents(
 
     // Verify the directory is valid
     if !Path::new(&path).is_dir() {
+        // a locked encrypted volume looks like an access error; let the UI offer to unlock it
+        if let Some(volume) = locked_volume_for(&path) {
+            let _ = handle.emit(
+                "volume-locked",
+                serde_json::json!({ "request_id": request_id, "volume": volume }),
+            );
+            return Err(format!("Volume is locked: {}", volume.path));
+        }
         return Err(format!("Path is not a valid directory: {}", path));
     }
 
+    // protected folders stat fine but list as empty; say why instead
+    if let Err(denied) = check_listable(Path::new(&path)) {
+        let _ = handle.emit(
+            "directory-access-denied",
+            serde_json::json!({
+                "request_id": request_id,
+                "path": denied.path,
+                "message": denied.message,
+                "elevation_possible": denied.elevation_possible,
+            }),
+        );
+        return Ok(());
+    }
+
+    // Per-entry metadata() is a network round trip on shares, so by default only
+    // names and types are listed up front and size/mtime are back-filled later
+    let fast = fast_listing.unwrap_or_else(|| is_network_path(&path));
+
     // ✅ Register the access in recents
     if let Err(e) = register_recent_access(&handle, &cache_state, path.clone()).await {
-        eprintln!("Failed to register recent access: {}", e);
+        tracing::warn!(target: "stream", "Failed to register recent access: {}", e);
     }
 
-    state.current_id.store(request_id, Ordering::Relaxed);
-    state.cancelled.store(false, Ordering::Relaxed);
+    state.begin(request_id);
     let pool_ref = pool.inner().clone();
 
     let walker = WalkDir::new(&path)
@@ -87,21 +187,39 @@ pub async fn stream_directory_contents(
             busy_timeout: Some(Duration::from_millis(20)),
         });
 
+    // Git-ignored entries are filtered out when the layout asks for it
+    let ignore_matcher = if hide_ignored.unwrap_or(false) {
+        IgnoreMatcher::for_dir(Path::new(&path))
+    } else {
+        None
+    };
+
     // Phase 1: Collect metadata only
     let mut items: Vec<_> = walker
         .into_iter()
         .filter_map(|entry| entry.ok())
         .filter(|entry| entry.path() != Path::new(&path))
         .filter_map(|entry| {
-            if state.cancelled.load(Ordering::Relaxed)
-                || state.current_id.load(Ordering::Relaxed) != request_id
-            {
+            if state.is_stale(request_id) {
                 return None;
             }
 
-            let meta = entry.metadata().ok()?;
-            let is_dir = meta.is_dir();
-            let size = if !is_dir { Some(meta.len()) } else { None };
+            // the file type comes from the enumeration itself; only stat when not in fast mode
+            let meta = if fast {
+                None
+            } else {
+                Some(entry.metadata().ok()?)
+            };
+            let is_dir = meta
+                .as_ref()
+                .map_or_else(|| entry.file_type().is_dir(), |m| m.is_dir());
+            if ignore_matcher
+                .as_ref()
+                .is_some_and(|m| m.is_ignored(&entry.path(), is_dir))
+            {
+                return None;
+            }
+            let size = meta.as_ref().filter(|_| !is_dir).map(|m| m.len());
             let name = entry.file_name.to_string_lossy().to_string();
             let path_str = entry.path().to_string_lossy().to_string();
             let filetype = entry
@@ -109,12 +227,35 @@ pub async fn stream_directory_contents(
                 .extension()
                 .map(|s| s.to_string_lossy().to_string())
                 .unwrap_or_default();
-            let modified = meta.modified().ok();
-
-            Some((name, path_str, is_dir, size, filetype, modified))
+            let modified = meta.as_ref().and_then(|m| m.modified().ok());
+            let cloud = meta.as_ref().and_then(cloud_state);
+            // the enumeration reports links themselves, so only links pay for the extra calls
+            let link = if entry.file_type().is_symlink() {
+                link_info(&entry.path())
+            } else {
+                LinkInfo::default()
+            };
+
+            Some((
+                name, path_str, is_dir, size, filetype, modified, cloud, link,
+            ))
         })
         .collect();
 
+    // Tags, ratings and comments for every entry in this folder, fetched in one pass
+    let (dir_tags, dir_notes) = open_tags_db(&handle)
+        .map(|conn| {
+            (
+                tags_in_directory(&conn, &path).unwrap_or_default(),
+                notes_in_directory(&conn, &path).unwrap_or_default(),
+            )
+        })
+        .unwrap_or_default();
+    let rating_of = |p: &String| dir_notes.get(p).and_then(|n| n.rating);
+
+    // Git badges when the folder lives inside a work tree
+    let git = git_status_for_dir(Path::new(&path));
+
     // Sort files
     items.sort_by(|a, b| {
         if a.2 != b.2 {
@@ -125,6 +266,7 @@ pub async fn stream_directory_contents(
             "size" => a.3.cmp(&b.3),
             "filetype" => a.4.to_lowercase().cmp(&b.4.to_lowercase()),
             "date_modified" => a.5.cmp(&b.5),
+            "rating" => rating_of(&a.1).cmp(&rating_of(&b.1)),
             _ => a.0.to_lowercase().cmp(&b.0.to_lowercase()),
         };
         if ascending {
@@ -135,10 +277,8 @@ pub async fn stream_directory_contents(
     });
 
     // Phase 1 emit: metadata only
-    for (name, path_str, is_dir, size, filetype, modified) in &items {
-        if state.cancelled.load(Ordering::Relaxed)
-            || state.current_id.load(Ordering::Relaxed) != request_id
-        {
+    for (name, path_str, is_dir, size, filetype, modified, cloud, link) in &items {
+        if state.is_stale(request_id) {
             return Ok(());
         }
 
@@ -154,34 +294,111 @@ pub async fn stream_directory_contents(
                 "date_modified": modified
                     .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
                     .map(|d| d.as_secs()),
-                "pinned": false
+                "pinned": false,
+                "tags": dir_tags.get(path_str).cloned().unwrap_or_default(),
+                "rating": rating_of(path_str),
+                "git_status": git.as_ref().and_then(|g| g.status_of(name)),
+                "cloud": cloud,
+                "is_symlink": link.is_symlink,
+                "is_junction": link.is_junction,
+                "link_target": link.link_target,
+                "link_broken": link.link_broken,
+                "icon_url": icon_url(&handle, Path::new(path_str), *is_dir),
+                // without a stat pass, only the extension is used; file-details sniffs the rest
+                "kind": if *is_dir {
+                    None
+                } else {
+                    file_kind(Path::new(path_str), !fast)
+                },
+                "project_kind": if *is_dir && !fast {
+                    detect_project_kind(Path::new(path_str))
+                } else {
+                    None
+                },
+                "item_count": if *is_dir && !fast {
+                    count_children(Path::new(path_str), show_hidden)
+                } else {
+                    None
+                },
+                "dimensions": if !*is_dir && !fast {
+                    fs::metadata(path_str)
+                        .ok()
+                        .and_then(|m| image_dimensions(Path::new(path_str), &m))
+                } else {
+                    None
+                }
             }),
         );
     }
 
+    if state.is_stale(request_id) {
+        return Ok(());
+    }
     let _ = handle.emit(
         "file-metadata-complete",
         serde_json::json!({
             "request_id": request_id,
-            "path": path
+            "path": path,
+            "fast_listing": fast,
+            "git": git.as_ref().map(|g| serde_json::json!({
+                "root": g.root,
+                "branch": g.branch,
+            }))
         }),
     );
 
     let ffmpeg_handler = ffmpeg_init(&handle);
+    let slots = thumbnail_slots(&handle, Path::new(&path), pool.current_num_threads()).await;
 
     // Phase 2: generate/fetch thumbnails in parallel
-    items
-        .into_par_iter()
-        .for_each(|(_name, path_str, _is_dir, _size, _filetype, _modified)| {
-            if state.cancelled.load(Ordering::Relaxed)
-                || state.current_id.load(Ordering::Relaxed) != request_id
-            {
+    items.into_par_iter().for_each(
+        |(_name, path_str, _is_dir, _size, _filetype, _modified, _cloud, _link)| {
+            if state.is_stale(request_id) {
                 return;
             }
 
+            // Back-fill what fast mode skipped before the (slower) thumbnail
+            if fast {
+                let meta = fs::metadata(&path_str).ok();
+                if state.is_stale(request_id) {
+                    return;
+                }
+                let _ = handle.emit(
+                    "file-details",
+                    serde_json::json!({
+                        "request_id": request_id,
+                        "path": path_str,
+                        "size": meta.as_ref().filter(|m| !m.is_dir()).map(|m| m.len()),
+                        "date_modified": meta
+                            .as_ref()
+                            .and_then(|m| m.modified().ok())
+                            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                            .map(|d| d.as_secs()),
+                        "cloud": meta.as_ref().and_then(cloud_state),
+                        "kind": meta
+                            .as_ref()
+                            .filter(|m| m.is_file())
+                            .and_then(|_| file_kind(Path::new(&path_str), true)),
+                        "item_count": meta
+                            .as_ref()
+                            .filter(|m| m.is_dir())
+                            .and_then(|_| count_children(Path::new(&path_str), show_hidden)),
+                        "dimensions": meta
+                            .as_ref()
+                            .filter(|m| m.is_file())
+                            .and_then(|m| image_dimensions(Path::new(&path_str), m)),
+                    }),
+                );
+            }
+
             // Use the helper to get a base64 thumbnail
-            let thumbnail = get_thumbnail_for_path(&handle, &ffmpeg_handler, &path_str);
+            let thumbnail =
+                slots.run(|| get_thumbnail_for_path(&handle, &ffmpeg_handler, &path_str));
 
+            // generation can take long enough for the user to have moved on
+            if state.is_stale(request_id) {
+                return;
+            }
             let _ = handle.emit(
                 "file-thumbnail",
                 serde_json::json!({
@@ -190,12 +407,11 @@ pub async fn stream_directory_contents(
                     "thumbnail": thumbnail,
                 }),
             );
-        });
+        },
+    );
 
     // Phase 3: complete
-    if !state.cancelled.load(Ordering::Relaxed)
-        && state.current_id.load(Ordering::Relaxed) == request_id
-    {
+    if !state.is_stale(request_id) {
         let _ = handle.emit(
             "file-stream-complete",
             serde_json::json!({ "request_id": request_id, "path": path }),
@@ -207,77 +423,161 @@ pub async fn stream_directory_contents(
 
 pub async fn stream_home_directory(
     handle: AppHandle,
+    state: State<'_, Arc<FileStreamState>>,
     cache_state: State<'_, SharedHomeCache>,
     request_id: u64,
 ) -> Result<(), String> {
+    state.begin(request_id);
     let cache = cache_state.0.read().await;
     let path = "Home".to_string();
-
-    // --- Phase 1: emit metadata for cached files ---
-    for item in cache.recent_files.iter() {
-        let modified = fs::metadata(&item.path)
+    let tag_conn = open_tags_db(&handle).ok();
+    let item_tags = |path: &str| {
+        tag_conn
+            .as_ref()
+            .and_then(|conn| tags_for_path(conn, path).ok())
+            .unwrap_or_default()
+    };
+    let item_rating = |path: &str| {
+        tag_conn
+            .as_ref()
+            .and_then(|conn| notes_for_path(conn, path).ok())
+            .and_then(|n| n.rating)
+    };
+
+    let layout = &cache.layout;
+    let recents = layout.recents_limit;
+    let modified_of = |path: &str| {
+        fs::metadata(path)
             .and_then(|m| m.modified())
             .ok()
             .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
-        let filetype = item.name.rsplit('.').next().unwrap_or("").to_string();
-        let _ = handle.emit(
-            "file-metadata",
-            serde_json::json!({
-                "request_id": request_id,
-                "name": item.name,
-                "path": item.path,
-                "is_dir": false,
-                "size": item.size,
-                "filetype": filetype,
-                "date_modified": modified,
-                "pinned": false
-            }),
-        );
-    }
+            .map(|d| d.as_secs())
+    };
+
+    // Recents whose thumbnail is cached and fresh are served straight from the
+    // thumb DB by URL; only the rest go through thumbnail generation below
+    let thumb_conn = open_thumb_db(&handle).ok();
+    let recent_thumb_urls: HashMap<&str, String> = cache
+        .recent_files
+        .iter()
+        .take(recents)
+        .filter_map(|item| {
+            let hash = item
+                .thumb_hash
+                .as_deref()
+                .and_then(|h| h.parse().ok())
+                .unwrap_or_else(|| hash_path(&item.path));
+            let mtime = modified_of(&item.path)? as i64;
+            thumb_is_fresh(thumb_conn.as_ref()?, hash, mtime)
+                .then(|| (item.path.as_str(), thumb_url(hash, mtime)))
+        })
+        .collect();
 
-    for item in cache.recent_dirs.iter() {
-        let modified = fs::metadata(&item.path)
-            .and_then(|m| m.modified())
-            .ok()
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
-        let filetype = item.name.rsplit('.').next().unwrap_or("").to_string();
-        let _ = handle.emit(
-            "file-metadata",
-            serde_json::json!({
-                "request_id": request_id,
-                "name": item.name,
-                "path": item.path,
-                "is_dir": true,
-                "size": item.size,
-                "filetype": filetype,
-                "date_modified": modified,
-                "pinned": false
-            }),
-        );
+    // --- Phase 1: emit metadata section by section, in the user's order ---
+    for section in layout.visible_sections() {
+        let items: Vec<serde_json::Value> = match section {
+            HomeSectionKind::RecentFiles => cache
+                .recent_files
+                .iter()
+                .take(recents)
+                .map(|item| {
+                    serde_json::json!({
+                        "name": item.name,
+                        "path": item.path,
+                        "is_dir": false,
+                        "size": item.size,
+                        "filetype": item.name.rsplit('.').next().unwrap_or(""),
+                        "date_modified": modified_of(&item.path),
+                        "pinned": false,
+                        "tags": item_tags(&item.path),
+                        "rating": item_rating(&item.path),
+                        "thumbnail_url": recent_thumb_urls.get(item.path.as_str())
+                    })
+                })
+                .collect(),
+            HomeSectionKind::RecentDirs => cache
+                .recent_dirs
+                .iter()
+                .take(recents)
+                .map(|item| {
+                    serde_json::json!({
+                        "name": item.name,
+                        "path": item.path,
+                        "is_dir": true,
+                        "size": item.size,
+                        "filetype": item.name.rsplit('.').next().unwrap_or(""),
+                        "date_modified": modified_of(&item.path),
+                        "pinned": false,
+                        "tags": item_tags(&item.path),
+                        "rating": item_rating(&item.path),
+                        "project_kind": detect_project_kind(Path::new(&item.path))
+                    })
+                })
+                .collect(),
+            HomeSectionKind::Pinned => cache
+                .pinned_items
+                .iter()
+                .map(|item| {
+                    serde_json::json!({
+                        "name": item.name,
+                        "path": item.path,
+                        "is_dir": false,
+                        "size": item.size,
+                        "filetype": item.name.rsplit('.').next().unwrap_or(""),
+                        "date_modified": modified_of(&item.path),
+                        "pinned": true,
+                        "tags": item_tags(&item.path),
+                        "rating": item_rating(&item.path)
+                    })
+                })
+                .collect(),
+            HomeSectionKind::Drives => enumerate_volumes()
+                .into_iter()
+                .map(|volume| {
+                    serde_json::json!({
+                        "name": volume.name,
+                        "path": volume.path,
+                        "is_dir": true,
+                        "size": volume.total_bytes,
+                        "free_bytes": volume.free_bytes,
+                        "volume_kind": volume.kind,
+                        "filetype": "",
+                        "pinned": false,
+                    })
+                })
+                .collect(),
+            HomeSectionKind::Workspaces => layout
+                .workspaces
+                .iter()
+                .map(|item| {
+                    serde_json::json!({
+                        "name": item.name,
+                        "path": item.path,
+                        "is_dir": true,
+                        "size": item.size,
+                        "filetype": "",
+                        "date_modified": modified_of(&item.path),
+                        "pinned": false,
+                        "tags": item_tags(&item.path),
+                        "rating": item_rating(&item.path),
+                        "project_kind": detect_project_kind(Path::new(&item.path))
+                    })
+                })
+                .collect(),
+        };
+
+        for mut item in items {
+            if state.is_stale(request_id) {
+                return Ok(());
+            }
+            item["request_id"] = request_id.into();
+            item["section"] = serde_json::json!(section);
+            let _ = handle.emit("file-metadata", item);
+        }
     }
 
-    for item in cache.pinned_items.iter() {
-        let modified = fs::metadata(&item.path)
-            .and_then(|m| m.modified())
-            .ok()
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
-        let filetype = item.name.rsplit('.').next().unwrap_or("").to_string();
-        let _ = handle.emit(
-            "file-metadata",
-            serde_json::json!({
-                "request_id": request_id,
-                "name": item.name,
-                "path": item.path,
-                "is_dir": false,
-                "size": item.size,
-                "filetype": filetype,
-                "date_modified": modified,
-                "pinned": true
-            }),
-        );
+    if state.is_stale(request_id) {
+        return Ok(());
     }
 
     let _ = handle.emit(
@@ -291,8 +591,17 @@ pub async fn stream_home_directory(
     // --- Phase 2: emit thumbnails using helper ---
     let ffmpeg_handler = ffmpeg_init(&handle);
 
-    for item in cache.recent_files.iter() {
+    let recent_files = layout
+        .is_visible(HomeSectionKind::RecentFiles)
+        .then(|| cache.recent_files.iter().take(recents))
+        .into_iter()
+        .flatten()
+        .filter(|item| !recent_thumb_urls.contains_key(item.path.as_str()));
+    for item in recent_files {
         if let Some(thumb) = get_thumbnail_for_path(&handle, &ffmpeg_handler, &item.path) {
+            if state.is_stale(request_id) {
+                return Ok(());
+            }
             let _ = handle.emit(
                 "file-thumbnail",
                 serde_json::json!({
@@ -304,8 +613,16 @@ pub async fn stream_home_directory(
         }
     }
 
-    for item in cache.pinned_items.iter() {
+    let pinned_items = layout
+        .is_visible(HomeSectionKind::Pinned)
+        .then_some(&cache.pinned_items)
+        .into_iter()
+        .flatten();
+    for item in pinned_items {
         if let Some(thumb) = get_thumbnail_for_path(&handle, &ffmpeg_handler, &item.path) {
+            if state.is_stale(request_id) {
+                return Ok(());
+            }
             let _ = handle.emit(
                 "file-thumbnail",
                 serde_json::json!({
@@ -318,6 +635,9 @@ pub async fn stream_home_directory(
     }
 
     // --- Phase 3: signal completion ---
+    if state.is_stale(request_id) {
+        return Ok(());
+    }
     let _ = handle.emit(
         "file-stream-complete",
         serde_json::json!({
@@ -328,3 +648,20 @@ pub async fn stream_home_directory(
 
     Ok(())
 }
+
+/// Stops a directory stream. Events still in flight for `request_id` are dropped;
+/// `file-stream-cancelled` confirms the cancellation.
+#[tauri::command]
+pub fn cancel_directory_stream(
+    handle: AppHandle,
+    state: State<'_, Arc<FileStreamState>>,
+    request_id: u64,
+) -> Result<(), String> {
+    if state.cancel(request_id) {
+        let _ = handle.emit(
+            "file-stream-cancelled",
+            serde_json::json!({ "request_id": request_id }),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::filesys::stream::opstream::CopyStreamState;
+
+/// The kind of work a background job performs
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Copy,
+    Move,
+    Delete,
+    SecureDelete,
+    Trash,
+    StorageAnalysis,
+    Encrypt,
+    Decrypt,
+    Snapshot,
+    SnapshotDiff,
+    DeviceTransfer,
+    CloudSync,
+    SelectionSummary,
+    Mirror,
+    Upload,
+    ThumbnailExport,
+}
+
+/// How to resolve a per-item error raised by a job
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Try the same item again
+    Retry,
+    /// Leave the item and continue with the rest
+    Skip,
+    /// Clear read-only attributes and try again
+    Force,
+    /// Stop the whole job
+    Abort,
+}
+
+/// The user's response to a job error (filled by resolve_job_error)
+#[derive(Clone, Debug)]
+pub struct ErrorResponse {
+    pub action: ErrorAction,
+    pub repeat_for_all: bool,
+}
+
+/// A single running job: cancellation flag plus the error-decision slot
+/// the worker blocks on while the UI asks the user what to do.
+pub struct JobHandle {
+    pub request_id: u64,
+    pub kind: JobKind,
+    cancelled: AtomicBool,
+    /// Set while the job is blocked on a user prompt (e.g. a name conflict)
+    waiting: AtomicBool,
+    /// Set while the user has paused the job; workers block on `pause_cv`
+    paused: Mutex<bool>,
+    pause_cv: Condvar,
+    done: AtomicU64,
+    total: AtomicU64,
+    error_lock: Mutex<ErrorSlot>,
+    error_cv: Condvar,
+}
+
+struct ErrorSlot {
+    pending: bool,
+    response: Option<ErrorResponse>,
+}
+
+impl JobHandle {
+    fn new(request_id: u64, kind: JobKind) -> Self {
+        Self {
+            request_id,
+            kind,
+            cancelled: AtomicBool::new(false),
+            waiting: AtomicBool::new(false),
+            paused: Mutex::new(false),
+            pause_cv: Condvar::new(),
+            done: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            error_lock: Mutex::new(ErrorSlot {
+                pending: false,
+                response: None,
+            }),
+            error_cv: Condvar::new(),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        // wake a worker blocked on an error decision or a pause
+        self.error_cv.notify_all();
+        self.pause_cv.notify_all();
+    }
+
+    /// Holds the job at its next checkpoint until `resume` is called
+    pub fn pause(&self) {
+        *self.paused.lock().unwrap() = true;
+    }
+
+    /// Lets a paused job continue; false if it wasn't paused
+    pub fn resume(&self) -> bool {
+        let was_paused = std::mem::replace(&mut *self.paused.lock().unwrap(), false);
+        self.pause_cv.notify_all();
+        was_paused
+    }
+
+    /// Blocks while the job is paused, flagged as waiting so the taskbar shows it
+    /// paused. Cancelling ends the pause. Returns whether the job was paused at all.
+    /// Safe to call from several worker threads of the same job at once.
+    pub fn wait_while_paused(&self) -> bool {
+        let mut paused = self.paused.lock().unwrap();
+        if !*paused {
+            return false;
+        }
+        self.set_waiting(true);
+        while *paused && !self.is_cancelled() {
+            paused = self
+                .pause_cv
+                .wait_timeout(paused, Duration::from_millis(200))
+                .unwrap()
+                .0;
+        }
+        self.set_waiting(false);
+        true
+    }
+
+    /// Records how far along the job is, in whatever unit it counts (bytes or items)
+    pub fn report_progress(&self, done: u64, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+        self.done.store(done.min(total), Ordering::Relaxed);
+    }
+
+    pub fn progress(&self) -> (u64, u64) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn set_waiting(&self, waiting: bool) {
+        self.waiting.store(waiting, Ordering::Relaxed);
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        self.waiting.load(Ordering::Relaxed)
+    }
+
+    /// True while an error event is waiting for the user's decision
+    pub fn has_pending_error(&self) -> bool {
+        self.error_lock.lock().unwrap().pending
+    }
+
+    /// Called by the job worker after emitting an error event. Blocks until the UI
+    /// answers; returns `Abort` if the job is cancelled while waiting.
+    pub fn request_error_decision(&self) -> ErrorResponse {
+        let mut slot = self.error_lock.lock().unwrap();
+        slot.pending = true;
+        slot.response = None;
+
+        loop {
+            if self.is_cancelled() {
+                slot.pending = false;
+                return ErrorResponse {
+                    action: ErrorAction::Abort,
+                    repeat_for_all: false,
+                };
+            }
+
+            if let Some(resp) = slot.response.take() {
+                slot.pending = false;
+                return resp;
+            }
+
+            slot = self
+                .error_cv
+                .wait_timeout(slot, Duration::from_millis(200))
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Called by the UI command to submit a decision and wake the blocked worker.
+    pub fn submit_error_response(&self, response: ErrorResponse) -> Result<(), String> {
+        let mut slot = self.error_lock.lock().unwrap();
+        if !slot.pending {
+            return Err("no pending job error".into());
+        }
+        slot.response = Some(response);
+        self.error_cv.notify_all();
+        Ok(())
+    }
+}
+
+/// Registry of running background jobs, keyed by request id.
+#[derive(Default)]
+pub struct JobStreamState {
+    jobs: Mutex<HashMap<u64, Arc<JobHandle>>>,
+    // jobs started by the backend itself so far
+    background_jobs: AtomicU64,
+}
+
+impl JobStreamState {
+    /// Registers a new job and returns its handle
+    pub fn start(&self, request_id: u64, kind: JobKind) -> Arc<JobHandle> {
+        let job = Arc::new(JobHandle::new(request_id, kind));
+        self.jobs.lock().unwrap().insert(request_id, job.clone());
+        job
+    }
+
+    /// Registers a job the backend starts on its own (e.g. auto-organize moving a
+    /// download), under an id counted down from `u64::MAX` so it can't collide
+    /// with the request ids the frontend hands out
+    pub fn start_background(&self, kind: JobKind) -> Arc<JobHandle> {
+        let request_id = u64::MAX - self.background_jobs.fetch_add(1, Ordering::Relaxed);
+        self.start(request_id, kind)
+    }
+
+    /// Removes a finished (or cancelled) job from the registry
+    pub fn finish(&self, request_id: u64) {
+        self.jobs.lock().unwrap().remove(&request_id);
+    }
+
+    pub fn get(&self, request_id: u64) -> Option<Arc<JobHandle>> {
+        self.jobs.lock().unwrap().get(&request_id).cloned()
+    }
+
+    /// Combined progress of every running job, or None when the queue is empty
+    pub fn summary(&self) -> Option<JobsSummary> {
+        let jobs = self.jobs.lock().unwrap();
+        if jobs.is_empty() {
+            return None;
+        }
+
+        let mut summary = JobsSummary::default();
+        for job in jobs.values() {
+            // each job weighs the same so bytes and item counts can be mixed
+            let (done, total) = job.progress();
+            if total > 0 {
+                summary.fraction += done as f64 / total as f64;
+            } else {
+                summary.scanning = true;
+            }
+            summary.count += 1;
+            summary.has_error |= job.has_pending_error();
+            summary.waiting |= job.is_waiting();
+        }
+        summary.fraction /= summary.count as f64;
+        Some(summary)
+    }
+}
+
+/// Aggregate state of the job queue, used for the taskbar indicator
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct JobsSummary {
+    pub count: usize,
+    /// Average completion across jobs, 0.0..=1.0
+    pub fraction: f64,
+    pub scanning: bool,
+    pub waiting: bool,
+    pub has_error: bool,
+}
+
+/// Classifies an I/O failure into a stable code the UI can map to a message.
+pub fn classify_io_error(err: &io::Error, path: &Path) -> &'static str {
+    // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+    if cfg!(windows) && matches!(err.raw_os_error(), Some(32) | Some(33)) {
+        return "locked";
+    }
+    match err.kind() {
+        io::ErrorKind::NotFound => "not_found",
+        io::ErrorKind::PermissionDenied => {
+            let readonly = std::fs::symlink_metadata(path)
+                .map(|m| m.permissions().readonly())
+                .unwrap_or(false);
+            if readonly {
+                "readonly"
+            } else {
+                "permission_denied"
+            }
+        }
+        _ => "other",
+    }
+}
+
+/// Clears the read-only flag on a path so it can be modified or removed.
+pub fn clear_readonly(path: &Path) -> io::Result<()> {
+    let mut perms = std::fs::symlink_metadata(path)?.permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    perms.set_readonly(false);
+    std::fs::set_permissions(path, perms)
+}
+
+/// Cancels a running job. The job emits its own cancelled event when it stops.
+#[tauri::command]
+pub fn cancel_job(
+    state: State<'_, Arc<JobStreamState>>,
+    copy_state: State<'_, Arc<CopyStreamState>>,
+    request_id: u64,
+) -> Result<(), String> {
+    match state.get(request_id) {
+        Some(job) => {
+            job.cancel();
+            // a transfer may be parked on a conflict prompt or in the queue
+            copy_state.wake_waiters();
+            Ok(())
+        }
+        None => Err(format!("no running job with id {}", request_id)),
+    }
+}
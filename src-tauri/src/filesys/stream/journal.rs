@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Manager, State};
+
+use crate::filesys::stream::{
+    jobs::JobStreamState,
+    opstream::{
+        run_journaled_transfer, CopyStreamState, DuplicateStrategy, TransferOp, TransferRoot,
+    },
+};
+
+// ===============================
+// Journal Format
+// ===============================
+//
+// Every copy/move writes `journal/<request_id>.jsonl` under app data: a header line
+// followed by one record per file started/finished. Appending a line per file keeps the
+// cost flat for huge jobs and survives the process being killed mid-write (at worst the
+// last line is truncated and ignored). The file is removed when the job ends normally,
+// so any journal found at launch belongs to an interrupted job.
+//
+// The journal is written as soon as a transfer is requested, while it may still be
+// waiting behind another one; a `begin` record marks it leaving the queue. A journal
+// without one belongs to a transfer that was queued but never started.
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JournalEntry {
+    pub src: PathBuf,
+    pub dest: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct JournalHeader {
+    id: u64,
+    op: TransferOp,
+    roots: Vec<JournalEntry>,
+    started_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JournalRecord {
+    /// The transfer left the queue and started running
+    Begin,
+    /// About to transfer a file; the destination may be partial until `Done`
+    Start {
+        src: PathBuf,
+        dest: PathBuf,
+    },
+    Done {
+        src: PathBuf,
+        dest: PathBuf,
+    },
+    /// The user chose "repeat for all" for conflicts
+    Strategy {
+        strategy: DuplicateStrategy,
+    },
+}
+
+/// An interrupted transfer reconstructed from its journal
+#[derive(Clone, Debug)]
+pub struct RecoveredTransfer {
+    pub id: u64,
+    pub op: TransferOp,
+    pub roots: Vec<JournalEntry>,
+    pub started_at: u64,
+    pub completed: Vec<JournalEntry>,
+    /// File that was being written when the app died
+    pub in_flight: Option<JournalEntry>,
+    pub repeat_strategy: Option<DuplicateStrategy>,
+    /// Still waiting behind another transfer when the app closed
+    pub queued: bool,
+}
+
+/// Summary shown in the "resume interrupted operations" prompt
+#[derive(Serialize, Clone, Debug)]
+pub struct InterruptedTransfer {
+    pub id: u64,
+    pub op: TransferOp,
+    pub sources: Vec<String>,
+    pub destinations: Vec<String>,
+    pub files_completed: usize,
+    pub started_at: u64,
+    /// Never started: resuming runs the whole job
+    pub queued: bool,
+}
+
+impl From<&RecoveredTransfer> for InterruptedTransfer {
+    fn from(t: &RecoveredTransfer) -> Self {
+        Self {
+            id: t.id,
+            op: t.op,
+            sources: t
+                .roots
+                .iter()
+                .map(|r| r.src.to_string_lossy().to_string())
+                .collect(),
+            destinations: t
+                .roots
+                .iter()
+                .map(|r| r.dest.to_string_lossy().to_string())
+                .collect(),
+            files_completed: t.completed.len(),
+            started_at: t.started_at,
+            queued: t.queued,
+        }
+    }
+}
+
+fn get_journal_dir(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("journal");
+    fs::create_dir_all(&path).ok();
+    path
+}
+
+fn journal_path(handle: &AppHandle, id: u64) -> PathBuf {
+    get_journal_dir(handle).join(format!("{}.jsonl", id))
+}
+
+// ===============================
+// Writer
+// ===============================
+
+/// Append-only journal for one running transfer.
+/// Write failures are ignored: journaling must never fail the copy itself.
+pub struct TransferJournal {
+    path: PathBuf,
+    file: Option<File>,
+    completed: HashSet<PathBuf>,
+    pub repeat_strategy: Option<DuplicateStrategy>,
+}
+
+impl TransferJournal {
+    /// Starts a fresh journal for a new job
+    pub fn create(handle: &AppHandle, id: u64, op: TransferOp, roots: &[TransferRoot]) -> Self {
+        let path = journal_path(handle, id);
+        let header = JournalHeader {
+            id,
+            op,
+            roots: roots
+                .iter()
+                .map(|r| JournalEntry {
+                    src: r.src.clone(),
+                    dest: r.dest.clone(),
+                })
+                .collect(),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+
+        let file = File::create(&path).ok().and_then(|mut f| {
+            let line = serde_json::to_string(&header).ok()?;
+            writeln!(f, "{}", line).ok()?;
+            Some(f)
+        });
+
+        Self {
+            path,
+            file,
+            completed: HashSet::new(),
+            repeat_strategy: None,
+        }
+    }
+
+    /// Re-opens an interrupted job's journal so progress keeps appending to it
+    pub fn reopen(handle: &AppHandle, recovered: &RecoveredTransfer) -> Self {
+        let path = journal_path(handle, recovered.id);
+        let file = OpenOptions::new().append(true).open(&path).ok();
+
+        Self {
+            path,
+            file,
+            completed: recovered.completed.iter().map(|e| e.src.clone()).collect(),
+            repeat_strategy: recovered.repeat_strategy,
+        }
+    }
+
+    fn append(&mut self, record: &JournalRecord) {
+        if let (Some(file), Ok(line)) = (self.file.as_mut(), serde_json::to_string(record)) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    /// True when a previous run already transferred this source
+    pub fn is_completed(&self, src: &Path) -> bool {
+        self.completed.contains(src)
+    }
+
+    /// The transfer got its turn in the queue
+    pub fn begin(&mut self) {
+        self.append(&JournalRecord::Begin);
+    }
+
+    pub fn start(&mut self, src: &Path, dest: &Path) {
+        self.append(&JournalRecord::Start {
+            src: src.to_path_buf(),
+            dest: dest.to_path_buf(),
+        });
+    }
+
+    pub fn done(&mut self, src: &Path, dest: &Path) {
+        self.append(&JournalRecord::Done {
+            src: src.to_path_buf(),
+            dest: dest.to_path_buf(),
+        });
+    }
+
+    pub fn set_strategy(&mut self, strategy: DuplicateStrategy) {
+        self.repeat_strategy = Some(strategy);
+        self.append(&JournalRecord::Strategy { strategy });
+    }
+
+    /// The job ended on its own (finished, failed or cancelled); nothing to recover
+    pub fn finish(mut self) {
+        self.file.take();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// ===============================
+// Recovery
+// ===============================
+
+fn read_journal(path: &Path) -> Option<RecoveredTransfer> {
+    let mut lines = BufReader::new(File::open(path).ok()?).lines();
+    let header: JournalHeader = serde_json::from_str(&lines.next()?.ok()?).ok()?;
+
+    let mut recovered = RecoveredTransfer {
+        id: header.id,
+        op: header.op,
+        roots: header.roots,
+        started_at: header.started_at,
+        completed: Vec::new(),
+        in_flight: None,
+        repeat_strategy: None,
+        queued: true,
+    };
+
+    // a torn final line just ends the replay
+    for line in lines.map_while(Result::ok) {
+        let Ok(record) = serde_json::from_str::<JournalRecord>(&line) else {
+            break;
+        };
+        // journals written before the queue existed have no `begin`
+        recovered.queued &= matches!(record, JournalRecord::Strategy { .. });
+        match record {
+            JournalRecord::Begin => {}
+            JournalRecord::Start { src, dest } => {
+                recovered.in_flight = Some(JournalEntry { src, dest });
+            }
+            JournalRecord::Done { src, dest } => {
+                recovered.in_flight = None;
+                recovered.completed.push(JournalEntry { src, dest });
+            }
+            JournalRecord::Strategy { strategy } => recovered.repeat_strategy = Some(strategy),
+        }
+    }
+
+    Some(recovered)
+}
+
+fn load_recovered(handle: &AppHandle, id: u64) -> Result<RecoveredTransfer, String> {
+    read_journal(&journal_path(handle, id))
+        .ok_or_else(|| format!("No interrupted operation with id {}", id))
+}
+
+/// Settles the file that was mid-transfer when the app died.
+/// Returns it as completed if a move's rename already went through.
+fn settle_in_flight(op: TransferOp, entry: &JournalEntry) -> Option<JournalEntry> {
+    match (entry.src.exists(), entry.dest.exists()) {
+        // a partial copy: throw it away, the source is intact
+        (true, true) => {
+            let _ = fs::remove_file(&entry.dest);
+            None
+        }
+        (false, true) if op == TransferOp::Move => Some(entry.clone()),
+        _ => None,
+    }
+}
+
+/// Moves a file back to where it came from, falling back to copy + delete across volumes
+fn move_back(entry: &JournalEntry) -> std::io::Result<()> {
+    if let Some(parent) = entry.src.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(&entry.dest, &entry.src).is_err() {
+        fs::copy(&entry.dest, &entry.src)?;
+        fs::remove_file(&entry.dest)?;
+    }
+    Ok(())
+}
+
+/// Transfers whose journal survived a crash or forced exit, including those still
+/// queued when the app closed, oldest first so resuming them keeps the queue order
+#[tauri::command]
+pub fn list_interrupted_transfers(handle: AppHandle) -> Vec<InterruptedTransfer> {
+    let Ok(entries) = fs::read_dir(get_journal_dir(&handle)) else {
+        return Vec::new();
+    };
+
+    let mut transfers: Vec<InterruptedTransfer> = entries
+        .flatten()
+        .filter_map(|e| read_journal(&e.path()))
+        .map(|t| InterruptedTransfer::from(&t))
+        .collect();
+    transfers.sort_by_key(|t| t.started_at);
+    transfers
+}
+
+/// Continues an interrupted transfer as a new job, skipping files it already finished
+#[tauri::command]
+pub async fn resume_interrupted_transfer(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    id: u64,
+    request_id: u64,
+) -> Result<(), String> {
+    let mut recovered = load_recovered(&handle, id)?;
+    if let Some(entry) = recovered.in_flight.take() {
+        if let Some(done) = settle_in_flight(recovered.op, &entry) {
+            recovered.completed.push(done);
+        }
+    }
+
+    let roots: Vec<TransferRoot> = recovered
+        .roots
+        .iter()
+        .filter(|r| r.src.exists())
+        .map(|r| TransferRoot {
+            src: r.src.clone(),
+            dest: r.dest.clone(),
+        })
+        .collect();
+    if roots.is_empty() {
+        let _ = fs::remove_file(journal_path(&handle, id));
+        return Err("Nothing left to transfer".into());
+    }
+
+    // progress keeps appending to the original journal, so a second crash is resumable too
+    let journal = TransferJournal::reopen(&handle, &recovered);
+    let state = state.inner().clone();
+    let jobs = jobs.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        run_journaled_transfer(
+            &handle,
+            &state,
+            &jobs,
+            roots,
+            recovered.op,
+            request_id,
+            journal,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r)
+}
+
+/// Undoes an interrupted transfer: copied files are deleted, moved files are moved back
+#[tauri::command]
+pub async fn rollback_interrupted_transfer(handle: AppHandle, id: u64) -> Result<usize, String> {
+    let recovered = load_recovered(&handle, id)?;
+
+    let mut completed = recovered.completed.clone();
+    if let Some(entry) = &recovered.in_flight {
+        completed.extend(settle_in_flight(recovered.op, entry));
+    }
+
+    let mut failed = 0;
+    for entry in completed.iter().rev() {
+        let result = match recovered.op {
+            TransferOp::Copy => fs::remove_file(&entry.dest),
+            TransferOp::Move => move_back(entry),
+        };
+        if result.is_err() {
+            failed += 1;
+        }
+    }
+
+    // drop destination folders the job created, now that they are empty
+    let mut dirs: Vec<&Path> = completed.iter().filter_map(|e| e.dest.parent()).collect();
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+    dirs.dedup();
+    for dir in dirs {
+        if recovered.roots.iter().any(|r| dir.starts_with(&r.dest)) {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+    for root in &recovered.roots {
+        if root.dest.is_dir() {
+            let _ = fs::remove_dir(&root.dest);
+        }
+    }
+
+    let _ = fs::remove_file(journal_path(&handle, id));
+    if failed > 0 {
+        return Err(format!("{} file(s) could not be rolled back", failed));
+    }
+    Ok(completed.len())
+}
+
+/// Forgets an interrupted transfer, leaving files as they are
+#[tauri::command]
+pub fn discard_interrupted_transfer(handle: AppHandle, id: u64) -> Result<(), String> {
+    fs::remove_file(journal_path(&handle, id))
+        .map_err(|e| format!("Failed to discard operation: {}", e))
+}
@@ -0,0 +1,354 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use jwalk::WalkDir;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::stream::delstream::{run_delete_job, DeleteMode};
+use crate::filesys::stream::jobs::{JobKind, JobStreamState};
+use crate::filesys::stream::journal::TransferJournal;
+use crate::filesys::stream::opstream::{
+    run_journaled_transfer, CopyStreamState, DuplicateStrategy, TransferOp, TransferRoot,
+};
+
+/// Extraneous items sent per `mirror-preview-batch` event
+const PREVIEW_BATCH_SIZE: usize = 200;
+
+/// Modification times closer than this count as equal (FAT stores 2 s steps)
+const MTIME_TOLERANCE_SECS: u64 = 2;
+
+/// A destination item with no counterpart in the source, removed by the mirror
+#[derive(Serialize, Clone, Debug)]
+pub struct MirrorEntry {
+    pub path: String,
+    pub is_dir: bool,
+    /// Size of the file, or of everything below the folder
+    pub size: u64,
+    /// Files and folders below a folder
+    pub items: u64,
+}
+
+/// What a previewed mirror will do once confirmed
+struct MirrorPlan {
+    src: PathBuf,
+    dest: PathBuf,
+    copies: Vec<TransferRoot>,
+    extraneous: Vec<MirrorEntry>,
+}
+
+/// Previewed mirrors waiting for the user's confirmation, by request id
+#[derive(Default)]
+pub struct MirrorState {
+    plans: Mutex<HashMap<u64, MirrorPlan>>,
+}
+
+/// Total size and item count below `dir`
+pub(crate) fn tree_size(dir: &Path) -> (u64, u64) {
+    WalkDir::new(dir)
+        .follow_links(false)
+        .skip_hidden(false)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .fold((0, 0), |(size, items), entry| {
+            let len = if entry.file_type().is_file() {
+                entry.metadata().map(|m| m.len()).unwrap_or(0)
+            } else {
+                0
+            };
+            (size.saturating_add(len), items + 1)
+        })
+}
+
+/// Whether the destination file is stale compared to the source
+fn differs(src: &fs::Metadata, dest: &fs::Metadata) -> bool {
+    if src.len() != dest.len() {
+        return true;
+    }
+    match (src.modified(), dest.modified()) {
+        (Ok(a), Ok(b)) => {
+            let delta = a
+                .duration_since(b)
+                .or_else(|_| b.duration_since(a))
+                .unwrap_or_default();
+            delta.as_secs() > MTIME_TOLERANCE_SECS
+        }
+        _ => true,
+    }
+}
+
+/// Compares both trees level by level. Missing or changed source items become
+/// copies; destination items absent from the source are reported through
+/// `on_extra` and not descended into. Returns `None` once `keep_going` fails.
+fn build_plan(
+    src: &Path,
+    dest: &Path,
+    keep_going: impl Fn() -> bool,
+    mut on_extra: impl FnMut(MirrorEntry),
+) -> Option<Vec<TransferRoot>> {
+    let mut copies = Vec::new();
+    if !dest.exists() {
+        copies.push(TransferRoot {
+            src: src.to_path_buf(),
+            dest: dest.to_path_buf(),
+        });
+        return Some(copies);
+    }
+
+    let mut stack = vec![(src.to_path_buf(), dest.to_path_buf())];
+    while let Some((src_dir, dest_dir)) = stack.pop() {
+        for entry in fs::read_dir(&dest_dir).into_iter().flatten().flatten() {
+            if !keep_going() {
+                return None;
+            }
+            let Ok(dest_meta) = fs::symlink_metadata(entry.path()) else {
+                continue;
+            };
+            let same_kind = fs::symlink_metadata(src_dir.join(entry.file_name()))
+                .is_ok_and(|m| m.is_dir() == dest_meta.is_dir());
+            if same_kind {
+                continue;
+            }
+            let (size, items) = if dest_meta.is_dir() {
+                tree_size(&entry.path())
+            } else {
+                (dest_meta.len(), 0)
+            };
+            on_extra(MirrorEntry {
+                path: entry.path().to_string_lossy().to_string(),
+                is_dir: dest_meta.is_dir(),
+                size,
+                items,
+            });
+        }
+
+        for entry in fs::read_dir(&src_dir).into_iter().flatten().flatten() {
+            if !keep_going() {
+                return None;
+            }
+            let Ok(src_meta) = fs::symlink_metadata(entry.path()) else {
+                continue;
+            };
+            let target = dest_dir.join(entry.file_name());
+            match fs::symlink_metadata(&target) {
+                Ok(dest_meta) if src_meta.is_dir() && dest_meta.is_dir() => {
+                    stack.push((entry.path(), target));
+                }
+                Ok(dest_meta) if dest_meta.is_dir() == src_meta.is_dir() => {
+                    if differs(&src_meta, &dest_meta) {
+                        copies.push(TransferRoot {
+                            src: entry.path(),
+                            dest: target,
+                        });
+                    }
+                }
+                // missing, or replaced after the extraneous item is deleted
+                _ => copies.push(TransferRoot {
+                    src: entry.path(),
+                    dest: target,
+                }),
+            }
+        }
+    }
+    Some(copies)
+}
+
+/// Compares `src` with `dest` and streams every destination item a mirror would
+/// delete (`mirror-preview-batch`), followed by totals (`mirror-preview-complete`).
+/// Nothing is changed until `run_mirror` confirms the preview.
+#[tauri::command]
+pub async fn preview_mirror(
+    handle: AppHandle,
+    state: State<'_, Arc<MirrorState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    src: String,
+    dest: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let src = PathBuf::from(src);
+    let dest = PathBuf::from(dest);
+    if !src.is_dir() {
+        return Err(format!("Not a directory: {}", src.display()));
+    }
+    if dest.exists() && !dest.is_dir() {
+        return Err(format!("Not a directory: {}", dest.display()));
+    }
+    // compared resolved, so symlinks and `..` can't hide one inside the other
+    let (real_src, real_dest) = (resolve_path(&src), resolve_path(&dest));
+    if real_dest.starts_with(&real_src) || real_src.starts_with(&real_dest) {
+        return Err("Source and destination cannot contain each other".into());
+    }
+
+    let job = jobs.start(request_id, JobKind::Mirror);
+    let mut batch: Vec<MirrorEntry> = Vec::new();
+    let mut extraneous: Vec<MirrorEntry> = Vec::new();
+    let emit_batch = |batch: &mut Vec<MirrorEntry>| {
+        if batch.is_empty() {
+            return;
+        }
+        let _ = handle.emit(
+            "mirror-preview-batch",
+            serde_json::json!({
+                "request_id": request_id,
+                "entries": std::mem::take(batch),
+            }),
+        );
+    };
+
+    let copies = build_plan(
+        &src,
+        &dest,
+        || !job.is_cancelled(),
+        |entry| {
+            extraneous.push(entry.clone());
+            batch.push(entry);
+            if batch.len() >= PREVIEW_BATCH_SIZE {
+                emit_batch(&mut batch);
+            }
+        },
+    );
+    jobs.finish(request_id);
+
+    let Some(copies) = copies else {
+        let _ = handle.emit(
+            "mirror-cancelled",
+            serde_json::json!({ "request_id": request_id }),
+        );
+        return Ok(());
+    };
+    emit_batch(&mut batch);
+
+    let copy_size: u64 = copies
+        .iter()
+        .map(|root| match fs::symlink_metadata(&root.src) {
+            Ok(m) if m.is_dir() => tree_size(&root.src).0,
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum();
+    let _ = handle.emit(
+        "mirror-preview-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "extra_items": extraneous.len(),
+            "extra_size": extraneous.iter().map(|e| e.size).sum::<u64>(),
+            "copy_items": copies.len(),
+            "copy_size": copy_size,
+        }),
+    );
+
+    state.plans.lock().unwrap().insert(
+        request_id,
+        MirrorPlan {
+            src,
+            dest,
+            copies,
+            extraneous,
+        },
+    );
+    Ok(())
+}
+
+/// `path` with symlinks and `..` resolved. A path that doesn't exist yet is
+/// resolved through its nearest existing ancestor.
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if let Ok(real) = dunce::canonicalize(current) {
+            return missing.iter().rev().fold(real, |acc, name| acc.join(name));
+        }
+        match (current.parent(), current.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                current = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// Runs a previewed mirror: deletes exactly the previewed extraneous items
+/// (default: to the trash), then copies new and changed files over. Items that
+/// reappeared in the source since the preview are kept.
+#[tauri::command]
+pub async fn run_mirror(
+    handle: AppHandle,
+    state: State<'_, Arc<MirrorState>>,
+    copy_state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    request_id: u64,
+    delete_mode: Option<DeleteMode>,
+) -> Result<(), String> {
+    let plan = state
+        .plans
+        .lock()
+        .unwrap()
+        .remove(&request_id)
+        .ok_or("No mirror preview for this request")?;
+    let mode = delete_mode.unwrap_or(DeleteMode::Trash);
+
+    let deletions: Vec<String> = plan
+        .extraneous
+        .iter()
+        .filter(|entry| !reappeared(&plan, entry))
+        .map(|entry| entry.path.clone())
+        .collect();
+
+    if !deletions.is_empty() {
+        let kind = mode.job_kind();
+        let job = jobs.start(request_id, kind);
+        let result = run_delete_job(&handle, &job, &deletions, mode, 0);
+        let cancelled = job.is_cancelled();
+        jobs.finish(request_id);
+        result?;
+        if cancelled {
+            return Ok(());
+        }
+    }
+
+    if !plan.copies.is_empty() {
+        let mut journal =
+            TransferJournal::create(&handle, request_id, TransferOp::Copy, &plan.copies);
+        // changed files are meant to be overwritten, never asked about
+        journal.set_strategy(DuplicateStrategy::Replace);
+        run_journaled_transfer(
+            &handle,
+            &copy_state,
+            &jobs,
+            plan.copies,
+            TransferOp::Copy,
+            request_id,
+            journal,
+        )?;
+    }
+
+    let _ = handle.emit(
+        "mirror-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "deleted_items": deletions.len(),
+        }),
+    );
+    Ok(())
+}
+
+/// Whether an extraneous item got a source counterpart of the same kind since
+/// the preview, in which case it is no longer extraneous
+fn reappeared(plan: &MirrorPlan, entry: &MirrorEntry) -> bool {
+    let Ok(rel) = Path::new(&entry.path).strip_prefix(&plan.dest) else {
+        return false;
+    };
+    fs::symlink_metadata(plan.src.join(rel)).is_ok_and(|m| m.is_dir() == entry.is_dir)
+}
+
+/// Drops a preview the user declined
+#[tauri::command]
+pub fn discard_mirror_preview(state: State<'_, Arc<MirrorState>>, request_id: u64) {
+    state.plans.lock().unwrap().remove(&request_id);
+}
@@ -1,10 +1,31 @@
+pub mod clipboard;
+pub mod delstream;
 pub mod fsstream;
+pub mod jobs;
+pub mod journal;
+pub mod mirror;
 pub mod opstream;
 pub mod resolver;
 pub mod thumbs;
+pub mod transfer;
+pub mod undo;
 
-pub use fsstream::{stream_directory_contents, FileStreamState};
+pub use clipboard::{
+    get_clipboard_history, get_clipboard_state, start_clipboard_watcher, ClipboardHistory,
+};
+pub use delstream::{delete_items, get_delete_summary, secure_delete};
+pub use fsstream::{cancel_directory_stream, stream_directory_contents, FileStreamState};
+pub use jobs::{cancel_job, JobStreamState};
+pub use journal::{
+    discard_interrupted_transfer, list_interrupted_transfers, resume_interrupted_transfer,
+    rollback_interrupted_transfer,
+};
+pub use mirror::{discard_mirror_preview, preview_mirror, run_mirror, MirrorState};
 pub use opstream::{
-    copy_items_to_clipboard, cut_items_to_clipboard, paste_items_from_clipboard, CopyStreamState,
+    copy_item, copy_items_to_clipboard, copy_to, cut_items_to_clipboard, move_to,
+    paste_from_history, paste_item_from_paths, paste_items_from_clipboard, pause_paste_stream,
+    resume_paste_stream, CopyStreamState,
 };
-pub use resolver::resolve_copy_conflict;
+pub use resolver::{resolve_copy_conflict, resolve_job_error};
+pub use thumbs::{export_thumbnails, get_animated_preview, get_preview_rule, serve_thumb};
+pub use undo::{get_undo_state, redo_last_operation, undo_last_operation, UndoJournal};
@@ -1,19 +1,34 @@
 use std::{
+    collections::{HashSet, VecDeque},
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
         Arc, Condvar, Mutex,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use jwalk::WalkDir;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, State};
 
+use crate::filesys::actions::unique_path;
+use crate::filesys::names::check_name_for_volume;
 use crate::filesys::os::windows::{get_system_clipboard, set_system_clipboard, ClipboardOp};
+use crate::filesys::stream::clipboard::ClipboardHistory;
+use crate::filesys::stream::jobs::{JobHandle, JobKind, JobStreamState};
+use crate::filesys::stream::journal::{JournalEntry, TransferJournal};
+use crate::filesys::stream::thumbs::cached_thumbnail;
+use crate::filesys::stream::transfer::{
+    copy_file_chunked, is_cross_device, is_sparse_file, verify_copy, TransferRate,
+    PROGRESS_INTERVAL,
+};
+use crate::filesys::stream::undo::{record_operation, FileOperation};
+use crate::filesys::volumes::{volume_capabilities, VolumeCapabilities};
+use crate::util::hooks::{fire_hooks, HookEvent};
+use crate::util::notify::notify_job_finished;
 
 /// How to resolve a single conflict
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -34,6 +49,53 @@ pub struct ConflictRequest {
     pub dest: String,
     /// Display-friendly filename (optional)
     pub name: String,
+    /// The incoming version
+    pub src_info: ConflictFileInfo,
+    /// The version already at `dest`
+    pub dest_info: ConflictFileInfo,
+}
+
+/// One side of a conflict, for comparing both versions
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ConflictFileInfo {
+    pub size: Option<u64>,
+    /// Seconds since the Unix epoch
+    pub date_modified: Option<u64>,
+    pub is_dir: bool,
+    /// Base64 thumbnail, only when one is already cached
+    pub thumbnail: Option<String>,
+}
+
+impl ConflictFileInfo {
+    fn read(handle: &AppHandle, path: &Path) -> Self {
+        let Ok(meta) = fs::metadata(path) else {
+            return Self::default();
+        };
+        Self {
+            size: meta.is_file().then_some(meta.len()),
+            date_modified: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            is_dir: meta.is_dir(),
+            thumbnail: cached_thumbnail(handle, path),
+        }
+    }
+}
+
+/// Whether sources are kept (copy) or removed once transferred (move)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TransferOp {
+    Copy,
+    Move,
+}
+
+/// A top-level source and the exact path it should be transferred to
+#[derive(Clone, Debug)]
+pub struct TransferRoot {
+    pub src: PathBuf,
+    pub dest: PathBuf,
 }
 
 /// The user's response to the conflict (filled by resolve_copy_conflict)
@@ -43,12 +105,24 @@ pub struct ConflictResponse {
     pub repeat_for_all: bool,
 }
 
+/// Files below this are copied in one call to the OS copy routine, which can clone
+/// them on copy-on-write filesystems; larger ones report progress chunk by chunk
+const OS_COPY_LIMIT: u64 = 8 * 1024 * 1024;
+
+/// How often a queued transfer re-checks whether it was cancelled
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Shared state for the copy/paste stream.
 /// - `conflict_lock` + `conflict_cv` are used to wait/notify for UI decisions.
+/// - `queue` + `queue_cv` run transfers one at a time, in the order they were requested.
 pub struct CopyStreamState {
     pub current_id: AtomicU64,
     pub cancelled: AtomicBool,
 
+    // request ids of the running transfer (front) and those waiting behind it
+    queue: Mutex<VecDeque<u64>>,
+    queue_cv: Condvar,
+
     // conflict synchronization
     // If there's a pending request, conflict_request will be Some(request)
     // and conflict_response will be Some(response) once the user answered.
@@ -66,6 +140,8 @@ impl CopyStreamState {
         Self {
             current_id: AtomicU64::new(0),
             cancelled: AtomicBool::new(false),
+            queue: Mutex::new(VecDeque::new()),
+            queue_cv: Condvar::new(),
             conflict_lock: Mutex::new(ConflictSlot {
                 request: None,
                 response: None,
@@ -74,11 +150,81 @@ impl CopyStreamState {
         }
     }
 
+    /// Queues the job and blocks until every transfer requested before it has ended,
+    /// emitting `transfer-queued` whenever its place in line changes.
+    /// Returns false if the job is cancelled while it waits.
+    fn wait_for_turn(&self, handle: &AppHandle, job: &JobHandle) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        queue.push_back(job.request_id);
+        let mut last_position = 0;
+        loop {
+            let position = queue
+                .iter()
+                .position(|id| *id == job.request_id)
+                .unwrap_or(0);
+            if position == 0 {
+                return true;
+            }
+            if job.is_cancelled() {
+                queue.retain(|id| *id != job.request_id);
+                drop(queue);
+                self.wake_waiters();
+                return false;
+            }
+            if position != last_position {
+                last_position = position;
+                let _ = handle.emit(
+                    "transfer-queued",
+                    serde_json::json!({
+                        "request_id": job.request_id,
+                        "position": position,
+                    }),
+                );
+            }
+            queue = self
+                .queue_cv
+                .wait_timeout(queue, QUEUE_POLL_INTERVAL)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Hands the turn to the next queued transfer
+    fn leave_queue(&self, request_id: u64) {
+        self.queue.lock().unwrap().retain(|id| *id != request_id);
+        self.queue_cv.notify_all();
+    }
+
+    /// Pauses a running or queued transfer; it stops at its next chunk or file.
+    /// The queue stays locked until the job is flagged, so a transfer that is
+    /// just leaving the queue can't be left holding a stale pause.
+    pub fn pause(&self, jobs: &JobStreamState, request_id: u64) -> Result<(), String> {
+        let queue = self.queue.lock().unwrap();
+        let job = queue
+            .contains(&request_id)
+            .then(|| jobs.get(request_id))
+            .flatten()
+            .ok_or("No paste in progress with that id")?;
+        job.pause();
+        Ok(())
+    }
+
+    /// Lets a paused transfer continue
+    pub fn resume(&self, jobs: &JobStreamState, request_id: u64) -> Result<(), String> {
+        let _queue = self.queue.lock().unwrap();
+        match jobs.get(request_id) {
+            Some(job) if job.resume() => Ok(()),
+            _ => Err("That paste is not paused".into()),
+        }
+    }
+
     /// Called by the copy stream to post a conflict request and block until UI answers.
     /// Returns the chosen strategy and whether it should repeat for all remaining conflicts.
-    /// The call will return early if the stream is cancelled or request_id doesn't match.
+    /// Returns `Err` once `job` is cancelled, so a transfer parked on a prompt doesn't
+    /// hold up the ones queued behind it.
     pub fn request_conflict_decision(
         &self,
+        job: &JobHandle,
         request: ConflictRequest,
     ) -> Result<ConflictResponse, String> {
         // lock and set request; clear previous response
@@ -86,25 +232,33 @@ impl CopyStreamState {
         slot.request = Some(request);
         slot.response = None;
 
-        // notify UI that a request is available (front-end should be listening for "clipboard-paste-conflict")
-        // The emitter is on your copy loop side (you emit the event there). Here we only block.
-
-        // wait for a response (blocking)
+        // the caller emits "clipboard-paste-conflict"; here we only block
         loop {
-            // Wait on condvar until response is set
-            slot = self.conflict_cv.wait(slot).unwrap();
-
-            // If response available, take it and return
             if let Some(resp) = slot.response.take() {
                 // clear request after reading
                 slot.request = None;
                 return Ok(resp);
             }
+            if job.is_cancelled() {
+                slot.request = None;
+                return Err("Transfer was cancelled".into());
+            }
 
-            // Otherwise, spurious wake — continue loop
+            slot = self
+                .conflict_cv
+                .wait_timeout(slot, QUEUE_POLL_INTERVAL)
+                .unwrap()
+                .0;
         }
     }
 
+    /// Wakes transfers blocked on a conflict prompt or waiting in the queue, so a
+    /// cancelled one notices right away
+    pub fn wake_waiters(&self) {
+        self.conflict_cv.notify_all();
+        self.queue_cv.notify_all();
+    }
+
     /// Called by the UI command to submit decision and wake the blocked thread.
     pub fn submit_conflict_response(
         &self,
@@ -137,27 +291,68 @@ impl CopyStreamState {
     }
 }
 
+/// Suspends the paste `request_id` at its next chunk or file, or before it starts
+/// when still queued, and emits `clipboard-paste-paused`.
+#[tauri::command]
+pub fn pause_paste_stream(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    request_id: u64,
+) -> Result<(), String> {
+    state.pause(&jobs, request_id)?;
+    let _ = handle.emit(
+        "clipboard-paste-paused",
+        serde_json::json!({ "request_id": request_id, "paused": true }),
+    );
+    Ok(())
+}
+
+/// Continues a paused paste, emitting `clipboard-paste-paused` with `paused: false`
 #[tauri::command]
-pub fn copy_items_to_clipboard(paths: Vec<String>) -> Result<(), String> {
+pub fn resume_paste_stream(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    request_id: u64,
+) -> Result<(), String> {
+    state.resume(&jobs, request_id)?;
+    let _ = handle.emit(
+        "clipboard-paste-paused",
+        serde_json::json!({ "request_id": request_id, "paused": false }),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn copy_items_to_clipboard(
+    handle: AppHandle,
+    history: State<'_, ClipboardHistory>,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    history.record(&handle, &paths, TransferOp::Copy);
     set_system_clipboard(paths, ClipboardOp::Copy)
 }
 
 #[tauri::command]
-pub fn cut_items_to_clipboard(paths: Vec<String>) -> Result<(), String> {
+pub fn cut_items_to_clipboard(
+    handle: AppHandle,
+    history: State<'_, ClipboardHistory>,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    history.record(&handle, &paths, TransferOp::Move);
     set_system_clipboard(paths, ClipboardOp::Move)
 }
 
+/// Paste whatever the OS clipboard holds into `working_dir`
 #[tauri::command]
 pub async fn paste_items_from_clipboard(
     handle: AppHandle,
     state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
     working_dir: String,
     request_id: u64,
 ) -> Result<(), String> {
-    // mark this request id active and reset cancelled flag
-    state.current_id.store(request_id, Ordering::Relaxed);
-    state.cancelled.store(false, Ordering::Relaxed);
-
     // 1) Get clipboard paths and operation
     let (clipboard_paths, clipboard_op) = match get_system_clipboard() {
         Ok(v) => v,
@@ -168,8 +363,129 @@ pub async fn paste_items_from_clipboard(
         return Err("Clipboard does not contain file paths".into());
     }
 
-    // Normalize working dir
-    let dest_root = PathBuf::from(&working_dir);
+    let op = match clipboard_op {
+        ClipboardOp::Move => TransferOp::Move,
+        // Explorer reports "Copy|Link" for plain copies
+        ClipboardOp::Copy | ClipboardOp::Link => TransferOp::Copy,
+        _ => {
+            return Err(format!(
+                "Unsupported clipboard operation: {:?}",
+                clipboard_op
+            ))
+        }
+    };
+
+    let roots = roots_in_directory(&clipboard_paths, &working_dir, op)?;
+    start_transfer_job(handle, &state, &jobs, roots, op, request_id).await
+}
+
+/// Re-paste an earlier copy/cut from the clipboard history into `working_dir`
+#[tauri::command]
+pub async fn paste_from_history(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    history: State<'_, ClipboardHistory>,
+    index: usize,
+    working_dir: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let entry = history
+        .get(index)
+        .ok_or("No clipboard history entry at that index")?;
+
+    let sources: Vec<PathBuf> = entry.paths.iter().map(PathBuf::from).collect();
+    let roots = roots_in_directory(&sources, &working_dir, entry.operation)?;
+    if roots.is_empty() {
+        // typically a cut that has already been pasted
+        return Err("None of the items in this entry exist anymore".into());
+    }
+    start_transfer_job(handle, &state, &jobs, roots, entry.operation, request_id).await
+}
+
+/// Paste frontend-provided paths into `dest` (copy)
+#[tauri::command]
+pub async fn paste_item_from_paths(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    dest: String,
+    paths: Vec<String>,
+    request_id: u64,
+) -> Result<(), String> {
+    let sources: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let roots = roots_in_directory(&sources, &dest, TransferOp::Copy)?;
+    start_transfer_job(handle, &state, &jobs, roots, TransferOp::Copy, request_id).await
+}
+
+/// Copy a single file or directory to an exact destination path
+#[tauri::command]
+pub async fn copy_item(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    src: String,
+    dest: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let src_path = PathBuf::from(&src);
+    if !src_path.exists() {
+        return Err("Source path does not exist".into());
+    }
+
+    let roots = vec![TransferRoot {
+        src: src_path,
+        dest: PathBuf::from(&dest),
+    }];
+    start_transfer_job(handle, &state, &jobs, roots, TransferOp::Copy, request_id).await
+}
+
+/// Copy `paths` into `dest_dir` without going through the OS clipboard
+/// (dual-pane transfers, drag between panes)
+#[tauri::command]
+pub async fn copy_to(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    paths: Vec<String>,
+    dest_dir: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let sources: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let roots = roots_in_directory(&sources, &dest_dir, TransferOp::Copy)?;
+    if roots.is_empty() {
+        return Err("None of the selected items exist".into());
+    }
+    start_transfer_job(handle, &state, &jobs, roots, TransferOp::Copy, request_id).await
+}
+
+/// Move `paths` into `dest_dir` without going through the OS clipboard.
+/// Items already in `dest_dir` are left where they are.
+#[tauri::command]
+pub async fn move_to(
+    handle: AppHandle,
+    state: State<'_, Arc<CopyStreamState>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    paths: Vec<String>,
+    dest_dir: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let sources: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let roots = roots_in_directory(&sources, &dest_dir, TransferOp::Move)?;
+    if roots.is_empty() {
+        return Err("Nothing to move".into());
+    }
+    start_transfer_job(handle, &state, &jobs, roots, TransferOp::Move, request_id).await
+}
+
+/// Maps each source to `<dest_dir>/<source name>`. A move leaves out items that
+/// are already in `dest_dir`, since there is nothing to do for them.
+fn roots_in_directory(
+    sources: &[PathBuf],
+    dest_dir: &str,
+    op: TransferOp,
+) -> Result<Vec<TransferRoot>, String> {
+    let dest_root = PathBuf::from(dest_dir);
     if !dest_root.is_dir() {
         return Err(format!(
             "Working dir is not a directory: {}",
@@ -177,71 +493,215 @@ pub async fn paste_items_from_clipboard(
         ));
     }
 
+    Ok(sources
+        .iter()
+        .filter(|src| src.exists())
+        .map(|src| TransferRoot {
+            src: src.clone(),
+            dest: dest_root.join(src.file_name().unwrap_or_else(|| "unknown".as_ref())),
+        })
+        .filter(|root| op == TransferOp::Copy || !same_file(&root.src, &root.dest))
+        .collect())
+}
+
+/// Registers the transfer as a job and runs it to completion on a blocking
+/// thread, since both waiting for its turn and the transfer itself block
+pub(crate) async fn start_transfer_job(
+    handle: AppHandle,
+    state: &Arc<CopyStreamState>,
+    jobs: &Arc<JobStreamState>,
+    roots: Vec<TransferRoot>,
+    op: TransferOp,
+    request_id: u64,
+) -> Result<(), String> {
+    let state = state.clone();
+    let jobs = jobs.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let journal = TransferJournal::create(&handle, request_id, op, &roots);
+        run_journaled_transfer(&handle, &state, &jobs, roots, op, request_id, journal)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r)
+}
+
+/// Runs a transfer job while recording progress in `journal`, so a crash mid-way
+/// can be resumed or rolled back on the next launch. The job waits behind any
+/// transfer already running; if the app closes first, its journal offers it for
+/// resuming just the same. Blocks throughout, so keep it off the async runtime.
+pub fn run_journaled_transfer(
+    handle: &AppHandle,
+    state: &CopyStreamState,
+    jobs: &JobStreamState,
+    roots: Vec<TransferRoot>,
+    op: TransferOp,
+    request_id: u64,
+    mut journal: TransferJournal,
+) -> Result<(), String> {
+    let kind = match op {
+        TransferOp::Copy => JobKind::Copy,
+        TransferOp::Move => JobKind::Move,
+    };
+    let job = jobs.start(request_id, kind);
+    if !state.wait_for_turn(handle, &job) {
+        jobs.finish(request_id);
+        journal.finish();
+        let _ = handle.emit(
+            "clipboard-paste-cancelled",
+            serde_json::json!({ "request_id": request_id }),
+        );
+        return Ok(());
+    }
+    journal.begin();
+
+    // mark this request id active and reset cancelled flag
+    state.current_id.store(request_id, Ordering::Relaxed);
+    state.cancelled.store(false, Ordering::Relaxed);
+
+    let result = run_transfer_job(handle, state, &job, &roots, op, &mut journal);
+    state.leave_queue(request_id);
+    jobs.finish(request_id);
+    journal.finish();
+    if let Err(e) = &result {
+        notify_job_finished(handle, kind, false, e);
+    }
+    result
+}
+
+/// Warns up front about items the destination filesystem cannot hold as-is:
+/// oversized files, names it rejects or folds together, symlinks and overlong paths
+fn destination_warnings(
+    caps: &VolumeCapabilities,
+    entries: &[(PathBuf, PathBuf, u64)],
+    dest_dirs: &[PathBuf],
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut seen = HashSet::new();
+    let targets = entries
+        .iter()
+        .map(|(src, dest, size)| (Some(src), dest, *size))
+        .chain(dest_dirs.iter().map(|dest| (None, dest, 0)));
+
+    for (src, dest, size) in targets {
+        if let Some(max) = caps.max_file_size.filter(|max| size > *max) {
+            warnings.push(format!(
+                "{} is larger than {} allows ({} bytes max)",
+                dest.display(),
+                caps.filesystem,
+                max
+            ));
+        }
+        if let Some(name) = dest.file_name().map(|n| n.to_string_lossy()) {
+            if let Err(e) = check_name_for_volume(caps, &name) {
+                warnings.push(format!("{}: {}", dest.display(), e.message));
+            }
+        }
+        if !caps.case_sensitive && !seen.insert(dest.to_string_lossy().to_lowercase()) {
+            warnings.push(format!(
+                "{} differs from another item only by case and will collide",
+                dest.display()
+            ));
+        }
+        if dest.as_os_str().len() > caps.max_path_length {
+            warnings.push(format!("{} exceeds the path length limit", dest.display()));
+        }
+        if !caps.supports_symlinks
+            && src.is_some_and(|src| fs::symlink_metadata(src).is_ok_and(|m| m.is_symlink()))
+        {
+            warnings.push(format!(
+                "{} is a symlink; {} cannot store links, so the target's contents are copied",
+                dest.display(),
+                caps.filesystem
+            ));
+        }
+    }
+    warnings
+}
+
+/// The single copy/move engine behind every paste and copy command:
+/// scan -> per-file transfer with shared conflict handling -> completion.
+/// Emits the `clipboard-paste-*` event family regardless of where the sources came from.
+pub fn run_transfer_job(
+    handle: &AppHandle,
+    state: &CopyStreamState,
+    job: &JobHandle,
+    roots: &[TransferRoot],
+    op: TransferOp,
+    journal: &mut TransferJournal,
+) -> Result<(), String> {
+    let request_id = job.request_id;
+    let is_stale = || {
+        job.is_cancelled()
+            || state.cancelled.load(Ordering::Relaxed)
+            || state.current_id.load(Ordering::Relaxed) != request_id
+    };
+    let emit_cancelled = || {
+        let _ = handle.emit(
+            "clipboard-paste-cancelled",
+            serde_json::json!({ "request_id": request_id }),
+        );
+    };
+
     // Phase 1: scan -> build list of files to copy/move
-    let mut entries: Vec<(PathBuf, PathBuf, u64)> = Vec::new(); // (src, rel, size)
+    let mut entries: Vec<(PathBuf, PathBuf, u64)> = Vec::new(); // (src, dest, size)
+    let mut dest_dirs: Vec<PathBuf> = Vec::new();
+    let mut src_dirs: Vec<PathBuf> = Vec::new();
     let mut total_size: u64 = 0;
 
-    for root_path in &clipboard_paths {
-        // cancellation check
-        if state.cancelled.load(Ordering::Relaxed)
-            || state.current_id.load(Ordering::Relaxed) != request_id
-        {
-            let _ = handle.emit(
-                "clipboard-paste-cancelled",
-                serde_json::json!({ "request_id": request_id }),
-            );
+    for root in roots {
+        if is_stale() {
+            emit_cancelled();
             return Ok(());
         }
 
-        if root_path.is_file() {
-            let size = fs::metadata(root_path).map(|m| m.len()).unwrap_or(0);
-            let rel = root_path
-                .file_name()
-                .map(PathBuf::from)
-                .unwrap_or_else(|| PathBuf::from("unknown"));
-            entries.push((root_path.clone(), rel, size));
+        if root.src.is_file() {
+            let size = fs::metadata(&root.src).map(|m| m.len()).unwrap_or(0);
+            entries.push((root.src.clone(), root.dest.clone(), size));
             total_size = total_size.saturating_add(size);
-        } else if root_path.is_dir() {
-            // include root folder name
-            let root_name = root_path
-                .file_name()
-                .map(PathBuf::from)
-                .unwrap_or_else(|| PathBuf::from("unknown"));
-
-            let walker = WalkDir::new(root_path).follow_links(false);
+        } else if root.src.is_dir() {
+            if root.dest.starts_with(&root.src) {
+                return Err(format!("Cannot copy {} into itself", root.src.display()));
+            }
+
+            let walker = WalkDir::new(&root.src)
+                .follow_links(false)
+                .skip_hidden(false);
             for entry in walker.into_iter().filter_map(|e| e.ok()) {
-                if state.cancelled.load(Ordering::Relaxed)
-                    || state.current_id.load(Ordering::Relaxed) != request_id
-                {
-                    let _ = handle.emit(
-                        "clipboard-paste-cancelled",
-                        serde_json::json!({ "request_id": request_id }),
-                    );
+                job.wait_while_paused();
+                if is_stale() {
+                    emit_cancelled();
                     return Ok(());
                 }
 
                 let path = entry.path();
-                if &path == root_path {
-                    continue;
-                }
+                let inner_rel = path
+                    .strip_prefix(&root.src)
+                    .map(|r| r.to_path_buf())
+                    .unwrap_or_else(|_| PathBuf::from("unknown"));
+                let dest = root.dest.join(inner_rel);
 
-                if let Ok(md) = entry.metadata() {
-                    if md.is_file() {
-                        let size = md.len();
-                        let inner_rel = path
-                            .strip_prefix(root_path)
-                            .map(|r| r.to_path_buf())
-                            .unwrap_or_else(|_| PathBuf::from("unknown"));
-                        let rel = root_name.join(inner_rel);
-
-                        entries.push((path.to_path_buf(), rel, size));
-                        total_size = total_size.saturating_add(size);
-                    }
+                if entry.file_type().is_dir() {
+                    // keep empty folders too
+                    dest_dirs.push(dest);
+                    src_dirs.push(path);
+                } else if let Ok(md) = entry.metadata() {
+                    let size = md.len();
+                    entries.push((path, dest, size));
+                    total_size = total_size.saturating_add(size);
                 }
             }
         }
     }
 
+    let dest_caps = roots
+        .first()
+        .and_then(|root| root.dest.parent())
+        .map(volume_capabilities);
+    let warnings = dest_caps
+        .as_ref()
+        .map(|caps| destination_warnings(caps, &entries, &dest_dirs))
+        .unwrap_or_default();
+
     // Emit scan result
     let _ = handle.emit(
         "clipboard-paste-scan",
@@ -249,127 +709,143 @@ pub async fn paste_items_from_clipboard(
             "request_id": request_id,
             "total_size": total_size,
             "file_count": entries.len(),
-            "operation": format!("{:?}", clipboard_op),
+            "operation": format!("{:?}", op),
+            "dest_filesystem": dest_caps.as_ref().map(|c| c.filesystem.clone()),
+            "warnings": warnings,
         }),
     );
 
+    // folders this job creates, so undoing it can remove them again
+    let mut created_dirs: Vec<PathBuf> = Vec::new();
+    for dir in &dest_dirs {
+        if !dir.exists() && fs::create_dir_all(dir).is_ok() {
+            created_dirs.push(dir.clone());
+        }
+    }
+
     // Phase 2: perform copying or moving
-    let mut repeat_strategy: Option<DuplicateStrategy> = None;
-    let mut repeat_for_all = false;
+    let mut repeat_strategy: Option<DuplicateStrategy> = journal.repeat_strategy;
+    let mut repeat_for_all = repeat_strategy.is_some();
+    let mut files_processed: usize = 0;
+    let mut files_failed: usize = 0;
+    let mut transferred: Vec<JournalEntry> = Vec::new();
 
-    for (src, rel, _size) in entries.iter() {
-        // cancellation check
-        if state.cancelled.load(Ordering::Relaxed)
-            || state.current_id.load(Ordering::Relaxed) != request_id
-        {
+    let mut bytes_done: u64 = 0;
+    let mut rate = TransferRate::new();
+    let mut last_progress = Instant::now();
+    // `file_done` of `file_size` bytes of `current`, so one huge file still shows movement
+    let emit_progress =
+        |rate: &TransferRate, done: u64, current: &Path, file_done: u64, file_size: u64| {
             let _ = handle.emit(
-                "clipboard-paste-cancelled",
-                serde_json::json!({ "request_id": request_id }),
+                "clipboard-paste-progress",
+                serde_json::json!({
+                    "request_id": request_id,
+                    "bytes_done": done,
+                    "total_size": total_size,
+                    "bytes_per_sec": rate.bytes_per_sec(),
+                    "eta_secs": rate.eta_secs(total_size.saturating_sub(done)),
+                    "current": current.display().to_string(),
+                    "current_bytes_done": file_done,
+                    "current_size": file_size,
+                }),
             );
+        };
+
+    for (src, dest, size) in entries.iter() {
+        if job.wait_while_paused() {
+            rate.restart();
+        }
+        // cancellation check
+        if is_stale() {
+            emit_cancelled();
             return Ok(());
         }
 
-        let mut dest_path = dest_root.join(&rel);
+        // counted up front so skipped and failed files still advance the bar
+        job.report_progress(bytes_done, total_size);
+        let file_base = bytes_done;
+        bytes_done = bytes_done.saturating_add(*size);
+
+        // finished by an earlier, interrupted run of this job
+        if journal.is_completed(src) {
+            files_processed += 1;
+            continue;
+        }
+
+        let mut dest_path = dest.clone();
         if let Some(parent) = dest_path.parent() {
             let _ = fs::create_dir_all(parent);
         }
 
         // conflict handling
         if dest_path.exists() {
-            let chosen_strategy = if repeat_for_all {
-                repeat_strategy.unwrap_or(DuplicateStrategy::Index)
+            if same_file(src, &dest_path) {
+                if op == TransferOp::Move {
+                    // already where it's meant to go
+                    continue;
+                }
+                // pasting into the source folder always produces a copy
+                dest_path = unique_path(&dest_path);
             } else {
-                thread::sleep(Duration::from_millis(50));
-                let _ = handle.emit(
-                    "clipboard-paste-conflict",
-                    serde_json::json!({
-                        "request_id": request_id,
-                        "src": src.display().to_string(),
-                        "dest": dest_path.display().to_string(),
-                        "name": dest_path.file_name().and_then(|s| s.to_str()).unwrap_or("").to_string(),
-                    }),
-                );
-
-                let conflict_req = ConflictRequest {
-                    request_id,
-                    src: src.display().to_string(),
-                    dest: dest_path.display().to_string(),
-                    name: dest_path
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("")
-                        .to_string(),
-                };
-
-                match state.request_conflict_decision(conflict_req) {
-                    Ok(resp) => {
-                        if resp.repeat_for_all {
-                            repeat_for_all = true;
-                            repeat_strategy = Some(resp.strategy);
+                let chosen_strategy = if repeat_for_all {
+                    repeat_strategy.unwrap_or(DuplicateStrategy::Index)
+                } else {
+                    job.set_waiting(true);
+                    let answer =
+                        ask_conflict_strategy(handle, state, job, request_id, src, &dest_path);
+                    job.set_waiting(false);
+                    // time spent on the prompt isn't transfer time
+                    rate.restart();
+                    match answer {
+                        Some(resp) => {
+                            if resp.repeat_for_all {
+                                repeat_for_all = true;
+                                repeat_strategy = Some(resp.strategy);
+                                journal.set_strategy(resp.strategy);
+                            }
+                            resp.strategy
                         }
-                        resp.strategy
+                        None => continue,
                     }
-                    Err(_) => continue,
-                }
-            };
+                };
 
-            match chosen_strategy {
-                DuplicateStrategy::Ignore => continue,
-                DuplicateStrategy::Replace => {
-                    let _ = fs::remove_file(&dest_path);
-                }
-                DuplicateStrategy::Index => {
-                    let file_name = dest_path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("file");
-                    let ext = dest_path
-                        .extension()
-                        .and_then(|s| s.to_str())
-                        .map(|s| format!(".{}", s))
-                        .unwrap_or_default();
-
-                    let mut i = 1;
-                    loop {
-                        let candidate = format!("{} ({}){}", file_name, i, ext);
-                        let try_path = dest_path.with_file_name(candidate);
-                        if !try_path.exists() {
-                            dest_path = try_path;
-                            break;
-                        }
-                        i += 1;
+                match chosen_strategy {
+                    DuplicateStrategy::Ignore => continue,
+                    DuplicateStrategy::Replace => {
+                        let _ = fs::remove_file(&dest_path);
+                    }
+                    DuplicateStrategy::Index => {
+                        dest_path = unique_path(&dest_path);
                     }
                 }
             }
         }
 
         // perform file operation (copy or move)
-        let result = match clipboard_op {
-            ClipboardOp::Copy | ClipboardOp::Link => fs::copy(src, &dest_path)
-                .map(|bytes| (bytes, false)), // false = not removed
-            ClipboardOp::Move => {
-                // try rename first (fast path)
-                match fs::rename(src, &dest_path) {
-                    Ok(_) => Ok((0, true)), // true = source removed
-                    Err(_) => {
-                        // fallback: cross-device move (copy + remove)
-                        let copy_result = fs::copy(src, &dest_path);
-                        if copy_result.is_ok() {
-                            let _ = fs::remove_file(src);
-                        }
-                        copy_result.map(|bytes| (bytes, true))
-                    }
-                }
-            },
-            // handle any future/unexpected variants gracefully
-            _ => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Unsupported clipboard operation: {:?}", clipboard_op),
-            )),
-        };
+        journal.start(src, &dest_path);
+        let mut file_copied = 0;
+        let result = transfer_file(job, src, &dest_path, op, |copied| {
+            // large files pause between chunks; paused time isn't transfer time
+            if job.wait_while_paused() {
+                rate.restart();
+            }
+            rate.add(copied.saturating_sub(file_copied));
+            file_copied = copied;
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                emit_progress(&rate, file_base + copied, src, copied, *size);
+            }
+        });
+        emit_progress(&rate, bytes_done, src, *size, *size);
 
         match result {
-            Ok((bytes, _removed)) => {
+            Ok(bytes) => {
+                journal.done(src, &dest_path);
+                files_processed += 1;
+                transferred.push(JournalEntry {
+                    src: src.clone(),
+                    dest: dest_path.clone(),
+                });
                 let _ = handle.emit(
                     "clipboard-paste-file",
                     serde_json::json!({
@@ -377,11 +853,17 @@ pub async fn paste_items_from_clipboard(
                         "src": src.display().to_string(),
                         "dest": dest_path.display().to_string(),
                         "size": bytes,
-                        "operation": format!("{:?}", clipboard_op),
+                        "operation": format!("{:?}", op),
                     }),
                 );
             }
+            Err(_) if is_stale() => {
+                // a large file was abandoned mid-copy
+                emit_cancelled();
+                return Ok(());
+            }
             Err(err) => {
+                files_failed += 1;
                 let _ = handle.emit(
                     "clipboard-paste-file-error",
                     serde_json::json!({
@@ -395,16 +877,172 @@ pub async fn paste_items_from_clipboard(
         }
     }
 
+    // A move leaves the emptied source folders behind; remove them deepest first
+    let mut removed_dirs: Vec<PathBuf> = Vec::new();
+    if op == TransferOp::Move {
+        src_dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+        for dir in src_dirs {
+            if fs::remove_dir(&dir).is_ok() {
+                removed_dirs.push(dir);
+            }
+        }
+    }
+
+    if !transferred.is_empty() {
+        let operation = match op {
+            TransferOp::Copy => FileOperation::Copy {
+                files: transferred,
+                created_dirs,
+            },
+            TransferOp::Move => FileOperation::Move {
+                items: transferred,
+                created_dirs,
+                removed_dirs,
+            },
+        };
+        record_operation(handle, operation);
+    }
+
     // Done
     let _ = handle.emit(
         "clipboard-paste-complete",
         serde_json::json!({
             "request_id": request_id,
             "total_size": total_size,
-            "files_processed": entries.len(),
-            "operation": format!("{:?}", clipboard_op),
+            "files_processed": files_processed,
+            "operation": format!("{:?}", op),
         }),
     );
 
+    let verb = match op {
+        TransferOp::Copy => "Copied",
+        TransferOp::Move => "Moved",
+    };
+    let summary = if files_failed == 0 {
+        format!("{} {} file(s)", verb, files_processed)
+    } else {
+        format!(
+            "{} {} file(s), {} failed",
+            verb, files_processed, files_failed
+        )
+    };
+    notify_job_finished(handle, job.kind, files_failed == 0, &summary);
+
+    // Run "pasted into" hooks once per destination folder with the top-level items
+    if files_processed > 0 {
+        let mut folders: Vec<(&Path, Vec<PathBuf>)> = Vec::new();
+        for root in roots.iter().filter(|r| r.dest.exists()) {
+            let Some(parent) = root.dest.parent() else {
+                continue;
+            };
+            match folders.iter_mut().find(|(f, _)| *f == parent) {
+                Some((_, paths)) => paths.push(root.dest.clone()),
+                None => folders.push((parent, vec![root.dest.clone()])),
+            }
+        }
+        for (folder, paths) in &folders {
+            fire_hooks(handle, HookEvent::PastedInto { folder, paths });
+        }
+    }
+
     Ok(())
 }
+
+/// Emits a conflict event and blocks until the UI answers
+fn ask_conflict_strategy(
+    handle: &AppHandle,
+    state: &CopyStreamState,
+    job: &JobHandle,
+    request_id: u64,
+    src: &Path,
+    dest: &Path,
+) -> Option<ConflictResponse> {
+    let name = dest
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    let conflict_req = ConflictRequest {
+        request_id,
+        src: src.display().to_string(),
+        dest: dest.display().to_string(),
+        name,
+        src_info: ConflictFileInfo::read(handle, src),
+        dest_info: ConflictFileInfo::read(handle, dest),
+    };
+
+    thread::sleep(Duration::from_millis(50));
+    let _ = handle.emit("clipboard-paste-conflict", &conflict_req);
+
+    state.request_conflict_decision(job, conflict_req).ok()
+}
+
+/// Copies a file's contents: the OS copy routine for small files, the chunked
+/// engine for anything from `OS_COPY_LIMIT` up or sparse, so progress keeps
+/// flowing (and pauses take hold) within a file. `on_progress` gets the bytes
+/// written so far.
+fn copy_contents(
+    job: &JobHandle,
+    src: &Path,
+    dest: &Path,
+    mut on_progress: impl FnMut(u64),
+) -> std::io::Result<u64> {
+    let len = fs::metadata(src)?.len();
+    if len < OS_COPY_LIMIT && !is_sparse_file(src) {
+        let copied = fs::copy(src, dest)?;
+        on_progress(copied);
+        return Ok(copied);
+    }
+    copy_file_chunked(job, src, dest, on_progress)?
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled"))
+}
+
+/// Copies or moves a single file, returning the number of bytes written.
+/// Renames report no progress, since no data is copied.
+fn transfer_file(
+    job: &JobHandle,
+    src: &Path,
+    dest: &Path,
+    op: TransferOp,
+    on_progress: impl FnMut(u64),
+) -> std::io::Result<u64> {
+    match op {
+        TransferOp::Copy => copy_contents(job, src, dest, on_progress),
+        TransferOp::Move => {
+            // try rename first (fast path)
+            match fs::rename(src, dest) {
+                Ok(_) => Ok(fs::metadata(dest).map(|m| m.len()).unwrap_or(0)),
+                Err(e) if !is_cross_device(&e) => Err(e),
+                Err(_) => {
+                    // across volumes: copy, and only delete the source once the copy checks out
+                    let bytes = copy_contents(job, src, dest, on_progress)?;
+                    let verified = verify_copy(src, dest).and_then(|same| {
+                        if same {
+                            Ok(())
+                        } else {
+                            Err(std::io::Error::other(format!(
+                                "Verification failed for {}",
+                                src.display()
+                            )))
+                        }
+                    });
+                    if let Err(e) = verified {
+                        let _ = fs::remove_file(dest);
+                        return Err(e);
+                    }
+                    fs::remove_file(src)?;
+                    Ok(bytes)
+                }
+            }
+        }
+    }
+}
+
+/// True when both paths resolve to the same file on disk
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (dunce::canonicalize(a), dunce::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -2,6 +2,7 @@ use serde::Deserialize;
 use std::sync::Arc;
 use tauri::State;
 
+use crate::filesys::stream::jobs::{ErrorAction, ErrorResponse, JobStreamState};
 use crate::filesys::stream::opstream::{ConflictResponse, CopyStreamState, DuplicateStrategy};
 
 #[derive(Deserialize)]
@@ -34,3 +35,34 @@ pub async fn resolve_copy_conflict(
         .submit_conflict_response(payload.request_id, resp)
         .map_err(|e| format!("failed to submit response: {}", e))
 }
+
+#[derive(Deserialize)]
+pub struct ResolveJobErrorPayload {
+    pub request_id: u64,
+    pub action: String, // "Retry" | "Skip" | "Force" | "Abort"
+    pub repeat_for_all: bool,
+}
+
+#[tauri::command]
+pub async fn resolve_job_error(
+    payload: ResolveJobErrorPayload,
+    state: State<'_, Arc<JobStreamState>>,
+) -> Result<(), String> {
+    let action = match payload.action.as_str() {
+        "Retry" => ErrorAction::Retry,
+        "Skip" => ErrorAction::Skip,
+        "Force" => ErrorAction::Force,
+        "Abort" => ErrorAction::Abort,
+        other => return Err(format!("unknown action: {}", other)),
+    };
+
+    let job = state
+        .get(payload.request_id)
+        .ok_or_else(|| format!("no running job with id {}", payload.request_id))?;
+
+    job.submit_error_response(ErrorResponse {
+        action,
+        repeat_for_all: payload.repeat_for_all,
+    })
+    .map_err(|e| format!("failed to submit response: {}", e))
+}
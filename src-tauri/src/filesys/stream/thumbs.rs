@@ -1,16 +1,336 @@
 use base64::{engine::GeneralPurpose, Engine};
-use image::ImageReader;
+use image::{DynamicImage, GrayImage, ImageFormat, ImageReader, Limits, RgbImage};
 use parselnk::Lnk;
 use std::{
-    fs,
+    fs::{self, File},
+    io::{BufReader, Cursor, Read},
     path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
 };
 
+use tauri::Emitter;
+
+use crate::filesys::{
+    actions::unique_path,
+    cloud::{cloud_state, CloudState},
+    stream::jobs::{JobKind, JobStreamState},
+    volumes::is_rotational,
+};
 use crate::util::{
-    caches::{get_thumb, hash_path, open_thumb_db, set_thumb},
-    ffutils::FFmpegHandler,
+    caches::{
+        get_thumb, get_thumb_by_hash, hash_path, open_thumb_db, set_thumb, PreviewAs, PreviewRule,
+        SharedAppSettings, ThumbnailSettings,
+    },
+    ffutils::{ffmpeg_init, FFmpegHandler},
+    icon_packs::serve_icon,
+    notify::notify_job_finished,
+    plugins::plugin_thumbnail,
+    power::should_throttle,
 };
 
+/// Longest edge of generated thumbnails
+const THUMBNAIL_SIZE: u32 = 128;
+
+/// Memory one worker may allocate while decoding a single image
+const DECODE_MEMORY_BUDGET: u64 = 256 * 1024 * 1024;
+
+/// How much of a JPEG's head is read looking for its EXIF block (APP1 is capped at 64KB)
+const EXIF_SCAN_LIMIT: u64 = 128 * 1024;
+
+/// Extensions whose pixel size is read for the Dimensions column
+const DIMENSION_EXTS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff", "ico", "tga", "qoi",
+];
+
+/// Video frames tried before settling for a blank one
+const VIDEO_FRAME_CANDIDATES: usize = 4;
+
+/// Gap between candidate video frames, in percent of the duration
+const VIDEO_CANDIDATE_STEP_PERCENT: f64 = 15.0;
+
+/// Luma standard deviation under which a frame counts as blank (black, white, flat color)
+const BLANK_FRAME_DEVIATION: f64 = 8.0;
+
+/// Extensions that get an animated hover preview
+const ANIMATED_EXTS: &[&str] = &["gif", "mp4", "mkv", "mov", "avi", "flv", "webm"];
+
+/// Length of animated previews
+const ANIMATED_PREVIEW_SECONDS: f64 = 3.0;
+
+/// Width of animated previews; low enough to stay small in the thumb DB
+const ANIMATED_PREVIEW_WIDTH: u32 = 192;
+
+/// Thumbnails generated at once on a spinning disk when the setting is automatic
+const ROTATIONAL_THUMBNAIL_CONCURRENCY: usize = 2;
+
+/// Thumbnails generated at once on battery or in power saver
+const BATTERY_THUMBNAIL_CONCURRENCY: usize = 1;
+
+/// Caps how many thumbnails a listing generates at once, independently of the
+/// worker pool size, so thumbnailing doesn't saturate slow disks
+pub struct ThumbnailSlots {
+    free: Mutex<usize>,
+    released: Condvar,
+}
+
+impl ThumbnailSlots {
+    pub fn new(slots: usize) -> Self {
+        Self {
+            free: Mutex::new(slots.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Runs `f` once a slot is free
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        {
+            let mut free = self.free.lock().unwrap();
+            while *free == 0 {
+                free = self.released.wait(free).unwrap();
+            }
+            *free -= 1;
+        }
+        let result = f();
+        *self.free.lock().unwrap() += 1;
+        self.released.notify_one();
+        result
+    }
+}
+
+/// Slots for thumbnailing `dir`: the `performance.thumbnail_concurrency` setting,
+/// or when automatic, a couple on spinning disks and one per worker otherwise.
+/// On battery or in power saver it drops to one unless throttling is turned off.
+pub async fn thumbnail_slots(
+    handle: &tauri::AppHandle,
+    dir: &Path,
+    workers: usize,
+) -> ThumbnailSlots {
+    use tauri::Manager;
+
+    let configured = match handle.try_state::<SharedAppSettings>() {
+        Some(settings) => settings.0.read().await.performance.thumbnail_concurrency,
+        None => 0,
+    };
+    let slots = match configured {
+        0 if is_rotational(dir) => ROTATIONAL_THUMBNAIL_CONCURRENCY,
+        0 => workers,
+        configured => configured,
+    };
+    if should_throttle(handle) {
+        return ThumbnailSlots::new(slots.min(BATTERY_THUMBNAIL_CONCURRENCY));
+    }
+    ThumbnailSlots::new(slots)
+}
+
+/// Stored pixel size of an image plus its EXIF orientation (1-8). Orientations
+/// 5-8 are rotated a quarter turn, so the image displays as `height` x `width`.
+#[derive(serde::Serialize, Clone, Copy, Debug)]
+pub struct ImageDimensions {
+    pub width: u32,
+    pub height: u32,
+    pub orientation: Option<u32>,
+}
+
+/// Reads an image's dimensions from its header without decoding any pixels.
+/// Online-only cloud files are skipped, since reading would download them.
+pub fn image_dimensions(path: &Path, meta: &fs::Metadata) -> Option<ImageDimensions> {
+    let ext = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let online_only = cloud_state(meta) == Some(CloudState::OnlineOnly);
+    if online_only || !DIMENSION_EXTS.contains(&ext.as_str()) {
+        return None;
+    }
+
+    let (width, height) = ImageReader::new(BufReader::new(File::open(path).ok()?))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()?;
+    let has_exif = ["jpg", "jpeg", "tif", "tiff", "webp", "png"].contains(&ext.as_str());
+    Some(ImageDimensions {
+        width,
+        height,
+        orientation: if has_exif {
+            exif_orientation(path)
+        } else {
+            None
+        },
+    })
+}
+
+fn exif_orientation(path: &Path) -> Option<u32> {
+    let mut head = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(EXIF_SCAN_LIMIT)
+        .read_to_end(&mut head)
+        .ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(&head))
+        .ok()?;
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+        .filter(|o| (1..=8).contains(o))
+}
+
+/// Current thumbnail settings, or the defaults while they're being replaced
+fn thumbnail_settings(handle: &tauri::AppHandle) -> ThumbnailSettings {
+    use tauri::Manager;
+
+    handle
+        .try_state::<SharedAppSettings>()
+        .and_then(|s| s.0.try_read().ok().map(|s| s.thumbnails.clone()))
+        .unwrap_or_default()
+}
+
+/// Picks the image a video's thumbnail is made from, following the thumbnail
+/// settings: embedded cover art first, then the frame at the configured position,
+/// moving further in while frames come out blank
+pub fn video_thumbnail_frame(
+    handle: &tauri::AppHandle,
+    ffmpeg: &FFmpegHandler,
+    video: &str,
+) -> Option<DynamicImage> {
+    let settings = thumbnail_settings(handle);
+    let video_position_percent = settings
+        .rule_for(Path::new(video))
+        .and_then(|rule| rule.video_position_percent)
+        .unwrap_or(settings.video_position_percent);
+    let ThumbnailSettings {
+        skip_blank_frames,
+        prefer_cover_art,
+        ..
+    } = settings;
+
+    if prefer_cover_art {
+        if let Some(cover) = ffmpeg.cover_art(video) {
+            return Some(cover);
+        }
+    }
+
+    // without a duration (some streams) fall back to the old fixed offset
+    let Some(duration) = ffmpeg.duration(video) else {
+        return ffmpeg.frame_at(video, 1.0);
+    };
+    let candidates = if skip_blank_frames {
+        VIDEO_FRAME_CANDIDATES
+    } else {
+        1
+    };
+
+    let mut fallback = None;
+    for i in 0..candidates {
+        let percent =
+            video_position_percent.clamp(0.0, 100.0) + i as f64 * VIDEO_CANDIDATE_STEP_PERCENT;
+        if percent >= 100.0 {
+            break;
+        }
+        let Some(frame) = ffmpeg.frame_at(video, duration * percent / 100.0) else {
+            continue;
+        };
+        if !skip_blank_frames || !is_blank_frame(&frame) {
+            return Some(frame);
+        }
+        fallback.get_or_insert(frame);
+    }
+    fallback.or_else(|| ffmpeg.frame_at(video, 0.0))
+}
+
+/// True for frames with almost no variation, like fade-ins and title cards
+fn is_blank_frame(frame: &DynamicImage) -> bool {
+    let luma = frame.thumbnail(32, 32).to_luma8();
+    let count = luma.pixels().len().max(1) as f64;
+    let mean = luma.pixels().map(|p| p.0[0] as f64).sum::<f64>() / count;
+    let variance = luma
+        .pixels()
+        .map(|p| (p.0[0] as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    variance.sqrt() < BLANK_FRAME_DEVIATION
+}
+
+/// Decodes an image for thumbnailing without holding more than
+/// `DECODE_MEMORY_BUDGET` per worker. JPEGs use their embedded EXIF preview when
+/// there is one, and are otherwise DCT-scaled while decoding; other formats are
+/// streamed from disk and give up once their pixel buffer would exceed the budget.
+fn decode_image_bounded(path: &Path, ext: &str) -> Option<DynamicImage> {
+    if matches!(ext, "jpg" | "jpeg") {
+        if let Some(preview) = exif_preview(path) {
+            return Some(preview);
+        }
+        if let Some(img) = decode_jpeg_scaled(path) {
+            return Some(img);
+        }
+    }
+
+    decode_with_budget(path)
+}
+
+/// Full decode, streamed from disk, giving up once the pixel buffer would
+/// exceed `DECODE_MEMORY_BUDGET`
+fn decode_with_budget(path: &Path) -> Option<DynamicImage> {
+    let mut reader = ImageReader::new(BufReader::new(File::open(path).ok()?))
+        .with_guessed_format()
+        .ok()?;
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(DECODE_MEMORY_BUDGET);
+    reader.limits(limits);
+    reader.decode().ok()
+}
+
+/// The thumbnail cameras embed in EXIF (IFD1), read from the head of the file
+fn exif_preview(path: &Path) -> Option<DynamicImage> {
+    let mut head = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(EXIF_SCAN_LIMIT)
+        .read_to_end(&mut head)
+        .ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(&head))
+        .ok()?;
+
+    let offset = exif
+        .get_field(exif::Tag::JPEGInterchangeFormat, exif::In::THUMBNAIL)?
+        .value
+        .get_uint(0)? as usize;
+    let length = exif
+        .get_field(exif::Tag::JPEGInterchangeFormatLength, exif::In::THUMBNAIL)?
+        .value
+        .get_uint(0)? as usize;
+    let preview = exif.buf().get(offset..offset.checked_add(length)?)?;
+
+    let img = image::load_from_memory_with_format(preview, ImageFormat::Jpeg).ok()?;
+    // some cameras embed tiny 80px previews; those look worse than a real decode
+    (img.width().max(img.height()) >= THUMBNAIL_SIZE).then_some(img)
+}
+
+/// Decodes a JPEG at the smallest DCT scale (1/2 to 1/8) still covering the thumbnail size
+fn decode_jpeg_scaled(path: &Path) -> Option<DynamicImage> {
+    use jpeg_decoder::{Decoder, PixelFormat};
+
+    let mut decoder = Decoder::new(BufReader::new(File::open(path).ok()?));
+    decoder.set_max_decoding_buffer_size(DECODE_MEMORY_BUDGET as usize);
+    decoder.read_info().ok()?;
+    let (width, height) = decoder
+        .scale(THUMBNAIL_SIZE as u16, THUMBNAIL_SIZE as u16)
+        .ok()?;
+    let pixels = decoder.decode().ok()?;
+    let (width, height) = (width as u32, height as u32);
+
+    match decoder.info()?.pixel_format {
+        PixelFormat::RGB24 => {
+            RgbImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8)
+        }
+        PixelFormat::L8 => GrayImage::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+        // 16-bit and CMYK JPEGs are rare; let the generic decoder handle them
+        PixelFormat::L16 | PixelFormat::CMYK32 => None,
+    }
+}
+
 pub fn resolve_lnk_target(path: &str) -> Option<String> {
     let data = fs::read(path).ok()?;
     let lnk = Lnk::try_from(data).ok()?;
@@ -35,6 +355,174 @@ pub fn resolve_lnk_target(path: &str) -> Option<String> {
     Some(PathBuf::from(path).to_string_lossy().to_string())
 }
 
+/// The cached thumbnail for `path`, if one is stored and still fresh. Never
+/// generates one, so it's cheap enough to call while a job waits on the user.
+pub fn cached_thumbnail(handle: &tauri::AppHandle, path: &Path) -> Option<String> {
+    let conn = open_thumb_db(handle).ok()?;
+    let mtime = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let hash = hash_path(&path.to_string_lossy());
+    let (thumb_bytes, _, _) = get_thumb(&conn, hash, mtime).ok()??;
+    Some(base64::engine::general_purpose::STANDARD.encode(thumb_bytes))
+}
+
+/// URL serving the cached thumbnail `hash` through the `thumb` protocol. `mtime`
+/// only busts the webview's cache when the source file changes.
+pub fn thumb_url(hash: u64, mtime: i64) -> String {
+    // WebView2 and Android expose custom schemes as http://<scheme>.localhost
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://thumb.localhost/{}?m={}", hash, mtime)
+    } else {
+        format!("thumb://localhost/{}?m={}", hash, mtime)
+    }
+}
+
+/// Handles `thumb://localhost/<hash>` requests with the raw bytes from the thumb DB,
+/// so cached thumbnails render without a base64 round trip through events.
+/// `thumb://localhost/icon/...` serves file type icons from the active icon pack.
+pub fn serve_thumb(
+    handle: &tauri::AppHandle,
+    request: &tauri::http::Request<Vec<u8>>,
+) -> tauri::http::Response<Vec<u8>> {
+    let path = request.uri().path().trim_start_matches('/');
+    if let Some(icon) = path.strip_prefix("icon/") {
+        let builder = tauri::http::Response::builder().header("Access-Control-Allow-Origin", "*");
+        let response = match serve_icon(handle, icon) {
+            Some(svg) => builder
+                .header("Content-Type", "image/svg+xml")
+                .header("Cache-Control", "max-age=86400")
+                .body(svg),
+            None => builder.status(404).body(Vec::new()),
+        };
+        return response.unwrap_or_default();
+    }
+
+    let thumb = path.parse::<u64>().ok().and_then(|hash| {
+        let conn = open_thumb_db(handle).ok()?;
+        get_thumb_by_hash(&conn, hash).ok().flatten()
+    });
+
+    let builder = tauri::http::Response::builder().header("Access-Control-Allow-Origin", "*");
+    let response = match thumb {
+        Some(bytes) => {
+            let mime = if bytes.starts_with(b"\x89PNG") {
+                "image/png"
+            } else if bytes.starts_with(b"RIFF") {
+                "image/webp"
+            } else if bytes.starts_with(b"GIF8") {
+                "image/gif"
+            } else {
+                "image/jpeg"
+            };
+            builder
+                .header("Content-Type", mime)
+                .header("Cache-Control", "max-age=31536000")
+                .body(bytes)
+        }
+        None => builder.status(404).body(Vec::new()),
+    };
+    response.unwrap_or_default()
+}
+
+/// A short looping animation for hover previews, base64-encoded
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct AnimatedPreview {
+    /// "image/webp", or "image/gif" when FFmpeg was built without libwebp
+    pub mime: String,
+    pub data: String,
+}
+
+/// The user's preview rule for `path`'s extension, so the previewer can honour
+/// "treat as" and "no preview" overrides. `None` when no rule is set.
+#[tauri::command]
+pub fn get_preview_rule(handle: tauri::AppHandle, path: String) -> Option<PreviewRule> {
+    thumbnail_settings(&handle)
+        .rule_for(Path::new(&path))
+        .cloned()
+}
+
+/// Produces (or fetches from the thumb DB) a silent, low-res loop of a GIF or
+/// video for hover previews in the grid. `None` for other files.
+#[tauri::command]
+pub async fn get_animated_preview(
+    handle: tauri::AppHandle,
+    path: String,
+) -> Result<Option<AnimatedPreview>, String> {
+    tauri::async_runtime::spawn_blocking(move || animated_preview(&handle, &path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn animated_preview(handle: &tauri::AppHandle, path: &str) -> Option<AnimatedPreview> {
+    let file = Path::new(path);
+    let ext = file
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let meta = fs::metadata(file).ok()?;
+    let settings = thumbnail_settings(handle);
+    let rule = settings.rule_for(file);
+    let animated = match rule.and_then(|rule| rule.preview_as) {
+        Some(PreviewAs::Video) => true,
+        Some(_) => false,
+        None => ANIMATED_EXTS.contains(&ext.as_str()),
+    };
+    if !animated
+        || rule.is_some_and(PreviewRule::skips_thumbnail)
+        || cloud_state(&meta) == Some(CloudState::OnlineOnly)
+    {
+        return None;
+    }
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    // stored beside the still thumbnail under its own key
+    let conn = open_thumb_db(handle).ok()?;
+    let key = format!("{}#animated", path);
+    let hash = hash_path(&key);
+    let encode = |bytes: &[u8], format: &str| AnimatedPreview {
+        mime: format!("image/{}", format),
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+    };
+    if let Ok(Some((bytes, _, format))) = get_thumb(&conn, hash, mtime) {
+        return Some(encode(&bytes, format.as_deref().unwrap_or("webp")));
+    }
+
+    let ffmpeg = crate::util::ffutils::ffmpeg_init(handle);
+    // GIFs play from the start; videos from the same spot as their thumbnail
+    let start = if ext == "gif" {
+        0.0
+    } else {
+        let percent = rule
+            .and_then(|rule| rule.video_position_percent)
+            .unwrap_or(settings.video_position_percent)
+            .clamp(0.0, 100.0);
+        ffmpeg.duration(path).map_or(0.0, |d| {
+            (d * percent / 100.0)
+                .min(d - ANIMATED_PREVIEW_SECONDS)
+                .max(0.0)
+        })
+    };
+
+    let (seconds, width) = (ANIMATED_PREVIEW_SECONDS, ANIMATED_PREVIEW_WIDTH);
+    let (bytes, format) = ["webp", "gif"].into_iter().find_map(|format| {
+        ffmpeg
+            .animated_clip(path, start, seconds, width, format)
+            .map(|bytes| (bytes, format))
+    })?;
+    // recorded under the source path so thumbnail pruning drops it with the file
+    let _ = set_thumb(&conn, hash, path, mtime, None, Some(format), &bytes);
+    Some(encode(&bytes, format))
+}
+
 pub fn get_thumbnail_for_path(
     handle: &tauri::AppHandle,
     ffmpeg: &FFmpegHandler,
@@ -52,10 +540,24 @@ pub fn get_thumbnail_for_path(
         .map(|s| s.to_string_lossy().to_lowercase())
         .unwrap_or_default();
 
+    // user rules come first, so a thumbnail cached before a rule was added stays hidden
+    let rule = thumbnail_settings(handle)
+        .rule_for(Path::new(&resolved_path))
+        .cloned()
+        .unwrap_or_default();
+    if rule.skips_thumbnail() {
+        return None;
+    }
+    let treat_as = |kind: PreviewAs, exts: &[&str]| match rule.preview_as {
+        Some(preview_as) => preview_as == kind,
+        None => exts.contains(&ext.as_str()),
+    };
+
     let conn = open_thumb_db(handle).ok()?;
     let hash = hash_path(&resolved_path);
-    let mtime = fs::metadata(&resolved_path)
-        .ok()
+    let meta = fs::metadata(&resolved_path).ok();
+    let mtime = meta
+        .as_ref()
         .and_then(|m| m.modified().ok())
         .map(|d| {
             d.duration_since(std::time::UNIX_EPOCH)
@@ -80,41 +582,47 @@ pub fn get_thumbnail_for_path(
         return None;
     }
 
+    // Decoding an online-only placeholder would download it, so only the shell icon is used
+    let online_only = meta.as_ref().and_then(cloud_state) == Some(CloudState::OnlineOnly);
+
     // Image files
-    if ["png", "jpg", "jpeg", "gif", "bmp"].contains(&ext.as_str()) {
-        if let Ok(bytes) = fs::read(&resolved_path) {
-            if let Ok(reader) = ImageReader::new(std::io::Cursor::new(&bytes)).with_guessed_format()
+    if !online_only && treat_as(PreviewAs::Image, &["png", "jpg", "jpeg", "gif", "bmp"]) {
+        if let Some(img) = decode_image_bounded(Path::new(&resolved_path), &ext) {
+            let thumb = img.resize(
+                THUMBNAIL_SIZE,
+                THUMBNAIL_SIZE,
+                image::imageops::FilterType::Nearest,
+            );
+            let mut buf = Vec::new();
+            if thumb
+                .write_to(
+                    &mut std::io::Cursor::new(&mut buf),
+                    image::ImageFormat::Jpeg,
+                )
+                .is_ok()
             {
-                if let Ok(img) = reader.decode() {
-                    let thumb = img.resize(128, 128, image::imageops::FilterType::Nearest);
-                    let mut buf = Vec::new();
-                    if thumb
-                        .write_to(
-                            &mut std::io::Cursor::new(&mut buf),
-                            image::ImageFormat::Jpeg,
-                        )
-                        .is_ok()
-                    {
-                        let _ = set_thumb(
-                            &conn,
-                            hash,
-                            mtime,
-                            Some(bytes.len() as i64),
-                            Some(&ext),
-                            &buf,
-                        );
-                        return Some(encoder.encode(&buf));
-                    }
-                }
+                let _ = set_thumb(
+                    &conn,
+                    hash,
+                    &resolved_path,
+                    mtime,
+                    meta.as_ref().map(|m| m.len() as i64),
+                    Some(&ext),
+                    &buf,
+                );
+                return Some(encoder.encode(&buf));
             }
         }
     }
 
     // Video files
-    if ["mp4", "mkv", "mov", "avi", "flv"].contains(&ext.as_str()) {
-        if let Some(buf) = std::panic::catch_unwind(|| {
-            let img = ffmpeg.generate_thumbnail(&resolved_path, 1.0); // 1 second timestamp
-            let thumb = img.resize(128, 128, image::imageops::FilterType::Nearest);
+    if !online_only && treat_as(PreviewAs::Video, &["mp4", "mkv", "mov", "avi", "flv"]) {
+        if let Some(buf) = video_thumbnail_frame(handle, ffmpeg, &resolved_path).and_then(|img| {
+            let thumb = img.resize(
+                THUMBNAIL_SIZE,
+                THUMBNAIL_SIZE,
+                image::imageops::FilterType::Nearest,
+            );
             let mut buf = Vec::new();
             thumb
                 .write_to(
@@ -123,11 +631,16 @@ pub fn get_thumbnail_for_path(
                 )
                 .ok()
                 .map(|_| buf)
-        })
-        .ok()
-        .flatten()
-        {
-            let _ = set_thumb(&conn, hash, mtime, None, Some(&ext), &buf);
+        }) {
+            let _ = set_thumb(&conn, hash, &resolved_path, mtime, None, Some(&ext), &buf);
+            return Some(encoder.encode(&buf));
+        }
+    }
+
+    // Plugin thumbnail providers
+    if !online_only {
+        if let Some(buf) = plugin_thumbnail(handle, Path::new(&resolved_path)) {
+            let _ = set_thumb(&conn, hash, &resolved_path, mtime, None, Some(&ext), &buf);
             return Some(encoder.encode(&buf));
         }
     }
@@ -156,7 +669,10 @@ pub fn get_thumbnail_for_path(
                 .write_to(&mut std::io::Cursor::new(&mut buf), image::ImageFormat::Png)
                 .is_ok()
             {
-                let _ = set_thumb(&conn, hash, mtime, None, Some(&ext), &buf);
+                // not cached for placeholders, so the real thumbnail shows once downloaded
+                if !online_only {
+                    let _ = set_thumb(&conn, hash, &resolved_path, mtime, None, Some(&ext), &buf);
+                }
                 return Some(encoder.encode(&buf));
             }
         }
@@ -166,6 +682,184 @@ pub fn get_thumbnail_for_path(
     None
 }
 
+// ===============================
+// Export
+// ===============================
+
+/// Tiles per row (and rows per page) of an exported contact sheet
+const CONTACT_SHEET_GRID: u32 = 10;
+
+/// Background behind tiles that don't fill their cell
+const CONTACT_SHEET_BACKGROUND: [u8; 3] = [0x20, 0x20, 0x20];
+
+/// Minimum gap between `thumbnail-export-progress` events
+const EXPORT_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Thumbnail of `path` fitted within `size`, going through the thumbnail cache
+/// so the user's preview rules apply. Beyond the cached size, images are decoded
+/// again at full resolution so exports above 128px stay sharp.
+fn export_image(
+    handle: &tauri::AppHandle,
+    ffmpeg: &FFmpegHandler,
+    path: &Path,
+    size: u32,
+) -> Option<DynamicImage> {
+    let thumb = get_thumbnail_for_path(handle, ffmpeg, &path.to_string_lossy())?;
+    let ext = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let online_only =
+        fs::metadata(path).ok().as_ref().and_then(cloud_state) == Some(CloudState::OnlineOnly);
+    if size > THUMBNAIL_SIZE
+        && !online_only
+        && ["png", "jpg", "jpeg", "gif", "bmp"].contains(&ext.as_str())
+    {
+        if let Some(img) = decode_with_budget(path) {
+            return Some(img.thumbnail(size, size));
+        }
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(thumb)
+        .ok()?;
+    let img = image::load_from_memory(&bytes).ok()?;
+    Some(if img.width().max(img.height()) == size {
+        img
+    } else {
+        img.resize(size, size, image::imageops::FilterType::Triangle)
+    })
+}
+
+/// Pastes `tiles` centered into `size`px cells, `CONTACT_SHEET_GRID` per row
+fn render_contact_sheet(tiles: &[DynamicImage], size: u32) -> RgbImage {
+    let columns = (tiles.len() as u32).clamp(1, CONTACT_SHEET_GRID);
+    let rows = (tiles.len() as u32).div_ceil(columns).max(1);
+    let mut sheet = RgbImage::from_pixel(
+        columns * size,
+        rows * size,
+        image::Rgb(CONTACT_SHEET_BACKGROUND),
+    );
+    for (i, tile) in tiles.iter().enumerate() {
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        let x = column * size + (size - tile.width().min(size)) / 2;
+        let y = row * size + (size - tile.height().min(size)) / 2;
+        image::imageops::overlay(&mut sheet, &tile.to_rgb8(), x as i64, y as i64);
+    }
+    sheet
+}
+
+/// Writes the thumbnails of every file directly in `dir` to `dest` as JPEGs of
+/// at most `size` px (default 128), named after the source file. With
+/// `contact_sheet` they are laid out instead on `contact-sheet-<n>.jpg` pages of
+/// up to 10×10 tiles. Missing thumbnails are generated on the way; files without
+/// one are skipped. Runs as a job (`cancel_job` stops it), emitting
+/// `thumbnail-export-progress`. Returns the number of thumbnails exported.
+#[tauri::command]
+pub async fn export_thumbnails(
+    handle: tauri::AppHandle,
+    jobs: tauri::State<'_, std::sync::Arc<JobStreamState>>,
+    dir: String,
+    dest: String,
+    size: Option<u32>,
+    contact_sheet: Option<bool>,
+    request_id: u64,
+) -> Result<usize, String> {
+    let size = size.unwrap_or(THUMBNAIL_SIZE).clamp(16, 1024);
+    let sheet = contact_sheet.unwrap_or(false);
+    let dir_path = PathBuf::from(&dir);
+    if !dir_path.is_dir() {
+        return Err(format!("Not a directory: {}", dir));
+    }
+    let dest_path = PathBuf::from(&dest);
+    fs::create_dir_all(&dest_path).map_err(|e| e.to_string())?;
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir_path)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .map(|e| e.path())
+        .collect();
+    files.sort_by_key(|p| p.file_name().map(|n| n.to_string_lossy().to_lowercase()));
+
+    let jobs = jobs.inner().clone();
+    let job = jobs.start(request_id, JobKind::ThumbnailExport);
+    let result = tauri::async_runtime::spawn_blocking({
+        let handle = handle.clone();
+        let job = job.clone();
+        move || {
+            let ffmpeg = ffmpeg_init(&handle);
+            let total = files.len() as u64;
+            let mut exported = 0;
+            let mut tiles = Vec::new();
+            let mut pages = 0;
+            let mut last_emit = std::time::Instant::now();
+
+            let write_page = |tiles: &[DynamicImage], page: usize| {
+                let page_path = unique_path(&dest_path.join(format!("contact-sheet-{}.jpg", page)));
+                render_contact_sheet(tiles, size)
+                    .save_with_format(&page_path, ImageFormat::Jpeg)
+                    .map_err(|e| format!("Failed to write {}: {}", page_path.display(), e))
+            };
+
+            for (done, path) in files.iter().enumerate() {
+                if job.is_cancelled() {
+                    return Err("Thumbnail export cancelled".to_string());
+                }
+                if let Some(img) = export_image(&handle, &ffmpeg, path, size) {
+                    if sheet {
+                        tiles.push(img);
+                        let per_page = (CONTACT_SHEET_GRID * CONTACT_SHEET_GRID) as usize;
+                        if tiles.len() == per_page {
+                            pages += 1;
+                            write_page(&tiles, pages)?;
+                            tiles.clear();
+                        }
+                    } else {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        let out = unique_path(&dest_path.join(format!("{}.jpg", name)));
+                        img.to_rgb8()
+                            .save_with_format(&out, ImageFormat::Jpeg)
+                            .map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+                    }
+                    exported += 1;
+                }
+
+                job.report_progress(done as u64 + 1, total);
+                if last_emit.elapsed() >= EXPORT_PROGRESS_INTERVAL || done as u64 + 1 == total {
+                    last_emit = std::time::Instant::now();
+                    let _ = handle.emit(
+                        "thumbnail-export-progress",
+                        serde_json::json!({
+                            "request_id": request_id,
+                            "done": done + 1,
+                            "total": total,
+                            "exported": exported,
+                            "current": path.display().to_string(),
+                        }),
+                    );
+                }
+            }
+            if !tiles.is_empty() {
+                write_page(&tiles, pages + 1)?;
+            }
+            Ok(exported)
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+    jobs.finish(request_id);
+
+    if !job.is_cancelled() {
+        let message = match &result {
+            Ok(count) => format!("Exported {} thumbnails to {}", count, dest),
+            Err(e) => format!("Thumbnail export failed: {}", e),
+        };
+        notify_job_finished(&handle, JobKind::ThumbnailExport, result.is_ok(), &message);
+    }
+    result
+}
+
 #[cfg(target_os = "windows")]
 fn extract_shell_icon(path: &Path) -> Option<Vec<u8>> {
     use std::{ffi::OsStr, os::windows::ffi::OsStrExt, ptr};
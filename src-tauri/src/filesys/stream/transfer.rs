@@ -0,0 +1,626 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use jwalk::WalkDir;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::filesys::{links::copy_symlink, stream::jobs::JobHandle, volumes::is_rotational};
+use crate::util::hashing::{hash_file, ChecksumAlgo};
+
+/// Buffer size for chunked copies and verification reads
+const COPY_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Files at least this large are copied by several streams at once
+pub const PARALLEL_COPY_THRESHOLD: u64 = 256 * 1024 * 1024;
+
+/// Concurrent reader/writer pairs used for large files on solid-state drives
+const PARALLEL_COPY_STREAMS: usize = 4;
+
+/// `performance.copy_streams` from settings; 0 picks by drive type
+static COPY_STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+/// Region each stream claims at a time; a multiple of any common sector/page size
+const PARALLEL_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Minimum delay between two progress events
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Span the transfer rate is averaged over
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Rolling average of the bytes copied per second, for speed and time-left reporting
+#[derive(Default)]
+pub struct TransferRate {
+    /// (when, bytes copied so far) within the last `RATE_WINDOW`
+    samples: VecDeque<(Instant, u64)>,
+    copied: u64,
+}
+
+impl TransferRate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `bytes` more copied
+    pub fn add(&mut self, bytes: u64) {
+        let now = Instant::now();
+        self.copied = self.copied.saturating_add(bytes);
+        self.samples.push_back((now, self.copied));
+        while self.samples.len() > 2
+            && self
+                .samples
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Forgets the window, e.g. after the job sat waiting on the user
+    pub fn restart(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Average speed over the window, once it spans long enough to be meaningful
+    pub fn bytes_per_sec(&self) -> Option<u64> {
+        let (first_at, first) = self.samples.front()?;
+        let (last_at, last) = self.samples.back()?;
+        let secs = last_at.duration_since(*first_at).as_secs_f64();
+        (secs >= 0.5).then(|| ((last - first) as f64 / secs) as u64)
+    }
+
+    /// Seconds left to copy `remaining` bytes at the current speed
+    pub fn eta_secs(&self, remaining: u64) -> Option<u64> {
+        let rate = self.bytes_per_sec().filter(|r| *r > 0)?;
+        Some(remaining.div_ceil(rate))
+    }
+}
+
+/// How a move was carried out
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveMethod {
+    /// Same volume: a single metadata rename
+    Rename,
+    /// Different volume: copy, verify, then delete the source
+    Transfer,
+}
+
+/// Result returned to the UI once a move finishes
+#[derive(Serialize, Clone, Debug)]
+pub struct MoveOutcome {
+    pub method: MoveMethod,
+    pub dest: String,
+    pub bytes: u64,
+}
+
+/// True when a rename failed only because source and destination are on different devices.
+pub fn is_cross_device(err: &io::Error) -> bool {
+    // ERROR_NOT_SAME_DEVICE on Windows, EXDEV on Unix
+    let code = if cfg!(windows) { 17 } else { 18 };
+    err.raw_os_error() == Some(code)
+}
+
+/// Opens `path` for reading with a hint that it will be read front to back
+fn open_sequential(path: &Path) -> io::Result<fs::File> {
+    #[cfg(windows)]
+    let file = {
+        use std::os::windows::fs::OpenOptionsExt;
+        // FILE_FLAG_SEQUENTIAL_SCAN
+        fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(0x0800_0000)
+            .open(path)?
+    };
+    #[cfg(not(windows))]
+    let file = fs::File::open(path)?;
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        unsafe {
+            libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_SEQUENTIAL);
+        }
+    }
+    Ok(file)
+}
+
+#[cfg(unix)]
+fn read_at(file: &fs::File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &fs::File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &fs::File, buf: &[u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::write_all_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn write_all_at(file: &fs::File, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+    while !buf.is_empty() {
+        let written = std::os::windows::fs::FileExt::seek_write(file, buf, offset)?;
+        if written == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        buf = &buf[written..];
+        offset += written as u64;
+    }
+    Ok(())
+}
+
+/// Data ranges of a sparse file via SEEK_DATA/SEEK_HOLE. Leaves the file offset at 0.
+#[cfg(target_os = "linux")]
+fn seek_data_ranges(file: &fs::File, len: u64) -> Option<Vec<(u64, u64)>> {
+    use std::os::fd::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    let mut ranges = Vec::new();
+    let mut pos: i64 = 0;
+
+    while (pos as u64) < len {
+        let start = unsafe { libc::lseek(fd, pos, libc::SEEK_DATA) };
+        if start < 0 {
+            // ENXIO: no data past `pos`, the rest of the file is a hole
+            if io::Error::last_os_error().raw_os_error() == Some(libc::ENXIO) {
+                break;
+            }
+            return None;
+        }
+        let end = unsafe { libc::lseek(fd, start, libc::SEEK_HOLE) };
+        if end < 0 {
+            return None;
+        }
+        ranges.push((start as u64, (end - start) as u64));
+        pos = end;
+    }
+
+    unsafe { libc::lseek(fd, 0, libc::SEEK_SET) };
+    Some(ranges)
+}
+
+/// The (offset, length) ranges of `file` that hold data, or None when the file
+/// has no holes or the filesystem can't report them.
+fn sparse_data_ranges(file: &fs::File, len: u64) -> Option<Vec<(u64, u64)>> {
+    #[cfg(windows)]
+    let ranges = crate::filesys::os::windows::allocated_ranges(file, len).ok()?;
+    #[cfg(target_os = "linux")]
+    let ranges = seek_data_ranges(file, len)?;
+    #[cfg(not(any(windows, target_os = "linux")))]
+    let ranges = {
+        // no hole reporting here; treat the file as fully allocated
+        let _ = file;
+        vec![(0, len)]
+    };
+
+    let data: u64 = ranges.iter().map(|(_, length)| length).sum();
+    (data < len).then_some(ranges)
+}
+
+/// True when `path` is a sparse file with at least one hole
+pub fn is_sparse_file(path: &Path) -> bool {
+    fs::File::open(path)
+        .and_then(|f| Ok((f.metadata()?.len(), f)))
+        .is_ok_and(|(len, f)| sparse_data_ranges(&f, len).is_some())
+}
+
+/// Reserves `len` bytes for `file` up front so the copy doesn't fragment
+/// or run out of space half way.
+fn preallocate(file: &fs::File, len: u64) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        if unsafe { libc::fallocate(file.as_raw_fd(), 0, 0, len as libc::off_t) } == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EOPNOTSUPP) {
+            return Err(err);
+        }
+    }
+    // NTFS allocates the full length on SetEndOfFile
+    file.set_len(len)
+}
+
+/// Copies only the data ranges of a sparse `src`, leaving the holes unallocated
+/// in a destination marked sparse, so disk images keep their on-disk size.
+fn copy_file_sparse(
+    job: &JobHandle,
+    reader: &fs::File,
+    src: &Path,
+    dest: &Path,
+    len: u64,
+    ranges: &[(u64, u64)],
+    mut on_progress: impl FnMut(u64),
+) -> io::Result<Option<u64>> {
+    let writer = fs::File::create(dest)?;
+    #[cfg(windows)]
+    {
+        // not every filesystem supports sparse files; the copy is still correct without it
+        let _ = crate::filesys::os::windows::set_sparse(&writer);
+    }
+    writer.set_len(len)?;
+
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    for &(offset, length) in ranges {
+        let end = (offset + length).min(len);
+        let mut pos = offset;
+        while pos < end {
+            if job.is_cancelled() {
+                drop(writer);
+                let _ = fs::remove_file(dest);
+                return Ok(None);
+            }
+
+            let want = (end - pos).min(COPY_CHUNK_SIZE as u64) as usize;
+            let read = read_at(reader, &mut buf[..want], pos)?;
+            if read == 0 {
+                break;
+            }
+            write_all_at(&writer, &buf[..read], pos)?;
+            pos += read as u64;
+            on_progress(pos);
+        }
+    }
+
+    writer.sync_all()?;
+    on_progress(len);
+    if let Ok(perms) = fs::metadata(src).map(|m| m.permissions()) {
+        let _ = fs::set_permissions(dest, perms);
+    }
+    Ok(Some(len))
+}
+
+/// Applies the `performance.copy_streams` setting to subsequent copies
+pub fn set_copy_streams(streams: usize) {
+    COPY_STREAMS.store(streams, Ordering::Relaxed);
+}
+
+/// Streams used for a large copy. Spinning disks get one, since concurrent
+/// requests at distant offsets make the heads seek back and forth.
+fn copy_streams_for(src: &Path, dest: &Path) -> usize {
+    match COPY_STREAMS.load(Ordering::Relaxed) {
+        0 if is_rotational(src) || dest.parent().is_some_and(is_rotational) => 1,
+        0 => PARALLEL_COPY_STREAMS,
+        configured => configured,
+    }
+}
+
+/// Copies `len` bytes of `src` with several streams, each claiming the next
+/// unclaimed chunk and writing it at the same offset in `dest`. Keeps enough
+/// requests in flight to saturate NVMe drives and fast network targets.
+fn copy_file_parallel(
+    job: &JobHandle,
+    src: &Path,
+    dest: &Path,
+    len: u64,
+    mut on_progress: impl FnMut(u64),
+) -> io::Result<Option<u64>> {
+    let writer = fs::File::create(dest)?;
+    preallocate(&writer, len)?;
+
+    let next_offset = AtomicU64::new(0);
+    let copied = AtomicU64::new(0);
+    let stop = AtomicBool::new(false);
+
+    let worker = || -> io::Result<()> {
+        let reader = open_sequential(src)?;
+        let writer = fs::OpenOptions::new().write(true).open(dest)?;
+        let mut buf = vec![0u8; PARALLEL_CHUNK_SIZE];
+
+        while !stop.load(Ordering::Relaxed) {
+            // hold here rather than claiming more chunks while the job is paused
+            if job.wait_while_paused() && (job.is_cancelled() || stop.load(Ordering::Relaxed)) {
+                break;
+            }
+            let offset = next_offset.fetch_add(PARALLEL_CHUNK_SIZE as u64, Ordering::Relaxed);
+            if offset >= len {
+                break;
+            }
+            let want = (len - offset).min(PARALLEL_CHUNK_SIZE as u64) as usize;
+
+            let mut filled = 0;
+            while filled < want {
+                let read = read_at(&reader, &mut buf[filled..want], offset + filled as u64)?;
+                if read == 0 {
+                    // source shrank while copying
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                filled += read;
+            }
+            write_all_at(&writer, &buf[..want], offset)?;
+            copied.fetch_add(want as u64, Ordering::Relaxed);
+        }
+        Ok(())
+    };
+
+    let result = thread::scope(|scope| {
+        let workers: Vec<_> = (0..copy_streams_for(src, dest))
+            .map(|_| {
+                scope.spawn(|| {
+                    let result = worker();
+                    if result.is_err() {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    result
+                })
+            })
+            .collect();
+
+        while !workers.iter().all(|w| w.is_finished()) {
+            if job.is_cancelled() {
+                stop.store(true, Ordering::Relaxed);
+            }
+            on_progress(copied.load(Ordering::Relaxed));
+            thread::sleep(PROGRESS_INTERVAL / 2);
+        }
+
+        workers.into_iter().try_for_each(|w| {
+            w.join()
+                .unwrap_or_else(|_| Err(io::Error::other("copy thread panicked")))
+        })
+    });
+
+    if let Err(e) = result {
+        drop(writer);
+        let _ = fs::remove_file(dest);
+        return Err(e);
+    }
+    if job.is_cancelled() {
+        drop(writer);
+        let _ = fs::remove_file(dest);
+        return Ok(None);
+    }
+
+    writer.sync_all()?;
+    on_progress(len);
+    if let Ok(perms) = fs::metadata(src).map(|m| m.permissions()) {
+        let _ = fs::set_permissions(dest, perms);
+    }
+    Ok(Some(len))
+}
+
+/// Copies a single file in chunks, reporting bytes written for this file.
+/// Sparse files keep their holes, files over `PARALLEL_COPY_THRESHOLD` are copied
+/// with several concurrent streams, and the destination is preallocated otherwise.
+/// Returns `Ok(None)` if the job was cancelled part way.
+pub fn copy_file_chunked(
+    job: &JobHandle,
+    src: &Path,
+    dest: &Path,
+    mut on_progress: impl FnMut(u64),
+) -> io::Result<Option<u64>> {
+    let mut reader = open_sequential(src)?;
+    let len = reader.metadata()?.len();
+    if let Some(ranges) = sparse_data_ranges(&reader, len) {
+        return copy_file_sparse(job, &reader, src, dest, len, &ranges, on_progress);
+    }
+    if len >= PARALLEL_COPY_THRESHOLD {
+        drop(reader);
+        return copy_file_parallel(job, src, dest, len, on_progress);
+    }
+
+    let mut writer = fs::File::create(dest)?;
+    preallocate(&writer, len)?;
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    let mut copied: u64 = 0;
+
+    loop {
+        if job.is_cancelled() {
+            drop(writer);
+            let _ = fs::remove_file(dest);
+            return Ok(None);
+        }
+
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buf[..read])?;
+        copied += read as u64;
+        on_progress(copied);
+    }
+
+    writer.flush()?;
+    // the source shrank while copying; drop the unused preallocated tail
+    if copied < len {
+        writer.set_len(copied)?;
+    }
+    if let Ok(perms) = fs::metadata(src).map(|m| m.permissions()) {
+        let _ = fs::set_permissions(dest, perms);
+    }
+    Ok(Some(copied))
+}
+
+/// Checks that `dest` is a byte-identical copy of `src`. Both files are read
+/// again rather than trusting cached hashes.
+pub fn verify_copy(src: &Path, dest: &Path) -> io::Result<bool> {
+    if fs::metadata(src)?.len() != fs::metadata(dest)?.len() {
+        return Ok(false);
+    }
+    let digest = |path| hash_file(path, ChecksumAlgo::Xxh3, || true);
+    Ok(digest(src)? == digest(dest)?)
+}
+
+/// Moves `src` to `dest` across devices: copy everything, verify each file,
+/// recreate symlinks as links, and only then delete the source. A cancelled or failed move leaves the
+/// source untouched and removes the partial destination.
+pub fn run_transfer_move(
+    handle: &AppHandle,
+    job: &JobHandle,
+    src: &Path,
+    dest: &Path,
+) -> Result<Option<MoveOutcome>, String> {
+    let request_id = job.request_id;
+
+    // Phase 1: scan
+    let mut files: Vec<(PathBuf, PathBuf, u64)> = Vec::new(); // (src, dest, size)
+    let mut dirs: Vec<PathBuf> = Vec::new(); // destination dirs, parents first
+                                             // symlinks are recreated as links, never followed
+    let mut links: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut total_size: u64 = 0;
+
+    let src_meta = fs::symlink_metadata(src).map_err(|e| e.to_string())?;
+    if src_meta.file_type().is_symlink() {
+        links.push((src.to_path_buf(), dest.to_path_buf()));
+    } else if src_meta.is_dir() {
+        for entry in WalkDir::new(src)
+            .follow_links(false)
+            .skip_hidden(false)
+            .sort(true)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if job.is_cancelled() {
+                return Ok(None);
+            }
+            let path = entry.path();
+            let rel = path.strip_prefix(src).unwrap_or(&path).to_path_buf();
+            if entry.file_type().is_symlink() {
+                links.push((path.clone(), dest.join(rel)));
+            } else if entry.file_type().is_dir() {
+                dirs.push(dest.join(rel));
+            } else {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                total_size = total_size.saturating_add(size);
+                files.push((path.clone(), dest.join(rel), size));
+            }
+        }
+    } else {
+        total_size = src_meta.len();
+        files.push((src.to_path_buf(), dest.to_path_buf(), total_size));
+    }
+
+    let _ = handle.emit(
+        "move-scan",
+        serde_json::json!({
+            "request_id": request_id,
+            "total_size": total_size,
+            "file_count": files.len() + links.len(),
+        }),
+    );
+
+    // Phase 2: copy + verify
+    let cleanup = |dirs: &[PathBuf], files: &[(PathBuf, PathBuf, u64)]| {
+        for (_, d, _) in files {
+            let _ = fs::remove_file(d);
+        }
+        for d in dirs.iter().rev() {
+            let _ = fs::remove_dir(d);
+        }
+    };
+
+    for dir in &dirs {
+        if let Err(e) = fs::create_dir_all(dir) {
+            cleanup(&dirs, &[]);
+            return Err(format!("Failed to create {}: {}", dir.display(), e));
+        }
+    }
+
+    let mut copied_total: u64 = 0;
+    let mut last_progress = Instant::now();
+    let mut rate = TransferRate::new();
+
+    for (index, (file_src, file_dest, _size)) in files.iter().enumerate() {
+        if let Some(parent) = file_dest.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let base = copied_total;
+        let mut file_copied = 0;
+        let result = copy_file_chunked(job, file_src, file_dest, |copied| {
+            job.report_progress(base + copied, total_size);
+            rate.add(copied.saturating_sub(file_copied));
+            file_copied = copied;
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                let _ = handle.emit(
+                    "move-progress",
+                    serde_json::json!({
+                        "request_id": request_id,
+                        "bytes_copied": base + copied,
+                        "total_size": total_size,
+                        "bytes_per_sec": rate.bytes_per_sec(),
+                        "eta_secs": rate.eta_secs(total_size.saturating_sub(base + copied)),
+                        "current": file_src.display().to_string(),
+                    }),
+                );
+            }
+        });
+
+        let copied = match result {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => {
+                cleanup(&dirs, &files[..index]);
+                return Ok(None);
+            }
+            Err(e) => {
+                cleanup(&dirs, &files[..=index]);
+                return Err(format!("Failed to copy {}: {}", file_src.display(), e));
+            }
+        };
+        copied_total += copied;
+
+        match verify_copy(file_src, file_dest) {
+            Ok(true) => {}
+            Ok(false) => {
+                cleanup(&dirs, &files[..=index]);
+                return Err(format!("Verification failed for {}", file_src.display()));
+            }
+            Err(e) => {
+                cleanup(&dirs, &files[..=index]);
+                return Err(format!("Failed to verify {}: {}", file_src.display(), e));
+            }
+        }
+    }
+
+    for (index, (link_src, link_dest)) in links.iter().enumerate() {
+        if let Err(e) = copy_symlink(link_src, link_dest) {
+            for (_, created) in &links[..index] {
+                let _ = fs::remove_file(created).or_else(|_| fs::remove_dir(created));
+            }
+            cleanup(&dirs, &files);
+            return Err(format!(
+                "Failed to recreate link {}: {}",
+                link_src.display(),
+                e
+            ));
+        }
+    }
+
+    // Phase 3: everything verified -> remove the source
+    let removal = if src_meta.is_dir() {
+        fs::remove_dir_all(src)
+    } else {
+        // directory symlinks on Windows need remove_dir
+        fs::remove_file(src).or_else(|e| fs::remove_dir(src).map_err(|_| e))
+    };
+    if let Err(e) = removal {
+        let _ = handle.emit(
+            "move-error",
+            serde_json::json!({
+                "request_id": request_id,
+                "path": src.display().to_string(),
+                "error": format!("Copied successfully but failed to remove source: {}", e),
+            }),
+        );
+    }
+
+    Ok(Some(MoveOutcome {
+        method: MoveMethod::Transfer,
+        dest: dest.display().to_string(),
+        bytes: copied_total,
+    }))
+}
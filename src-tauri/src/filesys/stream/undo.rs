@@ -0,0 +1,633 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::filesys::names::is_case_only_change;
+use crate::filesys::stream::{
+    jobs::{JobHandle, JobKind, JobStreamState},
+    journal::JournalEntry,
+    transfer::{is_cross_device, run_transfer_move},
+};
+use crate::util::caches::versioning::{load_versioned, save_versioned};
+
+/// Operations kept for undoing; the oldest are forgotten first
+const MAX_UNDO_OPERATIONS: usize = 100;
+
+/// Bump and give `load_versioned` a migration when the file's shape changes
+const UNDO_VERSION: u32 = 1;
+
+// ===============================
+// Operations
+// ===============================
+
+/// A completed file operation, recorded with what's needed to reverse and replay it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FileOperation {
+    /// Files written by a copy or paste, plus the folders it had to create.
+    /// Files a paste replaced are not brought back by undoing it.
+    Copy {
+        files: Vec<JournalEntry>,
+        created_dirs: Vec<PathBuf>,
+    },
+    /// Files or folders moved from `src` to `dest`. `created_dirs` were made at
+    /// the destination, `removed_dirs` emptied and removed at the source.
+    Move {
+        items: Vec<JournalEntry>,
+        created_dirs: Vec<PathBuf>,
+        removed_dirs: Vec<PathBuf>,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// Items moved to the system trash
+    Trash {
+        paths: Vec<PathBuf>,
+    },
+    /// A new, empty file or folder
+    Create {
+        path: PathBuf,
+        is_dir: bool,
+    },
+}
+
+impl FileOperation {
+    /// Short label for menus and notifications, e.g. "Rename of a.txt to b.txt"
+    pub fn describe(&self) -> String {
+        let name = |path: &Path| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string())
+        };
+        let items = |count: usize, single: Option<&PathBuf>| match (count, single) {
+            (1, Some(path)) => name(path),
+            (count, _) => format!("{} items", count),
+        };
+        match self {
+            FileOperation::Copy { files, .. } => {
+                format!(
+                    "Copy of {}",
+                    items(files.len(), files.first().map(|f| &f.dest))
+                )
+            }
+            FileOperation::Move { items: moved, .. } => {
+                format!(
+                    "Move of {}",
+                    items(moved.len(), moved.first().map(|m| &m.dest))
+                )
+            }
+            FileOperation::Rename { from, to } => {
+                format!("Rename of {} to {}", name(from), name(to))
+            }
+            FileOperation::Trash { paths } => {
+                format!("Moving {} to the trash", items(paths.len(), paths.first()))
+            }
+            FileOperation::Create { path, .. } => format!("Creation of {}", name(path)),
+        }
+    }
+
+    /// Reverses the operation
+    fn undo(&self, handle: &AppHandle, job: &JobHandle) -> Result<(), Box<StepFailure>> {
+        match self {
+            FileOperation::Copy {
+                files,
+                created_dirs,
+            } => {
+                // to the trash rather than gone, in case a copy was edited since
+                self.apply_each(files.len(), false, |i| {
+                    let copy = &files[i].dest;
+                    if fs::symlink_metadata(copy).is_err() {
+                        return Ok(());
+                    }
+                    trash::delete(copy).map_err(|e| {
+                        format!("Failed to move {} to the trash: {}", copy.display(), e)
+                    })
+                })?;
+                remove_empty_dirs(created_dirs);
+                Ok(())
+            }
+            FileOperation::Move {
+                items,
+                created_dirs,
+                removed_dirs,
+            } => {
+                create_dirs(removed_dirs).map_err(|e| self.failed(e))?;
+                self.apply_each(items.len(), true, |i| {
+                    move_path(handle, job, &items[i].dest, &items[i].src)
+                })?;
+                remove_empty_dirs(created_dirs);
+                Ok(())
+            }
+            FileOperation::Rename { from, to } => rename_path(to, from).map_err(|e| self.failed(e)),
+            FileOperation::Trash { paths } => {
+                restore_from_trash(paths).map_err(|(i, e)| self.failed_at(i, false, e))
+            }
+            FileOperation::Create { path, is_dir } => {
+                let meta = fs::symlink_metadata(path).map_err(|e| self.failed(e.to_string()))?;
+                let untouched = if *is_dir {
+                    fs::read_dir(path).is_ok_and(|mut d| d.next().is_none())
+                } else {
+                    meta.len() == 0
+                };
+                match (untouched, is_dir) {
+                    (true, true) => fs::remove_dir(path).map_err(|e| e.to_string()),
+                    (true, false) => fs::remove_file(path).map_err(|e| e.to_string()),
+                    // filled in since; keep the content recoverable
+                    (false, _) => trash::delete(path).map_err(|e| e.to_string()),
+                }
+                .map_err(|e| self.failed(e))
+            }
+        }
+    }
+
+    /// Performs the operation again after it was undone
+    fn redo(&self, handle: &AppHandle, job: &JobHandle) -> Result<(), Box<StepFailure>> {
+        match self {
+            FileOperation::Copy {
+                files,
+                created_dirs,
+            } => {
+                create_dirs(created_dirs).map_err(|e| self.failed(e))?;
+                self.apply_each(files.len(), false, |i| {
+                    let file = &files[i];
+                    if job.is_cancelled() {
+                        return Err("Redo cancelled".into());
+                    }
+                    ensure_free(&file.dest)?;
+                    if let Some(parent) = file.dest.parent() {
+                        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                    }
+                    fs::copy(&file.src, &file.dest)
+                        .map(|_| ())
+                        .map_err(|e| format!("Failed to copy {}: {}", file.src.display(), e))
+                })
+            }
+            FileOperation::Move {
+                items,
+                created_dirs,
+                removed_dirs,
+            } => {
+                create_dirs(created_dirs).map_err(|e| self.failed(e))?;
+                self.apply_each(items.len(), false, |i| {
+                    move_path(handle, job, &items[i].src, &items[i].dest)
+                })?;
+                remove_empty_dirs(removed_dirs);
+                Ok(())
+            }
+            FileOperation::Rename { from, to } => rename_path(from, to).map_err(|e| self.failed(e)),
+            FileOperation::Trash { paths } => self.apply_each(paths.len(), false, |i| {
+                trash::delete(&paths[i]).map_err(|e| format!("Failed to move to trash: {}", e))
+            }),
+            FileOperation::Create { path, is_dir } => {
+                ensure_free(path).map_err(|e| self.failed(e))?;
+                let created = if *is_dir {
+                    fs::create_dir(path)
+                } else {
+                    fs::OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(path)
+                        .map(|_| ())
+                };
+                created
+                    .map_err(|e| self.failed(format!("Failed to create {}: {}", path.display(), e)))
+            }
+        }
+    }
+
+    /// Applies `apply` to each of the operation's `count` items in turn, last to
+    /// first when `reverse`, stopping at the first failure
+    fn apply_each(
+        &self,
+        count: usize,
+        reverse: bool,
+        mut apply: impl FnMut(usize) -> Result<(), String>,
+    ) -> Result<(), Box<StepFailure>> {
+        let order: Vec<usize> = if reverse {
+            (0..count).rev().collect()
+        } else {
+            (0..count).collect()
+        };
+        for i in order {
+            apply(i).map_err(|e| self.failed_at(i, reverse, e))?;
+        }
+        Ok(())
+    }
+
+    /// A failure before any item took effect
+    fn failed(&self, error: String) -> Box<StepFailure> {
+        Box::new(StepFailure {
+            error,
+            done: None,
+            remaining: self.clone(),
+        })
+    }
+
+    /// A failure at item `index`, after the items before it (after it, when going
+    /// in `reverse`) took effect. The folder lists go to both halves, since
+    /// creating folders and removing empty ones is harmless to repeat.
+    fn failed_at(&self, index: usize, reverse: bool, error: String) -> Box<StepFailure> {
+        let is_done = |i: usize| if reverse { i > index } else { i < index };
+        fn split<T: Clone>(items: &[T], is_done: impl Fn(usize) -> bool) -> (Vec<T>, Vec<T>) {
+            let (mut done, mut remaining) = (Vec::new(), Vec::new());
+            for (i, item) in items.iter().enumerate() {
+                if is_done(i) {
+                    done.push(item.clone());
+                } else {
+                    remaining.push(item.clone());
+                }
+            }
+            (done, remaining)
+        }
+
+        let (done, remaining) = match self {
+            FileOperation::Copy {
+                files,
+                created_dirs,
+            } => {
+                let (done, remaining) = split(files, is_done);
+                let half = |files| FileOperation::Copy {
+                    files,
+                    created_dirs: created_dirs.clone(),
+                };
+                (half(done), half(remaining))
+            }
+            FileOperation::Move {
+                items,
+                created_dirs,
+                removed_dirs,
+            } => {
+                let (done, remaining) = split(items, is_done);
+                let half = |items| FileOperation::Move {
+                    items,
+                    created_dirs: created_dirs.clone(),
+                    removed_dirs: removed_dirs.clone(),
+                };
+                (half(done), half(remaining))
+            }
+            FileOperation::Trash { paths } => {
+                let (done, remaining) = split(paths, is_done);
+                (
+                    FileOperation::Trash { paths: done },
+                    FileOperation::Trash { paths: remaining },
+                )
+            }
+            FileOperation::Rename { .. } | FileOperation::Create { .. } => {
+                return self.failed(error)
+            }
+        };
+        Box::new(StepFailure {
+            error,
+            done: (!done.is_empty()).then_some(done),
+            remaining,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            FileOperation::Copy { files, .. } => files.is_empty(),
+            FileOperation::Move { items, .. } => items.is_empty(),
+            FileOperation::Trash { paths } => paths.is_empty(),
+            FileOperation::Rename { .. } | FileOperation::Create { .. } => false,
+        }
+    }
+}
+
+/// An undo or redo that stopped part way: `done` took effect and moves to the
+/// other stack, `remaining` did not and stays where it was
+struct StepFailure {
+    error: String,
+    done: Option<FileOperation>,
+    remaining: FileOperation,
+}
+
+/// The location the trash reports as an item's original path: absolute, with
+/// the symlinks in its parent folders resolved. Taken before trashing the item,
+/// so restoring can find it again.
+pub(crate) fn trash_origin(path: &Path) -> PathBuf {
+    let parent = path.parent().and_then(|p| dunce::canonicalize(p).ok());
+    match (parent, path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn ensure_free(path: &Path) -> Result<(), String> {
+    match fs::symlink_metadata(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        _ => Err(format!("{} already exists", path.display())),
+    }
+}
+
+/// Creates folders, parents first
+fn create_dirs(dirs: &[PathBuf]) -> Result<(), String> {
+    for dir in dirs {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Removes the folders that are empty, deepest first; others are left alone
+fn remove_empty_dirs(dirs: &[PathBuf]) {
+    let mut dirs: Vec<&PathBuf> = dirs.iter().collect();
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+    for dir in dirs {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// Moves `src` to the free path `dest`, copying across volumes as a job
+fn move_path(handle: &AppHandle, job: &JobHandle, src: &Path, dest: &Path) -> Result<(), String> {
+    ensure_free(dest)?;
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    match fs::rename(src, dest) {
+        Ok(()) => Ok(()),
+        Err(e) if !is_cross_device(&e) => Err(format!("Failed to move {}: {}", src.display(), e)),
+        Err(_) => match run_transfer_move(handle, job, src, dest)? {
+            Some(_) => Ok(()),
+            None => Err("Move was cancelled".into()),
+        },
+    }
+}
+
+/// Renames in place, going through a temporary name for case-only changes the
+/// way `rename_item` does
+fn rename_path(from: &Path, to: &Path) -> Result<(), String> {
+    let name = |p: &Path| {
+        p.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let io_error = |e: std::io::Error| format!("Failed to rename {}: {}", from.display(), e);
+    if is_case_only_change(&name(from), &name(to)) && to.exists() {
+        let tmp = from.with_file_name(format!(".{}.dagger-rename", name(from)));
+        fs::rename(from, &tmp).map_err(io_error)?;
+        return fs::rename(&tmp, to).map_err(io_error);
+    }
+    ensure_free(to)?;
+    fs::rename(from, to).map_err(io_error)
+}
+
+/// Puts trashed items back one by one, picking the most recently trashed item
+/// for each path. Fails with the index of the first path it couldn't restore.
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+fn restore_from_trash(paths: &[PathBuf]) -> Result<(), (usize, String)> {
+    let trashed = trash::os_limited::list().map_err(|e| (0, e.to_string()))?;
+    for (i, path) in paths.iter().enumerate() {
+        let item = trashed
+            .iter()
+            .filter(|item| item.original_path() == *path)
+            .max_by_key(|item| item.time_deleted)
+            .ok_or_else(|| (i, format!("{} is no longer in the trash", path.display())))?;
+        ensure_free(path).map_err(|e| (i, e))?;
+        trash::os_limited::restore_all([item.clone()]).map_err(|e| (i, e.to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+fn restore_from_trash(_paths: &[PathBuf]) -> Result<(), (usize, String)> {
+    Err((
+        0,
+        "Restoring from the trash is not supported on this platform".into(),
+    ))
+}
+
+// ===============================
+// Journal
+// ===============================
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+struct UndoStacks {
+    undo: Vec<FileOperation>,
+    redo: Vec<FileOperation>,
+}
+
+/// Undo and redo stacks of file operations, persisted to `undo.json` under app
+/// data so they outlive a restart. Loaded on first use.
+#[derive(Default)]
+pub struct UndoJournal {
+    stacks: Mutex<Option<UndoStacks>>,
+}
+
+/// What Undo and Redo would do next, for menu labels
+#[derive(Serialize, Clone, Debug)]
+pub struct UndoState {
+    pub undo: Option<String>,
+    pub redo: Option<String>,
+}
+
+fn undo_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    fs::create_dir_all(&path).ok();
+    path.push("undo.json");
+    path
+}
+
+impl UndoJournal {
+    /// Runs `change` on the stacks, then saves them and emits `undo-state-changed`
+    /// if it returned true
+    fn update<T>(
+        &self,
+        handle: &AppHandle,
+        change: impl FnOnce(&mut UndoStacks) -> (T, bool),
+    ) -> T {
+        let mut guard = self.stacks.lock().unwrap();
+        let stacks = guard.get_or_insert_with(|| {
+            load_versioned(&undo_path(handle), UNDO_VERSION, |_, _| {}).unwrap_or_default()
+        });
+        let (result, changed) = change(stacks);
+        if changed {
+            let _ = save_versioned(&undo_path(handle), &*stacks, UNDO_VERSION);
+            let _ = handle.emit("undo-state-changed", state_of(stacks));
+        }
+        result
+    }
+
+    /// Records a completed operation, clearing what could be redone
+    pub fn record(&self, handle: &AppHandle, operation: FileOperation) {
+        self.update(handle, |stacks| {
+            stacks.undo.push(operation);
+            if stacks.undo.len() > MAX_UNDO_OPERATIONS {
+                let excess = stacks.undo.len() - MAX_UNDO_OPERATIONS;
+                stacks.undo.drain(..excess);
+            }
+            stacks.redo.clear();
+            ((), true)
+        })
+    }
+
+    pub fn state(&self, handle: &AppHandle) -> UndoState {
+        self.update(handle, |stacks| (state_of(stacks), false))
+    }
+}
+
+fn state_of(stacks: &UndoStacks) -> UndoState {
+    UndoState {
+        undo: stacks.undo.last().map(FileOperation::describe),
+        redo: stacks.redo.last().map(FileOperation::describe),
+    }
+}
+
+/// Records `operation` for undoing, if the journal is managed
+pub fn record_operation(handle: &AppHandle, operation: FileOperation) {
+    if let Some(journal) = handle.try_state::<Arc<UndoJournal>>() {
+        journal.record(handle, operation);
+    }
+}
+
+// ===============================
+// Commands
+// ===============================
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+/// Pops the next operation off one stack, applies its inverse (or replays it) as a
+/// job, and pushes it onto the other stack. When a step fails part way, only the
+/// items that went through move over; the rest go back where they were.
+async fn step(
+    handle: AppHandle,
+    journal: Arc<UndoJournal>,
+    jobs: Arc<JobStreamState>,
+    direction: Direction,
+    request_id: u64,
+) -> Result<String, String> {
+    let operation = journal.update(&handle, |stacks| {
+        let operation = match direction {
+            Direction::Undo => stacks.undo.pop(),
+            Direction::Redo => stacks.redo.pop(),
+        };
+        let changed = operation.is_some();
+        (operation, changed)
+    });
+    let Some(operation) = operation else {
+        return Err(match direction {
+            Direction::Undo => "Nothing to undo".into(),
+            Direction::Redo => "Nothing to redo".into(),
+        });
+    };
+    let description = operation.describe();
+
+    let job = jobs.start(request_id, JobKind::Move);
+    let result = tauri::async_runtime::spawn_blocking({
+        let handle = handle.clone();
+        let operation = operation.clone();
+        let job = job.clone();
+        move || match direction {
+            Direction::Undo => operation.undo(&handle, &job),
+            Direction::Redo => operation.redo(&handle, &job),
+        }
+    })
+    .await
+    .unwrap_or_else(|e| Err(operation.failed(e.to_string())));
+    jobs.finish(request_id);
+
+    let (done, remaining, error) = match result {
+        Ok(()) => (Some(operation.clone()), None, None),
+        Err(failure) => {
+            let StepFailure {
+                error,
+                done,
+                remaining,
+            } = *failure;
+            (done, Some(remaining), Some(error))
+        }
+    };
+    let state = journal.update(&handle, |stacks| {
+        let (from, to) = match direction {
+            Direction::Undo => (&mut stacks.undo, &mut stacks.redo),
+            Direction::Redo => (&mut stacks.redo, &mut stacks.undo),
+        };
+        to.extend(done);
+        from.extend(remaining);
+        (state_of(stacks), true)
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    let event = match direction {
+        Direction::Undo => "operation-undone",
+        Direction::Redo => "operation-redone",
+    };
+    let _ = handle.emit(
+        event,
+        serde_json::json!({
+            "request_id": request_id,
+            "description": description,
+            "operation": operation,
+            "undo": state.undo,
+            "redo": state.redo,
+        }),
+    );
+    Ok(description)
+}
+
+/// Reverses the most recent copy, move, rename, move to trash or creation:
+/// copies go to the trash, moves and renames go back, trashed items are restored
+/// and new empty items are deleted. Moves back across volumes run as a job
+/// (`request_id`, cancellable). Emits `operation-undone` and returns its description.
+#[tauri::command]
+pub async fn undo_last_operation(
+    handle: AppHandle,
+    journal: State<'_, Arc<UndoJournal>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    request_id: u64,
+) -> Result<String, String> {
+    let journal = journal.inner().clone();
+    let jobs = jobs.inner().clone();
+    step(handle, journal, jobs, Direction::Undo, request_id).await
+}
+
+/// Replays the most recently undone operation. Emits `operation-redone` and
+/// returns its description.
+#[tauri::command]
+pub async fn redo_last_operation(
+    handle: AppHandle,
+    journal: State<'_, Arc<UndoJournal>>,
+    jobs: State<'_, Arc<JobStreamState>>,
+    request_id: u64,
+) -> Result<String, String> {
+    let journal = journal.inner().clone();
+    let jobs = jobs.inner().clone();
+    step(handle, journal, jobs, Direction::Redo, request_id).await
+}
+
+/// Labels of the operations Undo and Redo would act on next
+#[tauri::command]
+pub fn get_undo_state(handle: AppHandle, journal: State<'_, Arc<UndoJournal>>) -> UndoState {
+    journal.state(&handle)
+}
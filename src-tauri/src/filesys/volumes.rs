@@ -0,0 +1,908 @@
+use serde::Serialize;
+use std::{collections::BTreeMap, path::Path, sync::Mutex};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::links::LinkInfo;
+use crate::filesys::nav::FileItem;
+use crate::util::caches::SharedHomeCache;
+
+/// Broad classification of a mounted volume, used for sidebar icons.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeKind {
+    Fixed,
+    Removable,
+    Network,
+    Optical,
+    RamDisk,
+    Unknown,
+}
+
+/// Full-volume encryption schemes Dagger can recognise
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptionScheme {
+    BitLocker,
+    Luks,
+    FileVault,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct VolumeEncryption {
+    pub scheme: EncryptionScheme,
+    /// Encrypted and not yet unlocked; contents can't be listed until `unlock_volume`
+    pub locked: bool,
+}
+
+/// A mounted volume (drive letter on Windows, mount point elsewhere).
+#[derive(Serialize, Clone, Debug)]
+pub struct VolumeInfo {
+    /// Display name, e.g. "Local Disk (C:)"
+    pub name: String,
+    /// Root path of the volume, e.g. "C:\" or "/media/usb"
+    pub path: String,
+    pub label: String,
+    pub filesystem: String,
+    pub kind: VolumeKind,
+    pub total_bytes: Option<u64>,
+    pub free_bytes: Option<u64>,
+    /// Only filled in by `list_volumes`; probing encryption is too slow for hot paths
+    pub encryption: Option<VolumeEncryption>,
+}
+
+/// Lists every mounted volume on the system, with encryption state.
+/// Locked LUKS devices, which have no mount point yet, are listed by device path.
+#[tauri::command]
+pub fn list_volumes() -> Result<Vec<VolumeInfo>, String> {
+    let mut volumes = enumerate_volumes();
+    for volume in &mut volumes {
+        volume.encryption = volume_encryption(&volume.path);
+    }
+    #[cfg(target_os = "linux")]
+    volumes.extend(locked_luks_devices());
+    Ok(volumes)
+}
+
+#[cfg(target_os = "windows")]
+fn volume_encryption(root: &str) -> Option<VolumeEncryption> {
+    use crate::filesys::os::windows::bitlocker_protection;
+
+    // System.Volume.BitLockerProtection: 1 on, 2 off, 3 encrypting, 4 decrypting,
+    // 5 suspended, 6 locked, 8 waiting for activation
+    let locked = match bitlocker_protection(root)? {
+        1 | 3 | 4 | 5 | 8 => false,
+        6 => true,
+        _ => return None,
+    };
+    Some(VolumeEncryption {
+        scheme: EncryptionScheme::BitLocker,
+        locked,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn volume_encryption(mount_point: &str) -> Option<VolumeEncryption> {
+    // dm-crypt mappings carry a CRYPT-LUKS* uuid: /dev/mapper/x -> /dev/dm-N -> sysfs
+    let device = std::fs::canonicalize(mount_device(mount_point)?).ok()?;
+    let name = device.file_name()?.to_string_lossy().to_string();
+    let uuid = std::fs::read_to_string(format!("/sys/class/block/{}/dm/uuid", name)).ok()?;
+    uuid.starts_with("CRYPT-LUKS").then_some(VolumeEncryption {
+        scheme: EncryptionScheme::Luks,
+        locked: false,
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn volume_encryption(mount_point: &str) -> Option<VolumeEncryption> {
+    let output = std::process::Command::new("diskutil")
+        .args(["info", mount_point])
+        .output()
+        .ok()?;
+    let encrypted = String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+        let line = line.trim();
+        line.starts_with("FileVault:") && line.ends_with("Yes")
+    });
+    encrypted.then_some(VolumeEncryption {
+        scheme: EncryptionScheme::FileVault,
+        locked: false,
+    })
+}
+
+/// The block device mounted at `mount_point`, from /proc/mounts
+#[cfg(target_os = "linux")]
+pub fn mount_device(mount_point: &str) -> Option<String> {
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let device = fields.next()?;
+        let mount = fields.next()?.replace("\\040", " ").replace("\\011", "\t");
+        (Path::new(&mount) == Path::new(mount_point)).then(|| device.to_string())
+    })
+}
+
+/// LUKS containers that haven't been opened, i.e. have no mapped child device
+#[cfg(target_os = "linux")]
+fn locked_luks_devices() -> Vec<VolumeInfo> {
+    fn collect(node: &serde_json::Value, out: &mut Vec<VolumeInfo>) {
+        let opened = node["children"].as_array().is_some_and(|c| !c.is_empty());
+        if node["fstype"].as_str() == Some("crypto_LUKS") && !opened {
+            let path = node["name"].as_str().unwrap_or_default().to_string();
+            let label = node["label"].as_str().unwrap_or_default().to_string();
+            out.push(VolumeInfo {
+                name: if label.is_empty() {
+                    "Encrypted Volume".to_string()
+                } else {
+                    label.clone()
+                },
+                path,
+                label,
+                filesystem: "crypto_LUKS".to_string(),
+                kind: if node["rm"].as_bool().unwrap_or(false) {
+                    VolumeKind::Removable
+                } else {
+                    VolumeKind::Fixed
+                },
+                total_bytes: node["size"].as_u64(),
+                free_bytes: None,
+                encryption: Some(VolumeEncryption {
+                    scheme: EncryptionScheme::Luks,
+                    locked: true,
+                }),
+            });
+        }
+        for child in node["children"].as_array().into_iter().flatten() {
+            collect(child, out);
+        }
+    }
+
+    let Ok(output) = std::process::Command::new("lsblk")
+        .args(["-J", "-b", "-p", "-o", "NAME,FSTYPE,LABEL,SIZE,RM"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(&output.stdout) else {
+        return Vec::new();
+    };
+
+    let mut locked = Vec::new();
+    for device in json["blockdevices"].as_array().into_iter().flatten() {
+        collect(device, &mut locked);
+    }
+    locked
+}
+
+/// The locked volume `path` belongs to, if any. Lets callers report "locked"
+/// instead of a generic access error.
+pub fn locked_volume_for(path: &str) -> Option<VolumeInfo> {
+    let target = Path::new(path);
+    let volume = enumerate_volumes()
+        .into_iter()
+        .filter(|v| target.starts_with(&v.path))
+        .max_by_key(|v| v.path.len())?;
+    let encryption = volume_encryption(&volume.path)?;
+    encryption.locked.then_some(VolumeInfo {
+        encryption: Some(encryption),
+        ..volume
+    })
+}
+
+/// Starts unlocking an encrypted volume.
+/// - Windows: opens the drive in Explorer, which shows the system BitLocker prompt
+/// - Linux: unlocks the LUKS device with `passphrase` through udisks and mounts it
+///
+/// Returns the new mount point when it is known right away. Emits `volume-unlocked`.
+#[tauri::command]
+pub async fn unlock_volume(
+    handle: AppHandle,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<Option<String>, String> {
+    let mount_point = tauri::async_runtime::spawn_blocking(move || unlock(&path, passphrase))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let _ = handle.emit(
+        "volume-unlocked",
+        serde_json::json!({ "mount_point": mount_point }),
+    );
+    Ok(mount_point)
+}
+
+#[cfg(target_os = "windows")]
+fn unlock(path: &str, _passphrase: Option<String>) -> Result<Option<String>, String> {
+    // BitLocker unlock needs admin through the APIs; Explorer's prompt does not
+    std::process::Command::new("explorer")
+        .arg(path)
+        .spawn()
+        .map_err(|e| format!("Failed to open unlock prompt: {}", e))?;
+    Ok(None)
+}
+
+#[cfg(target_os = "linux")]
+fn unlock(path: &str, passphrase: Option<String>) -> Result<Option<String>, String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let passphrase = passphrase.ok_or("A passphrase is required to unlock this volume")?;
+    let mut child = Command::new("udisksctl")
+        .args(["unlock", "--block-device", path, "--key-file", "/dev/stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run udisksctl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(passphrase.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    // "Unlocked /dev/sdb1 as /dev/dm-3."
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some(mapped) = stdout
+        .split(" as ")
+        .nth(1)
+        .map(|s| s.trim().trim_end_matches('.').to_string())
+    else {
+        return Ok(None);
+    };
+
+    // "Mounted /dev/dm-3 at /run/media/user/label"
+    let output = Command::new("udisksctl")
+        .args(["mount", "--block-device", &mapped])
+        .output()
+        .map_err(|e| format!("Failed to run udisksctl: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split(" at ")
+        .nth(1)
+        .map(|s| s.trim().trim_end_matches('.').to_string()))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn unlock(_path: &str, _passphrase: Option<String>) -> Result<Option<String>, String> {
+    Err("Unlocking volumes from Dagger is not supported on this platform".into())
+}
+
+/// Disk image formats `mount_image` accepts
+const IMAGE_EXTENSIONS: [&str; 4] = ["iso", "img", "vhd", "vhdx"];
+
+/// Mounts an ISO, IMG or VHD(X) image so its contents can be browsed like a drive.
+/// - Windows: `Mount-DiskImage`, which assigns a drive letter
+/// - Linux: a udisks loop device, mounted under /run/media (or /media)
+///
+/// Returns the mount point and emits `volume-added` with it and the new volume.
+#[tauri::command]
+pub async fn mount_image(handle: AppHandle, path: String) -> Result<String, String> {
+    let supported = Path::new(&path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.as_str()));
+    if !supported {
+        return Err(format!("{} is not a disk image", path));
+    }
+
+    let image = path.clone();
+    let (mount_point, volume) = tauri::async_runtime::spawn_blocking(move || {
+        let mount_point = mount(&image)?;
+        let volume = volume_for(Path::new(&mount_point));
+        Ok::<_, String>((mount_point, volume))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let _ = handle.emit(
+        "volume-added",
+        serde_json::json!({ "image": path, "mount_point": mount_point, "volume": volume }),
+    );
+    Ok(mount_point)
+}
+
+/// Unmounts an image mounted by `mount_image` (or by the system) and releases it.
+/// Emits `volume-removed`.
+#[tauri::command]
+pub async fn unmount_image(handle: AppHandle, path: String) -> Result<(), String> {
+    let image = path.clone();
+    tauri::async_runtime::spawn_blocking(move || unmount(&image))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let _ = handle.emit("volume-removed", serde_json::json!({ "image": path }));
+    Ok(())
+}
+
+/// Runs a PowerShell snippet and returns its trimmed output
+#[cfg(target_os = "windows")]
+fn run_powershell(script: &str) -> Result<String, String> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// `path` as a single-quoted PowerShell string
+#[cfg(target_os = "windows")]
+fn ps_quote(path: &str) -> String {
+    format!("'{}'", path.replace('\'', "''"))
+}
+
+#[cfg(target_os = "windows")]
+fn mount(path: &str) -> Result<String, String> {
+    // raw .img files mount as optical images, like Explorer's "Mount"
+    let is_img = path.to_lowercase().ends_with(".img");
+    let script = format!(
+        "$i = Mount-DiskImage -ImagePath {} {} -PassThru; \
+         ($i | Get-Volume | Where-Object DriveLetter | Select-Object -First 1).DriveLetter",
+        ps_quote(path),
+        if is_img { "-StorageType ISO" } else { "" }
+    );
+    let letter = run_powershell(&script)?;
+    match letter.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => Ok(format!("{}:\\", c)),
+        _ => Err("The image was mounted but has no readable volume".into()),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn unmount(path: &str) -> Result<(), String> {
+    run_powershell(&format!(
+        "Dismount-DiskImage -ImagePath {} | Out-Null",
+        ps_quote(path)
+    ))
+    .map(|_| ())
+}
+
+/// Runs udisksctl and returns its output, e.g. "Mounted /dev/loop0 at /media/x."
+#[cfg(target_os = "linux")]
+fn udisksctl(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("udisksctl")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run udisksctl: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Loop devices backed by `path`, from /sys/block
+#[cfg(target_os = "linux")]
+fn loop_devices_for(path: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/sys/block") else {
+        return Vec::new();
+    };
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("loop"))
+        .filter(|e| {
+            std::fs::read_to_string(e.path().join("loop/backing_file"))
+                .is_ok_and(|backing| Path::new(backing.trim()) == path)
+        })
+        .map(|e| format!("/dev/{}", e.file_name().to_string_lossy()))
+        .collect()
+}
+
+/// Mounted block devices and their mount points, from /proc/mounts
+#[cfg(target_os = "linux")]
+fn mounted_devices() -> Vec<(String, String)> {
+    std::fs::read_to_string("/proc/mounts")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?.to_string();
+            let mount = fields.next()?.replace("\\040", " ").replace("\\011", "\t");
+            Some((device, mount))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn mount(path: &str) -> Result<String, String> {
+    // "Mapped file /x.iso as /dev/loop0."
+    let mapped = udisksctl(&["loop-setup", "--read-only", "--file", path])?;
+    let device = mapped
+        .split(" as ")
+        .nth(1)
+        .map(|s| s.trim().trim_end_matches('.').to_string())
+        .ok_or_else(|| format!("Unexpected udisksctl output: {}", mapped))?;
+
+    // desktops often auto-mount the new device (or its first partition) right away
+    let already = |devices: &[(String, String)]| {
+        devices
+            .iter()
+            .find(|(d, _)| d == &device || d.starts_with(&format!("{}p", device)))
+            .map(|(_, m)| m.clone())
+    };
+    if let Some(mount_point) = already(&mounted_devices()) {
+        return Ok(mount_point);
+    }
+
+    // whole-disk images keep their filesystem on the first partition
+    let partition = format!("{}p1", device);
+    let target = if Path::new(&partition).exists() {
+        partition
+    } else {
+        device.clone()
+    };
+    match udisksctl(&["mount", "--block-device", &target]) {
+        Ok(mounted) => mounted
+            .split(" at ")
+            .nth(1)
+            .map(|s| s.trim().trim_end_matches('.').to_string())
+            .ok_or_else(|| format!("Unexpected udisksctl output: {}", mounted)),
+        Err(e) => {
+            // an auto-mount may have won the race
+            if let Some(mount_point) = already(&mounted_devices()) {
+                return Ok(mount_point);
+            }
+            let _ = udisksctl(&["loop-delete", "--block-device", &device]);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn unmount(path: &str) -> Result<(), String> {
+    let loops = loop_devices_for(Path::new(path));
+    if loops.is_empty() {
+        return Err(format!("{} is not mounted", path));
+    }
+    let mounted = mounted_devices();
+    for device in &loops {
+        let partition_prefix = format!("{}p", device);
+        for (mounted_device, _) in mounted
+            .iter()
+            .filter(|(d, _)| d == device || d.starts_with(&partition_prefix))
+        {
+            udisksctl(&["unmount", "--block-device", mounted_device])?;
+        }
+        udisksctl(&["loop-delete", "--block-device", device])?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn mount(_path: &str) -> Result<String, String> {
+    Err("Mounting disk images from Dagger is not supported on this platform".into())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn unmount(_path: &str) -> Result<(), String> {
+    Err("Unmounting disk images from Dagger is not supported on this platform".into())
+}
+
+/// Whether `path` lives on a spinning disk, where concurrent reads thrash the heads.
+/// Unknown devices (and every Mac, which ship with SSDs) count as non-rotational.
+#[cfg(target_os = "windows")]
+pub fn is_rotational(path: &Path) -> bool {
+    let mut chars = path.to_str().unwrap_or_default().chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) => {
+            crate::filesys::os::windows::incurs_seek_penalty(letter).unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn is_rotational(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(meta) = std::fs::metadata(path) else {
+        return false;
+    };
+    let dev = meta.dev();
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    // partitions have no queue of their own; their parent disk does
+    let device = format!("/sys/dev/block/{}:{}", major, minor);
+    [
+        format!("{}/queue/rotational", device),
+        format!("{}/../queue/rotational", device),
+    ]
+    .iter()
+    .find_map(|p| std::fs::read_to_string(p).ok())
+    .is_some_and(|flag| flag.trim() == "1")
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn is_rotational(_path: &Path) -> bool {
+    false
+}
+
+/// Whether `path` lives on a network share: UNC paths, mapped network drives
+/// and remote mounts such as NFS/SMB/sshfs.
+pub fn is_network_path(path: &str) -> bool {
+    // \\server\share or //server/share, but not the \\?\ local long-path prefix
+    let unc = (path.starts_with(r"\\") && !path.starts_with(r"\\?\"))
+        || path.starts_with(r"\\?\UNC\")
+        || (cfg!(windows) && path.starts_with("//"));
+    if unc {
+        return true;
+    }
+
+    volume_for(Path::new(path)).is_some_and(|v| v.kind == VolumeKind::Network)
+}
+
+/// The mounted volume holding `path` (the deepest mount point containing it)
+pub fn volume_for(path: &Path) -> Option<VolumeInfo> {
+    enumerate_volumes()
+        .into_iter()
+        .filter(|v| path.starts_with(&v.path))
+        .max_by_key(|v| v.path.len())
+}
+
+/// Largest file FAT12/16/32 can hold
+const FAT_MAX_FILE_SIZE: u64 = 4 * 1024 * 1024 * 1024 - 1;
+
+/// What a volume's filesystem can store, so copies and renames can warn before
+/// something gets silently lost (e.g. a 5 GB file or a symlink going to a FAT stick)
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct VolumeCapabilities {
+    /// Root of the volume the probe resolved to
+    pub root: String,
+    pub filesystem: String,
+    pub case_sensitive: bool,
+    pub supports_symlinks: bool,
+    /// NTFS alternate data streams, e.g. the Zone.Identifier download marker
+    pub supports_streams: bool,
+    pub supports_compression: bool,
+    /// Longest single file or folder name
+    pub max_name_length: usize,
+    /// Longest full path the platform handles without special prefixes
+    pub max_path_length: usize,
+    /// Largest single file, when the filesystem caps it
+    pub max_file_size: Option<u64>,
+    /// Rejects the characters and device names Windows reserves, even when
+    /// mounted on Linux or macOS
+    pub windows_names: bool,
+}
+
+/// Capabilities already probed, keyed by volume root and filesystem
+static CAPABILITY_CACHE: Mutex<BTreeMap<(String, String), VolumeCapabilities>> =
+    Mutex::new(BTreeMap::new());
+
+/// Capabilities implied by the filesystem name alone
+fn capabilities_for_filesystem(root: &str, filesystem: &str) -> VolumeCapabilities {
+    let fs = filesystem.to_lowercase();
+    let fat = matches!(
+        fs.as_str(),
+        "fat" | "fat12" | "fat16" | "fat32" | "vfat" | "msdos"
+    );
+    let exfat = fs == "exfat";
+    let ntfs = matches!(fs.as_str(), "ntfs" | "ntfs3" | "fuseblk" | "refs");
+    let folds_case = fat
+        || exfat
+        || ntfs
+        || matches!(fs.as_str(), "apfs" | "hfs" | "hfsplus")
+        || cfg!(any(windows, target_os = "macos"));
+
+    VolumeCapabilities {
+        root: root.to_string(),
+        filesystem: filesystem.to_string(),
+        // NTFS can be case-sensitive per folder, but defaults to insensitive
+        case_sensitive: !folds_case,
+        supports_symlinks: !(fat || exfat),
+        supports_streams: fs == "ntfs" || fs == "refs",
+        supports_compression: matches!(fs.as_str(), "ntfs" | "btrfs" | "zfs" | "apfs"),
+        max_name_length: 255,
+        max_path_length: if cfg!(windows) {
+            260
+        } else if cfg!(target_os = "macos") {
+            1024
+        } else {
+            4096
+        },
+        max_file_size: fat.then_some(FAT_MAX_FILE_SIZE),
+        windows_names: cfg!(windows) || fat || exfat || ntfs,
+    }
+}
+
+/// Creates a lowercase probe file in `dir` and checks whether its uppercase
+/// name resolves to it. `None` when `dir` is not writable.
+fn probe_case_sensitivity(dir: &Path) -> Option<bool> {
+    let nonce = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let lower = dir.join(format!(".dagger-case-probe-{:x}", nonce));
+    let upper = dir.join(format!(".DAGGER-CASE-PROBE-{:X}", nonce));
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&lower)
+        .ok()?;
+    let sensitive = !upper.exists();
+    let _ = std::fs::remove_file(&lower);
+    Some(sensitive)
+}
+
+/// Probes the filesystem `path` lives on. Results are cached per volume; the
+/// case sensitivity probe writes a temporary file into `path` (or its parent)
+/// when the volume is first seen.
+pub fn volume_capabilities(path: &Path) -> VolumeCapabilities {
+    let volume = volume_for(path);
+    let (root, filesystem) = volume
+        .as_ref()
+        .map(|v| (v.path.clone(), v.filesystem.clone()))
+        .unwrap_or_default();
+    let key = (root.clone(), filesystem.clone());
+    if let Some(cached) = CAPABILITY_CACHE.lock().unwrap().get(&key) {
+        return cached.clone();
+    }
+
+    let mut caps = capabilities_for_filesystem(&root, &filesystem);
+
+    #[cfg(target_os = "windows")]
+    if let Some(features) = crate::filesys::os::windows::volume_features(&root) {
+        caps.supports_symlinks = features.symlinks;
+        caps.supports_streams = features.named_streams;
+        caps.supports_compression = features.compression;
+        if features.max_component_length > 0 {
+            caps.max_name_length = features.max_component_length as usize;
+        }
+    }
+
+    // NTFS (per-folder flag), network shares and unnamed filesystems can go either way
+    let fixed_case = matches!(
+        caps.filesystem.to_lowercase().as_str(),
+        "fat"
+            | "fat12"
+            | "fat16"
+            | "fat32"
+            | "vfat"
+            | "msdos"
+            | "exfat"
+            | "ext2"
+            | "ext3"
+            | "ext4"
+            | "xfs"
+            | "btrfs"
+            | "zfs"
+            | "f2fs"
+    );
+    if !fixed_case {
+        let probe_dir = if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent()
+        };
+        if let Some(sensitive) = probe_dir.and_then(probe_case_sensitivity) {
+            caps.case_sensitive = sensitive;
+        }
+    }
+
+    CAPABILITY_CACHE.lock().unwrap().insert(key, caps.clone());
+    caps
+}
+
+/// Reports what the filesystem under `path` supports, for warnings in the UI
+#[tauri::command]
+pub async fn get_volume_capabilities(path: String) -> Result<VolumeCapabilities, String> {
+    tauri::async_runtime::spawn_blocking(move || volume_capabilities(Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Adds a remote/network location (UNC share, mounted remote path) to the tree roots.
+#[tauri::command]
+pub async fn add_network_location(
+    handle: AppHandle,
+    state: State<'_, SharedHomeCache>,
+    path: String,
+) -> Result<(), String> {
+    let path = path.trim().to_string();
+    if path.is_empty() {
+        return Err("Path is empty".into());
+    }
+
+    let name = Path::new(&path)
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    {
+        let mut cache = state.0.write().await;
+        cache.network_locations.retain(|x| x.path != path);
+        cache.network_locations.push(FileItem {
+            name,
+            path,
+            is_dir: true,
+            size: None,
+            link: LinkInfo::default(),
+        });
+    }
+
+    state.save(&handle).await;
+    Ok(())
+}
+
+/// Removes a previously added remote/network location.
+#[tauri::command]
+pub async fn remove_network_location(
+    handle: AppHandle,
+    state: State<'_, SharedHomeCache>,
+    path: String,
+) -> Result<(), String> {
+    {
+        let mut cache = state.0.write().await;
+        cache.network_locations.retain(|x| x.path != path);
+    }
+
+    state.save(&handle).await;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub fn enumerate_volumes() -> Vec<VolumeInfo> {
+    use crate::filesys::os::windows::list_logical_drives;
+
+    list_logical_drives()
+        .into_iter()
+        .map(|drive| {
+            // GetDriveTypeW: 2=REMOVABLE, 3=FIXED, 4=REMOTE, 5=CDROM, 6=RAMDISK
+            let kind = match drive.drive_type {
+                2 => VolumeKind::Removable,
+                3 => VolumeKind::Fixed,
+                4 => VolumeKind::Network,
+                5 => VolumeKind::Optical,
+                6 => VolumeKind::RamDisk,
+                _ => VolumeKind::Unknown,
+            };
+
+            let letter = drive.root.trim_end_matches('\\');
+            let display = if !drive.label.is_empty() {
+                drive.label.clone()
+            } else {
+                match kind {
+                    VolumeKind::Fixed => "Local Disk".to_string(),
+                    VolumeKind::Removable => "Removable Disk".to_string(),
+                    VolumeKind::Network => "Network Drive".to_string(),
+                    VolumeKind::Optical => "DVD Drive".to_string(),
+                    _ => "Drive".to_string(),
+                }
+            };
+
+            VolumeInfo {
+                name: format!("{} ({})", display, letter),
+                path: drive.root,
+                label: drive.label,
+                filesystem: drive.filesystem,
+                kind,
+                total_bytes: drive.total_bytes,
+                free_bytes: drive.free_bytes,
+                encryption: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+pub fn enumerate_volumes() -> Vec<VolumeInfo> {
+    const NETWORK_FS: [&str; 7] = [
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb3",
+        "smbfs",
+        "sshfs",
+        "fuse.sshfs",
+    ];
+    const SKIPPED_PREFIXES: [&str; 6] = ["/proc", "/sys", "/dev", "/run", "/snap", "/boot"];
+
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let mut volumes = Vec::new();
+
+    for line in mounts.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 {
+            continue;
+        }
+        let (device, mount_point, fstype) = (fields[0], fields[1], fields[2]);
+
+        // /proc/mounts escapes spaces and tabs as octal sequences
+        let mount_point = mount_point.replace("\\040", " ").replace("\\011", "\t");
+
+        let is_network = NETWORK_FS.contains(&fstype);
+        if !device.starts_with("/dev/") && !is_network {
+            continue;
+        }
+        let removable_mount =
+            mount_point.starts_with("/run/media") || mount_point.starts_with("/media");
+        if !removable_mount
+            && SKIPPED_PREFIXES
+                .iter()
+                .any(|p| mount_point == *p || mount_point.starts_with(&format!("{}/", p)))
+        {
+            continue;
+        }
+        if volumes.iter().any(|v: &VolumeInfo| v.path == mount_point) {
+            continue;
+        }
+
+        let kind = if is_network {
+            VolumeKind::Network
+        } else if removable_mount {
+            VolumeKind::Removable
+        } else {
+            VolumeKind::Fixed
+        };
+
+        let label = Path::new(&mount_point)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = if mount_point == "/" {
+            "File System".to_string()
+        } else {
+            label.clone()
+        };
+
+        volumes.push(VolumeInfo {
+            name,
+            path: mount_point,
+            label,
+            filesystem: fstype.to_string(),
+            kind,
+            total_bytes: None,
+            free_bytes: None,
+            encryption: None,
+        });
+    }
+
+    volumes
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn enumerate_volumes() -> Vec<VolumeInfo> {
+    let mut volumes = vec![VolumeInfo {
+        name: "Macintosh HD".into(),
+        path: "/".into(),
+        label: String::new(),
+        filesystem: String::new(),
+        kind: VolumeKind::Fixed,
+        total_bytes: None,
+        free_bytes: None,
+        encryption: None,
+    }];
+
+    // Additional volumes are mounted under /Volumes (the boot volume links back to /)
+    if let Ok(entries) = std::fs::read_dir("/Volumes") {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.read_link().is_ok() {
+                continue;
+            }
+            let label = entry.file_name().to_string_lossy().to_string();
+            volumes.push(VolumeInfo {
+                name: label.clone(),
+                path: path.to_string_lossy().to_string(),
+                label,
+                filesystem: String::new(),
+                kind: VolumeKind::Removable,
+                total_bytes: None,
+                free_bytes: None,
+                encryption: None,
+            });
+        }
+    }
+
+    volumes
+}
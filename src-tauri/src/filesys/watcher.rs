@@ -21,7 +21,7 @@ pub fn start_file_watcher(app: &AppHandle, paths: Vec<String>) -> SharedWatcher
                         let _ =
                             handle.emit("file-change", serde_json::json!({ "paths": event.paths }));
                     }
-                    Err(err) => eprintln!("watch error: {:?}", err),
+                    Err(err) => tracing::warn!(target: "watcher", "watch error: {:?}", err),
                 }
             },
             notify::Config::default(),
@@ -30,7 +30,7 @@ pub fn start_file_watcher(app: &AppHandle, paths: Vec<String>) -> SharedWatcher
 
         for path in &paths {
             if let Err(e) = watcher_inner.watch(Path::new(path), RecursiveMode::Recursive) {
-                eprintln!("Failed to watch {}: {:?}", path, e);
+                tracing::warn!(target: "watcher", "Failed to watch {}: {:?}", path, e);
             }
         }
 
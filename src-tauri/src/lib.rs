@@ -1,26 +1,107 @@
 use std::sync::Arc;
 
-use rayon::ThreadPoolBuilder;
-
 pub mod filesys;
 pub mod search;
 pub mod util;
 
 use crate::{
     filesys::{
+        actions::{
+            create_new_directory, create_new_file, delete_item, move_item, move_to_trash,
+            rename_item,
+        },
+        adb::{adb_pull, adb_push, list_adb_devices, stream_adb_directory},
+        attributes::{set_attributes, set_timestamps, shift_timestamps},
+        cloud::set_cloud_availability,
+        crypt::{decrypt_file, encrypt_files},
+        export::export_listing,
+        hash::request_checksums,
+        health::get_drive_health,
+        history::{
+            get_history_config, list_file_versions, restore_file_version, update_history_config,
+        },
+        names::validate_filename,
         nav::{
-            get_tree_from_root, is_directory, list_directory_contents, open_from_path, resolve_user,
+            get_tree_children, get_tree_from_root, is_directory, list_directory_contents,
+            open_containing_folder, open_from_path, resolve_user, TreeCache,
         },
+        remote::upload_folder,
+        rules::{get_rules, run_rules_on_folder, update_rules},
+        selection::get_selection_summary,
+        shadow::{list_previous_versions, restore_previous_version},
+        snapshot::{create_snapshot, delete_snapshot, diff_snapshot, list_snapshots},
+        storage::analyze_storage,
         stream::{
-            copy_items_to_clipboard, cut_items_to_clipboard, paste_items_from_clipboard,
-            resolve_copy_conflict, stream_directory_contents, CopyStreamState, FileStreamState,
+            cancel_directory_stream, cancel_job, copy_item, copy_items_to_clipboard, copy_to,
+            cut_items_to_clipboard, delete_items, discard_interrupted_transfer,
+            discard_mirror_preview, export_thumbnails, get_animated_preview, get_clipboard_history,
+            get_clipboard_state, get_delete_summary, get_preview_rule, get_undo_state,
+            list_interrupted_transfers, move_to, paste_from_history, paste_item_from_paths,
+            paste_items_from_clipboard, pause_paste_stream, preview_mirror, redo_last_operation,
+            resolve_copy_conflict, resolve_job_error, resume_interrupted_transfer,
+            resume_paste_stream, rollback_interrupted_transfer, run_mirror, secure_delete,
+            serve_thumb, stream_directory_contents, undo_last_operation, ClipboardHistory,
+            CopyStreamState, FileStreamState, JobStreamState, MirrorState, UndoJournal,
+        },
+        volumes::{
+            add_network_location, get_volume_capabilities, list_volumes, mount_image,
+            remove_network_location, unlock_volume, unmount_image,
+        },
+    },
+    search::{
+        document::preview_document,
+        email::{extract_email_attachments, preview_email},
+        engine::{cancel_search, search_files, SearchState},
+        modals::{upload_audio_file, upload_document_file, upload_image_file},
+        providers::list_search_providers,
+        scopes::{delete_search_scope, get_search_scopes, save_search_scope},
+        smart::{
+            close_smart_folder, delete_smart_folder, get_smart_folders, open_smart_folder,
+            save_smart_folder, SmartFolderState,
         },
     },
-    search::modals::{upload_audio_file, upload_document_file, upload_image_file},
     util::{
-        caches::{fetch_layout_settings, update_layout_settings},
+        activity::{clear_activity_log, get_activity_log},
+        appearance::get_system_appearance,
+        associations::{get_association, set_association},
+        bookmarks::{get_bookmark_slots, goto_slot, set_bookmark_slot},
+        caches::{
+            fetch_layout_settings, get_settings,
+            home::{
+                add_home_workspace, get_home_layout, remove_home_workspace, reorder_home_sections,
+                set_home_recents_limit, set_home_section_visible,
+            },
+            update_layout_settings, update_settings,
+        },
         cmd::{resolve_path_command, resolve_quick_access},
-        setup::{open_window, setup_app_environment, window_event_handler},
+        custom_actions::{
+            execute_custom_action, get_custom_actions, get_custom_actions_for,
+            set_custom_action_enabled, update_custom_actions,
+        },
+        diagnostics::get_diagnostics,
+        editors::{list_editors, open_in_editor, set_default_editor},
+        hooks::{get_hooks, set_hook_enabled, update_hooks},
+        icon_packs::list_icon_packs,
+        keybindings::{get_keybindings, set_keybinding},
+        logging::{get_recent_logs, set_log_level},
+        plugins::{
+            get_plugin_actions, get_plugin_preview, install_plugin, list_plugins, reload_plugins,
+            run_plugin_action, set_plugin_enabled, uninstall_plugin,
+        },
+        power::get_power_state,
+        profile::{export_profile, import_profile},
+        setup::{
+            launch_folder, setup_app_environment, show_window_at, take_pending_open_path,
+            window_event_handler,
+        },
+        shell_integration::{
+            get_default_file_manager, get_shell_integration, install_shell_integration,
+            set_default_file_manager, uninstall_shell_integration,
+        },
+        tags::{
+            delete_tag, find_by_tag, get_file_properties, get_tags_for_path, list_tags,
+            set_comment, set_rating, set_tag_color, tag_paths, untag_paths,
+        },
     },
 };
 
@@ -28,41 +109,213 @@ use crate::{
 pub fn run() {
     let file_stream_state = Arc::new(FileStreamState::default());
     let copy_stream_state = Arc::new(CopyStreamState::new());
-    let rayon_thread_pool = Arc::new(ThreadPoolBuilder::new().num_threads(8).build().unwrap());
+    let job_stream_state = Arc::new(JobStreamState::default());
+    let tree_cache = Arc::new(TreeCache::default());
 
     tauri::Builder::default()
         // Single instance hook: any subsequent launch triggers window creation
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            // If window exists, show it
-            open_window(app);
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // If window exists, show it, opening any folder passed by a Jump List entry
+            show_window_at(app, launch_folder(&args));
         }))
+        .plugin(tauri_plugin_notification::init())
         // Managed state
         .manage(file_stream_state)
         .manage(copy_stream_state)
-        .manage(rayon_thread_pool)
+        .manage(Arc::new(UndoJournal::default()))
+        .manage(job_stream_state)
+        .manage(Arc::new(MirrorState::default()))
+        .manage(tree_cache)
+        .manage(ClipboardHistory::default())
+        .manage(Arc::new(SearchState::default()))
+        .manage(Arc::new(SmartFolderState::default()))
+        // Cached thumbnails by thumb DB hash: thumb://localhost/<hash>
+        .register_asynchronous_uri_scheme_protocol("thumb", |ctx, request, responder| {
+            let handle = ctx.app_handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(serve_thumb(&handle, &request));
+            });
+        })
         // Invoke handlers
         .invoke_handler(tauri::generate_handler![
             // modals
             upload_image_file,
             upload_audio_file,
             upload_document_file,
+            // search
+            search_files,
+            cancel_search,
+            list_search_providers,
+            get_search_scopes,
+            save_search_scope,
+            delete_search_scope,
+            get_smart_folders,
+            save_smart_folder,
+            delete_smart_folder,
+            open_smart_folder,
+            close_smart_folder,
+            preview_document,
+            preview_email,
+            extract_email_attachments,
+            export_listing,
             // filesys
             get_tree_from_root,
+            get_tree_children,
             resolve_user,
             open_from_path,
+            open_containing_folder,
             list_directory_contents,
             is_directory,
+            list_volumes,
+            get_volume_capabilities,
+            unlock_volume,
+            mount_image,
+            unmount_image,
+            analyze_storage,
+            get_selection_summary,
+            create_snapshot,
+            list_snapshots,
+            delete_snapshot,
+            diff_snapshot,
+            list_previous_versions,
+            restore_previous_version,
+            get_history_config,
+            update_history_config,
+            list_file_versions,
+            restore_file_version,
+            get_drive_health,
+            request_checksums,
+            encrypt_files,
+            decrypt_file,
+            add_network_location,
+            remove_network_location,
+            list_adb_devices,
+            stream_adb_directory,
+            adb_pull,
+            adb_push,
+            upload_folder,
+            set_cloud_availability,
+            // actions
+            create_new_file,
+            create_new_directory,
+            rename_item,
+            delete_item,
+            move_to_trash,
+            set_timestamps,
+            shift_timestamps,
+            set_attributes,
+            validate_filename,
             // stream
             stream_directory_contents,
+            cancel_directory_stream,
             copy_items_to_clipboard,
             cut_items_to_clipboard,
             paste_items_from_clipboard,
+            pause_paste_stream,
+            resume_paste_stream,
+            get_clipboard_history,
+            get_clipboard_state,
+            paste_from_history,
+            paste_item_from_paths,
+            copy_item,
+            copy_to,
+            move_to,
             resolve_copy_conflict,
+            get_animated_preview,
+            get_preview_rule,
+            export_thumbnails,
+            // jobs
+            delete_items,
+            get_delete_summary,
+            secure_delete,
+            move_item,
+            cancel_job,
+            list_interrupted_transfers,
+            resume_interrupted_transfer,
+            rollback_interrupted_transfer,
+            discard_interrupted_transfer,
+            resolve_job_error,
+            preview_mirror,
+            run_mirror,
+            discard_mirror_preview,
+            // undo
+            undo_last_operation,
+            redo_last_operation,
+            get_undo_state,
             // util
             resolve_path_command,
             resolve_quick_access,
             fetch_layout_settings,
-            update_layout_settings
+            get_home_layout,
+            reorder_home_sections,
+            set_home_section_visible,
+            set_home_recents_limit,
+            add_home_workspace,
+            remove_home_workspace,
+            update_layout_settings,
+            get_settings,
+            update_settings,
+            take_pending_open_path,
+            get_system_appearance,
+            // editors
+            list_editors,
+            open_in_editor,
+            set_default_editor,
+            get_keybindings,
+            set_keybinding,
+            get_bookmark_slots,
+            set_bookmark_slot,
+            goto_slot,
+            get_shell_integration,
+            install_shell_integration,
+            uninstall_shell_integration,
+            get_default_file_manager,
+            set_default_file_manager,
+            get_association,
+            set_association,
+            // plugins
+            list_plugins,
+            reload_plugins,
+            set_plugin_enabled,
+            install_plugin,
+            uninstall_plugin,
+            get_plugin_actions,
+            run_plugin_action,
+            get_plugin_preview,
+            // hooks
+            get_hooks,
+            update_hooks,
+            set_hook_enabled,
+            list_icon_packs,
+            get_activity_log,
+            clear_activity_log,
+            // custom actions
+            get_custom_actions,
+            update_custom_actions,
+            get_custom_actions_for,
+            execute_custom_action,
+            set_custom_action_enabled,
+            get_diagnostics,
+            set_log_level,
+            get_recent_logs,
+            export_profile,
+            import_profile,
+            get_power_state,
+            // rules
+            get_rules,
+            update_rules,
+            run_rules_on_folder,
+            // tags
+            tag_paths,
+            untag_paths,
+            list_tags,
+            set_tag_color,
+            delete_tag,
+            get_tags_for_path,
+            find_by_tag,
+            set_rating,
+            set_comment,
+            get_file_properties
         ])
         // Setup hook
         .setup(setup_app_environment)
@@ -0,0 +1,129 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+/// Separates the archive path from the entry path in search results (`a.zip!dir/b.txt`)
+pub const ARCHIVE_SEPARATOR: char = '!';
+
+/// Archive formats whose entries can be searched
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    SevenZip,
+}
+
+/// One entry listed from inside an archive
+pub struct ArchiveEntry {
+    /// Path inside the archive, always `/`-separated
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+pub fn archive_format(path: &Path) -> Option<ArchiveFormat> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        return Some(ArchiveFormat::TarGz);
+    }
+    match name.rsplit_once('.')?.1 {
+        "zip" | "jar" | "apk" | "epub" => Some(ArchiveFormat::Zip),
+        "tar" => Some(ArchiveFormat::Tar),
+        "7z" => Some(ArchiveFormat::SevenZip),
+        _ => None,
+    }
+}
+
+/// Builds the `archive!entry` path the archive browser opens
+pub fn archive_entry_path(archive: &Path, entry: &str) -> String {
+    format!("{}{}{}", archive.display(), ARCHIVE_SEPARATOR, entry)
+}
+
+/// Lists every entry of `path`, calling `visit` for each one. When `want_content` is
+/// set, file entries up to `content_limit` bytes come with a reader over their data
+/// (not available for 7z, whose solid blocks would have to be decompressed whole).
+/// `visit` returns `false` to stop early.
+pub fn scan_archive(
+    path: &Path,
+    format: ArchiveFormat,
+    want_content: bool,
+    content_limit: u64,
+    mut visit: impl FnMut(&ArchiveEntry, Option<&mut dyn Read>) -> bool,
+) -> io::Result<()> {
+    let wants_reader =
+        |entry: &ArchiveEntry| want_content && !entry.is_dir && entry.size <= content_limit;
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = ZipArchive::new(BufReader::new(File::open(path)?))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            for i in 0..zip.len() {
+                // entries using compression methods we don't build in still list fine
+                let Ok(mut file) = zip.by_index(i) else {
+                    continue;
+                };
+                let entry = ArchiveEntry {
+                    path: file.name().trim_end_matches('/').to_string(),
+                    size: file.size(),
+                    is_dir: file.is_dir(),
+                };
+                let reader = wants_reader(&entry).then_some(&mut file as &mut dyn Read);
+                if !visit(&entry, reader) {
+                    break;
+                }
+            }
+        }
+        ArchiveFormat::Tar => scan_tar(File::open(path)?, &wants_reader, &mut visit)?,
+        ArchiveFormat::TarGz => scan_tar(
+            GzDecoder::new(BufReader::new(File::open(path)?)),
+            &wants_reader,
+            &mut visit,
+        )?,
+        ArchiveFormat::SevenZip => {
+            let archive = sevenz_rust::Archive::open(path)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            for file in &archive.files {
+                let entry = ArchiveEntry {
+                    path: file.name().replace('\\', "/"),
+                    size: file.size(),
+                    is_dir: file.is_directory(),
+                };
+                if !visit(&entry, None) {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn scan_tar(
+    reader: impl Read,
+    wants_reader: &dyn Fn(&ArchiveEntry) -> bool,
+    visit: &mut dyn FnMut(&ArchiveEntry, Option<&mut dyn Read>) -> bool,
+) -> io::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for file in archive.entries()? {
+        let mut file = file?;
+        let entry = ArchiveEntry {
+            path: file
+                .path()?
+                .to_string_lossy()
+                .trim_end_matches('/')
+                .to_string(),
+            size: file.size(),
+            is_dir: file.header().entry_type().is_dir(),
+        };
+        let reader = wants_reader(&entry).then_some(&mut file as &mut dyn Read);
+        if !visit(&entry, reader) {
+            break;
+        }
+    }
+    Ok(())
+}
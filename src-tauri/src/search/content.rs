@@ -0,0 +1,147 @@
+use std::io::{self, Read};
+
+/// Files larger than this are never content-searched
+pub const MAX_CONTENT_FILE: u64 = 4 * 1024 * 1024;
+
+/// Archive entries larger than this are never content-searched
+pub const MAX_ARCHIVE_TEXT_ENTRY: u64 = 1024 * 1024;
+
+/// Leading bytes inspected for NUL bytes to tell binary files apart
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Matching lines reported per file; the rest are only counted
+const MAX_SNIPPETS: usize = 3;
+
+/// Snippet lines longer than this (in characters) are cut down around the match
+const MAX_SNIPPET_CHARS: usize = 200;
+
+/// Characters kept before the first match when a long line is cut
+const SNIPPET_LEAD_CHARS: usize = 40;
+
+/// Where the query was found inside a text file
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ContentMatch {
+    /// 1-based line number of the first match
+    pub line: usize,
+    /// Number of matching lines within the searched part of the file
+    pub match_count: usize,
+    /// The first few matching lines, with their neighbours for context
+    pub snippets: Vec<Snippet>,
+}
+
+/// A matching line ready to render with highlights
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Snippet {
+    /// 1-based line number of `text`
+    pub line: usize,
+    pub before: Option<String>,
+    /// The matching line, cut around the match (marked with "…") when very long
+    pub text: String,
+    pub after: Option<String>,
+    /// `[start, end)` of every match in `text`, in UTF-16 code units like JS string indices
+    pub matches: Vec<(usize, usize)>,
+}
+
+/// Reads at most `limit` bytes and looks for `needle` (already lowercase) line by line.
+/// Binary data yields `Ok(None)`.
+pub fn find_in_reader(
+    reader: impl Read,
+    needle: &str,
+    limit: u64,
+) -> io::Result<Option<ContentMatch>> {
+    let mut buf = Vec::new();
+    reader.take(limit).read_to_end(&mut buf)?;
+    if buf[..buf.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+        return Ok(None);
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let lines: Vec<&str> = text.lines().collect();
+    let mut found: Option<ContentMatch> = None;
+    for (i, line) in lines.iter().enumerate() {
+        let ranges = match_ranges(line, needle);
+        if ranges.is_empty() {
+            continue;
+        }
+
+        let m = found.get_or_insert_with(|| ContentMatch {
+            line: i + 1,
+            match_count: 0,
+            snippets: Vec::new(),
+        });
+        m.match_count += 1;
+        if m.snippets.len() < MAX_SNIPPETS {
+            let (text, matches) = cut_around(line, &ranges);
+            m.snippets.push(Snippet {
+                line: i + 1,
+                before: i.checked_sub(1).map(|j| clip(lines[j])),
+                text,
+                after: lines.get(i + 1).map(|l| clip(l)),
+                matches,
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// Byte ranges in `line` matching `needle` case-insensitively. Lowercasing can
+/// change byte lengths, so matches are found in the lowered text and mapped back.
+fn match_ranges(line: &str, needle: &str) -> Vec<(usize, usize)> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let mut lowered = String::with_capacity(line.len());
+    // (start, end) in `line` of the char each lowered byte came from
+    let mut origin: Vec<(usize, usize)> = Vec::with_capacity(line.len());
+    for (i, c) in line.char_indices() {
+        for lc in c.to_lowercase() {
+            lowered.push(lc);
+            origin.extend(std::iter::repeat_n((i, i + c.len_utf8()), lc.len_utf8()));
+        }
+    }
+
+    lowered
+        .match_indices(needle)
+        .map(|(start, m)| (origin[start].0, origin[start + m.len() - 1].1))
+        .collect()
+}
+
+/// Cuts a long line to `MAX_SNIPPET_CHARS` around its first match, returning
+/// the text and the match ranges inside it in UTF-16 units
+fn cut_around(line: &str, ranges: &[(usize, usize)]) -> (String, Vec<(usize, usize)>) {
+    let (mut from, mut to) = (0, line.len());
+    if line.chars().count() > MAX_SNIPPET_CHARS {
+        let first = ranges[0].0;
+        from = line[..first]
+            .char_indices()
+            .rev()
+            .nth(SNIPPET_LEAD_CHARS.saturating_sub(1))
+            .map_or(0, |(i, _)| i);
+        to = line[from..]
+            .char_indices()
+            .nth(MAX_SNIPPET_CHARS)
+            .map_or(line.len(), |(i, _)| from + i);
+    }
+
+    let prefix = if from > 0 { "…" } else { "" };
+    let suffix = if to < line.len() { "…" } else { "" };
+    let utf16 = |s: &str| s.encode_utf16().count();
+    let base = utf16(prefix);
+    let matches = ranges
+        .iter()
+        .filter(|(start, end)| *start >= from && *end <= to)
+        .map(|(start, end)| {
+            let start16 = base + utf16(&line[from..*start]);
+            (start16, start16 + utf16(&line[*start..*end]))
+        })
+        .collect();
+    (format!("{}{}{}", prefix, &line[from..to], suffix), matches)
+}
+
+/// Context lines are shown as-is, only shortened when very long
+fn clip(line: &str) -> String {
+    match line.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line.to_string(),
+    }
+}
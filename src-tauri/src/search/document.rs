@@ -1 +1,689 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+use zip::ZipArchive;
 
+/// Documents larger than this are not opened
+const MAX_DOCUMENT_FILE: u64 = 64 * 1024 * 1024;
+
+/// Largest XML part read from a document; guards against zip bombs
+const MAX_XML_PART: u64 = 32 * 1024 * 1024;
+
+/// Characters of text kept for a preview
+const MAX_PREVIEW_CHARS: usize = 100_000;
+
+/// Sheets, rows and columns kept per spreadsheet preview
+const MAX_SHEETS: usize = 16;
+const MAX_SHEET_ROWS: usize = 200;
+const MAX_SHEET_COLS: usize = 40;
+
+/// Office formats whose text can be extracted
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentFormat {
+    Docx,
+    Xlsx,
+    Pptx,
+    Odt,
+    Ods,
+    Odp,
+}
+
+impl DocumentFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "docx" | "docm" | "dotx" => Some(Self::Docx),
+            "xlsx" | "xlsm" | "xltx" => Some(Self::Xlsx),
+            "pptx" | "pptm" | "potx" => Some(Self::Pptx),
+            "odt" | "ott" => Some(Self::Odt),
+            "ods" | "ots" => Some(Self::Ods),
+            "odp" | "otp" => Some(Self::Odp),
+            _ => None,
+        }
+    }
+
+    fn is_spreadsheet(self) -> bool {
+        matches!(self, Self::Xlsx | Self::Ods)
+    }
+}
+
+/// The top-left corner of one sheet
+#[derive(Serialize, Clone, Debug)]
+pub struct SheetPreview {
+    pub name: String,
+    pub rows: Vec<Vec<String>>,
+    /// More rows or columns exist than were kept
+    pub truncated: bool,
+}
+
+/// What `preview_document` shows in the preview pane
+#[derive(Serialize, Clone, Debug)]
+pub struct DocumentPreview {
+    pub format: DocumentFormat,
+    /// Plain text for word processor and presentation files
+    pub text: Option<String>,
+    /// Sheets of a spreadsheet, in workbook order
+    pub sheets: Vec<SheetPreview>,
+    /// The text was cut at `MAX_PREVIEW_CHARS`, or sheets were left out
+    pub truncated: bool,
+}
+
+// ===============================
+// Minimal XML reading
+// ===============================
+
+/// A piece of an XML document; names keep their namespace prefix (e.g. "w:t")
+enum Token<'a> {
+    Open {
+        name: &'a str,
+        attrs: &'a str,
+        empty: bool,
+    },
+    Close(&'a str),
+    Text(&'a str),
+}
+
+/// Splits XML into tags and text. Comments, processing instructions and
+/// declarations are skipped; CDATA comes through as text.
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            if !self.rest.starts_with('<') {
+                let end = self.rest.find('<').unwrap_or(self.rest.len());
+                let (text, rest) = self.rest.split_at(end);
+                self.rest = rest;
+                return Some(Token::Text(text));
+            }
+
+            if let Some(body) = self.rest.strip_prefix("<![CDATA[") {
+                let end = body.find("]]>").unwrap_or(body.len());
+                self.rest = body.get(end + 3..).unwrap_or("");
+                return Some(Token::Text(&body[..end]));
+            }
+            let (skip_to, skip_len) = if self.rest.starts_with("<!--") {
+                (self.rest.find("-->"), 3)
+            } else if self.rest.starts_with("<?") || self.rest.starts_with("<!") {
+                (self.rest.find('>'), 1)
+            } else {
+                (None, 0)
+            };
+            if skip_len > 0 {
+                self.rest = skip_to.map_or("", |i| &self.rest[i + skip_len..]);
+                continue;
+            }
+
+            let end = self.rest.find('>')?;
+            let tag = &self.rest[1..end];
+            self.rest = &self.rest[end + 1..];
+            if let Some(name) = tag.strip_prefix('/') {
+                return Some(Token::Close(name.trim()));
+            }
+            let empty = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let split = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            return Some(Token::Open {
+                name: &tag[..split],
+                attrs: &tag[split..],
+                empty,
+            });
+        }
+    }
+}
+
+fn tokens(xml: &str) -> Tokens<'_> {
+    Tokens { rest: xml }
+}
+
+/// Value of attribute `name` (with prefix, e.g. "r:id") in a tag's attribute text
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    loop {
+        let eq = rest.find('=')?;
+        let key = rest[..eq].trim();
+        let after = rest[eq + 1..].trim_start();
+        let quote = after.chars().next()?;
+        let value_end = after[1..].find(quote)? + 1;
+        if key == name {
+            return Some(unescape(&after[1..value_end]));
+        }
+        rest = &after[value_end + 1..];
+    }
+}
+
+/// Resolves the predefined and numeric character entities
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';').filter(|&i| i <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(|n| n.ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ===============================
+// Package access
+// ===============================
+
+type Package = ZipArchive<BufReader<File>>;
+
+fn open_package(path: &Path) -> io::Result<Package> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() > MAX_DOCUMENT_FILE {
+        return Err(io::Error::other("Document is too large to preview"));
+    }
+    ZipArchive::new(BufReader::new(file)).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// One XML part of the package as text; `None` if it doesn't exist
+fn read_part(zip: &mut Package, name: &str) -> io::Result<Option<String>> {
+    let file = match zip.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+    };
+    let mut data = Vec::new();
+    file.take(MAX_XML_PART).read_to_end(&mut data)?;
+    Ok(Some(String::from_utf8_lossy(&data).into_owned()))
+}
+
+/// Names of numbered parts like `ppt/slides/slide12.xml`, in numeric order
+fn numbered_parts(zip: &Package, prefix: &str) -> Vec<String> {
+    let mut parts: Vec<(u32, String)> = zip
+        .file_names()
+        .filter_map(|name| {
+            let n = name
+                .strip_prefix(prefix)?
+                .strip_suffix(".xml")?
+                .parse()
+                .ok()?;
+            Some((n, name.to_string()))
+        })
+        .collect();
+    parts.sort();
+    parts.into_iter().map(|(_, name)| name).collect()
+}
+
+// ===============================
+// Text documents
+// ===============================
+
+/// Paragraph text of a WordprocessingML or DrawingML part. `text_tag` holds
+/// the runs ("w:t" / "a:t"), `para_tag` ends a line.
+fn ooxml_text(xml: &str, text_tag: &str, para_tag: &str, out: &mut String) {
+    let mut in_text = false;
+    for token in tokens(xml) {
+        match token {
+            Token::Open { name, empty, .. } => match name {
+                n if n == text_tag => in_text = !empty,
+                "w:tab" | "a:tab" => out.push('\t'),
+                "w:br" | "w:cr" | "a:br" => out.push('\n'),
+                _ => {}
+            },
+            Token::Close(name) if name == text_tag => in_text = false,
+            Token::Close(name) if name == para_tag => out.push('\n'),
+            Token::Text(text) if in_text => out.push_str(&unescape(text)),
+            _ => {}
+        }
+    }
+}
+
+/// Paragraph text of an ODF `content.xml`
+fn odf_text(xml: &str, out: &mut String) {
+    let mut depth_in_body = 0usize;
+    for token in tokens(xml) {
+        match token {
+            Token::Open { name, attrs, empty } => match name {
+                "office:body" => depth_in_body = 1,
+                "text:s" => {
+                    let count = attr(attrs, "text:c")
+                        .and_then(|c| c.parse().ok())
+                        .unwrap_or(1);
+                    out.extend(std::iter::repeat_n(' ', count));
+                }
+                "text:tab" => out.push('\t'),
+                "text:line-break" => out.push('\n'),
+                _ if depth_in_body > 0 && !empty => depth_in_body += 1,
+                _ => {}
+            },
+            Token::Close(name) => {
+                if matches!(name, "text:p" | "text:h") {
+                    out.push('\n');
+                }
+                depth_in_body = depth_in_body.saturating_sub(1);
+            }
+            Token::Text(text) if depth_in_body > 0 => out.push_str(&unescape(text)),
+            _ => {}
+        }
+    }
+}
+
+fn extract_text(zip: &mut Package, format: DocumentFormat) -> io::Result<String> {
+    let mut out = String::new();
+    match format {
+        DocumentFormat::Docx => {
+            let xml = read_part(zip, "word/document.xml")?.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Missing document body")
+            })?;
+            ooxml_text(&xml, "w:t", "w:p", &mut out);
+        }
+        DocumentFormat::Pptx => {
+            for (i, slide) in numbered_parts(zip, "ppt/slides/slide").iter().enumerate() {
+                if let Some(xml) = read_part(zip, slide)? {
+                    if i > 0 {
+                        out.push('\n');
+                    }
+                    ooxml_text(&xml, "a:t", "a:p", &mut out);
+                }
+                if out.len() > MAX_PREVIEW_CHARS * 4 {
+                    break;
+                }
+            }
+        }
+        DocumentFormat::Odt | DocumentFormat::Odp => {
+            let xml = read_part(zip, "content.xml")?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing content.xml"))?;
+            odf_text(&xml, &mut out);
+        }
+        DocumentFormat::Xlsx | DocumentFormat::Ods => {}
+    }
+    Ok(out)
+}
+
+// ===============================
+// Spreadsheets
+// ===============================
+
+/// Zero-based column of a cell reference like "AB12"
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(|b| b.is_ascii_alphabetic())
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    letters
+        .iter()
+        .try_fold(0usize, |acc, b| {
+            acc.checked_mul(26)?
+                .checked_add((b.to_ascii_uppercase() - b'A') as usize + 1)
+        })
+        .map(|n| n - 1)
+}
+
+/// Drops trailing empty cells and rows
+fn trim_grid(rows: &mut Vec<Vec<String>>) {
+    for row in rows.iter_mut() {
+        while row.last().is_some_and(|c| c.is_empty()) {
+            row.pop();
+        }
+    }
+    while rows.last().is_some_and(|r| r.is_empty()) {
+        rows.pop();
+    }
+}
+
+fn shared_strings(zip: &mut Package) -> io::Result<Vec<String>> {
+    let Some(xml) = read_part(zip, "xl/sharedStrings.xml")? else {
+        return Ok(Vec::new());
+    };
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let (mut in_text, mut in_phonetic) = (false, false);
+    for token in tokens(&xml) {
+        match token {
+            Token::Open { name: "si", .. } => current.clear(),
+            Token::Open {
+                name: "rPh", empty, ..
+            } => in_phonetic = !empty,
+            Token::Open {
+                name: "t", empty, ..
+            } => in_text = !empty,
+            Token::Close("t") => in_text = false,
+            Token::Close("rPh") => in_phonetic = false,
+            Token::Close("si") => strings.push(std::mem::take(&mut current)),
+            Token::Text(text) if in_text && !in_phonetic => current.push_str(&unescape(text)),
+            _ => {}
+        }
+    }
+    Ok(strings)
+}
+
+/// Sheet names and their part paths, in workbook order
+fn workbook_sheets(zip: &mut Package) -> io::Result<Vec<(String, String)>> {
+    let rels = read_part(zip, "xl/_rels/workbook.xml.rels")?.unwrap_or_default();
+    let targets: HashMap<String, String> = tokens(&rels)
+        .filter_map(|token| match token {
+            Token::Open {
+                name: "Relationship",
+                attrs,
+                ..
+            } => Some((attr(attrs, "Id")?, attr(attrs, "Target")?)),
+            _ => None,
+        })
+        .collect();
+
+    let workbook = read_part(zip, "xl/workbook.xml")?.unwrap_or_default();
+    Ok(tokens(&workbook)
+        .filter_map(|token| match token {
+            Token::Open {
+                name: "sheet",
+                attrs,
+                ..
+            } => {
+                let target = targets.get(&attr(attrs, "r:id")?)?;
+                let part = match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => format!("xl/{}", target),
+                };
+                Some((attr(attrs, "name")?, part))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+fn xlsx_sheet(xml: &str, strings: &[String]) -> (Vec<Vec<String>>, bool) {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut truncated = false;
+    let (mut cell_type, mut cell_col) = (String::new(), 0usize);
+    let mut value = String::new();
+    let mut in_value = false;
+
+    for token in tokens(xml) {
+        match token {
+            Token::Open { name: "row", .. } => row.clear(),
+            Token::Open {
+                name: "c", attrs, ..
+            } => {
+                cell_type = attr(attrs, "t").unwrap_or_default();
+                cell_col = attr(attrs, "r")
+                    .and_then(|r| column_index(&r))
+                    .unwrap_or(row.len());
+                value.clear();
+            }
+            Token::Open {
+                name: "v" | "t",
+                empty,
+                ..
+            } => in_value = !empty,
+            Token::Close("v" | "t") => in_value = false,
+            Token::Text(text) if in_value => value.push_str(&unescape(text)),
+            Token::Close("c") => {
+                if cell_col >= MAX_SHEET_COLS {
+                    truncated = true;
+                    continue;
+                }
+                let shown = match cell_type.as_str() {
+                    "s" => value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| strings.get(i).cloned())
+                        .unwrap_or_default(),
+                    "b" => if value.trim() == "1" { "TRUE" } else { "FALSE" }.to_string(),
+                    _ => value.clone(),
+                };
+                if row.len() <= cell_col {
+                    row.resize(cell_col + 1, String::new());
+                }
+                row[cell_col] = shown;
+            }
+            Token::Close("row") => {
+                if rows.len() >= MAX_SHEET_ROWS {
+                    truncated = true;
+                    break;
+                }
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => {}
+        }
+    }
+    trim_grid(&mut rows);
+    (rows, truncated)
+}
+
+fn xlsx_sheets(zip: &mut Package) -> io::Result<(Vec<SheetPreview>, bool)> {
+    let strings = shared_strings(zip)?;
+    let sheets = workbook_sheets(zip)?;
+    let more = sheets.len() > MAX_SHEETS;
+    let mut previews = Vec::new();
+    for (name, part) in sheets.into_iter().take(MAX_SHEETS) {
+        let Some(xml) = read_part(zip, &part)? else {
+            continue;
+        };
+        let (rows, truncated) = xlsx_sheet(&xml, &strings);
+        previews.push(SheetPreview {
+            name,
+            rows,
+            truncated,
+        });
+    }
+    Ok((previews, more))
+}
+
+/// Repeat count of an ODF row or cell, capped at what a preview can still show
+fn repeated(attrs: &str, name: &str, room: usize) -> usize {
+    attr(attrs, name)
+        .and_then(|n| n.parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, room.max(1))
+}
+
+fn ods_sheets(zip: &mut Package) -> io::Result<(Vec<SheetPreview>, bool)> {
+    let xml = read_part(zip, "content.xml")?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing content.xml"))?;
+
+    let mut sheets: Vec<SheetPreview> = Vec::new();
+    let mut more = false;
+    let mut row: Vec<String> = Vec::new();
+    let (mut row_repeat, mut cell_repeat) = (1usize, 1usize);
+    let mut cell = String::new();
+    let mut paragraphs = 0usize;
+    let mut in_cell = false;
+
+    for token in tokens(&xml) {
+        match token {
+            Token::Open {
+                name: "table:table",
+                attrs,
+                ..
+            } => {
+                if sheets.len() >= MAX_SHEETS {
+                    more = true;
+                    break;
+                }
+                sheets.push(SheetPreview {
+                    name: attr(attrs, "table:name").unwrap_or_default(),
+                    rows: Vec::new(),
+                    truncated: false,
+                });
+            }
+            Token::Open {
+                name: "table:table-row",
+                attrs,
+                ..
+            } => {
+                row.clear();
+                let room = sheets
+                    .last()
+                    .map_or(0, |s| MAX_SHEET_ROWS.saturating_sub(s.rows.len()));
+                row_repeat = repeated(attrs, "table:number-rows-repeated", room);
+            }
+            Token::Open {
+                name: "table:table-cell" | "table:covered-table-cell",
+                attrs,
+                empty,
+            } => {
+                cell.clear();
+                paragraphs = 0;
+                cell_repeat = repeated(
+                    attrs,
+                    "table:number-columns-repeated",
+                    MAX_SHEET_COLS.saturating_sub(row.len()),
+                );
+                in_cell = !empty;
+                if empty {
+                    row.extend(std::iter::repeat_n(String::new(), cell_repeat));
+                }
+            }
+            Token::Open { name: "text:p", .. } if in_cell => {
+                if paragraphs > 0 {
+                    cell.push('\n');
+                }
+                paragraphs += 1;
+            }
+            Token::Open { name: "text:s", .. } if in_cell => cell.push(' '),
+            Token::Text(text) if in_cell => cell.push_str(&unescape(text)),
+            Token::Close("table:table-cell" | "table:covered-table-cell") => {
+                in_cell = false;
+                row.extend(std::iter::repeat_n(cell.clone(), cell_repeat));
+            }
+            Token::Close("table:table-row") => {
+                let Some(sheet) = sheets.last_mut() else {
+                    continue;
+                };
+                if row.len() > MAX_SHEET_COLS {
+                    row.truncate(MAX_SHEET_COLS);
+                    sheet.truncated = true;
+                }
+                for _ in 0..row_repeat {
+                    if sheet.rows.len() >= MAX_SHEET_ROWS {
+                        sheet.truncated = true;
+                        break;
+                    }
+                    sheet.rows.push(row.clone());
+                }
+            }
+            Token::Close("table:table") => {
+                if let Some(sheet) = sheets.last_mut() {
+                    trim_grid(&mut sheet.rows);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((sheets, more))
+}
+
+// ===============================
+// Entry points
+// ===============================
+
+/// Cuts `text` to at most `limit` characters; returns whether it was cut
+fn truncate_chars(text: &mut String, limit: usize) -> bool {
+    match text.char_indices().nth(limit) {
+        Some((i, _)) => {
+            text.truncate(i);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn document_preview(path: &Path) -> io::Result<DocumentPreview> {
+    let format = DocumentFormat::from_path(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "Not an Office document"))?;
+    let mut zip = open_package(path)?;
+
+    if format.is_spreadsheet() {
+        let (sheets, more) = match format {
+            DocumentFormat::Xlsx => xlsx_sheets(&mut zip)?,
+            _ => ods_sheets(&mut zip)?,
+        };
+        return Ok(DocumentPreview {
+            format,
+            text: None,
+            sheets,
+            truncated: more,
+        });
+    }
+
+    let mut text = extract_text(&mut zip, format)?;
+    let truncated = truncate_chars(&mut text, MAX_PREVIEW_CHARS);
+    Ok(DocumentPreview {
+        format,
+        text: Some(text),
+        sheets: Vec::new(),
+        truncated,
+    })
+}
+
+/// Searchable text of an Office document; sheets become tab-separated lines.
+/// `None` for other files or documents that can't be read.
+pub fn document_text(path: &Path) -> Option<String> {
+    let preview = document_preview(path).ok()?;
+    if let Some(text) = preview.text {
+        return Some(text);
+    }
+    let mut out = String::new();
+    for sheet in preview.sheets {
+        out.push_str(&sheet.name);
+        out.push('\n');
+        for row in sheet.rows {
+            out.push_str(&row.join("\t"));
+            out.push('\n');
+        }
+    }
+    Some(out)
+}
+
+/// Plain text (or sheet previews) of a docx/xlsx/pptx or ODF document, for
+/// peeking at it without launching Office
+#[tauri::command]
+pub async fn preview_document(path: String) -> Result<DocumentPreview, String> {
+    let target = path.clone();
+    tauri::async_runtime::spawn_blocking(move || document_preview(Path::new(&target)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to preview {}: {}", path, e))
+}
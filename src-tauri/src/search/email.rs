@@ -0,0 +1,733 @@
+use base64::Engine;
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use tauri::AppHandle;
+
+use crate::filesys::{actions::unique_path, names::validate_file_name, staging::StagingDir};
+use crate::util::icon_packs::percent_decode;
+
+/// Email files larger than this are not opened
+const MAX_EMAIL_FILE: u64 = 100 * 1024 * 1024;
+
+/// Characters of body text kept for a preview
+const MAX_BODY_CHARS: usize = 100_000;
+
+/// Nesting of multipart bodies followed before giving up
+const MAX_MIME_DEPTH: usize = 8;
+
+/// Seconds between the FILETIME epoch (1601) and the Unix epoch
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailFormat {
+    /// RFC 5322 message, as saved by Thunderbird, Apple Mail and most webmail
+    Eml,
+    /// Outlook message (a compound file of MAPI properties)
+    Msg,
+}
+
+impl EmailFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "eml" => Some(Self::Eml),
+            "msg" => Some(Self::Msg),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EmailAttachment {
+    /// Position in the message; what `extract_email_attachments` selects by
+    pub index: usize,
+    pub name: String,
+    pub mime_type: Option<String>,
+    pub size: u64,
+}
+
+/// Headers, attachments and (for previews) the body of an email file
+#[derive(Serialize, Clone, Debug)]
+pub struct EmailSummary {
+    pub format: EmailFormat,
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub cc: Option<String>,
+    /// The date as written in the message
+    pub date: Option<String>,
+    /// The same date in seconds since the Unix epoch, when it could be read
+    pub timestamp: Option<i64>,
+    pub attachments: Vec<EmailAttachment>,
+    /// Plain text body, or the HTML body with tags stripped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// The body was cut at `MAX_BODY_CHARS`
+    pub truncated: bool,
+}
+
+/// A parsed message with attachment contents, before it's summarised
+struct ParsedEmail {
+    format: EmailFormat,
+    subject: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    cc: Option<String>,
+    date: Option<String>,
+    timestamp: Option<i64>,
+    text: Option<String>,
+    html: Option<String>,
+    attachments: Vec<(String, Option<String>, Vec<u8>)>,
+}
+
+impl ParsedEmail {
+    fn new(format: EmailFormat) -> Self {
+        Self {
+            format,
+            subject: None,
+            from: None,
+            to: None,
+            cc: None,
+            date: None,
+            timestamp: None,
+            text: None,
+            html: None,
+            attachments: Vec::new(),
+        }
+    }
+}
+
+// ===============================
+// Dates
+// ===============================
+
+/// Days from 1970-01-01 to a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Seconds since the Unix epoch of an RFC 5322 date such as
+/// "Tue, 1 Jul 2003 10:52:37 +0200"
+fn parse_email_date(date: &str) -> Option<i64> {
+    let date = date.split_once(',').map_or(date, |(_, rest)| rest);
+    let mut parts = date.split_whitespace();
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?.get(..3)?.to_ascii_lowercase();
+    let month = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ]
+    .iter()
+    .position(|m| *m == month)? as i64
+        + 1;
+    let year: i64 = match parts.next()?.parse().ok()? {
+        y @ 0..=49 => y + 2000,
+        y @ 50..=999 => y + 1900,
+        y => y,
+    };
+
+    let mut clock = parts.next()?.split(':').map(|n| n.parse::<i64>().ok());
+    let hours = clock.next()??;
+    let minutes = clock.next()??;
+    let seconds = clock.next().flatten().unwrap_or(0);
+
+    let zone = parts.next().unwrap_or("+0000");
+    let offset = match zone.as_bytes().first() {
+        Some(sign @ (b'+' | b'-')) => {
+            let digits: i64 = zone.get(1..5)?.parse().ok()?;
+            let secs = (digits / 100) * 3600 + (digits % 100) * 60;
+            if *sign == b'-' {
+                -secs
+            } else {
+                secs
+            }
+        }
+        _ => match zone.to_ascii_uppercase().as_str() {
+            "EDT" => -4 * 3600,
+            "EST" | "CDT" => -5 * 3600,
+            "CST" | "MDT" => -6 * 3600,
+            "MST" | "PDT" => -7 * 3600,
+            "PST" => -8 * 3600,
+            _ => 0,
+        },
+    };
+
+    Some(
+        days_from_civil(year, month, day) * 86_400 + hours * 3600 + minutes * 60 + seconds - offset,
+    )
+}
+
+// ===============================
+// MIME (.eml)
+// ===============================
+
+/// Text in a named charset; anything that isn't UTF-8 or Latin is read as UTF-8
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.trim().to_ascii_lowercase().as_str() {
+        "iso-8859-1" | "iso-8859-15" | "latin1" | "windows-1252" | "cp1252" => {
+            bytes.iter().map(|&b| b as char).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+/// Lenient base64: line breaks and stray characters are skipped
+fn decode_base64(data: &[u8]) -> Vec<u8> {
+    let mut clean: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/'))
+        .collect();
+    // a single leftover character can't encode a byte
+    if clean.len() % 4 == 1 {
+        clean.pop();
+    }
+    base64::engine::general_purpose::STANDARD_NO_PAD
+        .decode(&clean)
+        .unwrap_or_default()
+}
+
+/// Quoted-printable; `underscores` is the encoded-word variant where '_' is a space
+fn decode_quoted_printable(data: &[u8], underscores: bool) -> Vec<u8> {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'=' => {
+                match (data.get(i + 1).copied(), data.get(i + 2).copied()) {
+                    // soft line break
+                    (Some(b'\r'), Some(b'\n')) => i += 3,
+                    (Some(b'\n'), _) => i += 2,
+                    (Some(hi), Some(lo)) if hex(hi).is_some() && hex(lo).is_some() => {
+                        out.push(hex(hi).unwrap() << 4 | hex(lo).unwrap());
+                        i += 3;
+                    }
+                    _ => {
+                        out.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if underscores => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Decodes RFC 2047 encoded words ("=?utf-8?B?...?=") in a header value
+fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    // whitespace between two encoded words is dropped
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let word = rest[start + 2..].splitn(3, '?').collect::<Vec<_>>();
+        let decoded = match word.as_slice() {
+            [charset, encoding, tail] => tail.find("?=").and_then(|end| {
+                let text = &tail[..end];
+                let bytes = match encoding.to_ascii_lowercase().as_str() {
+                    "b" => decode_base64(text.as_bytes()),
+                    "q" => decode_quoted_printable(text.as_bytes(), true),
+                    _ => return None,
+                };
+                let charset = charset.split('*').next().unwrap_or(charset);
+                let len = charset.len() + encoding.len() + end + 6;
+                Some((decode_charset(&bytes, charset), len))
+            }),
+            _ => None,
+        };
+        let Some((text, len)) = decoded else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            after_word = false;
+            continue;
+        };
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        out.push_str(&text);
+        rest = &rest[start + len..];
+        after_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Splits a message or part into unfolded headers and the body after the blank line
+fn split_headers(raw: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let (head, body) = match raw.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(i) => (&raw[..i], &raw[i + 4..]),
+        None => match raw.windows(2).position(|w| w == b"\n\n") {
+            Some(i) => (&raw[..i], &raw[i + 2..]),
+            None => (raw, &raw[raw.len()..]),
+        },
+    };
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in String::from_utf8_lossy(head).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// A structured header value ("text/plain; charset=utf-8") split into its
+/// lowercased main value and parameters. RFC 2231 "name*=" parameters are decoded.
+fn parse_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = value.split(';');
+    let main = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let params = parts
+        .filter_map(|p| {
+            let (key, value) = p.split_once('=')?;
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim().trim_matches('"');
+            match key.strip_suffix('*') {
+                Some(key) => {
+                    // charset'language'percent-encoded
+                    let encoded = value.splitn(3, '\'').last().unwrap_or(value);
+                    Some((key.to_string(), percent_decode(encoded)))
+                }
+                None => Some((key, decode_encoded_words(value))),
+            }
+        })
+        .collect();
+    (main, params)
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// The parts of a multipart body between "--boundary" lines
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut pos = 0;
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if trimmed.starts_with(delimiter.as_bytes()) {
+            if let Some(start) = start {
+                // the line break before a delimiter belongs to it
+                let end = body[..pos]
+                    .strip_suffix(b"\r\n")
+                    .or_else(|| body[..pos].strip_suffix(b"\n"))
+                    .map_or(pos, |b| b.len());
+                parts.push(&body[start..end.max(start)]);
+            }
+            if trimmed[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            start = Some(pos + line.len());
+        }
+        pos += line.len();
+    }
+    parts
+}
+
+fn decode_transfer(body: &[u8], encoding: Option<&str>) -> Vec<u8> {
+    match encoding.map(|e| e.trim().to_ascii_lowercase()).as_deref() {
+        Some("base64") => decode_base64(body),
+        Some("quoted-printable") => decode_quoted_printable(body, false),
+        _ => body.to_vec(),
+    }
+}
+
+/// Walks a MIME part, keeping the first plain and HTML bodies and every attachment
+fn walk_part(email: &mut ParsedEmail, raw: &[u8], depth: usize) {
+    let (headers, body) = split_headers(raw);
+    let (mime, params) = parse_params(header(&headers, "content-type").unwrap_or("text/plain"));
+
+    if mime.starts_with("multipart/") {
+        if depth >= MAX_MIME_DEPTH {
+            return;
+        }
+        if let Some(boundary) = param(&params, "boundary") {
+            for part in split_multipart(body, boundary) {
+                walk_part(email, part, depth + 1);
+            }
+        }
+        return;
+    }
+
+    let (disposition, disposition_params) =
+        parse_params(header(&headers, "content-disposition").unwrap_or(""));
+    let name = param(&disposition_params, "filename")
+        .or_else(|| param(&params, "name"))
+        .map(str::to_string);
+    let data = decode_transfer(body, header(&headers, "content-transfer-encoding"));
+
+    let is_body = disposition != "attachment" && name.is_none();
+    match mime.as_str() {
+        "text/plain" if is_body && email.text.is_none() => {
+            email.text = Some(decode_charset(
+                &data,
+                param(&params, "charset").unwrap_or(""),
+            ));
+        }
+        "text/html" if is_body && email.html.is_none() => {
+            email.html = Some(decode_charset(
+                &data,
+                param(&params, "charset").unwrap_or(""),
+            ));
+        }
+        _ if is_body && mime != "message/rfc822" => {}
+        _ => {
+            let name = name.unwrap_or_else(|| {
+                if mime == "message/rfc822" {
+                    "message.eml".into()
+                } else {
+                    format!("attachment-{}", email.attachments.len() + 1)
+                }
+            });
+            email.attachments.push((name, Some(mime), data));
+        }
+    }
+}
+
+fn parse_eml(raw: &[u8]) -> ParsedEmail {
+    let mut email = ParsedEmail::new(EmailFormat::Eml);
+    let (headers, _) = split_headers(raw);
+    let text_header = |name: &str| {
+        header(&headers, name)
+            .map(decode_encoded_words)
+            .filter(|v| !v.is_empty())
+    };
+    email.subject = text_header("subject");
+    email.from = text_header("from");
+    email.to = text_header("to");
+    email.cc = text_header("cc");
+    email.date = text_header("date");
+    email.timestamp = email.date.as_deref().and_then(parse_email_date);
+    walk_part(&mut email, raw, 0);
+    email
+}
+
+// ===============================
+// Outlook (.msg)
+// ===============================
+
+type Compound = cfb::CompoundFile<File>;
+
+fn read_stream(msg: &mut Compound, path: &str) -> Option<Vec<u8>> {
+    let mut stream = msg.open_stream(path).ok()?;
+    let mut data = Vec::new();
+    stream
+        .by_ref()
+        .take(MAX_EMAIL_FILE)
+        .read_to_end(&mut data)
+        .ok()?;
+    Some(data)
+}
+
+/// A string property of the storage at `dir`: Unicode (PT_UNICODE) or, in old
+/// files, the ANSI code page (PT_STRING8)
+fn msg_string(msg: &mut Compound, dir: &str, id: u16) -> Option<String> {
+    if let Some(data) = read_stream(msg, &format!("{}/__substg1.0_{:04X}001F", dir, id)) {
+        let units: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        return Some(
+            String::from_utf16_lossy(&units)
+                .trim_end_matches('\0')
+                .to_string(),
+        );
+    }
+    read_stream(msg, &format!("{}/__substg1.0_{:04X}001E", dir, id))
+        .map(|data| {
+            decode_charset(&data, "windows-1252")
+                .trim_end_matches('\0')
+                .to_string()
+        })
+        .filter(|s| !s.is_empty())
+}
+
+fn msg_binary(msg: &mut Compound, dir: &str, id: u16) -> Option<Vec<u8>> {
+    read_stream(msg, &format!("{}/__substg1.0_{:04X}0102", dir, id))
+}
+
+/// A PT_SYSTIME property of the message itself, in Unix seconds. Fixed-size
+/// properties live in one stream: a 32-byte header, then 16 bytes per property.
+fn msg_time(msg: &mut Compound, id: u16) -> Option<i64> {
+    let data = read_stream(msg, "/__properties_version1.0")?;
+    let wanted = (id as u32) << 16 | 0x0040;
+    data.get(32..)?.chunks_exact(16).find_map(|entry| {
+        let tag = u32::from_le_bytes(entry[..4].try_into().ok()?);
+        (tag == wanted).then(|| {
+            let filetime = u64::from_le_bytes(entry[8..16].try_into().ok()?);
+            Some((filetime / 10_000_000) as i64 - FILETIME_UNIX_OFFSET)
+        })?
+    })
+}
+
+fn parse_msg(path: &Path) -> io::Result<ParsedEmail> {
+    let mut msg = cfb::open(path)?;
+    let mut email = ParsedEmail::new(EmailFormat::Msg);
+
+    email.subject = msg_string(&mut msg, "", 0x0037);
+    let sender_name = msg_string(&mut msg, "", 0x0C1A);
+    // the SMTP address, when the sender address itself is an Exchange one
+    let sender_address =
+        msg_string(&mut msg, "", 0x5D01).or_else(|| msg_string(&mut msg, "", 0x0C1F));
+    email.from = match (sender_name, sender_address) {
+        (Some(name), Some(address)) if name != address => Some(format!("{} <{}>", name, address)),
+        (name, address) => name.or(address),
+    };
+    email.to = msg_string(&mut msg, "", 0x0E04).filter(|s| !s.is_empty());
+    email.cc = msg_string(&mut msg, "", 0x0E03).filter(|s| !s.is_empty());
+
+    // the original Date header, when the message went through a mail server
+    let transport = msg_string(&mut msg, "", 0x007D)
+        .map(|headers| split_headers(headers.as_bytes()).0)
+        .unwrap_or_default();
+    email.date = header(&transport, "date").map(str::to_string);
+    email.timestamp = email
+        .date
+        .as_deref()
+        .and_then(parse_email_date)
+        // client submit time, then delivery time
+        .or_else(|| msg_time(&mut msg, 0x0039))
+        .or_else(|| msg_time(&mut msg, 0x0E06));
+
+    email.text = msg_string(&mut msg, "", 0x1000);
+    email.html = msg_binary(&mut msg, "", 0x1013).map(|h| String::from_utf8_lossy(&h).to_string());
+
+    let mut storages: Vec<String> = msg
+        .read_root_storage()
+        .filter(|e| e.is_storage() && e.name().starts_with("__attach_version1.0_#"))
+        .map(|e| format!("/{}", e.name()))
+        .collect();
+    storages.sort();
+    for dir in storages {
+        let Some(data) = msg_binary(&mut msg, &dir, 0x3701) else {
+            // embedded messages and OLE objects have no plain data stream
+            continue;
+        };
+        let name = msg_string(&mut msg, &dir, 0x3707)
+            .or_else(|| msg_string(&mut msg, &dir, 0x3704))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("attachment-{}", email.attachments.len() + 1));
+        let mime = msg_string(&mut msg, &dir, 0x370E).filter(|m| !m.is_empty());
+        email.attachments.push((name, mime, data));
+    }
+    Ok(email)
+}
+
+// ===============================
+// Summaries
+// ===============================
+
+/// Readable text of an HTML body: tags, scripts and styles dropped, common
+/// entities decoded, block ends turned into line breaks
+fn strip_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len() / 2);
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].to_ascii_lowercase();
+        rest = &rest[start + end + 1..];
+        let tag_name = tag.split([' ', '/', '\t', '\n']).find(|s| !s.is_empty());
+        match tag_name {
+            Some(name @ ("script" | "style")) if !tag.starts_with('/') => {
+                let close = format!("</{}", name);
+                rest = rest
+                    .to_ascii_lowercase()
+                    .find(&close)
+                    .map_or("", |i| &rest[i..]);
+            }
+            Some("br" | "p" | "div" | "tr" | "li" | "h1" | "h2" | "h3") => out.push('\n'),
+            _ => {}
+        }
+    }
+    out.push_str(rest);
+
+    let text = out
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let mut lines: Vec<&str> = text.lines().map(str::trim).collect();
+    lines.dedup_by(|a, b| a.is_empty() && b.is_empty());
+    lines.join("\n").trim().to_string()
+}
+
+fn read_email(path: &Path) -> io::Result<ParsedEmail> {
+    let format = EmailFormat::from_path(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "Not an email file"))?;
+    if fs::metadata(path)?.len() > MAX_EMAIL_FILE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Email file is too large",
+        ));
+    }
+    match format {
+        EmailFormat::Eml => Ok(parse_eml(&fs::read(path)?)),
+        EmailFormat::Msg => parse_msg(path),
+    }
+}
+
+/// Sender, subject, date and attachments of an email file; the body is only read
+/// into the summary when `with_body` is set
+pub fn email_summary(path: &Path, with_body: bool) -> io::Result<EmailSummary> {
+    let email = read_email(path)?;
+    let mut truncated = false;
+    let body = with_body
+        .then(|| {
+            email
+                .text
+                .filter(|t| !t.trim().is_empty())
+                .or_else(|| email.html.as_deref().map(strip_html))
+        })
+        .flatten()
+        .map(|mut body| {
+            if let Some((i, _)) = body.char_indices().nth(MAX_BODY_CHARS) {
+                body.truncate(i);
+                truncated = true;
+            }
+            body
+        });
+
+    Ok(EmailSummary {
+        format: email.format,
+        subject: email.subject,
+        from: email.from,
+        to: email.to,
+        cc: email.cc,
+        date: email.date,
+        timestamp: email.timestamp,
+        attachments: email
+            .attachments
+            .iter()
+            .enumerate()
+            .map(|(index, (name, mime_type, data))| EmailAttachment {
+                index,
+                name: name.clone(),
+                mime_type: mime_type.clone(),
+                size: data.len() as u64,
+            })
+            .collect(),
+        body,
+        truncated,
+    })
+}
+
+/// Searchable text of an email: its headers and body. `None` for other files or
+/// messages that can't be read.
+pub fn email_text(path: &Path) -> Option<String> {
+    let summary = email_summary(path, true).ok()?;
+    let fields = [
+        summary.subject,
+        summary.from,
+        summary.to,
+        summary.cc,
+        summary.body,
+    ];
+    Some(fields.into_iter().flatten().collect::<Vec<_>>().join("\n"))
+}
+
+/// Headers, body and attachment list of an .eml or .msg file
+#[tauri::command]
+pub async fn preview_email(path: String) -> Result<EmailSummary, String> {
+    let target = path.clone();
+    tauri::async_runtime::spawn_blocking(move || email_summary(Path::new(&target), true))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+/// Attachment name that is safe to create in `dest_dir`; names with path parts or
+/// characters the platform doesn't allow fall back to a numbered one
+fn attachment_file_name(name: &str, index: usize) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name).trim();
+    match validate_file_name(name) {
+        Ok(()) => name.to_string(),
+        Err(_) => format!("attachment-{}", index + 1),
+    }
+}
+
+/// Saves the attachments of an email file into `dest_dir`: all of them, or those at
+/// `indices` as listed by `preview_email`. Existing files are never overwritten;
+/// clashing names get a " (n)" suffix. Returns the paths written.
+#[tauri::command]
+pub async fn extract_email_attachments(
+    handle: AppHandle,
+    path: String,
+    dest_dir: String,
+    indices: Option<Vec<usize>>,
+) -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let email =
+            read_email(Path::new(&path)).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if let Some(bad) = indices
+            .iter()
+            .flatten()
+            .find(|&&i| i >= email.attachments.len())
+        {
+            return Err(format!("{} has no attachment {}", path, bad));
+        }
+
+        let dest = PathBuf::from(&dest_dir);
+        let staging = StagingDir::new(&handle, &dest)
+            .map_err(|e| format!("Failed to prepare {}: {}", dest_dir, e))?;
+        let mut written = Vec::new();
+        for (index, (name, _, data)) in email.attachments.iter().enumerate() {
+            if indices
+                .as_ref()
+                .is_some_and(|wanted| !wanted.contains(&index))
+            {
+                continue;
+            }
+            let name = attachment_file_name(name, index);
+            let staged = staging.path().join(format!("{}-{}", index, name));
+            let target = unique_path(&dest.join(&name));
+            fs::write(&staged, data)
+                .and_then(|()| staging.commit(&staged, &target, false))
+                .map_err(|e| format!("Failed to save {}: {}", name, e))?;
+            written.push(target.display().to_string());
+        }
+        Ok(written)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,606 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use jwalk::WalkDir;
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::filesys::kind::{file_kind, kind_from_extension, FileKind};
+use crate::search::{
+    archive::{archive_entry_path, archive_format, scan_archive},
+    content::{find_in_reader, ContentMatch, MAX_ARCHIVE_TEXT_ENTRY, MAX_CONTENT_FILE},
+    document::{document_text, DocumentFormat},
+    email::{email_text, EmailFormat},
+    matcher::FuzzyMatcher,
+    providers::SearchProvider,
+    scopes::resolve_search_roots,
+};
+use crate::util::{
+    caches::SharedAppSettings,
+    tags::{open_tags_db, tags_in_directory, Tag},
+};
+
+/// Results returned when the caller sets no limit
+const DEFAULT_MAX_RESULTS: usize = 5000;
+
+/// Entries walked in one root before moving on to the next, so every root
+/// gets a share of `max_results`
+const ROOT_BATCH: usize = 256;
+
+/// Tracks the active search so superseded or cancelled ones stop emitting
+pub struct SearchState {
+    pub current_id: AtomicU64,
+    pub cancelled: AtomicBool,
+    /// Hits of the most recent search, kept for `export_listing`
+    results: Mutex<(u64, Vec<SearchHit>)>,
+}
+
+impl SearchState {
+    /// Makes `request_id` the active search, implicitly superseding any older one
+    pub fn begin(&self, request_id: u64) {
+        self.current_id.store(request_id, Ordering::Relaxed);
+        self.cancelled.store(false, Ordering::Relaxed);
+        if let Ok(mut results) = self.results.lock() {
+            *results = (request_id, Vec::new());
+        }
+    }
+
+    /// True once `request_id` was cancelled or replaced
+    pub fn is_stale(&self, request_id: u64) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.current_id.load(Ordering::Relaxed) != request_id
+    }
+
+    /// Cancels `request_id` if it is still the active search
+    pub fn cancel(&self, request_id: u64) -> bool {
+        if self.current_id.load(Ordering::Relaxed) != request_id {
+            return false;
+        }
+        !self.cancelled.swap(true, Ordering::Relaxed)
+    }
+
+    /// Hits collected by search `request_id`, if it is still the latest one
+    pub fn results(&self, request_id: u64) -> Option<Vec<SearchHit>> {
+        let results = self.results.lock().ok()?;
+        (results.0 == request_id).then(|| results.1.clone())
+    }
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self {
+            current_id: AtomicU64::new(0),
+            cancelled: AtomicBool::new(false),
+            results: Mutex::new((0, Vec::new())),
+        }
+    }
+}
+
+/// Optional switches for `search_files`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Also match the query against the text of small files
+    pub content: bool,
+    pub include_hidden: bool,
+    /// Also list entries inside zip/tar/7z archives
+    pub include_archives: bool,
+    /// Also merge name matches from the OS search index (Windows Search, Spotlight, locate)
+    pub os_index: bool,
+    pub max_results: Option<usize>,
+    /// Only files of these kinds; empty means no kind filter (folders included)
+    pub kinds: Vec<FileKind>,
+    /// Also match names spelled in Latin letters, e.g. "moskva" finds "Москва"
+    pub transliterate: bool,
+    /// Only entries carrying every one of these tags; empty means no tag filter.
+    /// Archive entries cannot be tagged, so they never match a tag filter
+    pub tags: Vec<String>,
+}
+
+/// One search hit, streamed through `search-result`
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchHit {
+    /// Filesystem path, or `archive!entry` for entries inside archives
+    pub path: String,
+    /// The search root the hit was found under
+    pub root: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    pub date_modified: Option<SystemTime>,
+    pub kind: Option<FileKind>,
+    pub score: i64,
+    /// Containing archive, set for archive entries
+    pub archive: Option<String>,
+    pub content_match: Option<ContentMatch>,
+    /// OS index that reported the hit; `None` for Dagger's own walker
+    pub provider: Option<SearchProvider>,
+}
+
+/// Walks the search roots and streams entries whose name fuzzily matches `query`
+/// (or whose text contains it, with `options.content`). Roots come from the saved
+/// `scope`, else `roots`, else `root`; several roots are walked in turns and their
+/// hits merged into one stream. Cancel with `cancel_search(request_id)`; starting
+/// a new search supersedes the previous one.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_files(
+    handle: AppHandle,
+    state: State<'_, Arc<SearchState>>,
+    pool: State<'_, Arc<rayon::ThreadPool>>,
+    settings: State<'_, SharedAppSettings>,
+    root: Option<String>,
+    roots: Option<Vec<String>>,
+    scope: Option<String>,
+    query: String,
+    options: Option<SearchOptions>,
+    request_id: u64,
+) -> Result<(), String> {
+    let roots = resolve_search_roots(&settings, root, roots, scope).await?;
+    let options = options.unwrap_or_default();
+    let matcher = FuzzyMatcher::new(&query).with_transliteration(&query, options.transliterate);
+    if matcher.is_empty() {
+        return Err("Search query is empty".into());
+    }
+
+    let tag_filter = TagFilter::new(&handle, &options)?;
+
+    state.begin(request_id);
+    let run = SearchRun {
+        handle: &handle,
+        state: &state,
+        request_id,
+        matcher,
+        needle: query.trim().to_lowercase(),
+        options: &options,
+        tag_filter: tag_filter.as_ref(),
+    };
+
+    let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    let mut found: usize = 0;
+    let mut scanned: u64 = 0;
+
+    // index hits come back first; the walkers then only add what the index missed
+    let mut indexed: HashSet<PathBuf> = HashSet::new();
+    if options.os_index {
+        for root in &roots {
+            found = run.search_providers(root, max_results, &mut indexed);
+        }
+    }
+
+    let mut walkers: Vec<_> = roots
+        .iter()
+        .map(|root| {
+            let walker = WalkDir::new(root)
+                .follow_links(false)
+                .skip_hidden(!options.include_hidden)
+                .parallelism(jwalk::Parallelism::RayonExistingPool {
+                    pool: pool.inner().clone(),
+                    busy_timeout: Some(Duration::from_millis(20)),
+                });
+            (root, walker.into_iter().skip(1))
+        })
+        .collect();
+
+    'walk: while !walkers.is_empty() {
+        let mut i = 0;
+        while i < walkers.len() {
+            let (root, walker) = &mut walkers[i];
+            let mut exhausted = true;
+            for entry in walker.by_ref().take(ROOT_BATCH) {
+                exhausted = false;
+                if state.is_stale(request_id) {
+                    let _ = handle.emit(
+                        "search-cancelled",
+                        serde_json::json!({ "request_id": request_id }),
+                    );
+                    return Ok(());
+                }
+                if found >= max_results {
+                    break 'walk;
+                }
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                scanned += 1;
+                found += run.visit(root, &entry, &indexed, max_results - found);
+            }
+
+            if exhausted {
+                drop(walkers.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    if state.is_stale(request_id) {
+        let _ = handle.emit(
+            "search-cancelled",
+            serde_json::json!({ "request_id": request_id }),
+        );
+        return Ok(());
+    }
+
+    // keep the merged results ranked for export_listing
+    if let Ok(mut results) = state.results.lock() {
+        if results.0 == request_id {
+            results.1.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+        }
+    }
+
+    let _ = handle.emit(
+        "search-complete",
+        serde_json::json!({
+            "request_id": request_id,
+            "results": found,
+            "scanned": scanned,
+            "roots": roots,
+            "truncated": found >= max_results,
+        }),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_search(
+    handle: AppHandle,
+    state: State<'_, Arc<SearchState>>,
+    request_id: u64,
+) -> bool {
+    let cancelled = state.cancel(request_id);
+    if cancelled {
+        let _ = handle.emit(
+            "search-cancelled",
+            serde_json::json!({ "request_id": request_id }),
+        );
+    }
+    cancelled
+}
+
+/// Per-search context shared by the walker and the archive scanner
+struct SearchRun<'a> {
+    handle: &'a AppHandle,
+    state: &'a SearchState,
+    request_id: u64,
+    matcher: FuzzyMatcher,
+    /// Lowercased query used for content matching
+    needle: String,
+    options: &'a SearchOptions,
+    /// Set when `options.tags` is not empty
+    tag_filter: Option<&'a TagFilter>,
+}
+
+impl SearchRun<'_> {
+    /// Matches one walked entry (and, with `include_archives`, what's inside it).
+    /// Returns the hit count.
+    fn visit(
+        &self,
+        root: &Path,
+        entry: &jwalk::DirEntry<((), ())>,
+        indexed: &HashSet<PathBuf>,
+        limit: usize,
+    ) -> usize {
+        let path = entry.path();
+        let is_dir = entry.file_type().is_dir();
+        let meta = entry.metadata().ok();
+        let mut found = 0;
+
+        if !indexed.contains(&path) && self.matches_tags(&path) {
+            let hit = match_entry(
+                &self.matcher,
+                &self.needle,
+                self.options,
+                root,
+                &path,
+                is_dir,
+                meta.as_ref(),
+            );
+            if let Some(hit) = hit {
+                found += 1;
+                self.emit(hit);
+            }
+        }
+
+        if self.options.include_archives && self.tag_filter.is_none() && !is_dir && found < limit {
+            found += self.search_archive(root, &path, limit - found);
+        }
+        found
+    }
+
+    /// Whether an entry of `kind` passes `options.kinds`
+    fn matches_kind(&self, kind: Option<FileKind>) -> bool {
+        kind_allowed(self.options, kind)
+    }
+
+    /// Whether `path` carries every tag in `options.tags`
+    fn matches_tags(&self, path: &Path) -> bool {
+        self.tag_filter.is_none_or(|filter| filter.allows(path))
+    }
+
+    fn emit(&self, hit: SearchHit) {
+        if self.state.is_stale(self.request_id) {
+            return;
+        }
+        let _ = self.handle.emit(
+            "search-result",
+            serde_json::json!({
+                "request_id": self.request_id,
+                "result": hit,
+            }),
+        );
+        if let Ok(mut results) = self.state.results.lock() {
+            if results.0 == self.request_id {
+                results.1.push(hit);
+            }
+        }
+    }
+
+    /// Emits name matches reported by the platform's OS indexes, recording their
+    /// paths in `indexed`. Returns the hit count.
+    fn search_providers(&self, root: &Path, limit: usize, indexed: &mut HashSet<PathBuf>) -> usize {
+        let query = self.needle.as_str();
+        for &provider in SearchProvider::for_platform() {
+            if indexed.len() >= limit || self.state.is_stale(self.request_id) {
+                break;
+            }
+            if !provider.available() {
+                continue;
+            }
+
+            let paths = match provider.query(root, query, limit - indexed.len()) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    tracing::warn!(target: "search", "{} unavailable: {}", provider.name(), e);
+                    continue;
+                }
+            };
+            for path in paths {
+                if !self.options.include_hidden && is_hidden_below(root, &path) {
+                    continue;
+                }
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let Some(score) = self.matcher.score(&name) else {
+                    continue;
+                };
+                // the index may still list files that were deleted since
+                let Ok(meta) = fs::symlink_metadata(&path) else {
+                    continue;
+                };
+                let kind = if meta.is_dir() {
+                    None
+                } else {
+                    file_kind(&path, !self.options.kinds.is_empty())
+                };
+                if !self.matches_kind(kind) || !self.matches_tags(&path) {
+                    continue;
+                }
+                if !indexed.insert(path.clone()) {
+                    continue;
+                }
+                self.emit(SearchHit {
+                    path: path.display().to_string(),
+                    root: root.display().to_string(),
+                    name,
+                    is_dir: meta.is_dir(),
+                    size: (!meta.is_dir()).then_some(meta.len()),
+                    date_modified: meta.modified().ok(),
+                    kind,
+                    score,
+                    archive: None,
+                    content_match: None,
+                    provider: Some(provider),
+                });
+            }
+        }
+        indexed.len()
+    }
+
+    /// Matches entries inside `path` if it is a supported archive. Returns the hit count.
+    fn search_archive(&self, root: &Path, path: &Path, limit: usize) -> usize {
+        let Some(format) = archive_format(path) else {
+            return 0;
+        };
+
+        let mut found = 0;
+        let result = scan_archive(
+            path,
+            format,
+            self.options.content,
+            MAX_ARCHIVE_TEXT_ENTRY,
+            |entry, reader: Option<&mut dyn Read>| {
+                if found >= limit || self.state.is_stale(self.request_id) {
+                    return false;
+                }
+
+                let name = entry.path.rsplit('/').next().unwrap_or(&entry.path);
+                let kind = if entry.is_dir {
+                    None
+                } else {
+                    kind_from_extension(Path::new(name))
+                };
+                if !self.matches_kind(kind) {
+                    return true;
+                }
+                let name_score = self.matcher.score(name);
+                let content_match = match (name_score, reader) {
+                    (None, Some(reader)) => {
+                        find_in_reader(reader, &self.needle, MAX_ARCHIVE_TEXT_ENTRY)
+                            .ok()
+                            .flatten()
+                    }
+                    _ => None,
+                };
+
+                if name_score.is_some() || content_match.is_some() {
+                    found += 1;
+                    self.emit(SearchHit {
+                        path: archive_entry_path(path, &entry.path),
+                        root: root.display().to_string(),
+                        name: name.to_string(),
+                        is_dir: entry.is_dir,
+                        size: (!entry.is_dir).then_some(entry.size),
+                        date_modified: None,
+                        kind,
+                        score: name_score.unwrap_or(0),
+                        archive: Some(path.display().to_string()),
+                        content_match,
+                        provider: None,
+                    });
+                }
+                true
+            },
+        );
+
+        if let Err(e) = result {
+            tracing::warn!(target: "search", "Could not read archive {}: {}", path.display(), e);
+        }
+        found
+    }
+}
+
+/// Tag filter for `options.tags`. Tags are fetched with one query per directory
+/// the first time an entry in it is checked, then served from the cache.
+pub(crate) struct TagFilter {
+    /// Wanted tag names, lowercased since tag names are case-insensitive
+    wanted: Vec<String>,
+    conn: Connection,
+    /// Lowercased tag names by path, per directory looked up so far
+    dirs: Mutex<HashMap<PathBuf, HashMap<String, Vec<String>>>>,
+}
+
+impl TagFilter {
+    /// `None` when `options.tags` is empty
+    pub(crate) fn new(handle: &AppHandle, options: &SearchOptions) -> Result<Option<Self>, String> {
+        let wanted: Vec<String> = options
+            .tags
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if wanted.is_empty() {
+            return Ok(None);
+        }
+        let conn = open_tags_db(handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+        Ok(Some(Self {
+            wanted,
+            conn,
+            dirs: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Whether `path` carries every wanted tag
+    pub(crate) fn allows(&self, path: &Path) -> bool {
+        let Some(dir) = path.parent() else {
+            return false;
+        };
+        let Ok(mut dirs) = self.dirs.lock() else {
+            return false;
+        };
+        let tagged = dirs.entry(dir.to_path_buf()).or_insert_with(|| {
+            tags_in_directory(&self.conn, &dir.to_string_lossy())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(path, tags)| {
+                    let names = tags
+                        .into_iter()
+                        .map(|Tag { name, .. }| name.to_lowercase())
+                        .collect();
+                    (path, names)
+                })
+                .collect()
+        });
+        tagged
+            .get(path.to_string_lossy().as_ref())
+            .is_some_and(|names| self.wanted.iter().all(|w| names.contains(w)))
+    }
+}
+
+/// Whether an entry of `kind` passes `options.kinds`
+fn kind_allowed(options: &SearchOptions, kind: Option<FileKind>) -> bool {
+    options.kinds.is_empty() || kind.is_some_and(|k| options.kinds.contains(&k))
+}
+
+/// The hit for one filesystem entry below `root` if its name (or, with
+/// `options.content`, its text) matches; `None` otherwise
+pub(crate) fn match_entry(
+    matcher: &FuzzyMatcher,
+    needle: &str,
+    options: &SearchOptions,
+    root: &Path,
+    path: &Path,
+    is_dir: bool,
+    meta: Option<&fs::Metadata>,
+) -> Option<SearchHit> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+
+    // magic bytes are only read when a kind filter needs them
+    let kind = if is_dir {
+        None
+    } else {
+        file_kind(path, !options.kinds.is_empty())
+    };
+    if !kind_allowed(options, kind) {
+        return None;
+    }
+    let name_score = matcher.score(&name);
+    let content_match = match name_score {
+        // Office documents are zipped XML and emails are encoded; search their
+        // extracted text instead
+        None if options.content && !is_dir && DocumentFormat::from_path(path).is_some() => {
+            document_text(path)
+                .and_then(|text| find_in_reader(text.as_bytes(), needle, MAX_CONTENT_FILE).ok())
+                .flatten()
+        }
+        None if options.content && !is_dir && EmailFormat::from_path(path).is_some() => {
+            email_text(path)
+                .and_then(|text| find_in_reader(text.as_bytes(), needle, MAX_CONTENT_FILE).ok())
+                .flatten()
+        }
+        None if options.content && !is_dir => meta
+            .filter(|m| m.len() <= MAX_CONTENT_FILE)
+            .and_then(|_| File::open(path).ok())
+            .and_then(|f| find_in_reader(f, needle, MAX_CONTENT_FILE).ok())
+            .flatten(),
+        _ => None,
+    };
+    if name_score.is_none() && content_match.is_none() {
+        return None;
+    }
+
+    Some(SearchHit {
+        path: path.display().to_string(),
+        root: root.display().to_string(),
+        name,
+        is_dir,
+        size: meta.filter(|_| !is_dir).map(|m| m.len()),
+        date_modified: meta.and_then(|m| m.modified().ok()),
+        kind,
+        // content-only hits rank below every name hit
+        score: name_score.unwrap_or(0),
+        archive: None,
+        content_match,
+        provider: None,
+    })
+}
+
+/// True when any component of `path` below `root` is a dotfile, matching the walker's hidden rule
+pub(crate) fn is_hidden_below(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root).is_ok_and(|rel| {
+        rel.components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+    })
+}
@@ -0,0 +1,130 @@
+use crate::search::translit::transliterate;
+
+/// Case-insensitive fuzzy matcher for file names.
+///
+/// A contiguous match always ranks above a scattered one; matches at the start of
+/// the name or of a word (after `_`, `-`, `.`, space or a camelCase hump) get a bonus.
+/// With transliteration on, names and the query are also compared spelled in Latin
+/// letters, so "moskva" finds "Москва" and "Москва" finds "moskva".
+pub struct FuzzyMatcher {
+    needle: Vec<char>,
+    /// The query spelled in Latin letters, when transliteration is on and it differs
+    latin_needle: Option<Vec<char>>,
+    transliterate: bool,
+}
+
+/// Base score of a contiguous (substring) match
+const SUBSTRING_SCORE: i64 = 1000;
+
+/// Base score of a subsequence match
+const SUBSEQUENCE_SCORE: i64 = 500;
+
+/// Subtracted from transliterated matches so same-script matches rank first
+const TRANSLIT_PENALTY: i64 = 50;
+
+fn lowercase_chars(text: &str) -> Vec<char> {
+    text.trim().chars().flat_map(char::to_lowercase).collect()
+}
+
+impl FuzzyMatcher {
+    pub fn new(query: &str) -> Self {
+        Self {
+            needle: lowercase_chars(query),
+            latin_needle: None,
+            transliterate: false,
+        }
+    }
+
+    /// Also matches names and the query spelled in Latin letters
+    pub fn with_transliteration(mut self, query: &str, enabled: bool) -> Self {
+        self.transliterate = enabled;
+        self.latin_needle = transliterate(query)
+            .filter(|_| enabled)
+            .map(|latin| lowercase_chars(&latin));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.needle.is_empty()
+    }
+
+    /// Returns `None` if `name` does not contain every query character in order
+    pub fn score(&self, name: &str) -> Option<i64> {
+        if self.needle.is_empty() {
+            return Some(0);
+        }
+
+        let direct = score_with(&self.needle, name);
+        if !self.transliterate {
+            return direct;
+        }
+        // the query as typed only needs a second try if the name changed
+        let latin_name = transliterate(name);
+        let needles = latin_name
+            .as_ref()
+            .map(|_| &self.needle)
+            .into_iter()
+            .chain(self.latin_needle.as_ref());
+        let hay = latin_name.as_deref().unwrap_or(name);
+        needles
+            .filter_map(|needle| score_with(needle, hay))
+            .map(|score| score - TRANSLIT_PENALTY)
+            .chain(direct)
+            .max()
+    }
+}
+
+/// Scores one (lowercase) needle against `name`
+fn score_with(needle: &[char], name: &str) -> Option<i64> {
+    let original: Vec<char> = name.chars().collect();
+    let hay: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let n = needle.len();
+    if n > hay.len() {
+        return None;
+    }
+
+    // shorter names are a closer match for the same query
+    let length_penalty = (hay.len() - n) as i64;
+
+    if let Some(pos) = (0..=hay.len() - n).find(|&i| hay[i..i + n] == needle[..]) {
+        let mut score = SUBSTRING_SCORE - pos as i64 - length_penalty;
+        if pos == 0 {
+            score += 200;
+        } else if is_word_start(&original, pos) {
+            score += 100;
+        }
+        return Some(score);
+    }
+
+    // greedy subsequence: reward consecutive runs and word starts, punish gaps
+    let mut score = SUBSEQUENCE_SCORE - length_penalty;
+    let mut next = 0;
+    let mut prev: Option<usize> = None;
+    for &c in needle {
+        let pos = (next..hay.len()).find(|&i| hay[i] == c)?;
+        match prev {
+            Some(p) if pos == p + 1 => score += 15,
+            Some(p) => score -= (pos - p - 1) as i64 * 3,
+            None => score -= pos as i64 * 3,
+        }
+        if is_word_start(&original, pos) {
+            score += 20;
+        }
+        prev = Some(pos);
+        next = pos + 1;
+    }
+    Some(score)
+}
+
+fn is_word_start(chars: &[char], i: usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+    let (prev, cur) = (chars[i - 1], chars[i]);
+    matches!(prev, '_' | '-' | '.' | ' ' | '/' | '\\')
+        || (prev.is_lowercase() && cur.is_uppercase())
+        || (!prev.is_ascii_digit() && cur.is_ascii_digit())
+}
@@ -1,7 +1,16 @@
 pub mod audio;
 pub mod document;
+pub mod email;
 pub mod image;
 pub mod text;
 
+pub mod archive;
+pub mod content;
 pub mod driver;
+pub mod engine;
+pub mod matcher;
 pub mod modals;
+pub mod providers;
+pub mod scopes;
+pub mod smart;
+pub mod translit;
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// An OS file index a search can be delegated to
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchProvider {
+    /// Windows Search, queried through its OLE DB provider
+    WindowsSearch,
+    /// Spotlight, via `mdfind`
+    Spotlight,
+    /// `plocate` (or plain `locate`) databases
+    Locate,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProviderInfo {
+    pub id: SearchProvider,
+    pub name: String,
+    pub available: bool,
+}
+
+impl SearchProvider {
+    /// Providers that can exist on this platform
+    pub fn for_platform() -> &'static [SearchProvider] {
+        if cfg!(windows) {
+            &[SearchProvider::WindowsSearch]
+        } else if cfg!(target_os = "macos") {
+            &[SearchProvider::Spotlight]
+        } else {
+            &[SearchProvider::Locate]
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchProvider::WindowsSearch => "Windows Search",
+            SearchProvider::Spotlight => "Spotlight",
+            SearchProvider::Locate => "locate",
+        }
+    }
+
+    /// Whether the provider's tooling is installed. Windows Search can still be
+    /// disabled as a service, in which case `query` fails and the walker carries on alone.
+    pub fn available(self) -> bool {
+        match self {
+            SearchProvider::WindowsSearch => cfg!(windows),
+            SearchProvider::Spotlight => find_executable("mdfind").is_some(),
+            SearchProvider::Locate => locate_executable().is_some(),
+        }
+    }
+
+    /// Paths below `root` whose name contains `query`, as far as the index knows.
+    /// Indexes lag behind the disk, so callers should stat what comes back.
+    pub fn query(self, root: &Path, query: &str, limit: usize) -> Result<Vec<PathBuf>, String> {
+        let output = match self {
+            SearchProvider::WindowsSearch => windows_search_command(root, query, limit),
+            SearchProvider::Spotlight => {
+                let mut cmd = Command::new("mdfind");
+                cmd.arg("-onlyin").arg(root).arg("-name").arg(query);
+                cmd
+            }
+            SearchProvider::Locate => {
+                let executable = locate_executable().ok_or("locate is not installed")?;
+                let mut cmd = Command::new(executable);
+                // locate matches whole paths, so over-fetch and filter by root below
+                cmd.args(["-i", "-e", "-l", &(limit * 4).to_string(), query]);
+                cmd
+            }
+        }
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", self.name(), e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "{} query failed: {}",
+                self.name(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let needle = query.to_lowercase();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(PathBuf::from)
+            .filter(|path| path.starts_with(root) && path != root)
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|n| n.to_string_lossy().to_lowercase().contains(&needle))
+            })
+            .take(limit)
+            .collect())
+    }
+}
+
+/// Lists the OS search indexes Dagger can merge into its own search results
+#[tauri::command]
+pub fn list_search_providers() -> Vec<ProviderInfo> {
+    SearchProvider::for_platform()
+        .iter()
+        .map(|&provider| ProviderInfo {
+            id: provider,
+            name: provider.name().to_string(),
+            available: provider.available(),
+        })
+        .collect()
+}
+
+fn find_executable(name: &str) -> Option<PathBuf> {
+    env::var_os("PATH")
+        .map(|p| env::split_paths(&p).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|dir| dir.join(name))
+        .find(|p| p.is_file())
+}
+
+/// Prefers plocate, falling back to mlocate/busybox `locate`
+fn locate_executable() -> Option<PathBuf> {
+    find_executable("plocate").or_else(|| find_executable("locate"))
+}
+
+/// Builds a PowerShell call that runs a SYSTEMINDEX query over ADO. The SQL is
+/// handed over through the environment so the query needs no PowerShell quoting.
+fn windows_search_command(root: &Path, query: &str, limit: usize) -> Command {
+    // LIKE wildcards in the query match literally inside brackets
+    let pattern: String = query
+        .chars()
+        .map(|c| match c {
+            '%' | '_' | '[' => format!("[{}]", c),
+            '\'' => "''".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    let scope = root.display().to_string().replace('\'', "''");
+    let sql = format!(
+        "SELECT TOP {} System.ItemPathDisplay FROM SYSTEMINDEX \
+         WHERE SCOPE='file:{}' AND System.FileName LIKE '%{}%'",
+        limit, scope, pattern
+    );
+    let script = "[Console]::OutputEncoding = [Text.Encoding]::UTF8; \
+        $c = New-Object -ComObject ADODB.Connection; \
+        $c.Open(\"Provider=Search.CollatorDSO;Extended Properties='Application=Windows';\"); \
+        $r = $c.Execute($env:DAGGER_SEARCH_SQL); \
+        while (-not $r.EOF) { $r.Fields.Item('System.ItemPathDisplay').Value; $r.MoveNext() }; \
+        $c.Close()";
+
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .env("DAGGER_SEARCH_SQL", sql);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd
+}
@@ -0,0 +1,86 @@
+use std::{collections::BTreeMap, path::PathBuf};
+use tauri::{AppHandle, State};
+
+use crate::util::caches::SharedAppSettings;
+
+/// Search roots from a saved scope, an explicit list, or a single root, in that order
+/// of preference. Roots nested inside another root are dropped so nothing is walked twice.
+pub async fn resolve_search_roots(
+    settings: &SharedAppSettings,
+    root: Option<String>,
+    roots: Option<Vec<String>>,
+    scope: Option<String>,
+) -> Result<Vec<PathBuf>, String> {
+    let requested: Vec<String> = match scope {
+        Some(name) => settings
+            .0
+            .read()
+            .await
+            .search_scopes
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| format!("No search scope named \"{}\"", name))?,
+        None => roots.unwrap_or_default().into_iter().chain(root).collect(),
+    };
+
+    let mut resolved: Vec<PathBuf> = Vec::new();
+    for root in requested {
+        let path = PathBuf::from(&root);
+        if !path.is_dir() {
+            return Err(format!("Not a directory: {}", root));
+        }
+        let path = dunce::canonicalize(&path).unwrap_or(path);
+        if resolved.iter().any(|r| path.starts_with(r)) {
+            continue;
+        }
+        resolved.retain(|r| !r.starts_with(&path));
+        resolved.push(path);
+    }
+
+    if resolved.is_empty() {
+        return Err("No search location given".into());
+    }
+    Ok(resolved)
+}
+
+#[tauri::command]
+pub async fn get_search_scopes(
+    settings: State<'_, SharedAppSettings>,
+) -> Result<BTreeMap<String, Vec<String>>, String> {
+    Ok(settings.0.read().await.search_scopes.clone())
+}
+
+/// Saves (or replaces) a named set of search roots, e.g. "Work" = C:\Projects + D:\Clients
+#[tauri::command]
+pub async fn save_search_scope(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    name: String,
+    roots: Vec<String>,
+) -> Result<BTreeMap<String, Vec<String>>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Scope name cannot be empty".into());
+    }
+    if roots.is_empty() {
+        return Err("A scope needs at least one folder".into());
+    }
+    if let Some(missing) = roots.iter().find(|r| !PathBuf::from(r).is_dir()) {
+        return Err(format!("Not a directory: {}", missing));
+    }
+
+    settings.0.write().await.search_scopes.insert(name, roots);
+    settings.commit(&handle).await;
+    Ok(settings.0.read().await.search_scopes.clone())
+}
+
+#[tauri::command]
+pub async fn delete_search_scope(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    name: String,
+) -> Result<BTreeMap<String, Vec<String>>, String> {
+    settings.0.write().await.search_scopes.remove(&name);
+    settings.commit(&handle).await;
+    Ok(settings.0.read().await.search_scopes.clone())
+}
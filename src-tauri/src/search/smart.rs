@@ -0,0 +1,352 @@
+use notify::{
+    event::{CreateKind, ModifyKind, RenameMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use jwalk::WalkDir;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::search::{
+    engine::{
+        is_hidden_below, match_entry, search_files, SearchHit, SearchOptions, SearchState,
+        TagFilter,
+    },
+    matcher::FuzzyMatcher,
+    scopes::resolve_search_roots,
+};
+use crate::util::caches::SharedAppSettings;
+
+/// Quiet period that ends a burst of file changes before results are updated
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Longest a steady stream of changes can hold back an update
+const MAX_UPDATE_DELAY: Duration = Duration::from_secs(2);
+
+/// Entries checked inside a folder that was created or moved in
+const MAX_NEW_FOLDER_ENTRIES: usize = 10_000;
+
+/// A saved search shown as a folder whose results stay live while open
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct SmartFolder {
+    pub query: String,
+    /// Saved search scope to look in; otherwise `roots`
+    pub scope: Option<String>,
+    pub roots: Vec<String>,
+    pub options: SearchOptions,
+}
+
+/// Paths shown in an open smart folder, so removals are only reported for them
+type ShownPaths = Arc<Mutex<HashSet<PathBuf>>>;
+
+/// Watchers of open smart folders by request id; dropping one stops its updates
+#[derive(Default)]
+pub struct SmartFolderState {
+    watchers: Mutex<HashMap<u64, RecommendedWatcher>>,
+}
+
+/// A changed path and whether it just appeared (created or renamed into place)
+type Change = (PathBuf, bool);
+
+fn changes_of(event: Event) -> Vec<Change> {
+    let appeared = matches!(
+        event.kind,
+        EventKind::Create(CreateKind::Folder | CreateKind::Any)
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Both))
+    );
+    event.paths.into_iter().map(|p| (p, appeared)).collect()
+}
+
+/// Everything matching below a folder that was created or moved into a root
+fn hits_below(
+    dir: &Path,
+    root: &Path,
+    matcher: &FuzzyMatcher,
+    needle: &str,
+    options: &SearchOptions,
+    tag_filter: Option<&TagFilter>,
+) -> Vec<SearchHit> {
+    WalkDir::new(dir)
+        .follow_links(false)
+        .skip_hidden(!options.include_hidden)
+        .min_depth(1)
+        .into_iter()
+        .take(MAX_NEW_FOLDER_ENTRIES)
+        .flatten()
+        .filter(|entry| tag_filter.is_none_or(|filter| filter.allows(&entry.path())))
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok();
+            let is_dir = entry.file_type().is_dir();
+            match_entry(
+                matcher,
+                needle,
+                options,
+                root,
+                &entry.path(),
+                is_dir,
+                meta.as_ref(),
+            )
+        })
+        .collect()
+}
+
+/// Re-checks changed paths against the smart folder's query and emits
+/// `smart-folder-update` with the hits that appeared or changed and the shown paths that
+/// went away or stopped matching. A removed folder takes its shown entries with it.
+fn run_update_worker(
+    handle: AppHandle,
+    request_id: u64,
+    roots: Vec<PathBuf>,
+    folder: SmartFolder,
+    shown: ShownPaths,
+    rx: mpsc::Receiver<Vec<Change>>,
+) {
+    let matcher = FuzzyMatcher::new(&folder.query)
+        .with_transliteration(&folder.query, folder.options.transliterate);
+    let needle = folder.query.trim().to_lowercase();
+
+    // the channel closes once the watcher is dropped by `close_smart_folder`
+    while let Ok(first) = rx.recv() {
+        let mut changed: BTreeMap<PathBuf, bool> = BTreeMap::new();
+        let started = Instant::now();
+        let mut batch = first;
+        loop {
+            for (path, appeared) in batch {
+                *changed.entry(path).or_default() |= appeared;
+            }
+            if started.elapsed() >= MAX_UPDATE_DELAY {
+                break;
+            }
+            match rx.recv_timeout(UPDATE_DEBOUNCE) {
+                Ok(more) => batch = more,
+                Err(_) => break,
+            }
+        }
+
+        // opened per batch so tags changed while the folder is open are picked up
+        let tag_filter = match TagFilter::new(&handle, &folder.options) {
+            Ok(filter) => filter,
+            Err(e) => {
+                tracing::warn!(target: "search", "Smart folder tag filter unavailable: {}", e);
+                continue;
+            }
+        };
+        let mut added = Vec::new();
+        let mut removed: BTreeSet<String> = BTreeSet::new();
+        let mut shown = shown.lock().unwrap();
+        for (path, appeared) in changed {
+            let Some(root) = roots.iter().find(|r| path.starts_with(r) && path != **r) else {
+                continue;
+            };
+            if !folder.options.include_hidden && is_hidden_below(root, &path) {
+                continue;
+            }
+
+            let Ok(meta) = fs::symlink_metadata(&path) else {
+                // gone: drop it and anything shown below it
+                shown.retain(|p| {
+                    let below = p.starts_with(&path);
+                    if below {
+                        removed.insert(p.display().to_string());
+                    }
+                    !below
+                });
+                continue;
+            };
+
+            let tagged = tag_filter
+                .as_ref()
+                .is_none_or(|filter| filter.allows(&path));
+            let hit = tagged
+                .then(|| {
+                    match_entry(
+                        &matcher,
+                        &needle,
+                        &folder.options,
+                        root,
+                        &path,
+                        meta.is_dir(),
+                        Some(&meta),
+                    )
+                })
+                .flatten();
+            match hit {
+                Some(hit) => {
+                    shown.insert(path.clone());
+                    added.push(hit);
+                }
+                None => {
+                    if shown.remove(&path) {
+                        removed.insert(path.display().to_string());
+                    }
+                }
+            }
+            if appeared && meta.is_dir() {
+                for hit in hits_below(
+                    &path,
+                    root,
+                    &matcher,
+                    &needle,
+                    &folder.options,
+                    tag_filter.as_ref(),
+                ) {
+                    shown.insert(PathBuf::from(&hit.path));
+                    added.push(hit);
+                }
+            }
+        }
+        drop(shown);
+
+        if added.is_empty() && removed.is_empty() {
+            continue;
+        }
+        let _ = handle.emit(
+            "smart-folder-update",
+            serde_json::json!({
+                "request_id": request_id,
+                "added": added,
+                "removed": removed,
+            }),
+        );
+    }
+}
+
+#[tauri::command]
+pub async fn get_smart_folders(
+    settings: State<'_, SharedAppSettings>,
+) -> Result<BTreeMap<String, SmartFolder>, String> {
+    Ok(settings.0.read().await.smart_folders.clone())
+}
+
+/// Saves (or replaces) a named smart folder
+#[tauri::command]
+pub async fn save_smart_folder(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    name: String,
+    folder: SmartFolder,
+) -> Result<BTreeMap<String, SmartFolder>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Smart folder name cannot be empty".into());
+    }
+    if folder.query.trim().is_empty() {
+        return Err("Search query is empty".into());
+    }
+    if folder.scope.is_none() && folder.roots.is_empty() {
+        return Err("No search location given".into());
+    }
+
+    settings.0.write().await.smart_folders.insert(name, folder);
+    settings.commit(&handle).await;
+    Ok(settings.0.read().await.smart_folders.clone())
+}
+
+#[tauri::command]
+pub async fn delete_smart_folder(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    name: String,
+) -> Result<BTreeMap<String, SmartFolder>, String> {
+    settings.0.write().await.smart_folders.remove(&name);
+    settings.commit(&handle).await;
+    Ok(settings.0.read().await.smart_folders.clone())
+}
+
+/// Opens a smart folder: runs its search like `search_files` (same events, same
+/// `request_id`) and then keeps the results live through `smart-folder-update`
+/// until `close_smart_folder`. Watching starts before the walk so nothing that
+/// changes during it is missed.
+#[tauri::command]
+pub async fn open_smart_folder(
+    handle: AppHandle,
+    state: State<'_, Arc<SmartFolderState>>,
+    search: State<'_, Arc<SearchState>>,
+    pool: State<'_, Arc<rayon::ThreadPool>>,
+    settings: State<'_, SharedAppSettings>,
+    name: String,
+    request_id: u64,
+) -> Result<(), String> {
+    let folder = settings
+        .0
+        .read()
+        .await
+        .smart_folders
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| format!("No smart folder named \"{}\"", name))?;
+    let roots = resolve_search_roots(
+        &settings,
+        None,
+        Some(folder.roots.clone()),
+        folder.scope.clone(),
+    )
+    .await?;
+
+    let (tx, rx) = mpsc::channel::<Vec<Change>>();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: notify::Result<Event>| match res {
+            Ok(event) => {
+                let _ = tx.send(changes_of(event));
+            }
+            Err(e) => tracing::warn!(target: "search", "smart folder watch error: {:?}", e),
+        },
+        notify::Config::default(),
+    )
+    .map_err(|e| format!("Failed to watch smart folder: {}", e))?;
+    for root in &roots {
+        if let Err(e) = watcher.watch(root, RecursiveMode::Recursive) {
+            tracing::warn!(target: "search", "Failed to watch {}: {:?}", root.display(), e);
+        }
+    }
+
+    let shown: ShownPaths = Arc::default();
+    state.watchers.lock().unwrap().insert(request_id, watcher);
+    {
+        let (handle, roots, folder, shown) =
+            (handle.clone(), roots.clone(), folder.clone(), shown.clone());
+        std::thread::spawn(move || run_update_worker(handle, request_id, roots, folder, shown, rx));
+    }
+
+    let roots: Vec<String> = roots.iter().map(|r| r.display().to_string()).collect();
+    let result = search_files(
+        handle,
+        search.clone(),
+        pool,
+        settings,
+        None,
+        Some(roots),
+        None,
+        folder.query,
+        Some(folder.options),
+        request_id,
+    )
+    .await;
+
+    // what the walk found is what later changes are measured against
+    if let Some(hits) = search.results(request_id) {
+        shown.lock().unwrap().extend(
+            hits.into_iter()
+                .filter(|h| h.archive.is_none())
+                .map(|h| PathBuf::from(h.path)),
+        );
+    }
+    if result.is_err() {
+        state.watchers.lock().unwrap().remove(&request_id);
+    }
+    result
+}
+
+/// Stops live updates of an open smart folder
+#[tauri::command]
+pub fn close_smart_folder(state: State<'_, Arc<SmartFolderState>>, request_id: u64) {
+    state.watchers.lock().unwrap().remove(&request_id);
+}
@@ -0,0 +1,75 @@
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Entries kept in memory for the activity panel
+const MAX_ACTIVITY_ENTRIES: usize = 200;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityLevel {
+    Info,
+    Error,
+}
+
+/// One line in the activity panel, optionally with captured process output
+#[derive(Serialize, Clone, Debug)]
+pub struct ActivityEntry {
+    pub timestamp: u64,
+    /// Subsystem that produced the entry, e.g. "hook"
+    pub source: String,
+    pub level: ActivityLevel,
+    pub message: String,
+    pub output: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ActivityLog(pub Mutex<VecDeque<ActivityEntry>>);
+
+/// Appends to the activity log and emits `activity-logged`
+pub fn log_activity(
+    handle: &AppHandle,
+    source: &str,
+    level: ActivityLevel,
+    message: String,
+    output: Option<String>,
+) {
+    match level {
+        ActivityLevel::Info => tracing::info!(target: "activity", source, "{}", message),
+        ActivityLevel::Error => tracing::error!(target: "activity", source, "{}", message),
+    }
+
+    let entry = ActivityEntry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        source: source.to_string(),
+        level,
+        message,
+        output,
+    };
+
+    if let Some(log) = handle.try_state::<ActivityLog>() {
+        let mut entries = log.0.lock().unwrap();
+        entries.push_back(entry.clone());
+        while entries.len() > MAX_ACTIVITY_ENTRIES {
+            entries.pop_front();
+        }
+    }
+    let _ = handle.emit("activity-logged", entry);
+}
+
+#[tauri::command]
+pub fn get_activity_log(log: State<'_, ActivityLog>) -> Vec<ActivityEntry> {
+    log.0.lock().unwrap().iter().cloned().collect()
+}
+
+#[tauri::command]
+pub fn clear_activity_log(log: State<'_, ActivityLog>) {
+    log.0.lock().unwrap().clear();
+}
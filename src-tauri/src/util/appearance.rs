@@ -0,0 +1,121 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Theme};
+
+/// Desktop theme info the UI mirrors
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SystemAppearance {
+    /// "dark" or "light"
+    pub theme: String,
+    /// OS accent color as "#rrggbb", when the platform exposes one
+    pub accent_color: Option<String>,
+}
+
+/// Last appearance sent to the frontend, so change events are only emitted on real changes
+#[derive(Default)]
+pub struct AppearanceState(pub Mutex<Option<SystemAppearance>>);
+
+fn hex(rgb: (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb.0, rgb.1, rgb.2)
+}
+
+#[cfg(target_os = "windows")]
+fn accent_color() -> Option<String> {
+    crate::filesys::os::windows::get_accent_color().map(hex)
+}
+
+/// macOS stores the accent as an index into its fixed palette; absent means blue
+#[cfg(target_os = "macos")]
+fn accent_color() -> Option<String> {
+    let output = std::process::Command::new("defaults")
+        .args(["read", "-g", "AppleAccentColor"])
+        .output()
+        .ok()?;
+    let index = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<i32>()
+        .ok();
+    let rgb = match index {
+        Some(-1) => (0x8c, 0x8c, 0x8c), // graphite
+        Some(0) => (0xff, 0x52, 0x57),
+        Some(1) => (0xf7, 0x82, 0x1b),
+        Some(2) => (0xff, 0xc6, 0x00),
+        Some(3) => (0x62, 0xba, 0x46),
+        Some(5) => (0xa5, 0x50, 0xa7),
+        Some(6) => (0xf7, 0x4f, 0x9e),
+        _ => (0x00, 0x7a, 0xff), // blue / default
+    };
+    Some(hex(rgb))
+}
+
+/// GNOME 47+ exposes a named accent; other desktops report nothing
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn accent_color() -> Option<String> {
+    let output = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "accent-color"])
+        .output()
+        .ok()?;
+    let name = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .trim_matches('\'')
+        .to_string();
+    let rgb = match name.as_str() {
+        "blue" => (0x35, 0x84, 0xe4),
+        "teal" => (0x21, 0x90, 0xa4),
+        "green" => (0x3a, 0x94, 0x4a),
+        "yellow" => (0xc8, 0x88, 0x00),
+        "orange" => (0xed, 0x5b, 0x00),
+        "red" => (0xe6, 0x2d, 0x42),
+        "pink" => (0xd5, 0x61, 0x99),
+        "purple" => (0x91, 0x41, 0xac),
+        "slate" => (0x6f, 0x83, 0x96),
+        _ => return None,
+    };
+    Some(hex(rgb))
+}
+
+/// Reads the current appearance. `theme` overrides the window query when the
+/// caller already knows it (e.g. from a ThemeChanged event).
+pub fn read_system_appearance(handle: &AppHandle, theme: Option<Theme>) -> SystemAppearance {
+    let theme = theme.or_else(|| {
+        handle
+            .get_webview_window("main")
+            .and_then(|w| w.theme().ok())
+    });
+
+    SystemAppearance {
+        theme: match theme {
+            Some(Theme::Dark) => "dark".into(),
+            _ => "light".into(),
+        },
+        accent_color: accent_color(),
+    }
+}
+
+/// Re-reads the appearance and emits `system-appearance-changed` if it differs from
+/// what the frontend last saw. Called from theme-change and focus events.
+pub fn refresh_system_appearance(handle: &AppHandle, theme: Option<Theme>) {
+    let appearance = read_system_appearance(handle, theme);
+    let Some(state) = handle.try_state::<AppearanceState>() else {
+        return;
+    };
+
+    let mut last = state.0.lock().unwrap();
+    if last.as_ref() == Some(&appearance) {
+        return;
+    }
+    *last = Some(appearance.clone());
+    drop(last);
+
+    let _ = handle.emit("system-appearance-changed", appearance);
+}
+
+#[tauri::command]
+pub fn get_system_appearance(
+    handle: AppHandle,
+    state: tauri::State<'_, AppearanceState>,
+) -> SystemAppearance {
+    let appearance = read_system_appearance(&handle, None);
+    *state.0.lock().unwrap() = Some(appearance.clone());
+    appearance
+}
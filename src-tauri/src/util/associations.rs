@@ -0,0 +1,268 @@
+use serde::Serialize;
+
+/// The app that opens files with a given extension
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct Association {
+    /// Normalized extension, without the dot
+    pub extension: String,
+    /// MIME type the extension maps to (Linux only)
+    pub mime: Option<String>,
+    /// Handler id: a ProgId on Windows, a desktop entry id on Linux
+    pub app_id: Option<String>,
+    pub app_name: Option<String>,
+    /// The handler's executable, where known
+    pub executable: Option<String>,
+}
+
+/// "txt", ".TXT" -> "txt"; rejects anything that is not a plain extension
+fn normalize_extension(extension: &str) -> Result<String, String> {
+    let ext = extension.trim().trim_start_matches('.').to_lowercase();
+    if ext.is_empty()
+        || ext
+            .chars()
+            .any(|c| c == '/' || c == '\\' || c.is_whitespace())
+    {
+        return Err(format!("Invalid extension: {}", extension));
+    }
+    Ok(ext)
+}
+
+/// Per-user choice made in Explorer's Open With dialog
+#[cfg(target_os = "windows")]
+fn user_choice_key(ext: &str) -> String {
+    format!(
+        "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\FileExts\\.{}\\UserChoice",
+        ext
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn lookup(ext: &str) -> Result<Association, String> {
+    use crate::filesys::os::windows::query_association;
+
+    let info = query_association(&format!(".{}", ext));
+    Ok(Association {
+        extension: ext.to_string(),
+        mime: None,
+        app_id: info.prog_id,
+        app_name: info.app_name,
+        executable: info.executable,
+    })
+}
+
+/// Points the extension at `app`, which is either a ProgId ("txtfile") or the
+/// path of an executable. Windows hash-protects the UserChoice key, so it is
+/// removed rather than rewritten; if it cannot be removed, the user has to
+/// confirm the app in the system Open With dialog once.
+#[cfg(target_os = "windows")]
+fn apply(ext: &str, app: &str) -> Result<(), String> {
+    use crate::filesys::os::windows::{
+        delete_user_registry_tree, get_user_registry_string, notify_association_changed,
+        set_user_registry_string,
+    };
+    use std::path::Path;
+
+    let write = |key: &str, name: Option<&str>, value: &str| {
+        set_user_registry_string(key, name, value)
+            .map_err(|e| format!("Failed to write {}: {}", key, e))
+    };
+
+    let exe = Path::new(app);
+    let prog_id = if exe.is_file() {
+        let file_name = exe
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or("Invalid application path")?;
+        let prog_id = format!("Applications\\{}", file_name);
+        write(
+            &format!("Software\\Classes\\{}\\shell\\open\\command", prog_id),
+            None,
+            &format!("\"{}\" \"%1\"", exe.display()),
+        )?;
+        prog_id
+    } else {
+        app.to_string()
+    };
+
+    let ext_key = format!("Software\\Classes\\.{}", ext);
+    write(&ext_key, None, &prog_id)?;
+    write(&format!("{}\\OpenWithProgids", ext_key), Some(&prog_id), "")?;
+
+    let choice_key = user_choice_key(ext);
+    let choice = get_user_registry_string(&choice_key, Some("ProgId"));
+    if choice.is_some_and(|choice| !choice.eq_ignore_ascii_case(&prog_id)) {
+        let _ = delete_user_registry_tree(&choice_key);
+    }
+    notify_association_changed();
+
+    if get_user_registry_string(&choice_key, Some("ProgId"))
+        .is_some_and(|choice| !choice.eq_ignore_ascii_case(&prog_id))
+    {
+        return Err(format!(
+            "Windows protects the default app for .{}; confirm it in the system Open With dialog",
+            ext
+        ));
+    }
+    Ok(())
+}
+
+/// `$XDG_DATA_HOME` followed by `$XDG_DATA_DIRS`
+#[cfg(all(unix, not(target_os = "macos")))]
+fn xdg_data_dirs() -> Vec<std::path::PathBuf> {
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs_next::data_dir()
+        .into_iter()
+        .chain(std::env::split_paths(&system))
+        .collect()
+}
+
+/// MIME type for `*.ext` from the shared-mime-info glob lists, preferring the
+/// highest weight as `xdg-mime` does
+#[cfg(all(unix, not(target_os = "macos")))]
+fn mime_for_extension(ext: &str) -> Option<String> {
+    let pattern = format!("*.{}", ext);
+    let mut best: Option<(u32, String)> = None;
+    for dir in xdg_data_dirs() {
+        let Ok(globs) = std::fs::read_to_string(dir.join("mime/globs2")) else {
+            continue;
+        };
+        for line in globs.lines().filter(|l| !l.starts_with('#')) {
+            // weight:mime/type:glob[:flags]
+            let mut parts = line.split(':');
+            let (Some(weight), Some(mime), Some(glob)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            if !glob.eq_ignore_ascii_case(&pattern) {
+                continue;
+            }
+            let weight = weight.parse().unwrap_or(50);
+            if best.as_ref().is_none_or(|(w, _)| weight > *w) {
+                best = Some((weight, mime.to_string()));
+            }
+        }
+    }
+    best.map(|(_, mime)| mime)
+}
+
+/// Path of an installed desktop entry by id ("org.gnome.TextEditor.desktop")
+#[cfg(all(unix, not(target_os = "macos")))]
+fn find_desktop_entry(id: &str) -> Option<std::path::PathBuf> {
+    xdg_data_dirs()
+        .into_iter()
+        .map(|dir| dir.join("applications").join(id))
+        .find(|path| path.is_file())
+}
+
+/// `Name=` and the program from `Exec=` of a desktop entry
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_entry_info(path: &std::path::Path) -> (Option<String>, Option<String>) {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return (None, None);
+    };
+    let mut name = None;
+    let mut exec = None;
+    let mut in_entry = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("Name=") {
+            name.get_or_insert_with(|| value.to_string());
+        } else if let Some(value) = line.strip_prefix("Exec=") {
+            exec = value
+                .split_whitespace()
+                .next()
+                .map(|program| program.trim_matches('"').to_string());
+        }
+    }
+    (name, exec)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn lookup(ext: &str) -> Result<Association, String> {
+    use crate::util::shell_integration::xdg_mime;
+
+    let Some(mime) = mime_for_extension(ext) else {
+        return Ok(Association {
+            extension: ext.to_string(),
+            ..Default::default()
+        });
+    };
+    let app_id = xdg_mime(&["query", "default", &mime])
+        .ok()
+        .filter(|id| !id.is_empty());
+    let (app_name, executable) = app_id
+        .as_deref()
+        .and_then(find_desktop_entry)
+        .map(|path| desktop_entry_info(&path))
+        .unwrap_or_default();
+    Ok(Association {
+        extension: ext.to_string(),
+        mime: Some(mime),
+        app_id,
+        app_name,
+        executable,
+    })
+}
+
+/// Makes the desktop entry `app` the default for the extension's MIME type
+#[cfg(all(unix, not(target_os = "macos")))]
+fn apply(ext: &str, app: &str) -> Result<(), String> {
+    use crate::util::shell_integration::xdg_mime;
+
+    let mime = mime_for_extension(ext).ok_or_else(|| format!("Unknown file type: .{}", ext))?;
+    let app = if app.ends_with(".desktop") {
+        app.to_string()
+    } else {
+        format!("{}.desktop", app)
+    };
+    if find_desktop_entry(&app).is_none() {
+        return Err(format!("Application not found: {}", app));
+    }
+    xdg_mime(&["default", &app, &mime]).map(|_| ())
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn lookup(ext: &str) -> Result<Association, String> {
+    Ok(Association {
+        extension: ext.to_string(),
+        ..Default::default()
+    })
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn apply(_ext: &str, _app: &str) -> Result<(), String> {
+    Err("Changing default apps is not supported on this platform".into())
+}
+
+/// Looks up which app opens files with `extension`
+#[tauri::command]
+pub async fn get_association(extension: String) -> Result<Association, String> {
+    let ext = normalize_extension(&extension)?;
+    tauri::async_runtime::spawn_blocking(move || lookup(&ext))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Makes `app` the default for `extension` for the current user: a ProgId or
+/// executable path on Windows, a desktop entry id on Linux. Returns the
+/// association as the OS resolves it afterwards.
+#[tauri::command]
+pub async fn set_association(extension: String, app: String) -> Result<Association, String> {
+    let ext = normalize_extension(&extension)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        apply(&ext, &app)?;
+        lookup(&ext)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
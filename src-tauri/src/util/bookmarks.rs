@@ -0,0 +1,90 @@
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use crate::util::caches::SharedAppSettings;
+
+/// Numbered slots available for directory bookmarks (bound to `goto_slot_1`..`goto_slot_9`)
+const SLOT_RANGE: std::ops::RangeInclusive<u8> = 1..=9;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BookmarkSlot {
+    pub slot: u8,
+    pub path: Option<String>,
+    /// False when the bound folder has been moved or its drive isn't mounted
+    pub exists: bool,
+}
+
+fn check_slot(slot: u8) -> Result<(), String> {
+    if SLOT_RANGE.contains(&slot) {
+        Ok(())
+    } else {
+        Err(format!("Bookmark slots are numbered 1 to 9, got {}", slot))
+    }
+}
+
+async fn resolve_slots(settings: &SharedAppSettings) -> Vec<BookmarkSlot> {
+    let slots = settings.0.read().await.bookmark_slots.clone();
+    SLOT_RANGE
+        .map(|slot| {
+            let path = slots.get(&slot).cloned();
+            BookmarkSlot {
+                slot,
+                exists: path.as_deref().is_some_and(|p| Path::new(p).is_dir()),
+                path,
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_bookmark_slots(
+    settings: State<'_, SharedAppSettings>,
+) -> Result<Vec<BookmarkSlot>, String> {
+    Ok(resolve_slots(&settings).await)
+}
+
+/// Binds `slot` to a folder, or clears it when `path` is None
+#[tauri::command]
+pub async fn set_bookmark_slot(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    slot: u8,
+    path: Option<String>,
+) -> Result<Vec<BookmarkSlot>, String> {
+    check_slot(slot)?;
+    if let Some(p) = &path {
+        if !Path::new(p).is_dir() {
+            return Err(format!("Not a directory: {}", p));
+        }
+    }
+
+    {
+        let mut current = settings.0.write().await;
+        match path {
+            Some(p) => current.bookmark_slots.insert(slot, p),
+            None => current.bookmark_slots.remove(&slot),
+        };
+    }
+    settings.commit(&handle).await;
+
+    Ok(resolve_slots(&settings).await)
+}
+
+/// The folder bound to `slot`, for the `goto_slot_<n>` keybindings to navigate to
+#[tauri::command]
+pub async fn goto_slot(settings: State<'_, SharedAppSettings>, slot: u8) -> Result<String, String> {
+    check_slot(slot)?;
+    let path = settings
+        .0
+        .read()
+        .await
+        .bookmark_slots
+        .get(&slot)
+        .cloned()
+        .ok_or_else(|| format!("Slot {} is empty", slot))?;
+    if !Path::new(&path).is_dir() {
+        return Err(format!("{} no longer exists", path));
+    }
+    Ok(path)
+}
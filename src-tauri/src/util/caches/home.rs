@@ -1,21 +1,146 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::sync::Arc;
-use std::{collections::VecDeque, fs, io::Read, path::PathBuf};
-use tauri::AppHandle;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Manager, State};
 use tokio::sync::RwLock;
 
+use crate::filesys::links::link_info;
 use crate::filesys::nav::FileItem;
 use crate::filesys::nav::FileItemWithThumbnail;
-use crate::util::caches::get_cache_dir;
+use crate::util::caches::{
+    get_cache_dir, hash_path, load_app_settings,
+    versioning::{load_versioned, save_versioned},
+    PrivacySettings, SharedAppSettings,
+};
+use crate::util::setup::refresh_tray_menu;
+use crate::util::taskbar::refresh_jump_list;
 
-const MAX_RECENT_FILES: usize = 50;
-const MAX_RECENT_DIRS: usize = 18;
+/// Recents shown on the Home view until the user picks a number
+const DEFAULT_RECENTS_LIMIT: usize = 50;
+/// Bump and extend `migrate_home_cache` when the file's shape changes
+const HOME_CACHE_VERSION: u32 = 2;
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct HomeCache {
     pub recent_files: VecDeque<FileItemWithThumbnail>,
     pub recent_dirs: VecDeque<FileItem>,
     pub pinned_items: Vec<FileItemWithThumbnail>,
+    #[serde(default)]
+    pub network_locations: Vec<FileItem>,
+    #[serde(default)]
+    pub layout: HomeLayout,
+    /// When each recent file and folder was last opened (unix seconds), for
+    /// `privacy.recents_max_age_days`
+    #[serde(default)]
+    pub recent_access: HashMap<String, u64>,
+}
+
+impl HomeCache {
+    /// Enforces the recents caps and maximum age, forgetting the access times of
+    /// evicted paths. Recents saved before access times were kept count as opened `now`.
+    fn prune_recents(&mut self, policy: &PrivacySettings, now: u64) {
+        let paths = self.recent_files.iter().map(|i| &i.path);
+        for path in paths.chain(self.recent_dirs.iter().map(|i| &i.path)) {
+            self.recent_access.entry(path.clone()).or_insert(now);
+        }
+
+        let cutoff = policy
+            .recents_max_age_days
+            .map(|days| now.saturating_sub(days as u64 * 24 * 60 * 60));
+        let access = &self.recent_access;
+        let fresh = |path: &str| cutoff.is_none_or(|c| access.get(path).is_some_and(|t| *t >= c));
+        self.recent_files.retain(|i| fresh(&i.path));
+        self.recent_dirs.retain(|i| fresh(&i.path));
+        self.recent_files.truncate(policy.max_recent_files);
+        self.recent_dirs.truncate(policy.max_recent_dirs);
+
+        let kept = |path: &String| {
+            self.recent_files.iter().any(|i| &i.path == path)
+                || self.recent_dirs.iter().any(|i| &i.path == path)
+        };
+        self.recent_access.retain(|path, _| kept(path));
+    }
+}
+
+/// A block of the Home view
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HomeSectionKind {
+    RecentFiles,
+    RecentDirs,
+    Pinned,
+    Drives,
+    Workspaces,
+}
+
+const ALL_HOME_SECTIONS: [HomeSectionKind; 5] = [
+    HomeSectionKind::RecentFiles,
+    HomeSectionKind::RecentDirs,
+    HomeSectionKind::Pinned,
+    HomeSectionKind::Drives,
+    HomeSectionKind::Workspaces,
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct HomeSection {
+    pub kind: HomeSectionKind,
+    pub visible: bool,
+}
+
+/// User arrangement of the Home view: section order and visibility, how many
+/// recents to show, and the folders of the "Workspaces" section
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HomeLayout {
+    pub sections: Vec<HomeSection>,
+    /// Recent files and folders shown; the cache keeps up to its own caps regardless
+    pub recents_limit: usize,
+    pub workspaces: Vec<FileItem>,
+}
+
+impl Default for HomeLayout {
+    fn default() -> Self {
+        Self {
+            sections: ALL_HOME_SECTIONS
+                .iter()
+                .map(|&kind| HomeSection {
+                    kind,
+                    // drives used to live only in the sidebar
+                    visible: kind != HomeSectionKind::Drives,
+                })
+                .collect(),
+            recents_limit: DEFAULT_RECENTS_LIMIT,
+            workspaces: Vec::new(),
+        }
+    }
+}
+
+impl HomeLayout {
+    /// Visible sections in display order
+    pub fn visible_sections(&self) -> impl Iterator<Item = HomeSectionKind> + '_ {
+        self.sections.iter().filter(|s| s.visible).map(|s| s.kind)
+    }
+
+    pub fn is_visible(&self, kind: HomeSectionKind) -> bool {
+        self.sections.iter().any(|s| s.kind == kind && s.visible)
+    }
+
+    /// Adds sections missing from older files (or a partial reorder) at the end
+    fn complete(&mut self) {
+        for kind in ALL_HOME_SECTIONS {
+            if !self.sections.iter().any(|s| s.kind == kind) {
+                self.sections.push(HomeSection {
+                    kind,
+                    visible: true,
+                });
+            }
+        }
+    }
 }
 
 #[derive(Clone, Default)]
@@ -34,35 +159,156 @@ impl SharedHomeCache {
     pub async fn save(&self, handle: &AppHandle) {
         let cache = self.0.read().await;
         save_home_cache(handle, &cache);
+        refresh_tray_menu(handle, &cache);
+        refresh_jump_list(&cache);
     }
 
-    /// Add a recent file, deduplicate, and cap the deque
-    pub async fn push_recent_file(&self, item: FileItemWithThumbnail) {
+    /// Add a recent file, deduplicate, and apply the recents caps and retention
+    pub async fn push_recent_file(&self, handle: &AppHandle, item: FileItemWithThumbnail) {
         if is_root_path(&item.path) {
             return; // skip root paths
         }
 
+        let policy = recents_policy(handle);
         let mut cache = self.0.write().await;
         cache.recent_files.retain(|x| x.path != item.path);
+        cache.recent_access.insert(item.path.clone(), unix_now());
         cache.recent_files.push_front(item);
-        while cache.recent_files.len() > MAX_RECENT_FILES {
-            cache.recent_files.pop_back();
-        }
+        cache.prune_recents(&policy, unix_now());
     }
 
-    /// Add a recent directory, deduplicate, and cap the deque
-    pub async fn push_recent_dir(&self, item: FileItem) {
+    /// Add a recent directory, deduplicate, and apply the recents caps and retention
+    pub async fn push_recent_dir(&self, handle: &AppHandle, item: FileItem) {
         if is_root_path(&item.path) {
             return; // skip root paths
         }
 
+        let policy = recents_policy(handle);
         let mut cache = self.0.write().await;
         cache.recent_dirs.retain(|x| x.path != item.path);
+        cache.recent_access.insert(item.path.clone(), unix_now());
         cache.recent_dirs.push_front(item);
-        while cache.recent_dirs.len() > MAX_RECENT_DIRS {
-            cache.recent_dirs.pop_back();
+        cache.prune_recents(&policy, unix_now());
+    }
+}
+
+#[tauri::command]
+pub async fn get_home_layout(state: State<'_, SharedHomeCache>) -> Result<HomeLayout, String> {
+    Ok(state.0.read().await.layout.clone())
+}
+
+/// Reorders the Home sections; kinds left out keep their relative order after the given ones
+#[tauri::command]
+pub async fn reorder_home_sections(
+    handle: AppHandle,
+    state: State<'_, SharedHomeCache>,
+    order: Vec<HomeSectionKind>,
+) -> Result<HomeLayout, String> {
+    update_home_layout(&handle, &state, |layout| {
+        let mut sections: Vec<HomeSection> = order
+            .iter()
+            .filter_map(|kind| layout.sections.iter().find(|s| s.kind == *kind).copied())
+            .collect();
+        sections.dedup_by_key(|s| s.kind);
+        sections.extend(
+            layout
+                .sections
+                .iter()
+                .filter(|s| !order.contains(&s.kind))
+                .copied(),
+        );
+        layout.sections = sections;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_home_section_visible(
+    handle: AppHandle,
+    state: State<'_, SharedHomeCache>,
+    kind: HomeSectionKind,
+    visible: bool,
+) -> Result<HomeLayout, String> {
+    update_home_layout(&handle, &state, |layout| {
+        for section in layout.sections.iter_mut().filter(|s| s.kind == kind) {
+            section.visible = visible;
         }
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_home_recents_limit(
+    handle: AppHandle,
+    state: State<'_, SharedHomeCache>,
+    limit: usize,
+) -> Result<HomeLayout, String> {
+    update_home_layout(&handle, &state, |layout| {
+        layout.recents_limit = limit.min(recents_policy(&handle).max_recent_files);
+        Ok(())
+    })
+    .await
+}
+
+/// Adds a folder to the "Workspaces" section
+#[tauri::command]
+pub async fn add_home_workspace(
+    handle: AppHandle,
+    state: State<'_, SharedHomeCache>,
+    path: String,
+) -> Result<HomeLayout, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Not a directory: {}", path));
     }
+    let name = Path::new(&path)
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    update_home_layout(&handle, &state, |layout| {
+        if !layout.workspaces.iter().any(|w| w.path == path) {
+            layout.workspaces.push(FileItem {
+                name,
+                link: link_info(Path::new(&path)),
+                path,
+                is_dir: true,
+                size: None,
+            });
+        }
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_home_workspace(
+    handle: AppHandle,
+    state: State<'_, SharedHomeCache>,
+    path: String,
+) -> Result<HomeLayout, String> {
+    update_home_layout(&handle, &state, |layout| {
+        layout.workspaces.retain(|w| w.path != path);
+        Ok(())
+    })
+    .await
+}
+
+/// Applies `change` to the layout, persists it and returns the result
+async fn update_home_layout(
+    handle: &AppHandle,
+    state: &SharedHomeCache,
+    change: impl FnOnce(&mut HomeLayout) -> Result<(), String>,
+) -> Result<HomeLayout, String> {
+    let layout = {
+        let mut cache = state.0.write().await;
+        change(&mut cache.layout)?;
+        cache.layout.complete();
+        cache.layout.clone()
+    };
+    state.save(handle).await;
+    Ok(layout)
 }
 
 /// Location of the home cache JSON file
@@ -72,31 +318,61 @@ fn get_home_cache_path(handle: &AppHandle) -> PathBuf {
     path
 }
 
+/// Upgrades an older home cache document.
+/// v2: recent files reference their thumbnail by thumb DB hash instead of inline base64.
+fn migrate_home_cache(from: u32, doc: &mut Map<String, Value>) {
+    if from < 2 {
+        let recents = doc.get_mut("recent_files").and_then(Value::as_array_mut);
+        for item in recents.into_iter().flatten() {
+            let Some(item) = item.as_object_mut() else {
+                continue;
+            };
+            let had_thumb = item.remove("thumbnail").is_some_and(|t| !t.is_null());
+            let hash = item
+                .get("path")
+                .and_then(Value::as_str)
+                .filter(|_| had_thumb)
+                .map(|path| hash_path(path).to_string());
+            item.insert("thumbnail".into(), Value::Null);
+            item.insert("thumb_hash".into(), hash.into());
+        }
+    }
+}
+
 /// Loads the cached recent items from disk or creates an empty cache if missing
 pub fn load_home_cache(handle: &AppHandle) -> HomeCache {
     let path = get_home_cache_path(handle);
+    let mut cache: HomeCache =
+        load_versioned(&path, HOME_CACHE_VERSION, migrate_home_cache).unwrap_or_default();
+    cache.layout.complete();
+    cache.prune_recents(&recents_policy(handle), unix_now());
+    cache
+}
 
-    if let Ok(mut file) = fs::File::open(&path) {
-        let mut data = String::new();
-        if file.read_to_string(&mut data).is_ok() {
-            if let Ok(cache) = serde_json::from_str::<HomeCache>(&data) {
-                return cache;
-            }
-        }
+/// Recents caps and retention from the privacy settings. The home cache loads
+/// before settings are managed, so they're read from disk until then.
+fn recents_policy(handle: &AppHandle) -> PrivacySettings {
+    match handle.try_state::<SharedAppSettings>() {
+        Some(settings) => match settings.0.try_read() {
+            Ok(settings) => settings.privacy.clone(),
+            Err(_) => load_app_settings(handle).privacy,
+        },
+        None => load_app_settings(handle).privacy,
     }
+}
 
-    HomeCache::default()
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
 /// Saves the home cache to disk atomically
 pub fn save_home_cache(handle: &AppHandle, cache: &HomeCache) {
     let path = get_home_cache_path(handle);
-    let tmp_path = path.with_extension("tmp");
-
-    let serialized = serde_json::to_string_pretty(cache).unwrap();
-
-    fs::write(&tmp_path, serialized).unwrap_or_else(|_| panic!("Failed to write temp home cache"));
-    fs::rename(&tmp_path, &path).unwrap_or_else(|_| panic!("Failed to rename temp cache file"));
+    save_versioned(&path, cache, HOME_CACHE_VERSION)
+        .unwrap_or_else(|_| panic!("Failed to save home cache"));
 }
 
 /// Helper function: returns true if a path is a root path
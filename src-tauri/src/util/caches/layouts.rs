@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::{fs, io::Read, path::PathBuf, sync::Arc};
+use serde_json::{Map, Value};
+use std::{path::PathBuf, sync::Arc};
 use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 
-use crate::util::caches::get_cache_dir;
+use crate::util::caches::{
+    get_cache_dir,
+    versioning::{load_versioned, save_versioned},
+};
+
+/// Bump and extend `migrate_layout_cache` when the file's shape changes
+const LAYOUT_CACHE_VERSION: u32 = 1;
 
 // ===============================
 // LayoutCache Structure
@@ -18,6 +25,8 @@ pub struct LayoutCache {
     // Viewing
     pub view_mode: ViewMode,
     pub show_hidden: bool,
+    #[serde(default)]
+    pub hide_ignored: bool,
     pub show_extensions: bool,
     pub icon_size: IconSize,
 }
@@ -29,6 +38,7 @@ pub enum SortKey {
     Size,
     Filetype,
     DateModified,
+    Rating,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +63,7 @@ impl Default for LayoutCache {
             ascending: true,
             view_mode: ViewMode::Grid,
             show_hidden: false,
+            hide_ignored: false,
             show_extensions: true,
             icon_size: IconSize::Small,
         }
@@ -94,32 +105,20 @@ fn get_layout_cache_path(handle: &AppHandle) -> PathBuf {
     path
 }
 
+/// Upgrades an older layout document. v1 files predate versioning and need no changes.
+fn migrate_layout_cache(_from: u32, _doc: &mut Map<String, Value>) {}
+
 /// Loads layout settings from disk, or defaults if missing
 pub fn load_layout_cache(handle: &AppHandle) -> LayoutCache {
     let path = get_layout_cache_path(handle);
-
-    if let Ok(mut file) = fs::File::open(&path) {
-        let mut data = String::new();
-        if file.read_to_string(&mut data).is_ok() {
-            if let Ok(cache) = serde_json::from_str::<LayoutCache>(&data) {
-                return cache;
-            }
-        }
-    }
-
-    LayoutCache::default()
+    load_versioned(&path, LAYOUT_CACHE_VERSION, migrate_layout_cache).unwrap_or_default()
 }
 
 /// Saves layout settings to disk atomically
 pub fn save_layout_cache(handle: &AppHandle, cache: &LayoutCache) {
     let path = get_layout_cache_path(handle);
-    let tmp_path = path.with_extension("tmp");
-
-    let serialized = serde_json::to_string_pretty(cache).unwrap();
-
-    fs::write(&tmp_path, serialized)
-        .unwrap_or_else(|_| panic!("Failed to write temp layout cache"));
-    fs::rename(&tmp_path, &path).unwrap_or_else(|_| panic!("Failed to rename temp layout cache"));
+    save_versioned(&path, cache, LAYOUT_CACHE_VERSION)
+        .unwrap_or_else(|_| panic!("Failed to save layout cache"));
 }
 
 #[tauri::command]
@@ -0,0 +1,99 @@
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::AppHandle;
+
+use crate::{
+    filesys::stream::JobStreamState,
+    util::{
+        activity::{log_activity, ActivityLevel},
+        caches::{open_thumb_db, prune_thumbs, thumb_db_stats, vacuum_thumbs},
+        power::should_throttle,
+    },
+};
+
+/// How often the scheduler wakes up to look for an idle window
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Minimum time between two idle maintenance runs
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Startup maintenance runs when the DB is larger than this...
+const STARTUP_MAX_BYTES: u64 = 512 * 1024 * 1024;
+/// ...or when more than this fraction of it is free pages
+const STARTUP_MAX_FREE_RATIO: f64 = 0.25;
+/// Grace period after launch so maintenance doesn't compete with the first window
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+/// Prunes thumbnails of deleted files and vacuums the DB, then reports the
+/// before/after numbers to the activity log.
+pub fn run_thumb_maintenance(handle: &AppHandle) -> Result<(), String> {
+    let started = Instant::now();
+    let conn = open_thumb_db(handle).map_err(|e| format!("Failed to open thumb DB: {}", e))?;
+    let before = thumb_db_stats(&conn).map_err(|e| e.to_string())?;
+    let pruned = prune_thumbs(&conn).map_err(|e| format!("Failed to prune thumbs: {}", e))?;
+    vacuum_thumbs(&conn).map_err(|e| format!("Failed to vacuum thumb DB: {}", e))?;
+    let after = thumb_db_stats(&conn).map_err(|e| e.to_string())?;
+
+    log_activity(
+        handle,
+        "thumbs",
+        ActivityLevel::Info,
+        format!(
+            "Thumbnail cache maintenance: pruned {} of {} entries, {} KB -> {} KB in {} ms",
+            pruned,
+            before.entries,
+            before.bytes / 1024,
+            after.bytes / 1024,
+            started.elapsed().as_millis()
+        ),
+        None,
+    );
+    Ok(())
+}
+
+/// Whether the DB has grown enough to be worth maintaining right at startup
+fn exceeds_startup_thresholds(handle: &AppHandle) -> bool {
+    let Ok(conn) = open_thumb_db(handle) else {
+        return false;
+    };
+    let Ok(stats) = thumb_db_stats(&conn) else {
+        return false;
+    };
+    stats.bytes > STARTUP_MAX_BYTES
+        || (stats.bytes > 0
+            && stats.free_bytes as f64 / stats.bytes as f64 > STARTUP_MAX_FREE_RATIO)
+}
+
+/// Runs thumbnail DB maintenance in the background: shortly after launch when the
+/// DB is over its thresholds, then at most once a day while no jobs are running.
+pub fn start_thumb_maintenance(handle: &AppHandle, jobs: Arc<JobStreamState>) {
+    let handle = handle.clone();
+    thread::spawn(move || {
+        thread::sleep(STARTUP_DELAY);
+
+        let mut last_run = Instant::now();
+        if exceeds_startup_thresholds(&handle) {
+            if let Err(e) = run_thumb_maintenance(&handle) {
+                tracing::warn!(target: "thumbs", "startup maintenance failed: {}", e);
+            }
+        }
+
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+
+            // transfers hammer the disk already and batteries drain; wait for a
+            // quiet moment on mains power
+            if last_run.elapsed() < MAINTENANCE_INTERVAL
+                || jobs.summary().is_some()
+                || should_throttle(&handle)
+            {
+                continue;
+            }
+            if let Err(e) = run_thumb_maintenance(&handle) {
+                tracing::warn!(target: "thumbs", "scheduled maintenance failed: {}", e);
+            }
+            last_run = Instant::now();
+        }
+    });
+}
@@ -3,14 +3,29 @@ use tauri::{AppHandle, Manager};
 
 pub mod home;
 pub mod layouts;
+pub mod maintenance;
+pub mod settings;
 pub mod thumbs;
+pub mod versioning;
+pub mod window;
 
-pub use home::{load_home_cache, save_home_cache, HomeCache, SharedHomeCache};
+pub use home::{
+    load_home_cache, save_home_cache, HomeCache, HomeLayout, HomeSectionKind, SharedHomeCache,
+};
 pub use layouts::{
     fetch_layout_settings, load_layout_cache, save_layout_cache, update_layout_settings,
     LayoutCache, SharedLayoutCache,
 };
-pub use thumbs::{get_thumb, hash_path, open_thumb_db, prune_thumbs, set_thumb};
+pub use settings::{
+    get_settings, load_app_settings, save_app_settings, update_settings, AppSettings,
+    HotkeySettings, NotificationSettings, PreviewAs, PreviewRule, PrivacySettings,
+    SharedAppSettings, ThumbnailSettings, WindowEffect,
+};
+pub use thumbs::{
+    get_thumb, get_thumb_by_hash, hash_path, open_thumb_db, prune_thumbs, set_thumb,
+    thumb_db_stats, thumb_is_fresh, vacuum_thumbs, ThumbDbStats,
+};
+pub use window::{load_window_state, save_window_state, WindowStateCache};
 
 /// Location of the app cache directory
 fn get_cache_dir(handle: &AppHandle) -> PathBuf {
@@ -0,0 +1,423 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::RwLock;
+
+use crate::{
+    filesys::stream::{jobs::JobKind, transfer::set_copy_streams},
+    search::smart::SmartFolder,
+    util::{
+        caches::{
+            get_cache_dir,
+            versioning::{load_versioned, save_versioned},
+        },
+        effects::apply_window_effect,
+        logging::LogLevel,
+    },
+};
+
+/// Current on-disk schema version. Bump it and add a step to `migrate_settings`
+/// whenever a field is moved or renamed.
+pub const SETTINGS_VERSION: u32 = 2;
+
+// ===============================
+// AppSettings Structure
+// ===============================
+
+/// App-wide preferences that aren't part of the view layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub version: u32,
+
+    pub behavior: BehaviorSettings,
+    pub performance: PerformanceSettings,
+    pub integrations: IntegrationSettings,
+    pub privacy: PrivacySettings,
+    pub thumbnails: ThumbnailSettings,
+
+    // Notifications
+    pub notifications: NotificationSettings,
+
+    // Global hotkey
+    pub global_hotkey: HotkeySettings,
+
+    // Appearance
+    pub window_effect: WindowEffect,
+
+    // Diagnostics
+    pub log_level: LogLevel,
+
+    /// Keybinding overrides (action id -> chord, None = unbound); see util::keybindings
+    pub keybindings: BTreeMap<String, Option<String>>,
+
+    /// Directory bookmark slots (1-9 -> folder); see util::bookmarks
+    pub bookmark_slots: BTreeMap<u8, String>,
+
+    /// Saved search scopes (name -> root folders); see search::scopes
+    pub search_scopes: BTreeMap<String, Vec<String>>,
+
+    /// Saved searches shown as live folders; see search::smart
+    pub smart_folders: BTreeMap<String, SmartFolder>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            behavior: BehaviorSettings::default(),
+            performance: PerformanceSettings::default(),
+            integrations: IntegrationSettings::default(),
+            privacy: PrivacySettings::default(),
+            thumbnails: ThumbnailSettings::default(),
+            notifications: NotificationSettings::default(),
+            global_hotkey: HotkeySettings::default(),
+            window_effect: WindowEffect::default(),
+            log_level: LogLevel::default(),
+            keybindings: BTreeMap::new(),
+            bookmark_slots: BTreeMap::new(),
+            search_scopes: BTreeMap::new(),
+            smart_folders: BTreeMap::new(),
+        }
+    }
+}
+
+/// How the explorer reacts to user actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BehaviorSettings {
+    /// Ask before deleting items
+    pub confirm_delete: bool,
+    /// Open folders in a new tab on middle-click/Ctrl+click instead of navigating
+    pub open_folders_in_new_tab: bool,
+    /// Reopen the previous session's tabs on launch
+    pub restore_tabs: bool,
+    /// Show folder sizes in the sidebar tree, computed in the background
+    pub tree_folder_sizes: bool,
+}
+
+impl Default for BehaviorSettings {
+    fn default() -> Self {
+        Self {
+            confirm_delete: true,
+            open_folders_in_new_tab: true,
+            restore_tabs: false,
+            tree_folder_sizes: false,
+        }
+    }
+}
+
+/// Resource caps for background work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerformanceSettings {
+    /// Size of the metadata/thumbnail worker pool (takes effect on restart)
+    pub worker_threads: usize,
+    /// Skip thumbnail generation for files larger than this many megabytes
+    pub max_thumbnail_source_mb: u64,
+    /// Thumbnails generated at once per folder listing; 0 picks by drive type
+    pub thumbnail_concurrency: usize,
+    /// Streams copying one large file at once; 0 picks by drive type
+    pub copy_streams: usize,
+    /// Cut back thumbnailing and background work on battery or in power saver
+    pub throttle_on_battery: bool,
+}
+
+impl Default for PerformanceSettings {
+    fn default() -> Self {
+        // leave a couple of cores to the UI and the OS on small machines
+        let cores = std::thread::available_parallelism().map_or(8, |n| n.get());
+        Self {
+            worker_threads: cores.clamp(2, 16),
+            max_thumbnail_source_mb: 256,
+            thumbnail_concurrency: 0,
+            copy_streams: 0,
+            throttle_on_battery: true,
+        }
+    }
+}
+
+/// External tools Dagger hands files off to
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct IntegrationSettings {
+    /// Editor id from the `list_editors` catalog
+    pub default_editor: Option<String>,
+    /// Installed plugins the user switched off
+    pub disabled_plugins: Vec<String>,
+    /// Browse connected Android devices through adb
+    pub adb_enabled: bool,
+    /// Folder handler to restore once Dagger stops being the default file manager
+    pub previous_folder_handler: Option<String>,
+}
+
+/// What the app remembers about the user's activity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacySettings {
+    /// Record opened files and folders in the home page's recents
+    pub track_recents: bool,
+    /// Most recent files kept
+    pub max_recent_files: usize,
+    /// Most recent folders kept
+    pub max_recent_dirs: usize,
+    /// Recents not opened for this many days are dropped; `None` keeps them until
+    /// newer ones push them out
+    pub recents_max_age_days: Option<u32>,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        Self {
+            track_recents: true,
+            max_recent_files: 50,
+            max_recent_dirs: 18,
+            recents_max_age_days: None,
+        }
+    }
+}
+
+/// How thumbnails are picked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThumbnailSettings {
+    /// Where in a video its thumbnail frame is taken, in percent of the duration
+    pub video_position_percent: f64,
+    /// Try later frames when the chosen one is black or a flat color
+    pub skip_blank_frames: bool,
+    /// Use a video's embedded cover art instead of a frame when it has one
+    pub prefer_cover_art: bool,
+    /// Icon pack folder under `icon_packs` for files without a thumbnail;
+    /// `None` uses the built-in icons
+    pub icon_pack: Option<String>,
+    /// Per-extension overrides of the thumbnail and preview pipeline, keyed by
+    /// lowercase extension without the dot (e.g. "webm", "psd", "bak")
+    pub rules: BTreeMap<String, PreviewRule>,
+}
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            video_position_percent: 10.0,
+            skip_blank_frames: true,
+            prefer_cover_art: true,
+            icon_pack: None,
+            rules: BTreeMap::new(),
+        }
+    }
+}
+
+impl ThumbnailSettings {
+    /// The rule for `path`'s extension, if the user set one
+    pub fn rule_for(&self, path: &Path) -> Option<&PreviewRule> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        self.rules.get(ext.trim_start_matches('.'))
+    }
+}
+
+/// What a file is previewed as, regardless of its extension
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewAs {
+    Text,
+    Image,
+    Video,
+    Audio,
+    Document,
+    /// No preview and no thumbnail
+    None,
+}
+
+/// How files with one extension are thumbnailed and previewed, e.g. thumbnail
+/// .webm at 10%, never thumbnail .psd, or treat .bak as text
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PreviewRule {
+    /// Show the file's icon instead of generating a thumbnail
+    pub no_thumbnail: bool,
+    /// Video frame position for this extension instead of `video_position_percent`
+    pub video_position_percent: Option<f64>,
+    /// Handle the file as this kind instead of what its extension says
+    pub preview_as: Option<PreviewAs>,
+}
+
+impl PreviewRule {
+    /// Whether the thumbnail pipeline should skip the file entirely
+    pub fn skips_thumbnail(&self) -> bool {
+        self.no_thumbnail || self.preview_as == Some(PreviewAs::None)
+    }
+}
+
+/// Backdrop material behind the (transparent) main window
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowEffect {
+    #[default]
+    Acrylic,
+    Mica,
+    Blur,
+    None,
+}
+
+/// System-wide shortcut that summons the main window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    pub enabled: bool,
+    /// Accelerator string, e.g. "Ctrl+Alt+D"
+    pub shortcut: String,
+    /// Also open a new tab at `default_location`
+    pub open_new_tab: bool,
+    pub default_location: String,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shortcut: "Ctrl+Alt+D".into(),
+            open_new_tab: false,
+            default_location: "Home".into(),
+        }
+    }
+}
+
+/// When to raise native notifications for finished background jobs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Only notify while the window is hidden in the tray or minimized
+    pub only_when_hidden: bool,
+    /// Job types the user opted out of
+    pub disabled_jobs: Vec<JobKind>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            only_when_hidden: true,
+            disabled_jobs: Vec::new(),
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn allows(&self, kind: JobKind) -> bool {
+        self.enabled && !self.disabled_jobs.contains(&kind)
+    }
+}
+
+// ===============================
+// SharedAppSettings Wrapper
+// ===============================
+
+#[derive(Clone, Default)]
+pub struct SharedAppSettings(pub Arc<RwLock<AppSettings>>);
+
+impl SharedAppSettings {
+    pub fn new(settings: AppSettings) -> Self {
+        Self(Arc::new(RwLock::new(settings)))
+    }
+
+    /// Load settings from disk (or default)
+    pub async fn load(handle: &AppHandle) -> Self {
+        let settings = load_app_settings(handle);
+        Self::new(settings)
+    }
+
+    /// Save current settings to disk
+    pub async fn save(&self, handle: &AppHandle) {
+        let settings = self.0.read().await;
+        save_app_settings(handle, &settings);
+    }
+
+    /// Save and broadcast `settings-changed` so every window picks up the change
+    pub async fn commit(&self, handle: &AppHandle) {
+        let settings = self.0.read().await;
+        save_app_settings(handle, &settings);
+        let _ = handle.emit("settings-changed", &*settings);
+    }
+}
+
+// ===============================
+// Internal Helpers
+// ===============================
+
+fn get_settings_path(handle: &AppHandle) -> PathBuf {
+    let mut path = get_cache_dir(handle);
+    path.push("settings.json");
+    path
+}
+
+/// Upgrades a settings document from an older schema to `SETTINGS_VERSION`
+fn migrate_settings(from: u32, doc: &mut Map<String, Value>) {
+    // v1 -> v2: default_editor moved under integrations
+    if from < 2 {
+        if let Some(editor) = doc.remove("default_editor") {
+            doc.insert(
+                "integrations".into(),
+                serde_json::json!({ "default_editor": editor }),
+            );
+        }
+    }
+}
+
+/// Loads app settings from disk, migrating older schemas, or defaults if missing
+pub fn load_app_settings(handle: &AppHandle) -> AppSettings {
+    let path = get_settings_path(handle);
+    load_versioned(&path, SETTINGS_VERSION, migrate_settings).unwrap_or_default()
+}
+
+/// Saves app settings to disk atomically
+pub fn save_app_settings(handle: &AppHandle, settings: &AppSettings) {
+    let path = get_settings_path(handle);
+    save_versioned(&path, settings, SETTINGS_VERSION)
+        .unwrap_or_else(|_| panic!("Failed to save settings file"));
+}
+
+#[tauri::command]
+pub async fn get_settings(settings: State<'_, SharedAppSettings>) -> Result<AppSettings, String> {
+    let settings = settings.0.read().await.clone();
+    Ok(settings)
+}
+
+/// Replaces the settings, applies whatever can change live (hotkey, window effect),
+/// persists, and emits `settings-changed`.
+#[tauri::command]
+pub async fn update_settings(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    mut new_settings: AppSettings,
+) -> Result<(), String> {
+    new_settings.version = SETTINGS_VERSION;
+
+    #[cfg(desktop)]
+    crate::util::hotkey::apply_global_hotkey(&handle, &new_settings.global_hotkey)?;
+
+    set_copy_streams(new_settings.performance.copy_streams);
+
+    let effect = new_settings.window_effect;
+    let effect_changed = {
+        let mut current = settings.0.write().await;
+        let changed = current.window_effect != effect;
+        *current = new_settings;
+        changed
+    };
+
+    if effect_changed {
+        if let Some(window) = handle.get_webview_window("main") {
+            apply_window_effect(&window, effect);
+        }
+    }
+
+    // persist and broadcast
+    settings.commit(&handle).await;
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use rusqlite::{params, Connection, OptionalExtension, Result};
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use tauri::AppHandle;
 use xxhash_rust::xxh3::xxh3_64;
 
@@ -30,6 +33,16 @@ pub fn open_thumb_db(handle: &AppHandle) -> Result<Connection> {
         PRAGMA synchronous=NORMAL;",
     )?;
 
+    // DBs created before maintenance existed have no source path column
+    let has_path: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('thumbs') WHERE name = 'path'",
+        [],
+        |r| r.get(0),
+    )?;
+    if !has_path {
+        conn.execute_batch("ALTER TABLE thumbs ADD COLUMN path TEXT;")?;
+    }
+
     Ok(conn)
 }
 
@@ -61,30 +74,94 @@ pub fn get_thumb(
     Ok(None)
 }
 
+/// Whether a thumbnail for `hash` is cached and still matches `mtime`
+pub fn thumb_is_fresh(conn: &Connection, hash: u64, mtime: i64) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM thumbs WHERE hash = ?1 AND mtime = ?2",
+        params![hash, mtime],
+        |_| Ok(()),
+    )
+    .optional()
+    .is_ok_and(|row| row.is_some())
+}
+
+/// Reads a thumbnail by hash alone, whatever its age (for the `thumb` protocol)
+pub fn get_thumb_by_hash(conn: &Connection, hash: u64) -> Result<Option<Vec<u8>>> {
+    conn.query_row("SELECT thumb FROM thumbs WHERE hash = ?1", [hash], |r| {
+        r.get(0)
+    })
+    .optional()
+}
+
 /// Inserts or updates a thumbnail and optional metadata in the cache.
 pub fn set_thumb(
     conn: &Connection,
     hash: u64,
+    path: &str,
     mtime: i64,
     size: Option<i64>,
     filetype: Option<&str>,
     thumb: &[u8],
 ) -> Result<()> {
     conn.execute(
-        "INSERT INTO thumbs (hash, mtime, size, filetype, thumb) 
-         VALUES (?1, ?2, ?3, ?4, ?5)
+        "INSERT INTO thumbs (hash, path, mtime, size, filetype, thumb) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
          ON CONFLICT(hash) DO UPDATE SET 
+             path = excluded.path,
              mtime = excluded.mtime,
              size = excluded.size,
              filetype = excluded.filetype,
              thumb = excluded.thumb;",
-        params![hash, mtime, size, filetype, thumb],
+        params![hash, path, mtime, size, filetype, thumb],
     )?;
     Ok(())
 }
 
-// Optional: remove thumbnails older than a certain mtime (cleanup).
-pub fn prune_thumbs(conn: &Connection, min_mtime: i64) -> Result<()> {
-    conn.execute("DELETE FROM thumbs WHERE mtime < ?1;", [min_mtime])?;
-    Ok(())
+/// Size of the thumbnail DB and how much of it is reclaimable
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThumbDbStats {
+    pub entries: u64,
+    pub bytes: u64,
+    pub free_bytes: u64,
+}
+
+pub fn thumb_db_stats(conn: &Connection) -> Result<ThumbDbStats> {
+    let entries: i64 = conn.query_row("SELECT COUNT(*) FROM thumbs", [], |r| r.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |r| r.get(0))?;
+    let pages: i64 = conn.query_row("PRAGMA page_count", [], |r| r.get(0))?;
+    let free: i64 = conn.query_row("PRAGMA freelist_count", [], |r| r.get(0))?;
+    Ok(ThumbDbStats {
+        entries: entries as u64,
+        bytes: (pages * page_size) as u64,
+        free_bytes: (free * page_size) as u64,
+    })
+}
+
+/// Removes thumbnails whose source file no longer exists. Rows written before
+/// paths were recorded can't be checked and are left alone.
+/// Returns the number of rows removed.
+pub fn prune_thumbs(conn: &Connection) -> Result<usize> {
+    let missing: Vec<i64> = {
+        let mut stmt = conn.prepare("SELECT hash, path FROM thumbs WHERE path IS NOT NULL")?;
+        let rows = stmt.query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?;
+        rows.filter_map(|r| r.ok())
+            .filter(|(_, path)| !Path::new(path).exists())
+            .map(|(hash, _)| hash)
+            .collect()
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare("DELETE FROM thumbs WHERE hash = ?1")?;
+        for hash in &missing {
+            stmt.execute([hash])?;
+        }
+    }
+    tx.commit()?;
+    Ok(missing.len())
+}
+
+/// Rebuilds the DB file to hand freed pages back to the OS.
+pub fn vacuum_thumbs(conn: &Connection) -> Result<()> {
+    conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
 }
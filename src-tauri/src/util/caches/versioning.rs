@@ -0,0 +1,96 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::{fs, io, path::Path};
+
+/// Upgrades a cache document in place from the version it was written with
+pub type Migration = fn(from: u32, doc: &mut Map<String, Value>);
+
+/// Copies the current file aside as `<name>.<tag>.bak` before it gets rewritten
+fn backup(path: &Path, tag: &str) {
+    let Some(name) = path.file_name() else {
+        return;
+    };
+    let backup = path.with_file_name(format!("{}.{}.bak", name.to_string_lossy(), tag));
+    if let Err(e) = fs::copy(path, &backup) {
+        tracing::warn!(target: "caches", "Failed to back up {}: {}", path.display(), e);
+    }
+}
+
+/// Keeps every top-level field of `doc` that still deserializes, on top of the defaults,
+/// so one field with an outdated shape doesn't cost the user the rest of the file
+fn salvage<T: DeserializeOwned + Serialize + Default>(doc: &Map<String, Value>) -> T {
+    let Ok(Value::Object(mut merged)) = serde_json::to_value(T::default()) else {
+        return T::default();
+    };
+
+    for (key, value) in doc {
+        let previous = merged.insert(key.clone(), value.clone());
+        if serde_json::from_value::<T>(Value::Object(merged.clone())).is_err() {
+            match previous {
+                Some(previous) => merged.insert(key.clone(), previous),
+                None => merged.remove(key),
+            };
+        }
+    }
+
+    serde_json::from_value(Value::Object(merged)).unwrap_or_default()
+}
+
+/// Loads a versioned JSON cache file.
+///
+/// Files without a `version` field predate versioning and count as version 1. Older
+/// files are backed up as `<name>.v<N>.bak`, upgraded by `migrate`, and rewritten.
+/// A file that still doesn't fit `T` is backed up as `<name>.unreadable.bak` and as much
+/// of it as possible is kept. Returns None only when the file is missing or not JSON.
+pub fn load_versioned<T>(path: &Path, version: u32, migrate: Migration) -> Option<T>
+where
+    T: DeserializeOwned + Serialize + Default,
+{
+    let data = fs::read_to_string(path).ok()?;
+    let Ok(Value::Object(mut doc)) = serde_json::from_str::<Value>(&data) else {
+        backup(path, "unreadable");
+        return None;
+    };
+
+    let from = doc
+        .get("version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(1);
+    let outdated = from < version;
+    if outdated {
+        backup(path, &format!("v{}", from));
+        migrate(from, &mut doc);
+        doc.insert("version".into(), version.into());
+    }
+
+    let (value, rewrite) = match serde_json::from_value::<T>(Value::Object(doc.clone())) {
+        Ok(value) => (value, outdated),
+        Err(e) => {
+            tracing::warn!(target: "caches", "{} did not match its schema: {}", path.display(), e);
+            if !outdated {
+                backup(path, "unreadable");
+            }
+            (salvage(&doc), true)
+        }
+    };
+
+    if rewrite {
+        if let Err(e) = save_versioned(path, &value, version) {
+            tracing::warn!(target: "caches", "Failed to rewrite {}: {}", path.display(), e);
+        }
+    }
+    Some(value)
+}
+
+/// Saves a cache file atomically, stamped with `version`
+pub fn save_versioned<T: Serialize>(path: &Path, value: &T, version: u32) -> io::Result<()> {
+    let mut doc = serde_json::to_value(value)?;
+    if let Some(obj) = doc.as_object_mut() {
+        obj.insert("version".into(), version.into());
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(&doc)?)?;
+    fs::rename(&tmp_path, path)
+}
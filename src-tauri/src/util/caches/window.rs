@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io::Read, path::PathBuf};
+use tauri::{AppHandle, Monitor, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow};
+
+use crate::util::caches::get_cache_dir;
+
+// ===============================
+// WindowStateCache Structure
+// ===============================
+
+/// Last known geometry of the main window, in physical pixels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowStateCache {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    /// Name of the monitor the window was on, if the OS reports one
+    pub monitor: Option<String>,
+}
+
+/// Smallest size restored, so a corrupted cache can't produce an unusable window
+const MIN_WIDTH: u32 = 400;
+const MIN_HEIGHT: u32 = 300;
+
+impl WindowStateCache {
+    /// Snapshots a window's current geometry
+    pub fn capture<R: Runtime>(window: &WebviewWindow<R>) -> Option<Self> {
+        let maximized = window.is_maximized().unwrap_or(false);
+        let position = window.outer_position().ok()?;
+        let size = window.inner_size().ok()?;
+        let monitor = window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|m| m.name().cloned());
+
+        Some(Self {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized,
+            monitor,
+        })
+    }
+
+    /// Picks the monitor to restore onto: the saved one if still connected and the
+    /// window's center lies on it, otherwise any monitor containing the center.
+    /// None means the saved spot is off-screen (e.g. the monitor was unplugged).
+    fn target_monitor<'a>(&self, monitors: &'a [Monitor]) -> Option<&'a Monitor> {
+        let cx = self.x + (self.width / 2) as i32;
+        let cy = self.y + (self.height / 2) as i32;
+        let contains = |m: &Monitor| {
+            let pos = m.position();
+            let size = m.size();
+            cx >= pos.x
+                && cy >= pos.y
+                && cx < pos.x + size.width as i32
+                && cy < pos.y + size.height as i32
+        };
+
+        monitors
+            .iter()
+            .find(|m| m.name() == self.monitor.as_ref() && contains(m))
+            .or_else(|| monitors.iter().find(|m| contains(m)))
+    }
+
+    /// Applies the saved geometry. Returns false when there is no
+    /// connected monitor to restore onto, so the caller can fall back to defaults.
+    pub fn restore<R: Runtime>(&self, window: &WebviewWindow<R>) -> bool {
+        let monitors = window.available_monitors().unwrap_or_default();
+        let Some(monitor) = self.target_monitor(&monitors) else {
+            return false;
+        };
+
+        // never larger than the monitor it lands on
+        let width = self
+            .width
+            .clamp(MIN_WIDTH, monitor.size().width.max(MIN_WIDTH));
+        let height = self
+            .height
+            .clamp(MIN_HEIGHT, monitor.size().height.max(MIN_HEIGHT));
+
+        let _ = window.set_size(PhysicalSize::new(width, height));
+        let _ = window.set_position(PhysicalPosition::new(self.x, self.y));
+        if self.maximized {
+            let _ = window.maximize();
+        }
+        true
+    }
+}
+
+// ===============================
+// Internal Helpers
+// ===============================
+
+fn get_window_state_path(handle: &AppHandle) -> PathBuf {
+    let mut path = get_cache_dir(handle);
+    path.push("window.json");
+    path
+}
+
+/// Loads the saved window state, if any
+pub fn load_window_state(handle: &AppHandle) -> Option<WindowStateCache> {
+    let path = get_window_state_path(handle);
+
+    let mut file = fs::File::open(&path).ok()?;
+    let mut data = String::new();
+    file.read_to_string(&mut data).ok()?;
+    serde_json::from_str::<WindowStateCache>(&data).ok()
+}
+
+/// Saves the window state to disk atomically
+pub fn save_window_state(handle: &AppHandle, state: &WindowStateCache) {
+    let path = get_window_state_path(handle);
+    let tmp_path = path.with_extension("tmp");
+
+    let Ok(serialized) = serde_json::to_string_pretty(state) else {
+        return;
+    };
+    if fs::write(&tmp_path, serialized).is_ok() {
+        let _ = fs::rename(&tmp_path, &path);
+    }
+}
@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+    thread,
+};
+use tauri::{AppHandle, Manager, State};
+
+use crate::util::activity::{log_activity, ActivityLevel};
+
+// ===============================
+// Action Structures
+// ===============================
+
+/// A user-defined context-menu entry that runs an external command.
+///
+/// `command` is a template split like a shell command line (double quotes group words).
+/// Placeholders: `{path}`, `{dir}`, `{name}`, `{stem}`, `{ext}` for the current item,
+/// and `{paths}` which expands to every selected path as separate arguments.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CustomAction {
+    pub id: String,
+    pub label: String,
+    /// Disabled actions stay listed in settings but are hidden from the menu
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    /// Icon name or image path shown in the menu
+    #[serde(default)]
+    pub icon: Option<String>,
+    pub command: String,
+    /// Extensions (without dot) the action applies to; empty means any file
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Also offer the action on folders
+    #[serde(default)]
+    pub directories: bool,
+    /// Run once per selected item instead of once for the whole selection
+    #[serde(default)]
+    pub per_item: bool,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+impl CustomAction {
+    fn applies_to(&self, path: &Path) -> bool {
+        if path.is_dir() {
+            return self.directories;
+        }
+        self.extensions.is_empty()
+            || path
+                .extension()
+                .map(|e| e.to_string_lossy())
+                .is_some_and(|ext| self.extensions.iter().any(|x| x.eq_ignore_ascii_case(&ext)))
+    }
+}
+
+/// Persisted action list, managed as app state
+#[derive(Default)]
+pub struct CustomActionRegistry(pub Mutex<Vec<CustomAction>>);
+
+fn get_custom_actions_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    fs::create_dir_all(&path).ok();
+    path.push("custom_actions.json");
+    path
+}
+
+/// Loads custom actions from disk, or an empty list if missing
+pub fn load_custom_actions(handle: &AppHandle) -> Vec<CustomAction> {
+    fs::read_to_string(get_custom_actions_path(handle))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Saves custom actions to disk atomically
+fn save_custom_actions(handle: &AppHandle, actions: &[CustomAction]) -> Result<(), String> {
+    let path = get_custom_actions_path(handle);
+    let tmp_path = path.with_extension("tmp");
+
+    let serialized = serde_json::to_string_pretty(actions).map_err(|e| e.to_string())?;
+    fs::write(&tmp_path, serialized)
+        .map_err(|e| format!("Failed to write custom actions: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to save custom actions: {}", e))
+}
+
+// ===============================
+// Template Expansion
+// ===============================
+
+/// Splits a command line on whitespace, keeping double-quoted runs together
+fn split_command(template: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut has_token = false;
+
+    for c in template.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+    args
+}
+
+/// Expands placeholders for `current` into an argument list.
+/// Substitution happens after splitting, so paths with spaces stay one argument.
+fn expand_template(tokens: &[String], current: &Path, selection: &[String]) -> Vec<String> {
+    let lossy = |s: Option<&std::ffi::OsStr>| {
+        s.map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let path = current.to_string_lossy().to_string();
+    let dir = current
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = lossy(current.file_name());
+    let stem = lossy(current.file_stem());
+    let ext = lossy(current.extension());
+
+    let mut args = Vec::new();
+    for token in tokens {
+        if token == "{paths}" {
+            args.extend(selection.iter().cloned());
+            continue;
+        }
+        args.push(
+            token
+                .replace("{path}", &path)
+                .replace("{dir}", &dir)
+                .replace("{name}", &name)
+                .replace("{stem}", &stem)
+                .replace("{ext}", &ext),
+        );
+    }
+    args
+}
+
+/// Launches one expanded command and logs its result to the activity log when it exits
+fn spawn_action(
+    handle: &AppHandle,
+    action: &CustomAction,
+    argv: &[String],
+    cwd: &Path,
+) -> Result<(), String> {
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| format!("\"{}\" has an empty command", action.label))?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if cwd.is_dir() {
+        cmd.current_dir(cwd);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run \"{}\": {}", action.label, e))?;
+
+    let handle = handle.clone();
+    let label = action.label.clone();
+    thread::spawn(move || {
+        let (level, message, output) = match child.wait_with_output() {
+            Ok(out) => {
+                let mut text = String::from_utf8_lossy(&out.stdout).to_string();
+                text.push_str(&String::from_utf8_lossy(&out.stderr));
+                if out.status.success() {
+                    (ActivityLevel::Info, format!("\"{}\" finished", label), text)
+                } else {
+                    (
+                        ActivityLevel::Error,
+                        format!("\"{}\" exited with an error", label),
+                        text,
+                    )
+                }
+            }
+            Err(e) => (
+                ActivityLevel::Error,
+                format!("\"{}\" failed: {}", label, e),
+                String::new(),
+            ),
+        };
+        let output = (!output.trim().is_empty()).then_some(output);
+        log_activity(&handle, "custom_action", level, message, output);
+    });
+
+    Ok(())
+}
+
+// ===============================
+// Commands
+// ===============================
+
+#[tauri::command]
+pub fn get_custom_actions(registry: State<'_, CustomActionRegistry>) -> Vec<CustomAction> {
+    registry.0.lock().unwrap().clone()
+}
+
+/// Replaces the action list and persists it
+#[tauri::command]
+pub fn update_custom_actions(
+    handle: AppHandle,
+    registry: State<'_, CustomActionRegistry>,
+    actions: Vec<CustomAction>,
+) -> Result<(), String> {
+    for action in &actions {
+        if split_command(&action.command).is_empty() {
+            return Err(format!("\"{}\" has an empty command", action.label));
+        }
+    }
+    save_custom_actions(&handle, &actions)?;
+    *registry.0.lock().unwrap() = actions;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_custom_action_enabled(
+    handle: AppHandle,
+    registry: State<'_, CustomActionRegistry>,
+    id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut actions = registry.0.lock().unwrap();
+    let action = actions
+        .iter_mut()
+        .find(|a| a.id == id)
+        .ok_or_else(|| format!("Unknown action: {}", id))?;
+    action.enabled = enabled;
+    save_custom_actions(&handle, &actions)
+}
+
+/// Enabled custom actions that apply to every selected path, for the context menu
+#[tauri::command]
+pub fn get_custom_actions_for(
+    registry: State<'_, CustomActionRegistry>,
+    paths: Vec<String>,
+) -> Vec<CustomAction> {
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    registry
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|a| a.enabled && paths.iter().all(|p| a.applies_to(Path::new(p))))
+        .cloned()
+        .collect()
+}
+
+/// Runs a custom action on the selection. Output is reported through the activity log.
+#[tauri::command]
+pub fn execute_custom_action(
+    handle: AppHandle,
+    registry: State<'_, CustomActionRegistry>,
+    id: String,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    let action = registry
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|a| a.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown action: {}", id))?;
+    if !action.enabled {
+        return Err(format!("\"{}\" is disabled", action.label));
+    }
+
+    let first = paths.first().ok_or("No items selected")?;
+    if let Some(bad) = paths.iter().find(|p| !action.applies_to(Path::new(p))) {
+        return Err(format!("\"{}\" does not apply to {}", action.label, bad));
+    }
+
+    let tokens = split_command(&action.command);
+    let targets: Vec<&String> = if action.per_item {
+        paths.iter().collect()
+    } else {
+        vec![first]
+    };
+
+    for target in targets {
+        let target = Path::new(target);
+        let argv = expand_template(&tokens, target, &paths);
+        let cwd = target.parent().unwrap_or(target);
+        spawn_action(&handle, &action, &argv, cwd)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use rusqlite::Connection;
+use serde::Serialize;
+use std::{fs, path::Path, sync::Arc};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    filesys::{rules::RulesState, watcher::SharedWatcher},
+    util::{
+        activity::{ActivityEntry, ActivityLevel, ActivityLog},
+        caches::{open_thumb_db, SharedAppSettings},
+        tags::open_tags_db,
+    },
+};
+
+/// Recent errors included in the report
+const MAX_REPORTED_ERRORS: usize = 20;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub ffmpeg: FfmpegStatus,
+    pub databases: Vec<DatabaseHealth>,
+    pub watchers: WatcherStatus,
+    pub thread_pool: ThreadPoolStatus,
+    pub recent_errors: Vec<ActivityEntry>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FfmpegStatus {
+    pub available: bool,
+    pub path: String,
+    /// First line of `ffmpeg -version`
+    pub version: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DatabaseHealth {
+    pub name: String,
+    pub size_bytes: u64,
+    /// "ok", or the first problem reported by SQLite's quick_check
+    pub integrity: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WatcherStatus {
+    pub home_watcher: bool,
+    pub rules_watcher: bool,
+    pub auto_folders: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ThreadPoolStatus {
+    pub threads: usize,
+    pub configured_threads: usize,
+}
+
+/// Checks the bundled ffmpeg without going through `ffmpeg_init`, which panics when missing
+fn ffmpeg_status(handle: &AppHandle) -> FfmpegStatus {
+    let path = handle
+        .path()
+        .resource_dir()
+        .map(|d| d.join("binaries/windows/ffmpeg.exe"))
+        .unwrap_or_default();
+
+    let version = path
+        .is_file()
+        .then(|| {
+            std::process::Command::new(&path)
+                .arg("-version")
+                .output()
+                .ok()
+        })
+        .flatten()
+        .filter(|out| out.status.success())
+        .and_then(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .next()
+                .map(str::to_string)
+        });
+
+    FfmpegStatus {
+        available: version.is_some(),
+        path: path.to_string_lossy().to_string(),
+        version,
+    }
+}
+
+fn database_health(name: &str, conn: rusqlite::Result<Connection>) -> DatabaseHealth {
+    let (size_bytes, integrity) = match conn {
+        Ok(conn) => {
+            let size = conn
+                .path()
+                .and_then(|p| fs::metadata(Path::new(p)).ok())
+                .map(|m| m.len())
+                .unwrap_or(0);
+            let integrity = conn
+                .query_row("PRAGMA quick_check", [], |r| r.get::<_, String>(0))
+                .unwrap_or_else(|e| e.to_string());
+            (size, integrity)
+        }
+        Err(e) => (0, format!("Failed to open: {}", e)),
+    };
+
+    DatabaseHealth {
+        name: name.to_string(),
+        size_bytes,
+        integrity,
+    }
+}
+
+/// Environment and health report for the Help > Diagnostics page and bug reports
+#[tauri::command]
+pub async fn get_diagnostics(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    pool: State<'_, Arc<rayon::ThreadPool>>,
+    rules: State<'_, Arc<RulesState>>,
+    home_watcher: State<'_, SharedWatcher>,
+    activity: State<'_, ActivityLog>,
+) -> Result<Diagnostics, String> {
+    let configured_threads = settings.0.read().await.performance.worker_threads;
+    let app_version = handle.package_info().version.to_string();
+
+    let recent_errors: Vec<ActivityEntry> = {
+        let log = activity.0.lock().unwrap();
+        let errors: Vec<&ActivityEntry> = log
+            .iter()
+            .filter(|e| e.level == ActivityLevel::Error)
+            .collect();
+        errors
+            .iter()
+            .skip(errors.len().saturating_sub(MAX_REPORTED_ERRORS))
+            .map(|e| (*e).clone())
+            .collect()
+    };
+
+    let watchers = WatcherStatus {
+        home_watcher: home_watcher.lock().unwrap().is_some(),
+        rules_watcher: rules.is_watching(),
+        auto_folders: rules.config.lock().unwrap().auto_folders.len(),
+    };
+
+    let thread_pool = ThreadPoolStatus {
+        threads: pool.current_num_threads(),
+        configured_threads,
+    };
+
+    // ffmpeg and integrity checks touch the disk; keep them off the async runtime
+    let (ffmpeg, databases) = tauri::async_runtime::spawn_blocking(move || {
+        let databases = vec![
+            database_health("thumbs.db", open_thumb_db(&handle)),
+            database_health("tags.db", open_tags_db(&handle)),
+        ];
+        (ffmpeg_status(&handle), databases)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(Diagnostics {
+        app_version,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        ffmpeg,
+        databases,
+        watchers,
+        thread_pool,
+        recent_errors,
+    })
+}
@@ -0,0 +1,250 @@
+use serde::Serialize;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tauri::{AppHandle, State};
+
+use crate::util::caches::SharedAppSettings;
+
+/// A known editor/IDE and where to look for it
+struct EditorSpec {
+    id: &'static str,
+    name: &'static str,
+    /// Launcher names looked up on PATH (and in JetBrains Toolbox's scripts folder)
+    commands: &'static [&'static str],
+    /// Install locations relative to an environment variable, e.g. ("LOCALAPPDATA", "Programs\\...")
+    install_paths: &'static [(&'static str, &'static str)],
+}
+
+const EDITORS: &[EditorSpec] = &[
+    EditorSpec {
+        id: "vscode",
+        name: "Visual Studio Code",
+        commands: &["code"],
+        install_paths: &[
+            ("LOCALAPPDATA", "Programs\\Microsoft VS Code\\Code.exe"),
+            ("ProgramFiles", "Microsoft VS Code\\Code.exe"),
+        ],
+    },
+    EditorSpec {
+        id: "vscode_insiders",
+        name: "Visual Studio Code - Insiders",
+        commands: &["code-insiders"],
+        install_paths: &[(
+            "LOCALAPPDATA",
+            "Programs\\Microsoft VS Code Insiders\\Code - Insiders.exe",
+        )],
+    },
+    EditorSpec {
+        id: "cursor",
+        name: "Cursor",
+        commands: &["cursor"],
+        install_paths: &[("LOCALAPPDATA", "Programs\\cursor\\Cursor.exe")],
+    },
+    EditorSpec {
+        id: "zed",
+        name: "Zed",
+        commands: &["zed"],
+        install_paths: &[("LOCALAPPDATA", "Programs\\Zed\\Zed.exe")],
+    },
+    EditorSpec {
+        id: "sublime",
+        name: "Sublime Text",
+        commands: &["subl"],
+        install_paths: &[("ProgramFiles", "Sublime Text\\sublime_text.exe")],
+    },
+    EditorSpec {
+        id: "notepad_plus_plus",
+        name: "Notepad++",
+        commands: &[],
+        install_paths: &[("ProgramFiles", "Notepad++\\notepad++.exe")],
+    },
+    EditorSpec {
+        id: "idea",
+        name: "IntelliJ IDEA",
+        commands: &["idea", "idea64"],
+        install_paths: &[],
+    },
+    EditorSpec {
+        id: "pycharm",
+        name: "PyCharm",
+        commands: &["pycharm", "pycharm64"],
+        install_paths: &[],
+    },
+    EditorSpec {
+        id: "webstorm",
+        name: "WebStorm",
+        commands: &["webstorm", "webstorm64"],
+        install_paths: &[],
+    },
+    EditorSpec {
+        id: "clion",
+        name: "CLion",
+        commands: &["clion", "clion64"],
+        install_paths: &[],
+    },
+    EditorSpec {
+        id: "rustrover",
+        name: "RustRover",
+        commands: &["rustrover", "rustrover64"],
+        install_paths: &[],
+    },
+    EditorSpec {
+        id: "goland",
+        name: "GoLand",
+        commands: &["goland", "goland64"],
+        install_paths: &[],
+    },
+];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EditorInfo {
+    pub id: String,
+    pub name: String,
+    pub executable: String,
+    pub is_default: bool,
+}
+
+/// Executable extensions tried when resolving a bare command name
+fn executable_names(command: &str) -> Vec<String> {
+    if cfg!(windows) {
+        ["exe", "cmd", "bat"]
+            .iter()
+            .map(|ext| format!("{}.{}", command, ext))
+            .collect()
+    } else {
+        vec![command.to_string()]
+    }
+}
+
+/// Folder where JetBrains Toolbox drops its shell launchers
+fn toolbox_scripts_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(|d| PathBuf::from(d).join("JetBrains\\Toolbox\\scripts"))
+    } else if cfg!(target_os = "macos") {
+        dirs_next::home_dir()
+            .map(|h| h.join("Library/Application Support/JetBrains/Toolbox/scripts"))
+    } else {
+        dirs_next::data_dir().map(|d| d.join("JetBrains/Toolbox/scripts"))
+    }
+}
+
+/// Resolves an editor to an executable on this machine
+fn locate_editor(spec: &EditorSpec) -> Option<PathBuf> {
+    let mut search_dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|p| env::split_paths(&p).collect())
+        .unwrap_or_default();
+    search_dirs.extend(toolbox_scripts_dir());
+
+    for command in spec.commands {
+        for dir in &search_dirs {
+            for name in executable_names(command) {
+                let candidate = dir.join(name);
+                if candidate.is_file() {
+                    return Some(candidate);
+                }
+            }
+        }
+    }
+
+    if cfg!(windows) {
+        for (var, rel) in spec.install_paths {
+            if let Some(base) = env::var_os(var) {
+                let candidate = PathBuf::from(base).join(rel);
+                if candidate.is_file() {
+                    return Some(candidate);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Spawns an editor on the given path without flashing a console for .cmd launchers
+fn launch_editor(executable: &Path, path: &str) -> std::io::Result<()> {
+    let mut cmd = Command::new(executable);
+    cmd.arg(path);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    cmd.spawn().map(|_| ())
+}
+
+/// Lists editors/IDEs detected on this machine
+#[tauri::command]
+pub async fn list_editors(
+    settings: State<'_, SharedAppSettings>,
+) -> Result<Vec<EditorInfo>, String> {
+    let default_editor = settings.0.read().await.integrations.default_editor.clone();
+
+    Ok(EDITORS
+        .iter()
+        .filter_map(|spec| {
+            let executable = locate_editor(spec)?;
+            Some(EditorInfo {
+                id: spec.id.to_string(),
+                name: spec.name.to_string(),
+                executable: executable.to_string_lossy().to_string(),
+                is_default: default_editor.as_deref() == Some(spec.id),
+            })
+        })
+        .collect())
+}
+
+/// Opens a file or folder in an editor. Falls back to the configured default,
+/// then to the first editor found, when `editor_id` is omitted.
+#[tauri::command]
+pub async fn open_in_editor(
+    settings: State<'_, SharedAppSettings>,
+    path: String,
+    editor_id: Option<String>,
+) -> Result<(), String> {
+    if !Path::new(&path).exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+
+    let default_editor = settings.0.read().await.integrations.default_editor.clone();
+    let (spec, executable) = match editor_id.or(default_editor) {
+        Some(id) => {
+            let spec = EDITORS
+                .iter()
+                .find(|spec| spec.id == id)
+                .ok_or_else(|| format!("Unknown editor: {}", id))?;
+            let executable =
+                locate_editor(spec).ok_or_else(|| format!("{} is not installed", spec.name))?;
+            (spec, executable)
+        }
+        None => EDITORS
+            .iter()
+            .find_map(|spec| locate_editor(spec).map(|exe| (spec, exe)))
+            .ok_or("No supported editor found")?,
+    };
+
+    launch_editor(&executable, &path).map_err(|e| format!("Failed to launch {}: {}", spec.name, e))
+}
+
+/// Sets (or clears) the default editor used by `open_in_editor`
+#[tauri::command]
+pub async fn set_default_editor(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    editor_id: Option<String>,
+) -> Result<(), String> {
+    if let Some(id) = &editor_id {
+        if !EDITORS.iter().any(|spec| spec.id == id) {
+            return Err(format!("Unknown editor: {}", id));
+        }
+    }
+
+    settings.0.write().await.integrations.default_editor = editor_id;
+    settings.commit(&handle).await;
+    Ok(())
+}
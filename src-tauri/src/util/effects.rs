@@ -0,0 +1,45 @@
+use tauri::{webview::Color, Runtime, WebviewWindow};
+use window_vibrancy::{
+    apply_acrylic, apply_blur, apply_mica, apply_vibrancy, clear_acrylic, clear_blur, clear_mica,
+    clear_vibrancy, NSVisualEffectMaterial,
+};
+
+use crate::util::caches::WindowEffect;
+
+/// Tint used by the Windows acrylic and blur effects
+const EFFECT_TINT: (u8, u8, u8, u8) = (0, 0, 0, 20);
+/// Solid backdrop when no effect is wanted or the platform has none (e.g. Linux)
+const FALLBACK_BACKGROUND: Color = Color(120, 120, 120, 255);
+
+/// Applies the backdrop effect to a window, replacing any previous one.
+/// macOS maps each mode to a vibrancy material; platforms without
+/// compositor effects get a solid background instead.
+pub fn apply_window_effect<R: Runtime>(window: &WebviewWindow<R>, effect: WindowEffect) {
+    // unsupported-platform errors are expected here
+    let _ = clear_acrylic(window);
+    let _ = clear_mica(window);
+    let _ = clear_blur(window);
+    let _ = clear_vibrancy(window);
+
+    let applied = match effect {
+        WindowEffect::None => false,
+        _ if cfg!(target_os = "macos") => {
+            let material = match effect {
+                WindowEffect::Mica => NSVisualEffectMaterial::UnderWindowBackground,
+                WindowEffect::Blur => NSVisualEffectMaterial::Sidebar,
+                _ => NSVisualEffectMaterial::HudWindow,
+            };
+            apply_vibrancy(window, material, None, None).is_ok()
+        }
+        WindowEffect::Acrylic => apply_acrylic(window, Some(EFFECT_TINT)).is_ok(),
+        WindowEffect::Mica => apply_mica(window, None).is_ok(),
+        WindowEffect::Blur => apply_blur(window, Some(EFFECT_TINT)).is_ok(),
+    };
+
+    let background = if applied {
+        Color(0, 0, 0, 0)
+    } else {
+        FALLBACK_BACKGROUND
+    };
+    window.set_background_color(Some(background)).ok();
+}
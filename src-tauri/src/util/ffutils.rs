@@ -58,6 +58,120 @@ impl FFmpegHandler {
             .unwrap_or_else(|e| panic!("Failed to decode image from FFmpeg output: {}", e))
     }
 
+    /// One frame at `time_sec` as a PNG-decoded image, or None if FFmpeg fails
+    /// (e.g. the timestamp is past the end)
+    pub fn frame_at(&self, video: &str, time_sec: f64) -> Option<DynamicImage> {
+        let output = Command::new(&self.ffmpeg_path)
+            .args([
+                "-ss",
+                &format!("{:.3}", time_sec),
+                "-i",
+                video,
+                "-frames:v",
+                "1",
+                "-f",
+                "image2pipe",
+                "-vcodec",
+                "png",
+                "pipe:1",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() || output.stdout.is_empty() {
+            return None;
+        }
+        load_from_memory(&output.stdout).ok()
+    }
+
+    /// Encodes `seconds` of `video` from `start` as a silent, looping animation
+    /// `width` pixels wide, in `format` ("webp" or "gif")
+    pub fn animated_clip(
+        &self,
+        video: &str,
+        start: f64,
+        seconds: f64,
+        width: u32,
+        format: &str,
+    ) -> Option<Vec<u8>> {
+        let filter = format!("fps=10,scale={}:-2:flags=bicubic", width);
+        let mut cmd = Command::new(&self.ffmpeg_path);
+        cmd.args(["-ss", &format!("{:.3}", start)])
+            .args(["-t", &format!("{:.3}", seconds)])
+            .args(["-i", video, "-an", "-vf", &filter, "-loop", "0"]);
+        if format == "webp" {
+            cmd.args(["-c:v", "libwebp", "-q:v", "50"]);
+        }
+        let output = cmd.args(["-f", format, "pipe:1"]).output().ok()?;
+        (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
+    }
+
+    /// Container duration in seconds
+    pub fn duration(&self, video: &str) -> Option<f64> {
+        let output = Command::new(&self.ffprobe_path)
+            .args([
+                "-v",
+                "error",
+                "-show_entries",
+                "format=duration",
+                "-of",
+                "default=noprint_wrappers=1:nokey=1",
+                video,
+            ])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|d| d.is_finite() && *d > 0.0)
+    }
+
+    /// Embedded cover art (an `attached_pic` video stream, as in MKV/MP4 with artwork)
+    pub fn cover_art(&self, video: &str) -> Option<DynamicImage> {
+        let probe = Command::new(&self.ffprobe_path)
+            .args([
+                "-v",
+                "error",
+                "-select_streams",
+                "v",
+                "-show_entries",
+                "stream=index:stream_disposition=attached_pic",
+                "-of",
+                "csv=p=0",
+                video,
+            ])
+            .output()
+            .ok()?;
+        // one "index,attached_pic" line per video stream
+        let index = String::from_utf8_lossy(&probe.stdout)
+            .lines()
+            .find_map(|line| {
+                let (index, attached) = line.trim().split_once(',')?;
+                (attached == "1").then(|| index.to_string())
+            })?;
+
+        let output = Command::new(&self.ffmpeg_path)
+            .args([
+                "-i",
+                video,
+                "-map",
+                &format!("0:{}", index),
+                "-frames:v",
+                "1",
+                "-f",
+                "image2pipe",
+                "-vcodec",
+                "png",
+                "pipe:1",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        load_from_memory(&output.stdout).ok()
+    }
+
     /// Probe video metadata
     pub fn probe_video(&self, video: &str) -> String {
         let output = Command::new(&self.ffprobe_path)
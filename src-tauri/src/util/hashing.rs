@@ -0,0 +1,244 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::UNIX_EPOCH,
+};
+use tauri::{AppHandle, Manager};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Bytes read per hashing step; cancellation is checked between reads
+const HASH_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Hash algorithms offered by the checksum column, copy verification and the
+/// duplicate finder
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumAlgo {
+    Crc32,
+    Md5,
+    Sha256,
+    /// 64-bit XXH3: not cryptographic, but by far the fastest
+    Xxh3,
+}
+
+impl ChecksumAlgo {
+    fn id(self) -> &'static str {
+        match self {
+            ChecksumAlgo::Crc32 => "crc32",
+            ChecksumAlgo::Md5 => "md5",
+            ChecksumAlgo::Sha256 => "sha256",
+            ChecksumAlgo::Xxh3 => "xxh3",
+        }
+    }
+}
+
+enum Hasher {
+    Crc32(crc32fast::Hasher),
+    Md5(md5::Md5),
+    Sha256(sha2::Sha256),
+    Xxh3(Box<Xxh3>),
+}
+
+impl Hasher {
+    fn new(algo: ChecksumAlgo) -> Self {
+        match algo {
+            ChecksumAlgo::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            ChecksumAlgo::Md5 => Hasher::Md5(md5::Md5::new()),
+            ChecksumAlgo::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            ChecksumAlgo::Xxh3 => Hasher::Xxh3(Box::new(Xxh3::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Crc32(h) => h.update(data),
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Xxh3(h) => h.update(data),
+        }
+    }
+
+    /// Lowercase hex digest
+    fn finish(self) -> String {
+        match self {
+            Hasher::Crc32(h) => format!("{:08x}", h.finalize()),
+            Hasher::Md5(h) => to_hex(&h.finalize()),
+            Hasher::Sha256(h) => to_hex(&h.finalize()),
+            Hasher::Xxh3(h) => format!("{:016x}", h.digest()),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hashes a file's contents in `HASH_BUFFER_SIZE` chunks, always reading the
+/// file. Returns `Ok(None)` once `keep_going` returns false.
+pub fn hash_file(
+    path: &Path,
+    algo: ChecksumAlgo,
+    mut keep_going: impl FnMut() -> bool,
+) -> io::Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::new(algo);
+    let mut buf = vec![0u8; HASH_BUFFER_SIZE];
+    loop {
+        if !keep_going() {
+            return Ok(None);
+        }
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(Some(hasher.finish()))
+}
+
+// ===============================
+// Cache
+// ===============================
+
+/// Location of the checksum cache DB, next to the thumbnail cache
+fn get_checksum_db_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("caches");
+    fs::create_dir_all(&path).ok();
+    path.push("checksums.db");
+    path
+}
+
+fn open_checksum_db(handle: &AppHandle) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(get_checksum_db_path(handle))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS checksums (
+            path TEXT NOT NULL,
+            algo TEXT NOT NULL,
+            mtime INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            checksum TEXT NOT NULL,
+            PRIMARY KEY (path, algo)
+        );
+        PRAGMA journal_mode=WAL;
+        PRAGMA synchronous=NORMAL;",
+    )?;
+    Ok(conn)
+}
+
+/// A checksum cache connection, opened once per batch of files and shared by
+/// the threads hashing them. Without the DB, files are simply hashed uncached.
+pub struct ChecksumCache(Option<Mutex<Connection>>);
+
+impl ChecksumCache {
+    pub fn open(handle: &AppHandle) -> Self {
+        Self(open_checksum_db(handle).ok().map(Mutex::new))
+    }
+
+    /// Cached checksum, or None if missing or the file changed since
+    fn get(&self, path: &str, algo: ChecksumAlgo, mtime: i64, size: i64) -> Option<String> {
+        let conn = self.0.as_ref()?.lock().unwrap();
+        conn.query_row(
+            "SELECT checksum FROM checksums
+             WHERE path = ?1 AND algo = ?2 AND mtime = ?3 AND size = ?4",
+            params![path, algo.id(), mtime, size],
+            |r| r.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()
+    }
+
+    fn put(&self, path: &str, algo: ChecksumAlgo, mtime: i64, size: i64, checksum: &str) {
+        if let Some(conn) = &self.0 {
+            let _ = conn.lock().unwrap().execute(
+                "INSERT OR REPLACE INTO checksums (path, algo, mtime, size, checksum)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![path, algo.id(), mtime, size, checksum],
+            );
+        }
+    }
+}
+
+/// Hashes a regular file, reusing the value cached for the same path, size and
+/// mtime and caching new ones. Returns `Ok(None)` once `keep_going` returns false.
+pub fn hash_file_cached(
+    cache: &ChecksumCache,
+    path: &str,
+    algo: ChecksumAlgo,
+    keep_going: impl FnMut() -> bool,
+) -> Result<Option<String>, String> {
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    if !meta.is_file() {
+        return Err("Not a file".into());
+    }
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let size = meta.len() as i64;
+
+    if let Some(checksum) = cache.get(path, algo, mtime, size) {
+        return Ok(Some(checksum));
+    }
+    let Some(checksum) = hash_file(Path::new(path), algo, keep_going).map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    cache.put(path, algo, mtime, size, &checksum);
+    Ok(Some(checksum))
+}
+
+// ===============================
+// Work queue
+// ===============================
+
+/// Queues `paths` for hashing on the shared rayon pool, sharing one cache
+/// connection between them, and calls `on_hashed`
+/// with each path and its (cached) checksum or error as files finish, in no
+/// particular order. Missing paths and folders are skipped, and files still
+/// queued or mid-read once `keep_going` returns false are dropped, both without
+/// a callback.
+pub fn hash_files<K, F>(
+    handle: &AppHandle,
+    paths: Vec<String>,
+    algo: ChecksumAlgo,
+    keep_going: K,
+    on_hashed: F,
+) where
+    K: Fn() -> bool + Send + Sync + 'static,
+    F: Fn(String, Result<String, String>) + Send + Sync + 'static,
+{
+    let pool = handle.state::<Arc<rayon::ThreadPool>>().inner().clone();
+    let cache = Arc::new(ChecksumCache::open(handle));
+    let keep_going = Arc::new(keep_going);
+    let on_hashed = Arc::new(on_hashed);
+    for path in paths {
+        let cache = cache.clone();
+        let keep_going = keep_going.clone();
+        let on_hashed = on_hashed.clone();
+        pool.spawn(move || {
+            if !keep_going() || !fs::metadata(&path).is_ok_and(|m| m.is_file()) {
+                return;
+            }
+            let result = match hash_file_cached(&cache, &path, algo, || keep_going()) {
+                Ok(Some(checksum)) => Ok(checksum),
+                Ok(None) => return,
+                Err(e) => Err(e),
+            };
+            if keep_going() {
+                on_hashed(path, result);
+            }
+        });
+    }
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    filesys::stream::jobs::JobKind,
+    util::activity::{log_activity, ActivityLevel},
+};
+
+/// Hooks running longer than this are killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(300);
+/// Captured output beyond this many bytes is truncated in the activity log
+const MAX_HOOK_OUTPUT: usize = 8 * 1024;
+
+// ===============================
+// Hook Structures
+// ===============================
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookTrigger {
+    /// Files were pasted (copied or moved) into this folder
+    PastedInto { folder: String },
+    /// A background job finished; empty `kinds` matches every job
+    JobCompleted {
+        #[serde(default)]
+        kinds: Vec<JobKind>,
+        #[serde(default)]
+        only_failures: bool,
+    },
+}
+
+/// A user script run when its trigger fires.
+/// Event details are passed as `DAGGER_*` environment variables.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Hook {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    pub trigger: HookTrigger,
+    /// Program to run, e.g. "powershell", "bash" or a script path
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// Something that happened which hooks can react to
+pub enum HookEvent<'a> {
+    PastedInto {
+        folder: &'a Path,
+        paths: &'a [PathBuf],
+    },
+    JobCompleted {
+        kind: JobKind,
+        success: bool,
+        summary: &'a str,
+    },
+}
+
+impl HookEvent<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            HookEvent::PastedInto { .. } => "pasted_into",
+            HookEvent::JobCompleted { .. } => "job_completed",
+        }
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![("DAGGER_EVENT", self.name().to_string())];
+        match self {
+            HookEvent::PastedInto { folder, paths } => {
+                env.push(("DAGGER_DIR", folder.to_string_lossy().to_string()));
+                let paths: Vec<String> = paths
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect();
+                env.push(("DAGGER_PATHS", paths.join("\n")));
+            }
+            HookEvent::JobCompleted {
+                kind,
+                success,
+                summary,
+            } => {
+                let kind = serde_json::to_value(kind)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                env.push(("DAGGER_JOB_KIND", kind));
+                env.push(("DAGGER_SUCCESS", success.to_string()));
+                env.push(("DAGGER_SUMMARY", summary.to_string()));
+            }
+        }
+        env
+    }
+}
+
+fn same_folder(a: &Path, b: &Path) -> bool {
+    match (dunce::canonicalize(a), dunce::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+impl Hook {
+    fn matches(&self, event: &HookEvent) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match (&self.trigger, event) {
+            (HookTrigger::PastedInto { folder }, HookEvent::PastedInto { folder: dir, .. }) => {
+                same_folder(Path::new(folder), dir)
+            }
+            (
+                HookTrigger::JobCompleted {
+                    kinds,
+                    only_failures,
+                },
+                HookEvent::JobCompleted { kind, success, .. },
+            ) => (kinds.is_empty() || kinds.contains(kind)) && !(*only_failures && *success),
+            _ => false,
+        }
+    }
+}
+
+/// Persisted hook list, managed as app state
+#[derive(Default)]
+pub struct HookRegistry(pub Mutex<Vec<Hook>>);
+
+fn get_hooks_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    fs::create_dir_all(&path).ok();
+    path.push("hooks.json");
+    path
+}
+
+/// Loads hooks from disk, or an empty list if missing
+pub fn load_hooks(handle: &AppHandle) -> Vec<Hook> {
+    fs::read_to_string(get_hooks_path(handle))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Saves hooks to disk atomically
+fn save_hooks(handle: &AppHandle, hooks: &[Hook]) -> Result<(), String> {
+    let path = get_hooks_path(handle);
+    let tmp_path = path.with_extension("tmp");
+
+    let serialized = serde_json::to_string_pretty(hooks).map_err(|e| e.to_string())?;
+    fs::write(&tmp_path, serialized).map_err(|e| format!("Failed to write hooks: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to save hooks: {}", e))
+}
+
+// ===============================
+// Execution
+// ===============================
+
+fn read_capped(mut reader: impl Read) -> String {
+    let mut buf = Vec::new();
+    let _ = reader.read_to_end(&mut buf);
+    let mut out = String::from_utf8_lossy(&buf).to_string();
+    if out.len() > MAX_HOOK_OUTPUT {
+        let mut cut = MAX_HOOK_OUTPUT;
+        while !out.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        out.truncate(cut);
+        out.push_str("\n…");
+    }
+    out
+}
+
+/// Runs one hook to completion, returning (success, combined output)
+fn run_hook(hook: &Hook, env: &[(&'static str, String)]) -> Result<(bool, String), String> {
+    let mut cmd = Command::new(&hook.program);
+    cmd.args(&hook.args)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let out_reader = thread::spawn(move || read_capped(stdout));
+    let err_reader = thread::spawn(move || read_capped(stderr));
+
+    let deadline = Instant::now() + HOOK_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+
+    let mut output = out_reader.join().unwrap_or_default();
+    let errors = err_reader.join().unwrap_or_default();
+    if !errors.trim().is_empty() {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(&errors);
+    }
+
+    match status {
+        Some(status) => Ok((status.success(), output)),
+        None => Err(format!("Timed out after {}s", HOOK_TIMEOUT.as_secs())),
+    }
+}
+
+/// Runs every enabled hook matching `event` on background threads.
+/// Results and captured output go to the activity log.
+pub fn fire_hooks(handle: &AppHandle, event: HookEvent) {
+    let Some(registry) = handle.try_state::<HookRegistry>() else {
+        return;
+    };
+    let hooks: Vec<Hook> = registry
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|h| h.matches(&event))
+        .cloned()
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+
+    let env = event.env();
+    for hook in hooks {
+        let handle = handle.clone();
+        let env = env.clone();
+        thread::spawn(move || {
+            let (level, message, output) = match run_hook(&hook, &env) {
+                Ok((true, output)) => (
+                    ActivityLevel::Info,
+                    format!("Hook \"{}\" finished", hook.name),
+                    output,
+                ),
+                Ok((false, output)) => (
+                    ActivityLevel::Error,
+                    format!("Hook \"{}\" exited with an error", hook.name),
+                    output,
+                ),
+                Err(e) => (
+                    ActivityLevel::Error,
+                    format!("Hook \"{}\" failed: {}", hook.name, e),
+                    String::new(),
+                ),
+            };
+            let output = (!output.trim().is_empty()).then_some(output);
+            log_activity(&handle, "hook", level, message, output);
+        });
+    }
+}
+
+// ===============================
+// Commands
+// ===============================
+
+#[tauri::command]
+pub fn get_hooks(registry: State<'_, HookRegistry>) -> Vec<Hook> {
+    registry.0.lock().unwrap().clone()
+}
+
+/// Replaces the hook list and persists it
+#[tauri::command]
+pub fn update_hooks(
+    handle: AppHandle,
+    registry: State<'_, HookRegistry>,
+    hooks: Vec<Hook>,
+) -> Result<(), String> {
+    for hook in &hooks {
+        if hook.program.trim().is_empty() {
+            return Err(format!("Hook \"{}\" has no program", hook.name));
+        }
+    }
+    save_hooks(&handle, &hooks)?;
+    *registry.0.lock().unwrap() = hooks;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_hook_enabled(
+    handle: AppHandle,
+    registry: State<'_, HookRegistry>,
+    id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut hooks = registry.0.lock().unwrap();
+    let hook = hooks
+        .iter_mut()
+        .find(|h| h.id == id)
+        .ok_or_else(|| format!("Unknown hook: {}", id))?;
+    hook.enabled = enabled;
+    save_hooks(&handle, &hooks)
+}
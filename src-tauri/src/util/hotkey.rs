@@ -0,0 +1,50 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::util::{
+    caches::{HotkeySettings, SharedAppSettings},
+    setup::show_window_at,
+};
+
+/// Registers the configured shortcut, replacing any previous one.
+/// Fails on an unparsable accelerator or one already taken by another app.
+pub fn apply_global_hotkey(handle: &AppHandle, settings: &HotkeySettings) -> Result<(), String> {
+    let shortcut = if settings.enabled {
+        Some(
+            settings
+                .shortcut
+                .parse::<Shortcut>()
+                .map_err(|e| format!("Invalid shortcut \"{}\": {}", settings.shortcut, e))?,
+        )
+    } else {
+        None
+    };
+
+    let manager = handle.global_shortcut();
+    manager
+        .unregister_all()
+        .map_err(|e| format!("Failed to clear shortcuts: {}", e))?;
+    if let Some(shortcut) = shortcut {
+        manager
+            .register(shortcut)
+            .map_err(|e| format!("Failed to register \"{}\": {}", settings.shortcut, e))?;
+    }
+    Ok(())
+}
+
+/// Plugin handler: shows/focuses the main window and optionally opens a new tab
+pub fn on_global_hotkey(handle: &AppHandle, _shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+
+    let settings = handle
+        .try_state::<SharedAppSettings>()
+        .and_then(|s| s.0.try_read().ok().map(|s| s.global_hotkey.clone()))
+        .unwrap_or_default();
+    let location = settings
+        .open_new_tab
+        .then(|| settings.default_location.clone());
+
+    show_window_at(handle, location);
+}
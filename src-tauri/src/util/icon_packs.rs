@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::{AppHandle, Manager};
+
+use crate::filesys::kind::{kind_from_extension, FileKind};
+use crate::util::caches::SharedAppSettings;
+
+/// Id of the icons generated in code, used when no pack is chosen
+pub const BUILTIN_PACK: &str = "builtin";
+
+/// Optional `pack.json` at the root of an icon pack. Without one, icons are looked
+/// up by convention: `<ext>.svg`, then `<kind>.svg`, then `file.svg` / `folder.svg`.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct PackManifest {
+    name: Option<String>,
+    /// Extension (no dot) -> SVG file
+    extensions: BTreeMap<String, String>,
+    /// File kind -> SVG file
+    kinds: BTreeMap<FileKind, String>,
+    file: Option<String>,
+    folder: Option<String>,
+}
+
+/// An installed icon pack
+#[derive(Serialize, Clone, Debug)]
+pub struct IconPack {
+    pub id: String,
+    pub name: String,
+    /// Folder the pack lives in; `None` for the built-in icons
+    pub path: Option<String>,
+}
+
+/// What an icon stands for, parsed from the protocol path
+enum IconKey {
+    Folder,
+    File(Option<String>),
+}
+
+fn get_icon_pack_dir(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("icon_packs");
+    fs::create_dir_all(&path).ok();
+    path
+}
+
+fn read_manifest(dir: &Path) -> PackManifest {
+    fs::read_to_string(dir.join("pack.json"))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// The pack chosen in settings, if any
+fn active_pack(handle: &AppHandle) -> Option<String> {
+    handle
+        .try_state::<SharedAppSettings>()?
+        .0
+        .try_read()
+        .ok()?
+        .thumbnails
+        .icon_pack
+        .clone()
+}
+
+/// Icon URL for an entry, served through the `thumb` protocol. The pack id is part
+/// of the URL so switching packs bypasses the webview's cache.
+pub fn icon_url(handle: &AppHandle, path: &Path, is_dir: bool) -> String {
+    let pack = active_pack(handle).unwrap_or_else(|| BUILTIN_PACK.to_string());
+    let key = if is_dir {
+        "folder".to_string()
+    } else {
+        match path.extension() {
+            Some(ext) => format!(".{}", ext.to_string_lossy().to_lowercase()),
+            None => "file".to_string(),
+        }
+    };
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://thumb.localhost/icon/{}/{}", pack, key)
+    } else {
+        format!("thumb://localhost/icon/{}/{}", pack, key)
+    }
+}
+
+/// Picks the SVG a pack provides for `key`, if any
+fn pack_icon(dir: &Path, key: &IconKey) -> Option<Vec<u8>> {
+    let manifest = read_manifest(dir);
+    let read = |name: &str| -> Option<Vec<u8>> {
+        // pack files must stay inside the pack
+        let file = dir.join(name);
+        let file = dunce::canonicalize(&file).ok()?;
+        file.starts_with(dunce::canonicalize(dir).ok()?)
+            .then(|| fs::read(file).ok())
+            .flatten()
+    };
+
+    match key {
+        IconKey::Folder => manifest
+            .folder
+            .as_deref()
+            .and_then(read)
+            .or_else(|| read("folder.svg")),
+        IconKey::File(ext) => {
+            let kind = ext
+                .as_deref()
+                .and_then(|ext| kind_from_extension(Path::new(&format!("x.{}", ext))));
+            ext.as_deref()
+                .and_then(|ext| manifest.extensions.get(ext))
+                .and_then(|name| read(name))
+                .or_else(|| ext.as_deref().and_then(|ext| read(&format!("{}.svg", ext))))
+                .or_else(|| {
+                    kind.and_then(|k| manifest.kinds.get(&k))
+                        .and_then(|n| read(n))
+                })
+                .or_else(|| kind.and_then(|k| read(&format!("{}.svg", k.name()))))
+                .or_else(|| manifest.file.as_deref().and_then(read))
+                .or_else(|| read("file.svg"))
+        }
+    }
+}
+
+/// Accent color of the built-in icons per kind
+fn kind_color(kind: Option<FileKind>) -> &'static str {
+    match kind {
+        Some(FileKind::Image) => "#3fa66b",
+        Some(FileKind::Video) => "#d9534f",
+        Some(FileKind::Audio) => "#9b59b6",
+        Some(FileKind::Document) => "#3b7dd8",
+        Some(FileKind::Archive) => "#c7912c",
+        Some(FileKind::Code) => "#2a9d9f",
+        Some(FileKind::Executable) => "#5c6670",
+        None => "#8a939b",
+    }
+}
+
+/// The built-in icons: a folder, or a page with a colored band and the extension
+fn builtin_icon(key: &IconKey) -> Vec<u8> {
+    let svg = match key {
+        IconKey::Folder => r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64"><path d="M6 14a4 4 0 0 1 4-4h14l6 6h24a4 4 0 0 1 4 4v30a4 4 0 0 1-4 4H10a4 4 0 0 1-4-4z" fill="#e8b64c"/><path d="M6 22h52v28a4 4 0 0 1-4 4H10a4 4 0 0 1-4-4z" fill="#f4c95d"/></svg>"##.to_string(),
+        IconKey::File(ext) => {
+            let kind = ext
+                .as_deref()
+                .and_then(|ext| kind_from_extension(Path::new(&format!("x.{}", ext))));
+            let label: String = ext
+                .as_deref()
+                .unwrap_or("")
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .take(4)
+                .collect::<String>()
+                .to_uppercase();
+            format!(
+                r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64"><path d="M14 4h26l14 14v38a4 4 0 0 1-4 4H14a4 4 0 0 1-4-4V8a4 4 0 0 1 4-4z" fill="#f3f5f7" stroke="#c9ced3" stroke-width="2"/><path d="M40 4v10a4 4 0 0 0 4 4h10" fill="#dde2e6"/><rect x="10" y="38" width="44" height="14" fill="{}"/><text x="32" y="49" font-family="sans-serif" font-size="11" font-weight="700" fill="#fff" text-anchor="middle">{}</text></svg>"##,
+                kind_color(kind),
+                label
+            )
+        }
+    };
+    svg.into_bytes()
+}
+
+/// Undoes the percent-encoding the webview applies to non-ASCII path segments
+pub(crate) fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = segment
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Serves `icon/<pack>/<key>` for the `thumb` protocol, where `key` is `folder`,
+/// `file`, or `.<ext>`. Packs missing an icon fall back to the built-in one.
+pub fn serve_icon(handle: &AppHandle, path: &str) -> Option<Vec<u8>> {
+    let (pack, key) = path.split_once('/')?;
+    let (pack, key) = (percent_decode(pack), percent_decode(key));
+    let key = match key.as_str() {
+        "folder" => IconKey::Folder,
+        "file" => IconKey::File(None),
+        ext => IconKey::File(Some(ext.strip_prefix('.')?.to_lowercase())),
+    };
+
+    let dir = get_icon_pack_dir(handle).join(&pack);
+    // the pack id must name a single folder inside `icon_packs`
+    let plain = Path::new(&pack)
+        .file_name()
+        .is_some_and(|n| n == pack.as_str());
+    let custom = (pack != BUILTIN_PACK && plain && dir.is_dir())
+        .then(|| pack_icon(&dir, &key))
+        .flatten();
+    Some(custom.unwrap_or_else(|| builtin_icon(&key)))
+}
+
+/// The built-in icons plus every pack folder under `icon_packs` in app data
+#[tauri::command]
+pub fn list_icon_packs(handle: AppHandle) -> Vec<IconPack> {
+    let mut packs = vec![IconPack {
+        id: BUILTIN_PACK.to_string(),
+        name: "Dagger".to_string(),
+        path: None,
+    }];
+
+    let Ok(entries) = fs::read_dir(get_icon_pack_dir(&handle)) else {
+        return packs;
+    };
+    let mut installed: Vec<IconPack> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let id = e.file_name().to_string_lossy().to_string();
+            IconPack {
+                name: read_manifest(&e.path()).name.unwrap_or_else(|| id.clone()),
+                path: Some(e.path().to_string_lossy().to_string()),
+                id,
+            }
+        })
+        .collect();
+    installed.sort_by_key(|p| p.name.to_lowercase());
+    packs.extend(installed);
+    packs
+}
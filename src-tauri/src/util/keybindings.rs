@@ -0,0 +1,219 @@
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::util::caches::SharedAppSettings;
+
+/// Built-in shortcuts: (action id, chord on Windows/Linux, chord on macOS)
+const DEFAULT_KEYBINDINGS: &[(&str, &str, &str)] = &[
+    ("copy", "Ctrl+C", "Cmd+C"),
+    ("cut", "Ctrl+X", "Cmd+X"),
+    ("paste", "Ctrl+V", "Cmd+V"),
+    ("delete", "Delete", "Cmd+Backspace"),
+    ("delete_permanently", "Shift+Delete", "Alt+Cmd+Backspace"),
+    ("rename", "F2", "Enter"),
+    ("select_all", "Ctrl+A", "Cmd+A"),
+    ("new_folder", "Ctrl+Shift+N", "Cmd+Shift+N"),
+    ("new_tab", "Ctrl+T", "Cmd+T"),
+    ("close_tab", "Ctrl+W", "Cmd+W"),
+    ("next_tab", "Ctrl+Tab", "Ctrl+Tab"),
+    ("previous_tab", "Ctrl+Shift+Tab", "Ctrl+Shift+Tab"),
+    ("go_back", "Alt+Left", "Cmd+["),
+    ("go_forward", "Alt+Right", "Cmd+]"),
+    ("go_up", "Alt+Up", "Cmd+Up"),
+    ("refresh", "F5", "Cmd+R"),
+    ("focus_address_bar", "Ctrl+L", "Cmd+L"),
+    ("search", "Ctrl+F", "Cmd+F"),
+    ("toggle_hidden", "Ctrl+H", "Cmd+Shift+."),
+    ("properties", "Alt+Enter", "Cmd+I"),
+    ("undo", "Ctrl+Z", "Cmd+Z"),
+    ("redo", "Ctrl+Y", "Cmd+Shift+Z"),
+    ("goto_slot_1", "Ctrl+1", "Cmd+1"),
+    ("goto_slot_2", "Ctrl+2", "Cmd+2"),
+    ("goto_slot_3", "Ctrl+3", "Cmd+3"),
+    ("goto_slot_4", "Ctrl+4", "Cmd+4"),
+    ("goto_slot_5", "Ctrl+5", "Cmd+5"),
+    ("goto_slot_6", "Ctrl+6", "Cmd+6"),
+    ("goto_slot_7", "Ctrl+7", "Cmd+7"),
+    ("goto_slot_8", "Ctrl+8", "Cmd+8"),
+    ("goto_slot_9", "Ctrl+9", "Cmd+9"),
+];
+
+/// Modifier names in the order they are written back out
+const MODIFIERS: &[&str] = &["Ctrl", "Alt", "Shift", "Cmd"];
+
+const NAMED_KEYS: &[&str] = &[
+    "Enter",
+    "Escape",
+    "Tab",
+    "Space",
+    "Backspace",
+    "Delete",
+    "Insert",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Keybinding {
+    pub action: String,
+    pub chord: Option<String>,
+    pub default_chord: String,
+    pub is_default: bool,
+}
+
+fn default_chord(action: &str) -> Option<&'static str> {
+    DEFAULT_KEYBINDINGS
+        .iter()
+        .find(|(id, _, _)| *id == action)
+        .map(|(_, other, mac)| {
+            if cfg!(target_os = "macos") {
+                *mac
+            } else {
+                *other
+            }
+        })
+}
+
+/// Parses a chord like "ctrl+shift+n" into canonical form ("Ctrl+Shift+N").
+/// Exactly one non-modifier key is required; bare printable keys need a modifier
+/// so they don't swallow typing.
+pub fn normalize_chord(chord: &str) -> Result<String, String> {
+    let mut modifiers = [false; 4];
+    let mut key: Option<String> = None;
+
+    for part in chord.split('+').map(str::trim) {
+        if part.is_empty() {
+            return Err(format!("Invalid chord \"{}\"", chord));
+        }
+        let modifier = match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Some(0),
+            "alt" | "option" => Some(1),
+            "shift" => Some(2),
+            "cmd" | "command" | "meta" | "super" | "win" => Some(3),
+            _ => None,
+        };
+        if let Some(i) = modifier {
+            modifiers[i] = true;
+            continue;
+        }
+        if key.is_some() {
+            return Err(format!("Chord \"{}\" has more than one key", chord));
+        }
+        key = Some(normalize_key(part).ok_or_else(|| format!("Unknown key \"{}\"", part))?);
+    }
+
+    let key = key.ok_or_else(|| format!("Chord \"{}\" has no key", chord))?;
+    let has_modifier = modifiers.iter().any(|m| *m);
+    if !has_modifier && key.chars().count() == 1 {
+        return Err(format!("\"{}\" needs a modifier", key));
+    }
+
+    let mut parts: Vec<&str> = MODIFIERS
+        .iter()
+        .zip(modifiers)
+        .filter(|(_, on)| *on)
+        .map(|(name, _)| *name)
+        .collect();
+    parts.push(&key);
+    Ok(parts.join("+"))
+}
+
+fn normalize_key(key: &str) -> Option<String> {
+    if key.chars().count() == 1 {
+        let c = key.chars().next()?;
+        return (c.is_ascii_graphic()).then(|| c.to_ascii_uppercase().to_string());
+    }
+    if let Some(n) = key
+        .strip_prefix(['F', 'f'])
+        .and_then(|n| n.parse::<u8>().ok())
+    {
+        return (1..=24).contains(&n).then(|| format!("F{}", n));
+    }
+    let key = match key.to_ascii_lowercase().as_str() {
+        "esc" => "Escape",
+        "return" => "Enter",
+        "del" => "Delete",
+        "arrowup" => "Up",
+        "arrowdown" => "Down",
+        "arrowleft" => "Left",
+        "arrowright" => "Right",
+        other => NAMED_KEYS.iter().find(|k| k.eq_ignore_ascii_case(other))?,
+    };
+    Some(key.to_string())
+}
+
+/// Effective bindings: user overrides layered over the platform defaults
+async fn resolve_keybindings(settings: &SharedAppSettings) -> Vec<Keybinding> {
+    let overrides = settings.0.read().await.keybindings.clone();
+
+    DEFAULT_KEYBINDINGS
+        .iter()
+        .map(|(action, _, _)| {
+            let default_chord = default_chord(action).unwrap_or_default().to_string();
+            let chord = match overrides.get(*action) {
+                Some(chord) => chord.clone(),
+                None => Some(default_chord.clone()),
+            };
+            Keybinding {
+                action: action.to_string(),
+                is_default: chord.as_deref() == Some(default_chord.as_str()),
+                chord,
+                default_chord,
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_keybindings(
+    settings: State<'_, SharedAppSettings>,
+) -> Result<Vec<Keybinding>, String> {
+    Ok(resolve_keybindings(&settings).await)
+}
+
+/// Rebinds an action. `chord: None` unbinds it; `reset: true` restores the default.
+/// Fails if the chord is already bound to another action.
+#[tauri::command]
+pub async fn set_keybinding(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    action: String,
+    chord: Option<String>,
+    reset: Option<bool>,
+) -> Result<Vec<Keybinding>, String> {
+    let default = default_chord(&action).ok_or_else(|| format!("Unknown action: {}", action))?;
+
+    let chord = match (reset.unwrap_or(false), chord) {
+        (true, _) => Some(default.to_string()),
+        (false, Some(chord)) => Some(normalize_chord(&chord)?),
+        (false, None) => None,
+    };
+
+    if let Some(chord) = &chord {
+        let conflict = resolve_keybindings(&settings)
+            .await
+            .into_iter()
+            .find(|b| b.action != action && b.chord.as_deref() == Some(chord.as_str()));
+        if let Some(other) = conflict {
+            return Err(format!("{} is already bound to {}", chord, other.action));
+        }
+    }
+
+    {
+        let mut current = settings.0.write().await;
+        if chord.as_deref() == Some(default) {
+            current.keybindings.remove(&action);
+        } else {
+            current.keybindings.insert(action, chord);
+        }
+    }
+    settings.commit(&handle).await;
+
+    Ok(resolve_keybindings(&settings).await)
+}
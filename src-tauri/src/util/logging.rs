@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::{AppHandle, Manager, State};
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    filter::LevelFilter, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, Registry,
+};
+
+use crate::util::caches::SharedAppSettings;
+
+/// Daily log files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+const LOG_FILE_PREFIX: &str = "dagger";
+/// Lines returned by `get_recent_logs` when no count is given
+const DEFAULT_LOG_LINES: usize = 500;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Handles kept alive for the lifetime of the app
+pub struct LoggingState {
+    level: reload::Handle<LevelFilter, Registry>,
+    dir: PathBuf,
+    /// Flushes the background writer on drop
+    _guard: WorkerGuard,
+}
+
+/// `%APPDATA%\dagger\logs`
+fn get_log_dir(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("logs");
+    fs::create_dir_all(&path).ok();
+    path
+}
+
+/// Installs the global `tracing` subscriber: a daily-rotated file under app data,
+/// mirrored to stderr in debug builds. Returns None if a subscriber already exists.
+pub fn init_logging(handle: &AppHandle, level: LogLevel) -> Option<LoggingState> {
+    let dir = get_log_dir(handle);
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .ok()?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let (filter, level_handle) = reload::Layer::new(LevelFilter::from(level));
+    let file_layer = fmt::layer().with_writer(writer).with_ansi(false);
+    let stderr_layer = cfg!(debug_assertions).then(|| fmt::layer().with_writer(std::io::stderr));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(stderr_layer)
+        .try_init()
+        .ok()?;
+
+    Some(LoggingState {
+        level: level_handle,
+        dir,
+        _guard: guard,
+    })
+}
+
+/// Newest log file in the folder (file names embed the date, so they sort chronologically)
+fn latest_log_file(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        })
+        .max()
+}
+
+/// Changes the log level at runtime and remembers it for the next launch
+#[tauri::command]
+pub async fn set_log_level(
+    handle: AppHandle,
+    logging: State<'_, LoggingState>,
+    settings: State<'_, SharedAppSettings>,
+    level: LogLevel,
+) -> Result<(), String> {
+    logging
+        .level
+        .modify(|filter| *filter = level.into())
+        .map_err(|e| format!("Failed to change log level: {}", e))?;
+
+    settings.0.write().await.log_level = level;
+    settings.commit(&handle).await;
+    tracing::info!(target: "logging", "log level set to {:?}", level);
+    Ok(())
+}
+
+/// Returns the last `lines` lines of the current log file, for bug reports
+#[tauri::command]
+pub async fn get_recent_logs(
+    logging: State<'_, LoggingState>,
+    lines: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let Some(path) = latest_log_file(&logging.dir) else {
+        return Ok(Vec::new());
+    };
+    let data = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read log: {}", e))?;
+    let text = String::from_utf8_lossy(&data);
+
+    let all: Vec<&str> = text.lines().collect();
+    let count = lines.unwrap_or(DEFAULT_LOG_LINES);
+    Ok(all[all.len().saturating_sub(count)..]
+        .iter()
+        .map(|l| l.to_string())
+        .collect())
+}
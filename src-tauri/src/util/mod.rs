@@ -1,4 +1,26 @@
+pub mod activity;
+pub mod appearance;
+pub mod associations;
+pub mod bookmarks;
 pub mod caches;
 pub mod cmd;
+pub mod custom_actions;
+pub mod diagnostics;
+pub mod editors;
+pub mod effects;
 pub mod ffutils;
+pub mod hashing;
+pub mod hooks;
+#[cfg(desktop)]
+pub mod hotkey;
+pub mod icon_packs;
+pub mod keybindings;
+pub mod logging;
+pub mod notify;
+pub mod plugins;
+pub mod power;
+pub mod profile;
 pub mod setup;
+pub mod shell_integration;
+pub mod tags;
+pub mod taskbar;
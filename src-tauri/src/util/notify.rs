@@ -0,0 +1,92 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    filesys::stream::jobs::JobKind,
+    util::{
+        caches::SharedAppSettings,
+        hooks::{fire_hooks, HookEvent},
+    },
+};
+
+/// Whether the main window is out of sight (closed to tray or minimized)
+fn window_hidden(handle: &AppHandle) -> bool {
+    match handle.get_webview_window("main") {
+        Some(window) => {
+            !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false)
+        }
+        None => true,
+    }
+}
+
+fn job_title(kind: JobKind, success: bool) -> &'static str {
+    match (kind, success) {
+        (JobKind::Copy, true) => "Copy complete",
+        (JobKind::Copy, false) => "Copy finished with errors",
+        (JobKind::Move, true) => "Move complete",
+        (JobKind::Move, false) => "Move finished with errors",
+        (JobKind::Delete, true) => "Delete complete",
+        (JobKind::Delete, false) => "Delete finished with errors",
+        (JobKind::SecureDelete, true) => "Secure delete complete",
+        (JobKind::SecureDelete, false) => "Secure delete finished with errors",
+        (JobKind::Trash, true) => "Moved to trash",
+        (JobKind::Trash, false) => "Move to trash finished with errors",
+        (JobKind::StorageAnalysis, true) => "Storage analysis complete",
+        (JobKind::StorageAnalysis, false) => "Storage analysis failed",
+        (JobKind::Encrypt, true) => "Encryption complete",
+        (JobKind::Encrypt, false) => "Encryption finished with errors",
+        (JobKind::Decrypt, true) => "Decryption complete",
+        (JobKind::Decrypt, false) => "Decryption failed",
+        (JobKind::Snapshot, true) => "Snapshot saved",
+        (JobKind::Snapshot, false) => "Snapshot failed",
+        (JobKind::SnapshotDiff, true) => "Snapshot comparison complete",
+        (JobKind::SnapshotDiff, false) => "Snapshot comparison failed",
+        (JobKind::DeviceTransfer, true) => "Device transfer complete",
+        (JobKind::DeviceTransfer, false) => "Device transfer finished with errors",
+        (JobKind::CloudSync, true) => "Cloud files updated",
+        (JobKind::CloudSync, false) => "Cloud files updated with errors",
+        (JobKind::SelectionSummary, true) => "Selection measured",
+        (JobKind::SelectionSummary, false) => "Could not measure the selection",
+        (JobKind::Mirror, true) => "Mirror preview ready",
+        (JobKind::Mirror, false) => "Mirror preview failed",
+        (JobKind::Upload, true) => "Upload complete",
+        (JobKind::Upload, false) => "Upload failed",
+        (JobKind::ThumbnailExport, true) => "Thumbnails exported",
+        (JobKind::ThumbnailExport, false) => "Thumbnail export failed",
+    }
+}
+
+/// Raises a native notification for a finished job, honoring the notification settings,
+/// and runs any `job_completed` hooks. Cancelled jobs should not call this.
+pub fn notify_job_finished(handle: &AppHandle, kind: JobKind, success: bool, summary: &str) {
+    fire_hooks(
+        handle,
+        HookEvent::JobCompleted {
+            kind,
+            success,
+            summary,
+        },
+    );
+
+    let Some(settings) = handle.try_state::<SharedAppSettings>() else {
+        return;
+    };
+    // jobs run on blocking threads; skip rather than wait if settings are being written
+    let Ok(settings) = settings.0.try_read().map(|s| s.notifications.clone()) else {
+        return;
+    };
+
+    if !settings.allows(kind) || (settings.only_when_hidden && !window_hidden(handle)) {
+        return;
+    }
+
+    if let Err(e) = handle
+        .notification()
+        .builder()
+        .title(job_title(kind, success))
+        .body(summary)
+        .show()
+    {
+        tracing::warn!(target: "notify", "Failed to show notification: {}", e);
+    }
+}
@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::RwLock,
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager};
+
+use crate::util::plugins::sandbox::{self, Grants};
+
+/// Name of the manifest every plugin folder must contain
+pub const MANIFEST_FILE: &str = "plugin.json";
+
+/// Environment variables passed through to plugin processes; everything else is cleared
+const INHERITED_ENV: &[&str] = &[
+    "PATH",
+    "SYSTEMROOT",
+    "WINDIR",
+    "TEMP",
+    "TMP",
+    "TMPDIR",
+    "HOME",
+    "USERPROFILE",
+    "LANG",
+];
+
+// ===============================
+// Manifest
+// ===============================
+
+/// `plugin.json`: identity, how to launch the plugin, and what it contributes
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Program and arguments. A relative program is resolved inside the plugin folder.
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub actions: Vec<PluginAction>,
+    /// Extensions (without dot) the plugin can render thumbnails for
+    #[serde(default)]
+    pub thumbnails: Vec<String>,
+    /// Extensions (without dot) the plugin can render previews for
+    #[serde(default)]
+    pub previews: Vec<String>,
+    /// Per-request timeout in seconds
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+/// A context-menu entry contributed by a plugin
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PluginAction {
+    pub id: String,
+    pub label: String,
+    /// Extensions the action applies to; empty means any file
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Also offer the action on folders
+    #[serde(default)]
+    pub directories: bool,
+}
+
+impl PluginAction {
+    /// True when the action applies to every selected path
+    pub fn matches(&self, paths: &[String]) -> bool {
+        !paths.is_empty()
+            && paths.iter().all(|p| {
+                let path = Path::new(p);
+                if path.is_dir() {
+                    return self.directories;
+                }
+                self.extensions.is_empty() || handles_extension(&self.extensions, path)
+            })
+    }
+}
+
+fn handles_extension(extensions: &[String], path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy())
+        .is_some_and(|ext| extensions.iter().any(|x| x.eq_ignore_ascii_case(&ext)))
+}
+
+// ===============================
+// Host State
+// ===============================
+
+/// A discovered plugin folder. Broken manifests are kept with `error` set so the
+/// settings page can show why a plugin didn't load.
+#[derive(Serialize, Clone, Debug)]
+pub struct Plugin {
+    pub id: String,
+    pub dir: String,
+    pub enabled: bool,
+    pub manifest: Option<PluginManifest>,
+    pub error: Option<String>,
+}
+
+impl Plugin {
+    fn active(&self) -> Option<&PluginManifest> {
+        self.manifest.as_ref().filter(|_| self.enabled)
+    }
+}
+
+#[derive(Default)]
+pub struct PluginHost {
+    pub plugins: RwLock<Vec<Plugin>>,
+}
+
+impl PluginHost {
+    /// First enabled plugin that renders thumbnails for `path`
+    pub fn thumbnail_provider(&self, path: &Path) -> Option<Plugin> {
+        self.find(|m| handles_extension(&m.thumbnails, path))
+    }
+
+    /// First enabled plugin that renders previews for `path`
+    pub fn preview_provider(&self, path: &Path) -> Option<Plugin> {
+        self.find(|m| handles_extension(&m.previews, path))
+    }
+
+    fn find(&self, pred: impl Fn(&PluginManifest) -> bool) -> Option<Plugin> {
+        self.plugins
+            .read()
+            .unwrap()
+            .iter()
+            .find(|p| p.active().is_some_and(&pred))
+            .cloned()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Plugin> {
+        self.plugins
+            .read()
+            .unwrap()
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+    }
+}
+
+/// `%APPDATA%\dagger\plugins`, one subfolder per plugin
+pub fn get_plugins_dir(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("plugins");
+    fs::create_dir_all(&path).ok();
+    path
+}
+
+/// Private scratch folder handed to a plugin as its working data directory
+fn get_plugin_data_dir(handle: &AppHandle, id: &str) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    path.push("plugin-data");
+    path.push(id);
+    fs::create_dir_all(&path).ok();
+    path
+}
+
+/// Reads and validates a plugin folder's manifest
+pub fn read_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let data = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("Missing {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest =
+        serde_json::from_str(&data).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+
+    if manifest.id.is_empty()
+        || !manifest
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return Err(format!("Invalid plugin id \"{}\"", manifest.id));
+    }
+    if manifest.command.is_empty() {
+        return Err("Manifest has an empty command".into());
+    }
+    Ok(manifest)
+}
+
+/// Scans the plugins folder. Plugins listed in `disabled` are loaded but inactive.
+pub fn discover_plugins(handle: &AppHandle, disabled: &[String]) -> Vec<Plugin> {
+    let Ok(entries) = fs::read_dir(get_plugins_dir(handle)) else {
+        return Vec::new();
+    };
+
+    let mut plugins: Vec<Plugin> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|entry| {
+            let dir = entry.path();
+            let folder = entry.file_name().to_string_lossy().to_string();
+            match read_manifest(&dir) {
+                Ok(manifest) => Plugin {
+                    id: manifest.id.clone(),
+                    dir: dir.to_string_lossy().to_string(),
+                    enabled: !disabled.contains(&manifest.id),
+                    manifest: Some(manifest),
+                    error: None,
+                },
+                Err(e) => Plugin {
+                    id: folder,
+                    dir: dir.to_string_lossy().to_string(),
+                    enabled: false,
+                    manifest: None,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect();
+
+    plugins.sort_by(|a, b| a.id.cmp(&b.id));
+    plugins
+}
+
+// ===============================
+// JSON-over-stdio Protocol
+// ===============================
+
+/// Sends one request to a plugin and returns its `result`.
+///
+/// Each request launches the plugin fresh: the host writes
+/// `{"method": ..., "params": ...}` as a single line on stdin and expects a single
+/// line `{"result": ...}` or `{"error": "..."}` on stdout. The process runs inside
+/// its own folder with a cleared environment (plus `DAGGER_PLUGIN_DATA`) and is
+/// killed when it exceeds the manifest timeout.
+///
+/// Where the platform supports it the process is sandboxed: besides system files
+/// it may read its own folder, change its data folder, and reach the paths in
+/// `params` (read-only, except for `action` requests the user started).
+pub fn call_plugin(
+    handle: &AppHandle,
+    plugin: &Plugin,
+    method: &str,
+    params: Value,
+) -> Result<Value, String> {
+    let manifest = plugin
+        .active()
+        .ok_or_else(|| format!("Plugin {} is not enabled", plugin.id))?;
+    let dir = Path::new(&plugin.dir);
+
+    let program = dir.join(&manifest.command[0]);
+    let program = if program.is_file() {
+        program
+    } else {
+        PathBuf::from(&manifest.command[0])
+    };
+
+    let data_dir = get_plugin_data_dir(handle, &plugin.id);
+    let param_access = if method == "action" {
+        sandbox::FULL
+    } else {
+        sandbox::READ
+    };
+    let mut grants = Grants::default();
+    grants
+        .allow(dir, sandbox::READ)
+        .allow(&program, sandbox::READ)
+        .allow(&data_dir, sandbox::FULL)
+        .allow_params(&params, param_access);
+
+    let mut cmd = Command::new(&program);
+    cmd.args(&manifest.command[1..])
+        .current_dir(dir)
+        .env_clear()
+        .envs(
+            INHERITED_ENV
+                .iter()
+                .filter_map(|k| Some((k, std::env::var_os(k)?))),
+        )
+        // the shared temp folder is outside the sandbox
+        .env("TMPDIR", &data_dir)
+        .env("DAGGER_PLUGIN_DATA", &data_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    grants.confine(&mut cmd)?;
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start plugin {}: {}", plugin.id, e))?;
+
+    let request = json!({ "method": method, "params": params }).to_string();
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = writeln!(stdin, "{}", request);
+    }
+
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stdout.read_to_string(&mut out);
+        out
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(manifest.timeout_secs.max(1));
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Plugin {} timed out", plugin.id));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("Plugin {} failed: {}", plugin.id, e)),
+        }
+    }
+
+    let output = reader.join().unwrap_or_default();
+    let line = output
+        .lines()
+        .rev()
+        .find(|l| !l.trim().is_empty())
+        .ok_or_else(|| format!("Plugin {} returned no response", plugin.id))?;
+    let mut response: Value = serde_json::from_str(line)
+        .map_err(|e| format!("Plugin {} returned invalid JSON: {}", plugin.id, e))?;
+
+    if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+        let message = error
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or(error.to_string());
+        return Err(format!("{}: {}", plugin.id, message));
+    }
+    Ok(response
+        .get_mut("result")
+        .map(Value::take)
+        .unwrap_or(Value::Null))
+}
+
+/// Asks the first matching thumbnail provider for an image of `path`.
+/// Plugins answer with `{"data": "<base64 png/jpeg>"}`.
+pub fn plugin_thumbnail(handle: &AppHandle, path: &Path) -> Option<Vec<u8>> {
+    use base64::Engine;
+
+    let host = handle.try_state::<std::sync::Arc<PluginHost>>()?;
+    let plugin = host.thumbnail_provider(path)?;
+    let result = call_plugin(
+        handle,
+        &plugin,
+        "thumbnail",
+        json!({ "path": path, "size": 128 }),
+    )
+    .ok()?;
+
+    base64::engine::general_purpose::STANDARD
+        .decode(result.get("data")?.as_str()?)
+        .ok()
+}
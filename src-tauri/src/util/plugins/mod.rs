@@ -0,0 +1,196 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{fs, path::Path, sync::Arc};
+use tauri::{AppHandle, State};
+
+use crate::util::caches::SharedAppSettings;
+
+pub mod host;
+pub mod sandbox;
+
+pub use host::{discover_plugins, plugin_thumbnail, Plugin, PluginHost};
+
+/// A plugin action offered for the current selection
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginMenuItem {
+    pub plugin_id: String,
+    pub action_id: String,
+    pub label: String,
+}
+
+/// Re-scans the plugins folder, keeping the user's enabled/disabled choices
+async fn rescan(
+    handle: &AppHandle,
+    host: &PluginHost,
+    settings: &SharedAppSettings,
+) -> Vec<Plugin> {
+    let disabled = settings
+        .0
+        .read()
+        .await
+        .integrations
+        .disabled_plugins
+        .clone();
+    let plugins = discover_plugins(handle, &disabled);
+    *host.plugins.write().unwrap() = plugins.clone();
+    plugins
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_plugins(host: State<'_, Arc<PluginHost>>) -> Vec<Plugin> {
+    host.plugins.read().unwrap().clone()
+}
+
+#[tauri::command]
+pub async fn reload_plugins(
+    handle: AppHandle,
+    host: State<'_, Arc<PluginHost>>,
+    settings: State<'_, SharedAppSettings>,
+) -> Result<Vec<Plugin>, String> {
+    Ok(rescan(&handle, &host, &settings).await)
+}
+
+#[tauri::command]
+pub async fn set_plugin_enabled(
+    handle: AppHandle,
+    host: State<'_, Arc<PluginHost>>,
+    settings: State<'_, SharedAppSettings>,
+    id: String,
+    enabled: bool,
+) -> Result<Vec<Plugin>, String> {
+    {
+        let mut current = settings.0.write().await;
+        let disabled = &mut current.integrations.disabled_plugins;
+        disabled.retain(|p| p != &id);
+        if !enabled {
+            disabled.push(id);
+        }
+    }
+    settings.commit(&handle).await;
+    Ok(rescan(&handle, &host, &settings).await)
+}
+
+/// Installs a plugin by copying a folder containing `plugin.json` into the plugins folder
+#[tauri::command]
+pub async fn install_plugin(
+    handle: AppHandle,
+    host: State<'_, Arc<PluginHost>>,
+    settings: State<'_, SharedAppSettings>,
+    source: String,
+) -> Result<Vec<Plugin>, String> {
+    let source = Path::new(&source);
+    let manifest = host::read_manifest(source)?;
+    let target = host::get_plugins_dir(&handle).join(&manifest.id);
+    if target.exists() {
+        return Err(format!("Plugin {} is already installed", manifest.id));
+    }
+
+    copy_dir(source, &target).map_err(|e| format!("Failed to install plugin: {}", e))?;
+    Ok(rescan(&handle, &host, &settings).await)
+}
+
+#[tauri::command]
+pub async fn uninstall_plugin(
+    handle: AppHandle,
+    host: State<'_, Arc<PluginHost>>,
+    settings: State<'_, SharedAppSettings>,
+    id: String,
+) -> Result<Vec<Plugin>, String> {
+    let plugin = host
+        .get(&id)
+        .ok_or_else(|| format!("Unknown plugin: {}", id))?;
+    fs::remove_dir_all(&plugin.dir).map_err(|e| format!("Failed to remove plugin: {}", e))?;
+    Ok(rescan(&handle, &host, &settings).await)
+}
+
+/// Plugin actions that apply to every selected path, for the context menu
+#[tauri::command]
+pub fn get_plugin_actions(
+    host: State<'_, Arc<PluginHost>>,
+    paths: Vec<String>,
+) -> Vec<PluginMenuItem> {
+    host.plugins
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|p| p.enabled)
+        .filter_map(|p| Some((p, p.manifest.as_ref()?)))
+        .flat_map(|(p, manifest)| {
+            manifest
+                .actions
+                .iter()
+                .filter(|a| a.matches(&paths))
+                .map(|a| PluginMenuItem {
+                    plugin_id: p.id.clone(),
+                    action_id: a.id.clone(),
+                    label: a.label.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Runs a plugin action on the selection and returns the plugin's result
+#[tauri::command]
+pub async fn run_plugin_action(
+    handle: AppHandle,
+    host: State<'_, Arc<PluginHost>>,
+    plugin_id: String,
+    action_id: String,
+    paths: Vec<String>,
+) -> Result<Value, String> {
+    let plugin = host
+        .get(&plugin_id)
+        .ok_or_else(|| format!("Unknown plugin: {}", plugin_id))?;
+    let action = plugin
+        .manifest
+        .as_ref()
+        .and_then(|m| m.actions.iter().find(|a| a.id == action_id))
+        .ok_or_else(|| format!("Unknown action: {}", action_id))?;
+    if !action.matches(&paths) {
+        return Err(format!("{} does not apply to the selection", action.label));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        host::call_plugin(
+            &handle,
+            &plugin,
+            "action",
+            json!({ "action": action_id, "paths": paths }),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Renders a preview through a plugin. Plugins answer with
+/// `{"kind": "html" | "text" | "image", "content": ...}`; None when no plugin handles the type.
+#[tauri::command]
+pub async fn get_plugin_preview(
+    handle: AppHandle,
+    host: State<'_, Arc<PluginHost>>,
+    path: String,
+) -> Result<Option<Value>, String> {
+    let Some(plugin) = host.preview_provider(Path::new(&path)) else {
+        return Ok(None);
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        host::call_plugin(&handle, &plugin, "preview", json!({ "path": path })).map(Some)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
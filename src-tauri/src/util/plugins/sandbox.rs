@@ -0,0 +1,201 @@
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Read and execute rights
+pub const READ: u64 = imp::READ;
+
+/// Read, write, create and delete rights
+pub const FULL: u64 = imp::FULL;
+
+/// System folders every plugin may read and run programs from
+#[cfg(target_os = "linux")]
+const SYSTEM_DIRS: &[&str] = &[
+    "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/nix", "/proc", "/sys",
+];
+
+/// Paths a plugin process may touch beyond the system folders.
+///
+/// On Linux these become a Landlock ruleset applied right before the plugin execs,
+/// so it can only read and run system files plus whatever is granted here.
+/// Windows and macOS have no confinement yet; plugins there run with the user's
+/// own rights.
+#[derive(Default)]
+pub struct Grants {
+    rules: Vec<(PathBuf, u64)>,
+}
+
+impl Grants {
+    /// Allows `access` on `path` and, for a folder, everything below it
+    pub fn allow(&mut self, path: impl Into<PathBuf>, access: u64) -> &mut Self {
+        self.rules.push((path.into(), access));
+        self
+    }
+
+    /// Allows `access` on every absolute path among the string values of `params`
+    pub fn allow_params(&mut self, params: &Value, access: u64) -> &mut Self {
+        match params {
+            Value::String(s) if Path::new(s).is_absolute() => {
+                self.allow(s, access);
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.allow_params(item, access);
+                }
+            }
+            Value::Object(map) => {
+                for item in map.values() {
+                    self.allow_params(item, access);
+                }
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Restricts `cmd` to the granted paths once it is spawned. Fails when the
+    /// platform supports confinement but it can't be set up, so a plugin never runs
+    /// with more access than intended.
+    pub fn confine(&self, cmd: &mut Command) -> Result<(), String> {
+        imp::confine(self, cmd).map_err(|e| format!("Plugin sandbox unavailable: {}", e))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::{
+        ffi::CString,
+        fs, io,
+        os::{
+            fd::{AsRawFd, FromRawFd, OwnedFd},
+            unix::{ffi::OsStrExt, process::CommandExt},
+        },
+        path::Path,
+        process::Command,
+    };
+
+    use super::{Grants, SYSTEM_DIRS};
+
+    // Filesystem rights of Landlock ABI v1, supported by every Landlock kernel
+    const ACCESS_EXECUTE: u64 = 1 << 0;
+    const ACCESS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_READ_FILE: u64 = 1 << 2;
+    const ACCESS_READ_DIR: u64 = 1 << 3;
+    /// Removing and creating entries of every type (bits 4 to 12)
+    const ACCESS_CHANGE_DIR: u64 = 0x1ff << 4;
+    /// The rights that apply to a single file rather than a folder
+    const ACCESS_FILE: u64 = ACCESS_EXECUTE | ACCESS_WRITE_FILE | ACCESS_READ_FILE;
+
+    pub const READ: u64 = ACCESS_EXECUTE | ACCESS_READ_FILE | ACCESS_READ_DIR;
+    pub const FULL: u64 = READ | ACCESS_WRITE_FILE | ACCESS_CHANGE_DIR;
+
+    const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: libc::c_int,
+    }
+
+    /// Builds the ruleset in the parent, where allocating and opening paths is
+    /// safe, and only enforces it in the forked child
+    pub fn confine(grants: &Grants, cmd: &mut Command) -> io::Result<()> {
+        let attr = RulesetAttr {
+            handled_access_fs: FULL,
+        };
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                std::mem::size_of::<RulesetAttr>(),
+                0u32,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // the kernel opens the ruleset close-on-exec, so the plugin never sees it
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as libc::c_int) };
+
+        // /dev/null and friends are written by most runtimes
+        add_rule(&ruleset, Path::new("/dev"), READ | ACCESS_WRITE_FILE)?;
+        for dir in SYSTEM_DIRS {
+            add_rule(&ruleset, Path::new(dir), READ)?;
+        }
+        for (path, access) in &grants.rules {
+            add_rule(&ruleset, path, *access)?;
+        }
+
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if libc::syscall(libc::SYS_landlock_restrict_self, ruleset.as_raw_fd(), 0u32) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds one path-beneath rule; paths that don't exist are skipped
+    fn add_rule(ruleset: &OwnedFd, path: &Path, access: u64) -> io::Result<()> {
+        let Ok(meta) = fs::metadata(path) else {
+            return Ok(());
+        };
+        let access = if meta.is_dir() {
+            access
+        } else {
+            access & ACCESS_FILE
+        };
+
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let fd = unsafe { libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let parent = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let attr = PathBeneathAttr {
+            allowed_access: access,
+            parent_fd: parent.as_raw_fd(),
+        };
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_add_rule,
+                ruleset.as_raw_fd(),
+                LANDLOCK_RULE_PATH_BENEATH,
+                &attr as *const PathBeneathAttr,
+                0u32,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+/// No confinement is available here; the plugin runs unrestricted
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use std::{io, process::Command};
+
+    use super::Grants;
+
+    pub const READ: u64 = 0;
+    pub const FULL: u64 = 0;
+
+    pub fn confine(_grants: &Grants, _cmd: &mut Command) -> io::Result<()> {
+        Ok(())
+    }
+}
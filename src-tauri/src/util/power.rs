@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager};
+
+use crate::util::caches::SharedAppSettings;
+
+/// How long a power reading is reused; listings ask for it on every folder
+const POWER_STATE_TTL: Duration = Duration::from_secs(30);
+
+/// Last power reading and when it was taken
+static LAST_READING: Mutex<Option<(Instant, PowerState)>> = Mutex::new(None);
+
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PowerState {
+    /// Running on battery rather than mains power
+    pub on_battery: bool,
+    /// The OS power saver (battery saver, low power mode, low-power profile) is on
+    pub power_saver: bool,
+}
+
+#[cfg(target_os = "windows")]
+fn read_power_state() -> PowerState {
+    let (on_battery, power_saver) = crate::filesys::os::windows::power_status().unwrap_or_default();
+    PowerState {
+        on_battery,
+        power_saver,
+    }
+}
+
+/// Battery state from /sys/class/power_supply: on battery when no mains supply is
+/// online and a battery is discharging. The power saver is the ACPI platform profile.
+#[cfg(target_os = "linux")]
+fn read_power_state() -> PowerState {
+    use std::fs;
+
+    let read = |path: std::path::PathBuf| {
+        fs::read_to_string(path)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut mains_online = false;
+    let mut discharging = false;
+    for supply in fs::read_dir("/sys/class/power_supply")
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let dir = supply.path();
+        match read(dir.join("type")).as_str() {
+            "Mains" | "USB" => mains_online |= read(dir.join("online")) == "1",
+            "Battery" => discharging |= read(dir.join("status")) == "Discharging",
+            _ => {}
+        }
+    }
+    PowerState {
+        on_battery: discharging && !mains_online,
+        power_saver: read("/sys/firmware/acpi/platform_profile".into()) == "low-power",
+    }
+}
+
+/// Battery state and Low Power Mode from `pmset`
+#[cfg(target_os = "macos")]
+fn read_power_state() -> PowerState {
+    let pmset = |args: &[&str]| {
+        std::process::Command::new("pmset")
+            .args(args)
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+            .unwrap_or_default()
+    };
+    PowerState {
+        on_battery: pmset(&["-g", "batt"]).contains("'Battery Power'"),
+        power_saver: pmset(&["-g"])
+            .lines()
+            .any(|l| l.split_whitespace().collect::<Vec<_>>() == ["lowpowermode", "1"]),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn read_power_state() -> PowerState {
+    PowerState::default()
+}
+
+/// Current power state, re-read at most every `POWER_STATE_TTL`
+pub fn power_state() -> PowerState {
+    let mut last = LAST_READING.lock().unwrap();
+    match *last {
+        Some((at, state)) if at.elapsed() < POWER_STATE_TTL => state,
+        _ => {
+            let state = read_power_state();
+            *last = Some((Instant::now(), state));
+            state
+        }
+    }
+}
+
+/// Whether background work (thumbnailing, prefetching, folder sizes, cache
+/// maintenance) should hold back: on battery or in power saver, unless the user
+/// turned `performance.throttle_on_battery` off
+pub fn should_throttle(handle: &AppHandle) -> bool {
+    let enabled = handle
+        .try_state::<SharedAppSettings>()
+        .and_then(|s| {
+            s.0.try_read()
+                .ok()
+                .map(|s| s.performance.throttle_on_battery)
+        })
+        .unwrap_or(true);
+    if !enabled {
+        return false;
+    }
+    let state = power_state();
+    state.on_battery || state.power_saver
+}
+
+/// Power state plus whether background work is held back because of it
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct PowerStatus {
+    #[serde(flatten)]
+    pub state: PowerState,
+    pub throttled: bool,
+}
+
+#[tauri::command]
+pub fn get_power_state(handle: AppHandle) -> PowerStatus {
+    PowerStatus {
+        state: power_state(),
+        throttled: should_throttle(&handle),
+    }
+}
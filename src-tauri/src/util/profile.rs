@@ -0,0 +1,325 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Manager};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    filesys::{
+        nav::{FileItem, FileItemWithThumbnail},
+        rules::{update_rules, RulesConfig, RulesState},
+        staging::StagingDir,
+    },
+    util::{
+        caches::{
+            update_layout_settings, update_settings, AppSettings, LayoutCache, SharedAppSettings,
+            SharedHomeCache, SharedLayoutCache,
+        },
+        custom_actions::{update_custom_actions, CustomAction, CustomActionRegistry},
+        hooks::{update_hooks, Hook, HookRegistry},
+        tags::{open_tags_db, store},
+    },
+};
+
+/// Bumped when the archive layout changes incompatibly
+const PROFILE_FORMAT: u32 = 1;
+
+/// `manifest.json` at the root of an exported profile
+#[derive(Serialize, Deserialize, Debug)]
+struct ProfileManifest {
+    format: u32,
+    app_version: String,
+    exported_at: u64,
+    /// Sections present in the archive
+    sections: Vec<String>,
+}
+
+/// Pinned folders and saved network locations from the home page
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct ProfilePins {
+    pinned_items: Vec<FileItemWithThumbnail>,
+    network_locations: Vec<FileItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ProfileTag {
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ProfileAssignment {
+    path: String,
+    tag: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ProfileNote {
+    path: String,
+    rating: Option<u8>,
+    comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct ProfileTags {
+    tags: Vec<ProfileTag>,
+    assignments: Vec<ProfileAssignment>,
+    notes: Vec<ProfileNote>,
+}
+
+/// Every section a profile can carry, in export order
+const SECTIONS: &[&str] = &[
+    "settings",
+    "layout",
+    "pins",
+    "tags",
+    "rules",
+    "hooks",
+    "custom_actions",
+];
+
+fn entry_name(section: &str) -> String {
+    format!("{}.json", section)
+}
+
+fn write_entry<T: Serialize>(
+    zip: &mut ZipWriter<File>,
+    section: &str,
+    value: &T,
+) -> Result<(), String> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let data = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    zip.start_file(entry_name(section), options)
+        .and_then(|_| zip.write_all(&data).map_err(Into::into))
+        .map_err(|e| format!("Failed to write {}: {}", section, e))
+}
+
+fn read_entry<T: DeserializeOwned>(
+    zip: &mut ZipArchive<File>,
+    section: &str,
+) -> Result<Option<T>, String> {
+    let mut file = match zip.by_name(&entry_name(section)) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", section, e)),
+    };
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read {}: {}", section, e))?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(|e| format!("Invalid {} in profile: {}", section, e))
+}
+
+fn export_tags(handle: &AppHandle) -> Result<ProfileTags, String> {
+    let conn = open_tags_db(handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    let err = |e: rusqlite::Error| format!("Failed to read tags: {}", e);
+
+    Ok(ProfileTags {
+        tags: store::list_tags(&conn)
+            .map_err(err)?
+            .into_iter()
+            .map(|t| ProfileTag {
+                name: t.name,
+                color: t.color,
+            })
+            .collect(),
+        assignments: store::all_assignments(&conn)
+            .map_err(err)?
+            .into_iter()
+            .map(|(path, tag)| ProfileAssignment { path, tag })
+            .collect(),
+        notes: store::all_notes(&conn)
+            .map_err(err)?
+            .into_iter()
+            .map(|(path, notes)| ProfileNote {
+                path,
+                rating: notes.rating,
+                comment: notes.comment,
+            })
+            .collect(),
+    })
+}
+
+/// Merges tags into the local store; existing tags and assignments are kept
+fn import_tags(handle: &AppHandle, tags: ProfileTags) -> Result<(), String> {
+    let conn = open_tags_db(handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    let err = |e: rusqlite::Error| format!("Failed to import tags: {}", e);
+
+    for tag in &tags.tags {
+        store::upsert_tag(&conn, &tag.name, tag.color.as_deref()).map_err(err)?;
+    }
+    for assignment in &tags.assignments {
+        let tag_id = store::upsert_tag(&conn, &assignment.tag, None).map_err(err)?;
+        store::add_tag(&conn, &assignment.path, tag_id).map_err(err)?;
+    }
+    for note in &tags.notes {
+        if note.rating.is_some() {
+            store::set_rating(&conn, &note.path, note.rating).map_err(err)?;
+        }
+        if note.comment.is_some() {
+            store::set_comment(&conn, &note.path, note.comment.as_deref()).map_err(err)?;
+        }
+    }
+    Ok(())
+}
+
+/// Bundles settings, layout, pins, tags, rules, hooks and custom actions into a zip at `dest`
+#[tauri::command]
+pub async fn export_profile(handle: AppHandle, dest: String) -> Result<(), String> {
+    let settings = handle.state::<SharedAppSettings>().0.read().await.clone();
+    let layout = handle.state::<SharedLayoutCache>().0.read().await.clone();
+    let pins = {
+        let home = handle.state::<SharedHomeCache>();
+        let home = home.0.read().await;
+        ProfilePins {
+            pinned_items: home.pinned_items.clone(),
+            network_locations: home.network_locations.clone(),
+        }
+    };
+    let rules = handle
+        .state::<Arc<RulesState>>()
+        .config
+        .lock()
+        .unwrap()
+        .clone();
+    let hooks = handle.state::<HookRegistry>().0.lock().unwrap().clone();
+    let actions = handle
+        .state::<CustomActionRegistry>()
+        .0
+        .lock()
+        .unwrap()
+        .clone();
+    let tags = export_tags(&handle)?;
+
+    // built in a staging folder so a failed export never clobbers an existing file
+    let dest_path = Path::new(&dest);
+    let staging = StagingDir::new(&handle, dest_path.parent().unwrap_or(Path::new(".")))
+        .map_err(|e| format!("Failed to create {}: {}", dest, e))?;
+    let staged = staging.path().join("profile.zip");
+    let file = File::create(&staged).map_err(|e| format!("Failed to create {}: {}", dest, e))?;
+    let mut zip = ZipWriter::new(file);
+
+    write_entry(
+        &mut zip,
+        "manifest",
+        &ProfileManifest {
+            format: PROFILE_FORMAT,
+            app_version: handle.package_info().version.to_string(),
+            exported_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            sections: SECTIONS.iter().map(|s| s.to_string()).collect(),
+        },
+    )?;
+    write_entry(&mut zip, "settings", &settings)?;
+    write_entry(&mut zip, "layout", &layout)?;
+    write_entry(&mut zip, "pins", &pins)?;
+    write_entry(&mut zip, "tags", &tags)?;
+    write_entry(&mut zip, "rules", &rules)?;
+    write_entry(&mut zip, "hooks", &hooks)?;
+    write_entry(&mut zip, "custom_actions", &actions)?;
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish profile: {}", e))?;
+    staging
+        .commit(&staged, dest_path, true)
+        .map_err(|e| format!("Failed to save {}: {}", dest, e))
+}
+
+/// Restores a profile exported by `export_profile`. `sections` limits what is imported
+/// (all by default). Tags merge into the local store; everything else replaces local
+/// values. Imported hooks and custom actions arrive disabled so commands never run
+/// unreviewed on a new machine.
+/// Returns the sections that were imported.
+#[tauri::command]
+pub async fn import_profile(
+    handle: AppHandle,
+    src: String,
+    sections: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let file = File::open(Path::new(&src)).map_err(|e| format!("Failed to open {}: {}", src, e))?;
+    let mut zip = ZipArchive::new(file).map_err(|e| format!("Not a profile archive: {}", e))?;
+
+    let manifest: ProfileManifest =
+        read_entry(&mut zip, "manifest")?.ok_or("Not a profile archive: missing manifest")?;
+    if manifest.format > PROFILE_FORMAT {
+        return Err("This profile was exported by a newer version of Dagger".into());
+    }
+
+    let wanted = |section: &str| {
+        sections
+            .as_ref()
+            .is_none_or(|s| s.iter().any(|x| x == section))
+    };
+    let mut imported = Vec::new();
+
+    if wanted("settings") {
+        if let Some(settings) = read_entry::<AppSettings>(&mut zip, "settings")? {
+            update_settings(handle.clone(), handle.state(), settings).await?;
+            imported.push("settings".to_string());
+        }
+    }
+
+    if wanted("layout") {
+        if let Some(layout) = read_entry::<LayoutCache>(&mut zip, "layout")? {
+            update_layout_settings(handle.clone(), handle.state(), layout).await?;
+            imported.push("layout".to_string());
+        }
+    }
+
+    if wanted("pins") {
+        if let Some(pins) = read_entry::<ProfilePins>(&mut zip, "pins")? {
+            let home = handle.state::<SharedHomeCache>();
+            {
+                let mut cache = home.0.write().await;
+                cache.pinned_items = pins.pinned_items;
+                cache.network_locations = pins.network_locations;
+            }
+            home.save(&handle).await;
+            imported.push("pins".to_string());
+        }
+    }
+
+    if wanted("tags") {
+        if let Some(tags) = read_entry::<ProfileTags>(&mut zip, "tags")? {
+            import_tags(&handle, tags)?;
+            imported.push("tags".to_string());
+        }
+    }
+
+    if wanted("rules") {
+        if let Some(rules) = read_entry::<RulesConfig>(&mut zip, "rules")? {
+            update_rules(handle.clone(), handle.state(), rules)?;
+            imported.push("rules".to_string());
+        }
+    }
+
+    if wanted("hooks") {
+        if let Some(mut hooks) = read_entry::<Vec<Hook>>(&mut zip, "hooks")? {
+            for hook in &mut hooks {
+                hook.enabled = false;
+            }
+            update_hooks(handle.clone(), handle.state(), hooks)?;
+            imported.push("hooks".to_string());
+        }
+    }
+
+    if wanted("custom_actions") {
+        if let Some(mut actions) = read_entry::<Vec<CustomAction>>(&mut zip, "custom_actions")? {
+            for action in &mut actions {
+                action.enabled = false;
+            }
+            update_custom_actions(handle.clone(), handle.state(), actions)?;
+            imported.push("custom_actions".to_string());
+        }
+    }
+
+    Ok(imported)
+}
@@ -1,42 +1,85 @@
-use std::error::Error;
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
 
+use rayon::ThreadPoolBuilder;
 use tauri::{
-    menu::{Menu, MenuItem}, tray::{MouseButton, TrayIconBuilder, TrayIconEvent}, webview::Color, App, AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, Window, WindowEvent
+    menu::{Menu, MenuItem, PredefinedMenuItem}, tray::{MouseButton, TrayIconBuilder, TrayIconEvent}, App, AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder, Window, WindowEvent, Wry
 };
-use window_vibrancy::{apply_acrylic, clear_acrylic};
 
-use crate::util::caches::{load_home_cache, load_layout_cache, SharedHomeCache, SharedLayoutCache};
+use crate::filesys::foldersize::{start_folder_size_worker, FolderSizeState};
+use crate::filesys::history::{load_history_config, restart_history_watcher, HistoryState};
+use crate::filesys::nav::prefetch_quick_access;
+use crate::filesys::rules::{load_rules, restart_rules_watcher, RulesState};
+use crate::filesys::staging::clean_orphaned_staging;
+use crate::filesys::stream::transfer::set_copy_streams;
+use crate::filesys::stream::{start_clipboard_watcher, JobStreamState};
+use crate::util::activity::ActivityLog;
+use crate::util::appearance::{refresh_system_appearance, AppearanceState};
+use crate::util::caches::maintenance::start_thumb_maintenance;
+use crate::util::caches::{
+    load_app_settings, load_home_cache, load_layout_cache, load_window_state, save_window_state,
+    HomeCache, SharedAppSettings, SharedHomeCache, SharedLayoutCache, WindowStateCache,
+};
+use crate::util::custom_actions::{load_custom_actions, CustomActionRegistry};
+use crate::util::effects::apply_window_effect;
+use crate::util::hooks::{load_hooks, HookRegistry};
+use crate::util::logging::init_logging;
+use crate::util::plugins::{discover_plugins, PluginHost};
+use crate::util::taskbar::{refresh_jump_list, start_taskbar_progress};
 
 pub fn setup_app_environment(app: &mut App) -> Result<(), Box<dyn Error>> {
     #[cfg(desktop)]
     setup_autostart(app);
-    setup_system_tray(app).expect("Failed to setup system tray!");
     manage_home_cache(app);
+    app.manage(AppearanceState::default());
+    setup_system_tray(app).expect("Failed to setup system tray!");
     manage_layout_cache(app);
+    manage_app_settings(app);
+    manage_logging(app);
+    manage_rules_engine(app);
+    manage_file_history(app);
+    manage_plugin_host(app);
+    app.manage(ActivityLog::default());
+    app.manage(HookRegistry(Mutex::new(load_hooks(app.handle()))));
+    app.manage(CustomActionRegistry(Mutex::new(load_custom_actions(
+        app.handle(),
+    ))));
+    #[cfg(desktop)]
+    setup_global_hotkey(app);
+    let jobs = app.state::<Arc<JobStreamState>>().inner().clone();
+    start_taskbar_progress(&app.handle(), jobs.clone());
+    start_thumb_maintenance(&app.handle(), jobs);
+    prefetch_quick_access(app.handle());
+    start_clipboard_watcher(app.handle());
+    clean_orphaned_staging(app.handle());
+    let folder_sizes = Arc::new(FolderSizeState::default());
+    app.manage(folder_sizes.clone());
+    start_folder_size_worker(app.handle(), folder_sizes);
     let paths_to_watch = vec![dirs_next::home_dir().unwrap().to_string_lossy().to_string()];
     let watcher = crate::filesys::watcher::start_file_watcher(&app.handle(), paths_to_watch);
     app.manage(watcher);
     Ok(())
 }
 
-#[cfg(target_os = "windows")]
 pub fn window_event_handler(window: &Window, event: &WindowEvent) {
     match event {
         WindowEvent::CloseRequested { api, .. } => {
             api.prevent_close();
+            persist_window_state(window.app_handle());
             window.hide().unwrap();
         }
         WindowEvent::Focused(true) => {
             let _ = window.emit("window-focus", ());
-            apply_acrylic(&window, Some((0, 0, 0, 20))).ok();
-            window.set_background_color(Some(Color(0, 0, 0, 0))).ok();
+            // accent changes have no window event; focus is a cheap point to catch them
+            refresh_system_appearance(window.app_handle(), None);
+        }
+        WindowEvent::ThemeChanged(theme) => {
+            refresh_system_appearance(window.app_handle(), Some(*theme));
         }
         WindowEvent::Focused(false) => {
             let _ = window.emit("window-blur", ());
-            clear_acrylic(&window).ok();
-            window
-                .set_background_color(Some(Color(120, 120, 120, 255)))
-                .ok();
         }
         _ => {}
     }
@@ -45,6 +88,7 @@ pub fn window_event_handler(window: &Window, event: &WindowEvent) {
 fn manage_home_cache(app: &mut App) {
     let handle = app.handle();
     let cache = load_home_cache(&handle);
+    refresh_jump_list(&cache);
     app.manage(SharedHomeCache::new(cache));
 }
 
@@ -54,11 +98,144 @@ fn manage_layout_cache(app: &mut App) {
     app.manage(SharedLayoutCache::new(cache));
 }
 
+fn manage_app_settings(app: &mut App) {
+    let handle = app.handle();
+    let settings = load_app_settings(&handle);
+
+    // Worker pool for metadata/thumbnail streaming, sized from settings
+    let threads = settings.performance.worker_threads.max(1);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    app.manage(Arc::new(pool));
+    set_copy_streams(settings.performance.copy_streams);
+
+    app.manage(SharedAppSettings::new(settings));
+}
+
+fn manage_logging(app: &mut App) {
+    let level = app
+        .state::<SharedAppSettings>()
+        .0
+        .try_read()
+        .map(|s| s.log_level)
+        .unwrap_or_default();
+    if let Some(logging) = init_logging(app.handle(), level) {
+        app.manage(logging);
+    }
+    tracing::info!(
+        target: "startup",
+        "Dagger {} starting",
+        app.package_info().version
+    );
+}
+
+fn manage_rules_engine(app: &mut App) {
+    let handle = app.handle();
+    let state = Arc::new(RulesState::new(load_rules(&handle)));
+    restart_rules_watcher(&handle, &state);
+    app.manage(state);
+}
+
+fn manage_file_history(app: &mut App) {
+    let handle = app.handle();
+    let state = Arc::new(HistoryState::new(load_history_config(handle)));
+    restart_history_watcher(handle, &state);
+    app.manage(state);
+}
+
+fn manage_plugin_host(app: &mut App) {
+    let handle = app.handle();
+    let disabled = app
+        .state::<SharedAppSettings>()
+        .0
+        .try_read()
+        .map(|s| s.integrations.disabled_plugins.clone())
+        .unwrap_or_default();
+    let host = PluginHost::default();
+    *host.plugins.write().unwrap() = discover_plugins(&handle, &disabled);
+    app.manage(Arc::new(host));
+}
+
+const TRAY_ID: &str = "dagger-tray";
+/// How many recent and pinned folders the tray menu lists
+const TRAY_MENU_LIMIT: usize = 5;
+const OPEN_PATH_PREFIX: &str = "open-path:";
+
+/// Folder the window should navigate to once it (re)loads, set from the tray menu
+#[derive(Default)]
+pub struct PendingOpenPath(pub Mutex<Option<String>>);
+
+/// Called by the frontend on load to pick up a folder chosen from the tray
+#[tauri::command]
+pub fn take_pending_open_path(state: State<'_, PendingOpenPath>) -> Option<String> {
+    state.0.lock().unwrap().take()
+}
+
+/// Builds the tray menu: recent and pinned folders, then Open/Close
+fn build_tray_menu<M: Manager<Wry>>(
+    manager: &M,
+    cache: Option<&HomeCache>,
+) -> tauri::Result<Menu<Wry>> {
+    let menu = Menu::new(manager)?;
+
+    if let Some(cache) = cache {
+        let recent: Vec<_> = cache
+            .recent_dirs
+            .iter()
+            .take(TRAY_MENU_LIMIT)
+            .map(|item| (item.name.clone(), item.path.clone()))
+            .collect();
+        let pinned: Vec<_> = cache
+            .pinned_items
+            .iter()
+            .filter(|item| item.is_dir)
+            .take(TRAY_MENU_LIMIT)
+            .map(|item| (item.name.clone(), item.path.clone()))
+            .collect();
+
+        for (title, items) in [("Recent folders", recent), ("Pinned", pinned)] {
+            if items.is_empty() {
+                continue;
+            }
+            menu.append(&MenuItem::new(manager, title, false, None::<&str>)?)?;
+            for (name, path) in items {
+                let id = format!("{}{}", OPEN_PATH_PREFIX, path);
+                menu.append(&MenuItem::with_id(manager, id, name, true, None::<&str>)?)?;
+            }
+            menu.append(&PredefinedMenuItem::separator(manager)?)?;
+        }
+    }
+
+    let open = MenuItem::with_id(manager, "open", "Open", true, None::<&str>)?;
+    let close = MenuItem::with_id(manager, "close", "Close", true, None::<&str>)?;
+    menu.append_items(&[&open, &close])?;
+    Ok(menu)
+}
+
+/// Rebuilds the tray menu after the home cache changes
+pub fn refresh_tray_menu(handle: &AppHandle, cache: &HomeCache) {
+    let Some(tray) = handle.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_tray_menu(handle, Some(cache)) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => tracing::warn!(target: "tray", "Failed to rebuild tray menu: {}", e),
+    }
+}
+
 fn setup_system_tray(app: &App) -> Result<(), Box<dyn Error>> {
-    let open = MenuItem::with_id(app, "open", "Open", true, None::<&str>)?;
-    let close = MenuItem::with_id(app, "close", "Close", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&open, &close])?;
-    let _tray = TrayIconBuilder::new()
+    let args: Vec<String> = std::env::args().collect();
+    app.manage(PendingOpenPath(Mutex::new(launch_folder(&args))));
+    let home_cache = app.state::<SharedHomeCache>();
+    let cache = home_cache.0.try_read().ok();
+    let menu = build_tray_menu(app, cache.as_deref())?;
+    drop(cache);
+
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .show_menu_on_left_click(true)
@@ -68,9 +245,15 @@ fn setup_system_tray(app: &App) -> Result<(), Box<dyn Error>> {
                 open_window(app);
             }
             "close" => {
+                persist_window_state(app);
                 app.exit(0);
             }
-            _ => {}
+            id => {
+                if let Some(path) = id.strip_prefix(OPEN_PATH_PREFIX) {
+                    *app.state::<PendingOpenPath>().0.lock().unwrap() = Some(path.to_string());
+                    open_window(app);
+                }
+            }
         })
         .on_tray_icon_event(move |tray, event| {
             let app = tray.app_handle();
@@ -90,6 +273,51 @@ fn setup_system_tray(app: &App) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Saves the main window's geometry so the next launch reopens it in place
+pub fn persist_window_state(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_visible().unwrap_or(false) {
+            if let Some(state) = WindowStateCache::capture(&window) {
+                save_window_state(app, &state);
+            }
+        }
+    }
+}
+
+/// Shows the main window, optionally opening `location` in a new tab
+pub fn show_window_at(handle: &AppHandle, location: Option<String>) {
+    match handle.get_webview_window("main") {
+        // Already loaded: bring it forward without reloading
+        Some(window) if window.is_visible().unwrap_or(false) => {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+            if let Some(path) = location {
+                let _ = window.emit("open-new-tab", serde_json::json!({ "path": path }));
+            }
+        }
+        // Hidden in the tray: the window reloads, so hand the location over as pending
+        _ => {
+            if let Some(path) = location {
+                if let Some(pending) = handle.try_state::<PendingOpenPath>() {
+                    *pending.0.lock().unwrap() = Some(path);
+                }
+            }
+            open_window(handle);
+        }
+    }
+}
+
+/// Folder passed on the command line (e.g. by a Jump List entry or the
+/// "Open in Dagger" menu), if any
+pub fn launch_folder(args: &[String]) -> Option<String> {
+    args.iter()
+        .skip(1)
+        // a quoted drive root ("C:\") reaches us as C:" under Windows quoting rules
+        .map(|arg| arg.trim_end_matches('"'))
+        .find(|arg| !arg.starts_with('-') && std::path::Path::new(arg).is_dir())
+        .map(|arg| arg.to_string())
+}
+
 /// Spawns the app window if none available
 pub fn open_window(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
@@ -97,25 +325,56 @@ pub fn open_window(app: &AppHandle) {
         window.set_focus().unwrap();
         window.reload().unwrap();
     } else {
+        // created hidden so the saved geometry is applied before the first paint
         let new_window = WebviewWindowBuilder::new(
             app,
             "main",
             WebviewUrl::App("index.html".into()),
         )
         .title("Dagger File Explorer")
-        .visible(true)
+        .visible(false)
         .decorations(false)
         .transparent(true)
         .resizable(true)
-        .maximized(true)
-        .center()
         .build()
         .unwrap();
+        let effect = app
+            .state::<SharedAppSettings>()
+            .0
+            .try_read()
+            .map(|s| s.window_effect)
+            .unwrap_or_default();
+        apply_window_effect(&new_window, effect);
+        let restored = load_window_state(app).is_some_and(|state| state.restore(&new_window));
+        if !restored {
+            new_window.center().ok();
+            new_window.maximize().ok();
+        }
         new_window.show().unwrap();
         new_window.set_focus().unwrap();
     }
 }
 
+/// Registers the global-shortcut plugin and the configured summon hotkey
+#[cfg(desktop)]
+fn setup_global_hotkey(app: &App) {
+    use crate::util::hotkey::{apply_global_hotkey, on_global_hotkey};
+    let _ = app.handle().plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(on_global_hotkey)
+            .build(),
+    );
+    let hotkey = app
+        .state::<SharedAppSettings>()
+        .0
+        .try_read()
+        .map(|s| s.global_hotkey.clone())
+        .unwrap_or_default();
+    if let Err(e) = apply_global_hotkey(app.handle(), &hotkey) {
+        tracing::warn!(target: "hotkey", "{}", e);
+    }
+}
+
 /// Setup system autostart
 #[cfg(desktop)]
 fn setup_autostart(app: &App) {
@@ -0,0 +1,460 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+use crate::util::caches::SharedAppSettings;
+
+/// Label of the context-menu entry
+const MENU_LABEL: &str = "Open in Dagger";
+
+/// Keys below HKCU that get an "Open in Dagger" verb, with the placeholder
+/// Explorer substitutes: `%1` for a clicked folder or drive, `%V` for the
+/// background of an open folder
+#[cfg(target_os = "windows")]
+const SHELL_KEYS: [(&str, &str); 3] = [
+    ("Software\\Classes\\Directory\\shell\\Dagger", "%1"),
+    (
+        "Software\\Classes\\Directory\\Background\\shell\\Dagger",
+        "%V",
+    ),
+    ("Software\\Classes\\Drive\\shell\\Dagger", "%1"),
+];
+
+/// Whether the file manager context-menu entry is installed for this user
+#[derive(Serialize, Clone, Debug)]
+pub struct ShellIntegrationStatus {
+    pub supported: bool,
+    pub installed: bool,
+    /// The entry points at a different executable, e.g. after Dagger was moved or updated
+    pub stale: bool,
+}
+
+fn current_exe() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|e| format!("Failed to locate the Dagger executable: {}", e))
+}
+
+/// Command line the shell runs; the folder becomes the launch argument picked up
+/// by `launch_folder`, or forwarded to the running instance
+#[cfg(target_os = "windows")]
+fn shell_command(exe: &std::path::Path, placeholder: &str) -> String {
+    format!("\"{}\" \"{}\"", exe.display(), placeholder)
+}
+
+#[cfg(target_os = "windows")]
+fn read_status() -> Result<ShellIntegrationStatus, String> {
+    use crate::filesys::os::windows::get_user_registry_string;
+
+    let exe = current_exe()?;
+    let commands: Vec<Option<String>> = SHELL_KEYS
+        .iter()
+        .map(|(key, _)| get_user_registry_string(&format!("{}\\command", key), None))
+        .collect();
+    Ok(ShellIntegrationStatus {
+        supported: true,
+        installed: commands.iter().any(|c| c.is_some()),
+        stale: SHELL_KEYS
+            .iter()
+            .zip(&commands)
+            .any(|((_, placeholder), c)| {
+                c.as_ref()
+                    .is_some_and(|c| *c != shell_command(&exe, placeholder))
+            }),
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn install() -> Result<(), String> {
+    use crate::filesys::os::windows::set_user_registry_string;
+
+    let exe = current_exe()?;
+    for (key, placeholder) in SHELL_KEYS {
+        let command = shell_command(&exe, placeholder);
+        set_user_registry_string(key, None, MENU_LABEL)
+            .and_then(|_| set_user_registry_string(key, Some("Icon"), &exe.display().to_string()))
+            .and_then(|_| set_user_registry_string(&format!("{}\\command", key), None, &command))
+            .map_err(|e| format!("Failed to write {}: {}", key, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn uninstall() -> Result<(), String> {
+    use crate::filesys::os::windows::delete_user_registry_tree;
+
+    for (key, _) in SHELL_KEYS {
+        delete_user_registry_tree(key).map_err(|e| format!("Failed to remove {}: {}", key, e))?;
+    }
+    Ok(())
+}
+
+/// The desktop entry (shown as "Open With" for folders) and the KDE Dolphin
+/// service menu, both under `$XDG_DATA_HOME`
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_files() -> Result<[PathBuf; 2], String> {
+    let data = dirs_next::data_dir().ok_or("Failed to locate the user data directory")?;
+    Ok([
+        data.join("applications/dagger-open-here.desktop"),
+        data.join("kio/servicemenus/dagger-open-here.desktop"),
+    ])
+}
+
+/// Quotes an argument for a desktop entry `Exec` key
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_exec_quote(arg: &str) -> String {
+    let escaped: String = arg
+        .chars()
+        .flat_map(|c| match c {
+            '"' | '`' | '$' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect();
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_contents(exe: &std::path::Path) -> [String; 2] {
+    let exec = format!("{} %f", desktop_exec_quote(&exe.display().to_string()));
+    [
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name={label}\n\
+             Exec={exec}\n\
+             Icon=dagger\n\
+             MimeType=inode/directory;\n\
+             NoDisplay=true\n\
+             Terminal=false\n",
+            label = MENU_LABEL,
+            exec = exec
+        ),
+        format!(
+            "[Desktop Entry]\n\
+             Type=Service\n\
+             MimeType=inode/directory;\n\
+             Actions=openInDagger;\n\
+             X-KDE-ServiceTypes=KonqPopupMenu/Plugin\n\
+             \n\
+             [Desktop Action openInDagger]\n\
+             Name={label}\n\
+             Icon=dagger\n\
+             Exec={exec}\n",
+            label = MENU_LABEL,
+            exec = exec
+        ),
+    ]
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_status() -> Result<ShellIntegrationStatus, String> {
+    let exe = current_exe()?;
+    let files = desktop_files()?;
+    let expected = desktop_contents(&exe);
+    let existing: Vec<Option<String>> = files
+        .iter()
+        .map(|path| std::fs::read_to_string(path).ok())
+        .collect();
+    Ok(ShellIntegrationStatus {
+        supported: true,
+        installed: existing.iter().any(|c| c.is_some()),
+        stale: existing
+            .iter()
+            .zip(&expected)
+            .any(|(c, expected)| c.as_ref().is_some_and(|c| c != expected)),
+    })
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn install() -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let exe = current_exe()?;
+    for (path, contents) in desktop_files()?.iter().zip(desktop_contents(&exe)) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        // Dolphin only loads service menus marked executable
+        let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755));
+    }
+    // refresh the MIME cache so "Open With" picks up the entry; harmless if missing
+    if let Some(dir) = desktop_files()?[0].parent() {
+        let _ = std::process::Command::new("update-desktop-database")
+            .arg(dir)
+            .output();
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn uninstall() -> Result<(), String> {
+    for path in desktop_files()? {
+        match std::fs::remove_file(&path) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to remove {}: {}", path.display(), e)),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn read_status() -> Result<ShellIntegrationStatus, String> {
+    Ok(ShellIntegrationStatus {
+        supported: false,
+        installed: false,
+        stale: false,
+    })
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn install() -> Result<(), String> {
+    Err("The \"Open in Dagger\" menu entry is not supported on this platform".into())
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn uninstall() -> Result<(), String> {
+    Ok(())
+}
+
+/// Reports whether the "Open in Dagger" context-menu entry is installed
+#[tauri::command]
+pub fn get_shell_integration() -> Result<ShellIntegrationStatus, String> {
+    read_status()
+}
+
+/// Adds "Open in Dagger" to the file manager's folder context menu for the
+/// current user: registry verbs on Windows, desktop entries on Linux. Installing
+/// again rewrites the entry for the current executable.
+#[tauri::command]
+pub fn install_shell_integration() -> Result<ShellIntegrationStatus, String> {
+    install()?;
+    read_status()
+}
+
+/// Removes the "Open in Dagger" context-menu entry
+#[tauri::command]
+pub fn uninstall_shell_integration() -> Result<ShellIntegrationStatus, String> {
+    uninstall()?;
+    read_status()
+}
+
+/// Whether Dagger opens folders in place of the system file manager
+#[derive(Serialize, Clone, Debug)]
+pub struct DefaultFileManagerStatus {
+    pub supported: bool,
+    pub is_default: bool,
+    /// The current folder handler (a verb on Windows, a desktop entry on Linux)
+    pub current: Option<String>,
+}
+
+/// Shell verb set as the default for folders and drives; shared with the
+/// context-menu entry
+#[cfg(target_os = "windows")]
+const DEFAULT_VERB: &str = "Dagger";
+
+/// Keys whose default value picks the verb Explorer runs on double-click
+#[cfg(target_os = "windows")]
+const DEFAULT_VERB_KEYS: [&str; 2] = [
+    "Software\\Classes\\Directory\\shell",
+    "Software\\Classes\\Drive\\shell",
+];
+
+#[cfg(target_os = "windows")]
+fn current_folder_handler() -> Option<String> {
+    crate::filesys::os::windows::get_user_registry_string(DEFAULT_VERB_KEYS[0], None)
+        .filter(|verb| !verb.is_empty())
+}
+
+#[cfg(target_os = "windows")]
+fn is_folder_handler(current: Option<&str>) -> bool {
+    current == Some(DEFAULT_VERB)
+}
+
+/// Makes the "Open in Dagger" verb the default action for folders and drives.
+/// Explorer windows then hand folders off to Dagger too; virtual folders such
+/// as This PC or Control Panel keep opening in Explorer.
+#[cfg(target_os = "windows")]
+fn make_folder_handler() -> Result<(), String> {
+    use crate::filesys::os::windows::set_user_registry_string;
+
+    install()?;
+    for key in DEFAULT_VERB_KEYS {
+        set_user_registry_string(key, None, DEFAULT_VERB)
+            .map_err(|e| format!("Failed to write {}: {}", key, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn restore_folder_handler(previous: Option<&str>) -> Result<(), String> {
+    use crate::filesys::os::windows::{delete_user_registry_value, set_user_registry_string};
+
+    for key in DEFAULT_VERB_KEYS {
+        match previous {
+            Some(verb) => set_user_registry_string(key, None, verb),
+            None => delete_user_registry_value(key, None),
+        }
+        .map_err(|e| format!("Failed to write {}: {}", key, e))?;
+    }
+    Ok(())
+}
+
+/// Desktop entry registered as the `inode/directory` handler
+#[cfg(all(unix, not(target_os = "macos")))]
+const FOLDER_HANDLER_DESKTOP: &str = "dagger-folder-handler.desktop";
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) fn xdg_mime(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("xdg-mime")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run xdg-mime: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "xdg-mime failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn current_folder_handler() -> Option<String> {
+    xdg_mime(&["query", "default", "inode/directory"])
+        .ok()
+        .filter(|entry| !entry.is_empty())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_folder_handler(current: Option<&str>) -> bool {
+    current == Some(FOLDER_HANDLER_DESKTOP)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn make_folder_handler() -> Result<(), String> {
+    let exe = current_exe()?;
+    let data = dirs_next::data_dir().ok_or("Failed to locate the user data directory")?;
+    let path = data.join("applications").join(FOLDER_HANDLER_DESKTOP);
+    let contents = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Dagger\n\
+         Exec={} %f\n\
+         Icon=dagger\n\
+         MimeType=inode/directory;\n\
+         NoDisplay=true\n\
+         Terminal=false\n",
+        desktop_exec_quote(&exe.display().to_string())
+    );
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    xdg_mime(&["default", FOLDER_HANDLER_DESKTOP, "inode/directory"]).map(|_| ())
+}
+
+/// Hands folders back to `previous`. Without one, Dagger's line is dropped from
+/// `mimeapps.list` so the desktop's own default applies again.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn restore_folder_handler(previous: Option<&str>) -> Result<(), String> {
+    if let Some(entry) = previous {
+        return xdg_mime(&["default", entry, "inode/directory"]).map(|_| ());
+    }
+
+    let Some(path) = dirs_next::config_dir().map(|d| d.join("mimeapps.list")) else {
+        return Ok(());
+    };
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let kept: Vec<&str> = contents
+        .lines()
+        .filter(|line| {
+            let line = line.replace(' ', "");
+            line != format!("inode/directory={}", FOLDER_HANDLER_DESKTOP)
+                && line != format!("inode/directory={};", FOLDER_HANDLER_DESKTOP)
+        })
+        .collect();
+    std::fs::write(&path, kept.join("\n") + "\n")
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn current_folder_handler() -> Option<String> {
+    None
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn is_folder_handler(_current: Option<&str>) -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn make_folder_handler() -> Result<(), String> {
+    Err("Finder cannot be replaced as the default file manager".into())
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn restore_folder_handler(_previous: Option<&str>) -> Result<(), String> {
+    Ok(())
+}
+
+fn default_status() -> DefaultFileManagerStatus {
+    let current = current_folder_handler();
+    DefaultFileManagerStatus {
+        supported: cfg!(any(
+            target_os = "windows",
+            all(unix, not(target_os = "macos"))
+        )),
+        is_default: is_folder_handler(current.as_deref()),
+        current,
+    }
+}
+
+/// Reports whether Dagger is the default handler for opening folders
+#[tauri::command]
+pub fn get_default_file_manager() -> DefaultFileManagerStatus {
+    default_status()
+}
+
+/// Registers Dagger as the handler for opening folders (`enabled`), or hands
+/// them back to whatever handled them before. The previous handler is kept in
+/// the settings so it can be restored later.
+#[tauri::command]
+pub async fn set_default_file_manager(
+    handle: AppHandle,
+    settings: State<'_, SharedAppSettings>,
+    enabled: bool,
+) -> Result<DefaultFileManagerStatus, String> {
+    let current = current_folder_handler();
+    if enabled {
+        if is_folder_handler(current.as_deref()) {
+            return Ok(default_status());
+        }
+        make_folder_handler()?;
+        settings
+            .0
+            .write()
+            .await
+            .integrations
+            .previous_folder_handler = current;
+    } else {
+        if !is_folder_handler(current.as_deref()) {
+            return Ok(default_status());
+        }
+        let previous = settings
+            .0
+            .write()
+            .await
+            .integrations
+            .previous_folder_handler
+            .take();
+        restore_folder_handler(previous.as_deref())?;
+    }
+    settings.commit(&handle).await;
+    Ok(default_status())
+}
@@ -0,0 +1,212 @@
+use serde::Serialize;
+use std::{path::Path, time::UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::filesys::{
+    cloud::{cloud_state, CloudState},
+    links::link_info,
+    nav::FileItem,
+};
+use crate::search::email::{email_summary, EmailFormat, EmailSummary};
+
+pub mod store;
+
+pub use store::{
+    notes_for_path, notes_in_directory, open_tags_db, tags_for_path, tags_in_directory, FileNotes,
+    Tag, TagSummary,
+};
+
+/// Properties panel payload: basic metadata plus user annotations
+#[derive(Serialize, Clone, Debug)]
+pub struct FileProperties {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    pub readonly: bool,
+    pub date_created: Option<u64>,
+    pub date_modified: Option<u64>,
+    /// Sync state for files kept by a cloud provider
+    pub cloud: Option<CloudState>,
+    pub tags: Vec<Tag>,
+    pub rating: Option<u8>,
+    pub comment: Option<String>,
+    /// Sender, subject, date and attachments of .eml/.msg files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailSummary>,
+}
+
+/// Validates a tag name and optional "#RGB" / "#RRGGBB" color
+fn validate_tag(name: &str, color: Option<&str>) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Tag name cannot be empty".into());
+    }
+    if let Some(color) = color {
+        let hex = color.strip_prefix('#').unwrap_or("");
+        if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid tag color: {}", color));
+        }
+    }
+    Ok(())
+}
+
+/// Assigns a tag (creating it if needed) to every path
+#[tauri::command]
+pub fn tag_paths(
+    handle: AppHandle,
+    paths: Vec<String>,
+    tag: String,
+    color: Option<String>,
+) -> Result<(), String> {
+    let tag = tag.trim();
+    validate_tag(tag, color.as_deref())?;
+
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    let tag_id = store::upsert_tag(&conn, tag, color.as_deref())
+        .map_err(|e| format!("Failed to save tag: {}", e))?;
+    for path in &paths {
+        store::add_tag(&conn, path, tag_id)
+            .map_err(|e| format!("Failed to tag {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+/// Removes a tag from every path
+#[tauri::command]
+pub fn untag_paths(handle: AppHandle, paths: Vec<String>, tag: String) -> Result<(), String> {
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    for path in &paths {
+        store::remove_tag(&conn, path, &tag)
+            .map_err(|e| format!("Failed to untag {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+/// Lists all tags with usage counts
+#[tauri::command]
+pub fn list_tags(handle: AppHandle) -> Result<Vec<TagSummary>, String> {
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    store::list_tags(&conn).map_err(|e| format!("Failed to list tags: {}", e))
+}
+
+/// Changes a tag's color (or creates the tag)
+#[tauri::command]
+pub fn set_tag_color(handle: AppHandle, tag: String, color: String) -> Result<(), String> {
+    validate_tag(&tag, Some(&color))?;
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    conn.execute(
+        "UPDATE tags SET color = ?1 WHERE name = ?2;",
+        rusqlite::params![color, tag],
+    )
+    .map_err(|e| format!("Failed to update tag: {}", e))?;
+    Ok(())
+}
+
+/// Deletes a tag and removes it from all paths
+#[tauri::command]
+pub fn delete_tag(handle: AppHandle, tag: String) -> Result<(), String> {
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    store::delete_tag(&conn, &tag).map_err(|e| format!("Failed to delete tag: {}", e))
+}
+
+/// Returns the tags attached to a path
+#[tauri::command]
+pub fn get_tags_for_path(handle: AppHandle, path: String) -> Result<Vec<Tag>, String> {
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    tags_for_path(&conn, &path).map_err(|e| format!("Failed to read tags: {}", e))
+}
+
+/// Returns every existing file/folder carrying the tag
+#[tauri::command]
+pub fn find_by_tag(handle: AppHandle, tag: String) -> Result<Vec<FileItem>, String> {
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    if store::find_tag_id(&conn, &tag)
+        .map_err(|e| format!("Failed to read tags: {}", e))?
+        .is_none()
+    {
+        return Err(format!("Unknown tag: {}", tag));
+    }
+
+    let paths =
+        store::paths_with_tag(&conn, &tag).map_err(|e| format!("Failed to read tags: {}", e))?;
+
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| {
+            let meta = std::fs::metadata(&path).ok()?;
+            let is_dir = meta.is_dir();
+            Some(FileItem {
+                name: Path::new(&path)
+                    .file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone()),
+                link: link_info(Path::new(&path)),
+                path,
+                is_dir,
+                size: if is_dir { None } else { Some(meta.len()) },
+            })
+        })
+        .collect())
+}
+
+/// Sets (1–5) or clears (None) the star rating of every path
+#[tauri::command]
+pub fn set_rating(handle: AppHandle, paths: Vec<String>, rating: Option<u8>) -> Result<(), String> {
+    if let Some(r) = rating {
+        if !(1..=5).contains(&r) {
+            return Err(format!("Rating must be between 1 and 5, got {}", r));
+        }
+    }
+
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    for path in &paths {
+        store::set_rating(&conn, path, rating)
+            .map_err(|e| format!("Failed to rate {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+/// Sets or clears the comment of a path; blank comments are cleared
+#[tauri::command]
+pub fn set_comment(handle: AppHandle, path: String, comment: Option<String>) -> Result<(), String> {
+    let comment = comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    store::set_comment(&conn, &path, comment)
+        .map_err(|e| format!("Failed to save comment for {}: {}", path, e))
+}
+
+/// Returns file metadata together with its tags, rating and comment
+#[tauri::command]
+pub fn get_file_properties(handle: AppHandle, path: String) -> Result<FileProperties, String> {
+    let meta = std::fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let secs = |t: std::io::Result<std::time::SystemTime>| {
+        t.ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+    };
+
+    let conn = open_tags_db(&handle).map_err(|e| format!("Failed to open tag DB: {}", e))?;
+    let tags = tags_for_path(&conn, &path).map_err(|e| format!("Failed to read tags: {}", e))?;
+    let notes = notes_for_path(&conn, &path).map_err(|e| format!("Failed to read notes: {}", e))?;
+
+    let is_dir = meta.is_dir();
+    Ok(FileProperties {
+        name: Path::new(&path)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone()),
+        is_dir,
+        size: if is_dir { None } else { Some(meta.len()) },
+        readonly: meta.permissions().readonly(),
+        date_created: secs(meta.created()),
+        date_modified: secs(meta.modified()),
+        cloud: cloud_state(&meta),
+        tags,
+        rating: notes.rating,
+        comment: notes.comment,
+        email: EmailFormat::from_path(Path::new(&path))
+            .filter(|_| !is_dir)
+            .and_then(|_| email_summary(Path::new(&path), false).ok()),
+        path,
+    })
+}
@@ -0,0 +1,286 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::Serialize;
+use std::{collections::HashMap, fs, path::Path, path::PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// A named tag with an optional display color ("#RRGGBB")
+#[derive(Serialize, Clone, Debug)]
+pub struct Tag {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+/// A tag plus the number of paths carrying it
+#[derive(Serialize, Clone, Debug)]
+pub struct TagSummary {
+    pub name: String,
+    pub color: Option<String>,
+    pub count: i64,
+}
+
+/// Rating (1–5 stars) and freeform comment attached to a path
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct FileNotes {
+    pub rating: Option<u8>,
+    pub comment: Option<String>,
+}
+
+/// Location of the tag store at `%APPDATA%\dagger\tags.db`.
+/// Tags are user data, so they live next to (not inside) the caches folder.
+fn get_tags_db_path(handle: &AppHandle) -> PathBuf {
+    let mut path = handle
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| panic!("Failed to get app data dir"));
+    fs::create_dir_all(&path).ok();
+    path.push("tags.db");
+    path
+}
+
+/// Opens (or creates) the SQLite tag store.
+pub fn open_tags_db(handle: &AppHandle) -> Result<Connection> {
+    let path = get_tags_db_path(handle);
+    let conn = Connection::open(path)?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT
+        );
+        CREATE TABLE IF NOT EXISTS file_tags (
+            path TEXT NOT NULL,
+            parent TEXT NOT NULL,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (path, tag_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_file_tags_parent ON file_tags(parent);
+        CREATE INDEX IF NOT EXISTS idx_file_tags_tag ON file_tags(tag_id);
+        CREATE TABLE IF NOT EXISTS file_notes (
+            path TEXT PRIMARY KEY,
+            parent TEXT NOT NULL,
+            rating INTEGER CHECK (rating BETWEEN 1 AND 5),
+            comment TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_file_notes_parent ON file_notes(parent);
+        PRAGMA foreign_keys=ON;
+        PRAGMA journal_mode=WAL;
+        PRAGMA synchronous=NORMAL;",
+    )?;
+
+    Ok(conn)
+}
+
+/// Parent directory string used to look up all tags inside one folder.
+fn parent_of(path: &str) -> String {
+    Path::new(path)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Both spellings a directory may be stored under as a parent.
+/// Drive roots keep their trailing separator ("C:\"), everything else does not.
+fn parent_forms(dir: &str) -> (String, String) {
+    let dir = dir.trim_end_matches(['\\', '/']);
+    (
+        dir.to_string(),
+        format!("{}{}", dir, std::path::MAIN_SEPARATOR),
+    )
+}
+
+/// Creates the tag if needed (updating its color when one is given) and returns its id.
+pub fn upsert_tag(conn: &Connection, name: &str, color: Option<&str>) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO tags (name, color) VALUES (?1, ?2)
+         ON CONFLICT(name) DO UPDATE SET color = COALESCE(excluded.color, tags.color);",
+        params![name, color],
+    )?;
+    conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |r| r.get(0))
+}
+
+/// Attaches a tag to a path.
+pub fn add_tag(conn: &Connection, path: &str, tag_id: i64) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO file_tags (path, parent, tag_id) VALUES (?1, ?2, ?3);",
+        params![path, parent_of(path), tag_id],
+    )?;
+    Ok(())
+}
+
+/// Detaches a tag from a path.
+pub fn remove_tag(conn: &Connection, path: &str, name: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM file_tags
+         WHERE path = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2);",
+        params![path, name],
+    )?;
+    Ok(())
+}
+
+/// Deletes a tag and all its assignments.
+pub fn delete_tag(conn: &Connection, name: &str) -> Result<()> {
+    conn.execute("DELETE FROM tags WHERE name = ?1;", [name])?;
+    Ok(())
+}
+
+/// Lists every tag with its usage count.
+pub fn list_tags(conn: &Connection) -> Result<Vec<TagSummary>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.name, t.color, COUNT(f.path)
+         FROM tags t LEFT JOIN file_tags f ON f.tag_id = t.id
+         GROUP BY t.id ORDER BY t.name COLLATE NOCASE;",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(TagSummary {
+            name: r.get(0)?,
+            color: r.get(1)?,
+            count: r.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Returns the tags attached to a single path.
+pub fn tags_for_path(conn: &Connection, path: &str) -> Result<Vec<Tag>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.name, t.color FROM file_tags f JOIN tags t ON t.id = f.tag_id
+         WHERE f.path = ?1 ORDER BY t.name COLLATE NOCASE;",
+    )?;
+    let rows = stmt.query_map([path], |r| {
+        Ok(Tag {
+            name: r.get(0)?,
+            color: r.get(1)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Returns tags for every tagged entry directly inside `dir`, keyed by path.
+/// Used by the directory stream to annotate metadata events in one query.
+pub fn tags_in_directory(conn: &Connection, dir: &str) -> Result<HashMap<String, Vec<Tag>>> {
+    let (dir, root_form) = parent_forms(dir);
+    let mut stmt = conn.prepare(
+        "SELECT f.path, t.name, t.color FROM file_tags f JOIN tags t ON t.id = f.tag_id
+         WHERE f.parent = ?1 OR f.parent = ?2 ORDER BY t.name COLLATE NOCASE;",
+    )?;
+    let rows = stmt.query_map(params![dir, root_form], |r| {
+        Ok((
+            r.get::<_, String>(0)?,
+            Tag {
+                name: r.get(1)?,
+                color: r.get(2)?,
+            },
+        ))
+    })?;
+
+    let mut map: HashMap<String, Vec<Tag>> = HashMap::new();
+    for row in rows {
+        let (path, tag) = row?;
+        map.entry(path).or_default().push(tag);
+    }
+    Ok(map)
+}
+
+/// Returns every path carrying the tag.
+pub fn paths_with_tag(conn: &Connection, name: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.path FROM file_tags f JOIN tags t ON t.id = f.tag_id
+         WHERE t.name = ?1 ORDER BY f.path;",
+    )?;
+    let rows = stmt.query_map([name], |r| r.get(0))?;
+    rows.collect()
+}
+
+/// Looks up a tag's id by name.
+pub fn find_tag_id(conn: &Connection, name: &str) -> Result<Option<i64>> {
+    conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |r| r.get(0))
+        .optional()
+}
+
+/// Sets or clears (None) the star rating of a path.
+pub fn set_rating(conn: &Connection, path: &str, rating: Option<u8>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO file_notes (path, parent, rating) VALUES (?1, ?2, ?3)
+         ON CONFLICT(path) DO UPDATE SET rating = excluded.rating;",
+        params![path, parent_of(path), rating],
+    )?;
+    prune_notes(conn, path)
+}
+
+/// Sets or clears (None) the comment of a path.
+pub fn set_comment(conn: &Connection, path: &str, comment: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO file_notes (path, parent, comment) VALUES (?1, ?2, ?3)
+         ON CONFLICT(path) DO UPDATE SET comment = excluded.comment;",
+        params![path, parent_of(path), comment],
+    )?;
+    prune_notes(conn, path)
+}
+
+/// Drops the row once neither a rating nor a comment is left.
+fn prune_notes(conn: &Connection, path: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM file_notes WHERE path = ?1 AND rating IS NULL AND comment IS NULL;",
+        [path],
+    )?;
+    Ok(())
+}
+
+/// Returns the rating and comment of a single path.
+pub fn notes_for_path(conn: &Connection, path: &str) -> Result<FileNotes> {
+    conn.query_row(
+        "SELECT rating, comment FROM file_notes WHERE path = ?1",
+        [path],
+        |r| {
+            Ok(FileNotes {
+                rating: r.get(0)?,
+                comment: r.get(1)?,
+            })
+        },
+    )
+    .optional()
+    .map(Option::unwrap_or_default)
+}
+
+/// Returns ratings and comments for every annotated entry directly inside `dir`.
+pub fn notes_in_directory(conn: &Connection, dir: &str) -> Result<HashMap<String, FileNotes>> {
+    let (dir, root_form) = parent_forms(dir);
+    let mut stmt = conn.prepare(
+        "SELECT path, rating, comment FROM file_notes WHERE parent = ?1 OR parent = ?2;",
+    )?;
+    let rows = stmt.query_map(params![dir, root_form], |r| {
+        Ok((
+            r.get::<_, String>(0)?,
+            FileNotes {
+                rating: r.get(1)?,
+                comment: r.get(2)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+/// Returns every (path, tag name) assignment, for profile export.
+pub fn all_assignments(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.path, t.name FROM file_tags f JOIN tags t ON t.id = f.tag_id ORDER BY f.path;",
+    )?;
+    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+    rows.collect()
+}
+
+/// Returns every path's rating and comment, for profile export.
+pub fn all_notes(conn: &Connection) -> Result<Vec<(String, FileNotes)>> {
+    let mut stmt = conn.prepare("SELECT path, rating, comment FROM file_notes ORDER BY path;")?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get(0)?,
+            FileNotes {
+                rating: r.get(1)?,
+                comment: r.get(2)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
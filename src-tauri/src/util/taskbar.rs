@@ -0,0 +1,103 @@
+use std::{sync::Arc, thread, time::Duration};
+use tauri::{
+    window::{ProgressBarState, ProgressBarStatus},
+    AppHandle, Manager,
+};
+
+use crate::filesys::stream::jobs::{JobStreamState, JobsSummary};
+use crate::util::caches::HomeCache;
+
+/// How often the taskbar indicator is refreshed from the job registry
+const TASKBAR_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How many pinned and recent folders the Jump List shows per category
+const JUMP_LIST_LIMIT: usize = 8;
+
+fn progress_state(summary: Option<JobsSummary>) -> ProgressBarState {
+    let Some(summary) = summary else {
+        return ProgressBarState {
+            status: Some(ProgressBarStatus::None),
+            progress: None,
+        };
+    };
+
+    let status = if summary.has_error {
+        ProgressBarStatus::Error
+    } else if summary.waiting {
+        ProgressBarStatus::Paused
+    } else if summary.scanning && summary.fraction == 0.0 {
+        ProgressBarStatus::Indeterminate
+    } else {
+        ProgressBarStatus::Normal
+    };
+
+    ProgressBarState {
+        status: Some(status),
+        progress: Some((summary.fraction * 100.0).round() as u64),
+    }
+}
+
+/// Mirrors aggregate job progress on the taskbar button (ITaskbarList3 on Windows)
+/// so copies can be watched while Dagger is minimized or in the tray.
+/// The indicator is cleared as soon as the job queue drains.
+pub fn start_taskbar_progress(handle: &AppHandle, jobs: Arc<JobStreamState>) {
+    let handle = handle.clone();
+    thread::spawn(move || {
+        // (queue active, error, waiting, percent) of the last state pushed to the taskbar
+        let mut last: Option<(bool, bool, bool, Option<u64>)> = None;
+
+        loop {
+            thread::sleep(TASKBAR_INTERVAL);
+
+            let summary = jobs.summary();
+            let state = progress_state(summary);
+            let key = (
+                summary.is_some(),
+                summary.is_some_and(|s| s.has_error),
+                summary.is_some_and(|s| s.waiting),
+                state.progress,
+            );
+            // only touch the taskbar when something visible changed
+            if last == Some(key) {
+                continue;
+            }
+
+            if let Some(window) = handle.get_webview_window("main") {
+                if window.set_progress_bar(state).is_ok() {
+                    last = Some(key);
+                }
+            }
+        }
+    });
+}
+
+/// Rebuilds the taskbar Jump List from the home cache's pinned and recent folders.
+/// Selecting an entry relaunches Dagger with the folder, which the running instance opens.
+pub fn refresh_jump_list(cache: &HomeCache) {
+    #[cfg(target_os = "windows")]
+    {
+        let pinned: Vec<(String, String)> = cache
+            .pinned_items
+            .iter()
+            .filter(|item| item.is_dir)
+            .take(JUMP_LIST_LIMIT)
+            .map(|item| (item.name.clone(), item.path.clone()))
+            .collect();
+        let recent: Vec<(String, String)> = cache
+            .recent_dirs
+            .iter()
+            .take(JUMP_LIST_LIMIT)
+            .map(|item| (item.name.clone(), item.path.clone()))
+            .collect();
+
+        // COM needs an apartment of its own, so stay off the async runtime's workers
+        thread::spawn(move || {
+            let categories = [("Pinned", pinned), ("Recent folders", recent)];
+            if let Err(e) = crate::filesys::os::windows::update_jump_list(&categories) {
+                tracing::warn!(target: "taskbar", "Failed to update the Jump List: {}", e);
+            }
+        });
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (cache, JUMP_LIST_LIMIT);
+}